@@ -42,7 +42,11 @@ use strum::IntoEnumIterator;
 use tempfile::TempDir;
 
 mod format;
+mod package_cache;
+#[cfg(not(windows))]
+mod test_report;
 pub use format::{format_files, format_src, FormatMode};
+pub use package_cache::cache_command;
 
 pub const CMD_BUILD: &str = "build";
 pub const CMD_RUN: &str = "run";
@@ -55,6 +59,11 @@ pub const CMD_FORMAT: &str = "format";
 pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
 pub const CMD_PREPROCESS_HOST: &str = "preprocess-host";
+pub const CMD_VENDOR: &str = "vendor";
+pub const CMD_CACHE: &str = "cache";
+pub const CMD_CACHE_LIST: &str = "list";
+pub const CMD_CACHE_VERIFY: &str = "verify";
+pub const CMD_CACHE_PRUNE: &str = "prune";
 
 pub const FLAG_EMIT_LLVM_IR: &str = "emit-llvm-ir";
 pub const FLAG_PROFILING: &str = "profiling";
@@ -78,8 +87,21 @@ pub const FLAG_STDIN: &str = "stdin";
 pub const FLAG_STDOUT: &str = "stdout";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
 pub const FLAG_OUTPUT: &str = "output";
+pub const FLAG_DOCS_FORMAT: &str = "format";
 pub const FLAG_FUZZ: &str = "fuzz";
 pub const FLAG_MAIN: &str = "main";
+pub const FLAG_FILTER: &str = "filter";
+pub const FLAG_LIST: &str = "list";
+pub const FLAG_FAIL_FAST: &str = "fail-fast";
+pub const FLAG_DOC: &str = "doc";
+pub const FLAG_REPORT: &str = "report";
+pub const FLAG_TIMEOUT: &str = "timeout";
+pub const FLAG_SEED: &str = "seed";
+pub const FLAG_OFFLINE: &str = "offline";
+pub const FLAG_REMOVE: &str = "remove";
+pub const FLAG_UNUSED_DAYS: &str = "unused-days";
+pub const FLAG_MAX_SIZE_MB: &str = "max-size-mb";
+pub const FLAG_DRY_RUN: &str = "dry-run";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_DIR: &str = "GLUE_DIR";
@@ -91,6 +113,10 @@ pub const FLAG_PP_PLATFORM: &str = "platform";
 pub const FLAG_PP_DYLIB: &str = "lib";
 
 pub const VERSION: &str = env!("ROC_VERSION");
+
+/// Setting this to anything but "" or "0" is the same as passing `--offline`
+pub const ROC_OFFLINE_ENV_VAR: &str = "ROC_OFFLINE";
+
 const DEFAULT_GENERATED_DOCS_DIR: &str = "generated-docs";
 
 pub fn build_app() -> Command {
@@ -172,6 +198,12 @@ pub fn build_app() -> Command {
         .value_parser(value_parser!(PathBuf))
         .required(false);
 
+    let flag_offline = Arg::new(FLAG_OFFLINE)
+        .long(FLAG_OFFLINE)
+        .help(concatcp!("Don't download any packages; only use the ones in the project's vendor/ directory (see `roc vendor`) or in the package cache\n(Setting the ", ROC_OFFLINE_ENV_VAR, " environment variable does the same.)"))
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let roc_file_to_run = Arg::new(ROC_FILE)
         .help("The .roc file of an app to run")
         .value_parser(value_parser!(PathBuf))
@@ -210,6 +242,7 @@ pub fn build_app() -> Command {
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
             .arg(flag_wasm_stack_size_kb)
             .arg(
                 Arg::new(FLAG_TARGET)
@@ -263,6 +296,7 @@ pub fn build_app() -> Command {
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
             .arg(
                 Arg::new(FLAG_VERBOSE)
                     .long(FLAG_VERBOSE)
@@ -270,6 +304,56 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false)
             )
+            .arg(
+                Arg::new(FLAG_FILTER)
+                    .long(FLAG_FILTER)
+                    .help("Only run the `expect`s whose module name or enclosing def name contains this text")
+                    .value_parser(value_parser!(String))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_LIST)
+                    .long(FLAG_LIST)
+                    .help("Print the `expect`s that would run, with their source locations, without running them")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_FAIL_FAST)
+                    .long(FLAG_FAIL_FAST)
+                    .help("Stop running `expect`s after the first failure")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_DOC)
+                    .long(FLAG_DOC)
+                    .help("Also run the ```roc code examples with an `expect` in them in the doc comments of the tested modules")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_TIMEOUT)
+                    .long(FLAG_TIMEOUT)
+                    .help("Stop any single top-level `expect` that runs for longer than this many seconds, and report it as timed out. Use 0 or `none` to never stop them. [default: 60]")
+                    .value_parser(test_timeout_parser)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_SEED)
+                    .long(FLAG_SEED)
+                    .help("Seed the random inputs of property `expect`s, like `expect \\xs -> ...`, to get the same inputs as an earlier run")
+                    .value_parser(value_parser!(u64))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_REPORT)
+                    .long(FLAG_REPORT)
+                    .help("Also write the results to a file, as `junit=<path>` or `json=<path>`. Can be given more than once.")
+                    .value_parser(test_report_request_parser)
+                    .action(ArgAction::Append)
+                    .required(false)
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to test")
@@ -310,6 +394,7 @@ pub fn build_app() -> Command {
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
@@ -326,6 +411,7 @@ pub fn build_app() -> Command {
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
@@ -367,6 +453,7 @@ pub fn build_app() -> Command {
             .arg(flag_main.clone())
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_offline.clone())
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to check")
@@ -385,6 +472,13 @@ pub fn build_app() -> Command {
                     .required(false)
                     .default_value(DEFAULT_GENERATED_DOCS_DIR),
                 )
+                .arg(Arg::new(FLAG_DOCS_FORMAT)
+                    .long(FLAG_DOCS_FORMAT)
+                    .help("Generate HTML pages, or a docs.json file with each module's entries, type annotations, and source regions.")
+                    .value_parser(["html", "json"])
+                    .required(false)
+                    .default_value("html"),
+                )
                 .arg(Arg::new(ROC_FILE)
                     .help("The package's main .roc file")
                     .value_parser(value_parser!(PathBuf))
@@ -415,6 +509,60 @@ pub fn build_app() -> Command {
                     .default_value(DEFAULT_ROC_FILENAME)
             )
         )
+        .subcommand(Command::new(CMD_VENDOR)
+            .about("Copy every package a project downloads from a URL into its vendor/ directory,\nso it can be built with --offline")
+            .arg(flag_main.clone())
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file of the project's main app/package module")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
+        .subcommand(Command::new(CMD_CACHE)
+            .about("Look after the cache of downloaded packages")
+            .subcommand_required(true)
+            .subcommand(Command::new(CMD_CACHE_LIST)
+                .about("List the cached packages, with their sizes and when they were last used")
+            )
+            .subcommand(Command::new(CMD_CACHE_VERIFY)
+                .about("Rehash the tarball each cached package came in, check that against the hash in its URL, and check the package's files against the tarball")
+                .after_help("Packages cached by versions of roc that didn't keep their tarballs can't be rehashed, so they're reported as unverified.")
+                .arg(
+                    Arg::new(FLAG_REMOVE)
+                        .long(FLAG_REMOVE)
+                        .help("Remove the packages that fail verification, so they get downloaded again")
+                        .action(ArgAction::SetTrue)
+                        .required(false)
+                )
+            )
+            .subcommand(Command::new(CMD_CACHE_PRUNE)
+                .about("Remove cached packages that haven't been used in a while, or that take up too much space")
+                .arg(
+                    Arg::new(FLAG_UNUSED_DAYS)
+                        .long(FLAG_UNUSED_DAYS)
+                        .help("Remove the packages no build has used for more than this many days")
+                        .value_parser(value_parser!(u64))
+                        .required(false)
+                )
+                .arg(
+                    Arg::new(FLAG_MAX_SIZE_MB)
+                        .long(FLAG_MAX_SIZE_MB)
+                        .help("Remove the least recently used packages until the rest take up at most this many megabytes")
+                        .value_parser(value_parser!(u64))
+                        .required(false)
+                )
+                .arg(
+                    Arg::new(FLAG_DRY_RUN)
+                        .long(FLAG_DRY_RUN)
+                        .help("Print which packages would be removed, without removing them")
+                        .action(ArgAction::SetTrue)
+                        .required(false)
+                )
+            )
+            .after_help(concatcp!("To have every build verify the cached packages it uses, set the ", roc_packaging::manifest::VERIFY_PACKAGES_ENV_VAR, " environment variable."))
+        )
         .subcommand(Command::new(CMD_PREPROCESS_HOST)
             .about("Runs the surgical linker preprocessor to generate `.rh` and `.rm` files.")
             .arg(
@@ -462,6 +610,7 @@ pub fn build_app() -> Command {
         .arg(flag_build_host)
         .arg(flag_suppress_build_host_warning)
         .arg(flag_fuzz)
+        .arg(flag_offline)
         .arg(roc_file_to_run)
         .arg(args_for_app.trailing_var_arg(true))
 }
@@ -487,6 +636,127 @@ fn opt_level_from_flags(matches: &ArgMatches) -> OptLevel {
     }
 }
 
+/// `--offline` was passed, or the ROC_OFFLINE env var is set
+pub fn is_offline(matches: &ArgMatches) -> bool {
+    let flag = matches!(matches.try_get_one::<bool>(FLAG_OFFLINE), Ok(Some(true)));
+    let env_var =
+        env::var_os(ROC_OFFLINE_ENV_VAR).is_some_and(|value| !value.is_empty() && value != "0");
+
+    flag || env_var
+}
+
+/// The vendor/ dir of the project being built; its main module is either
+/// given with `--main`, or it's the ROC_FILE (for `roc test`, the first one).
+pub fn vendor_dir(matches: &ArgMatches) -> PathBuf {
+    let root_path = matches
+        .get_one::<PathBuf>(FLAG_MAIN)
+        .or_else(|| matches.get_one::<PathBuf>(ROC_FILE))
+        .map_or(Path::new(DEFAULT_ROC_FILENAME), PathBuf::as_path);
+
+    roc_packaging::vendor::vendor_dir(root_path)
+}
+
+/// Where URL packages come from: the cache, downloading them if need be,
+/// or when offline, only the project's vendor/ dir and the cache.
+pub fn roc_cache_dir<'a>(
+    matches: &ArgMatches,
+    cache_dir: &'a Path,
+    vendor_dir: &'a Path,
+) -> RocCacheDir<'a> {
+    if is_offline(matches) {
+        RocCacheDir::Offline {
+            vendor_dir,
+            cache_dir,
+        }
+    } else {
+        RocCacheDir::Persistent(cache_dir)
+    }
+}
+
+pub fn vendor(matches: &ArgMatches) -> io::Result<i32> {
+    use roc_packaging::vendor::{vendor_packages, VendorProblem};
+    use roc_reporting::report::to_https_problem_report_string;
+
+    let root_module = matches
+        .get_one::<PathBuf>(FLAG_MAIN)
+        .or_else(|| matches.get_one::<PathBuf>(ROC_FILE))
+        .unwrap();
+
+    if !root_module.is_file() {
+        eprintln!(
+            "\nThis file was not found: {}\n\nYou can run `roc help vendor` for more information on how to provide a .roc file.\n",
+            root_module.display()
+        );
+
+        return Ok(1);
+    }
+
+    let cache_dir = roc_packaging::cache::roc_cache_packages_dir();
+    let vendor_dir = vendor_dir(matches);
+
+    match vendor_packages(
+        RocCacheDir::Persistent(&cache_dir),
+        root_module,
+        &vendor_dir,
+    ) {
+        Ok(packages) if packages.is_empty() => {
+            println!(
+                "This project doesn't use any packages from URLs, so there was nothing to vendor."
+            );
+
+            Ok(0)
+        }
+        Ok(packages) => {
+            for package in packages {
+                let status = if package.newly_vendored {
+                    "Vendored"
+                } else {
+                    "Already vendored"
+                };
+
+                println!(
+                    "{status} {}\n    into {}",
+                    package.url,
+                    package.dir.display()
+                );
+            }
+
+            Ok(0)
+        }
+        Err(VendorProblem::Package {
+            url,
+            module_path,
+            problem,
+        }) => {
+            print!(
+                "{}",
+                to_https_problem_report_string(&url, problem, module_path)
+            );
+
+            Ok(1)
+        }
+        Err(VendorProblem::Header {
+            module_path,
+            problem,
+        }) => {
+            eprintln!(
+                "\nI could not read the header of {} to find its packages: {problem}\n",
+                module_path.display()
+            );
+
+            Ok(1)
+        }
+        Err(VendorProblem::Copy { dir, problem }) => {
+            eprintln!(
+                "\nI could not copy a package into {}: {problem}\n",
+                dir.display()
+            );
+
+            Ok(1)
+        }
+    }
+}
+
 #[cfg(windows)]
 pub fn test(_matches: &ArgMatches, _target: Target) -> io::Result<i32> {
     todo!("running tests does not work on windows right now")
@@ -498,16 +768,12 @@ struct ModuleTestResults {
     failed_count: usize,
     passed_count: usize,
     tests_duration: Duration,
+    outcomes: Vec<roc_repl_expect::run::ExpectOutcome>,
 }
 
 #[cfg(not(windows))]
 pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
-    use roc_build::program::report_problems_monomorphized;
-    use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
-    use roc_packaging::cache;
-
     let start_time = Instant::now();
-    let arena = Bump::new();
     let opt_level = opt_level_from_flags(matches);
 
     let threading = match matches.get_one::<usize>(FLAG_MAX_THREADS) {
@@ -549,154 +815,594 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         flatten_paths
     };
 
-    let mut all_files_total_failed_count = 0;
-    let mut all_files_total_passed_count = 0;
+    let list_only = matches.get_flag(FLAG_LIST);
+    let report_requests: Vec<test_report::ReportRequest> = matches
+        .get_many::<test_report::ReportRequest>(FLAG_REPORT)
+        .map(|requests| requests.cloned().collect())
+        .unwrap_or_default();
+    let run_options = roc_repl_expect::run::ExpectRunOptions {
+        fail_fast: matches.get_flag(FLAG_FAIL_FAST),
+        timeout: matches
+            .get_one::<Option<Duration>>(FLAG_TIMEOUT)
+            .copied()
+            .unwrap_or(Some(
+                roc_repl_expect::run::ExpectRunOptions::DEFAULT_TIMEOUT,
+            )),
+        // a failing property prints its seed, so a random one is fine here
+        seed: matches
+            .get_one::<u64>(FLAG_SEED)
+            .copied()
+            .unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+            }),
+        collect_reports: !report_requests.is_empty(),
+    };
+
+    // Test several files at once, and split the threads we may use between them.
+    let available_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let max_threads = match threading {
+        Threading::Single => 1,
+        Threading::AllAvailable => available_threads,
+        Threading::AtMost(n) => n.min(available_threads),
+    };
+    let worker_count = max_threads.min(paths.len()).max(1);
+    let load_threading = match max_threads / worker_count {
+        0 | 1 => Threading::Single,
+        n => Threading::AtMost(n),
+    };
 
-    for path in paths.iter() {
-        let arena = &arena;
-        let function_kind = FunctionKind::from_env();
+    let cache_dir = roc_packaging::cache::roc_cache_packages_dir();
+    let vendor_dir = vendor_dir(matches);
+    let config = TestConfig {
+        target,
+        opt_level,
+        threading: load_threading,
+        opt_main_path: matches.get_one::<PathBuf>(FLAG_MAIN),
+        filter: matches.get_one::<String>(FLAG_FILTER),
+        list_only,
+        verbose: matches.get_flag(FLAG_VERBOSE),
+        doc_tests: matches.get_flag(FLAG_DOC),
+        roc_cache_dir: roc_cache_dir(matches, &cache_dir, &vendor_dir),
+        run_options,
+        start_time,
+    };
+    let turns = TestTurns::default();
+    let next_path = std::sync::atomic::AtomicUsize::new(0);
+
+    // Each worker runs its expects in processes forked by its own runner. They have to be
+    // started while this is the only thread, so that forking is safe.
+    let mut runners = (0..worker_count)
+        .map(|_| roc_repl_expect::run::ExpectRunner::start())
+        .collect::<io::Result<Vec<_>>>()?;
+
+    std::thread::scope(|scope| {
+        for runner in runners.iter_mut() {
+            let (paths, config, turns, next_path) = (&paths, &config, &turns, &next_path);
+
+            scope.spawn(move || loop {
+                let path_index = next_path.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let Some(path) = paths.get(path_index) else {
+                    break;
+                };
+
+                let tested = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    test_path(path_index, path, config, turns, runner)
+                }));
+
+                if let Err(panic) = tested {
+                    turns.abort();
+                    std::panic::resume_unwind(panic);
+                }
+            });
+        }
+    });
+
+    let TestProgress {
+        failed_count: all_files_total_failed_count,
+        passed_count: all_files_total_passed_count,
+        listed_count: all_files_listed_count,
+        module_reports,
+        exit,
+        ..
+    } = turns.progress.into_inner().unwrap();
+
+    // one of the files didn't compile, and we already reported why
+    if let Some(exit) = exit {
+        return exit;
+    }
 
-        let opt_main_path = matches.get_one::<PathBuf>(FLAG_MAIN);
+    for request in report_requests.iter() {
+        if let Err(err) = test_report::write_report(request, &module_reports) {
+            eprintln!(
+                "\nI could not write the test report to {}: {err}\n",
+                request.path.display()
+            );
+            return Ok(1);
+        }
+    }
 
-        // Step 1: compile the app and generate the .o file
-        let load_config = LoadConfig {
-            target,
-            function_kind,
-            // TODO: expose this from CLI?
-            render: roc_reporting::report::RenderTarget::ColorTerminal,
-            palette: roc_reporting::report::DEFAULT_PALETTE,
-            threading,
-            exec_mode: ExecutionMode::Test,
-        };
-        let load_result = roc_load::load_and_monomorphize(
-            arena,
-            path.to_path_buf(),
-            opt_main_path.cloned(),
-            RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
-            load_config,
-        );
+    if list_only && all_files_listed_count > 0 {
+        Ok(0)
+    } else if all_files_total_failed_count == 0 && all_files_total_passed_count == 0 {
+        // TODO print this in a more nicely formatted way!
+        println!("No expectations were found.");
 
-        let mut loaded = match load_result {
-            Ok(loaded) => loaded,
-            Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
-                return handle_loading_problem(problem);
-            }
-            Err(LoadMonomorphizedError::ErrorModule(module)) => {
-                return handle_error_module(module, start_time.elapsed(), path.as_os_str(), false);
-            }
-        };
-        let problems = report_problems_monomorphized(&mut loaded);
+        // If no tests ran, treat that as an error. This is perhaps
+        // briefly annoying at the very beginning of a project when
+        // you actually have zero tests, but it can save you from
+        // having a change to your CI script accidentally stop
+        // running tests altogether!
+        Ok(2)
+    } else {
+        Ok((all_files_total_failed_count > 0) as i32)
+    }
+}
 
-        let mut expectations = std::mem::take(&mut loaded.expectations);
+#[cfg(not(windows))]
+struct TestConfig<'m> {
+    target: Target,
+    opt_level: OptLevel,
+    /// How many threads loading a single file may use
+    threading: Threading,
+    opt_main_path: Option<&'m PathBuf>,
+    filter: Option<&'m String>,
+    list_only: bool,
+    verbose: bool,
+    /// Also test the code examples in doc comments
+    doc_tests: bool,
+    roc_cache_dir: RocCacheDir<'m>,
+    run_options: roc_repl_expect::run::ExpectRunOptions,
+    start_time: Instant,
+}
 
-        let interns = loaded.interns.clone();
-        let sources = loaded.sources.clone();
+/// The results `roc test` has printed so far.
+#[cfg(not(windows))]
+#[derive(Default)]
+struct TestProgress {
+    /// The index of the path whose turn it is to print
+    next_path: usize,
+    failed_count: usize,
+    passed_count: usize,
+    listed_count: usize,
+    module_reports: Vec<test_report::ModuleReport>,
+    /// What `roc test` returns if a file didn't compile
+    exit: Option<io::Result<i32>>,
+    /// Another file's test run panicked
+    aborted: bool,
+}
 
-        let (dyn_lib, expects_by_module, layout_interner) =
-            roc_repl_expect::run::expect_mono_module_to_dylib(
-                arena,
-                target,
-                loaded,
-                opt_level,
-                LlvmBackendMode::CliTest,
-            )
-            .unwrap();
+#[cfg(not(windows))]
+impl TestProgress {
+    fn is_stopped(&self, fail_fast: bool) -> bool {
+        self.aborted || self.exit.is_some() || (fail_fast && self.failed_count > 0)
+    }
+}
 
-        // Print warnings before running tests.
-        {
-            debug_assert_eq!(
-                problems.errors, 0,
-                "if there were errors, we would have already exited."
-            );
-            if problems.warnings > 0 {
-                problems.print_error_warning_count(start_time.elapsed());
-                println!(".\n\nRunning tests…\n\n\x1B[36m{}\x1B[39m", "─".repeat(80));
+/// Files are tested concurrently, but each one waits for its turn to print,
+/// so the output is in the same order as the paths on the command line.
+#[cfg(not(windows))]
+#[derive(Default)]
+struct TestTurns {
+    progress: std::sync::Mutex<TestProgress>,
+    turn_changed: std::sync::Condvar,
+}
+
+#[cfg(not(windows))]
+impl TestTurns {
+    fn lock(&self) -> std::sync::MutexGuard<'_, TestProgress> {
+        // a panic while printing shouldn't leave the other files waiting forever
+        self.progress
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Waits until every path before this one has printed its results.
+    /// The next path gets its turn when the returned `TestTurn` is dropped.
+    fn take_turn(&self, path_index: usize) -> TestTurn<'_> {
+        let progress = self
+            .turn_changed
+            .wait_while(self.lock(), |progress| {
+                progress.next_path != path_index && !progress.aborted
+            })
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        TestTurn {
+            turns: self,
+            progress,
+        }
+    }
+
+    fn is_stopped(&self, fail_fast: bool) -> bool {
+        self.lock().is_stopped(fail_fast)
+    }
+
+    fn abort(&self) {
+        self.lock().aborted = true;
+        self.turn_changed.notify_all();
+    }
+}
+
+#[cfg(not(windows))]
+struct TestTurn<'t> {
+    turns: &'t TestTurns,
+    progress: std::sync::MutexGuard<'t, TestProgress>,
+}
+
+#[cfg(not(windows))]
+impl std::ops::Deref for TestTurn<'_> {
+    type Target = TestProgress;
+
+    fn deref(&self) -> &TestProgress {
+        &self.progress
+    }
+}
+
+#[cfg(not(windows))]
+impl std::ops::DerefMut for TestTurn<'_> {
+    fn deref_mut(&mut self) -> &mut TestProgress {
+        &mut self.progress
+    }
+}
+
+#[cfg(not(windows))]
+impl Drop for TestTurn<'_> {
+    fn drop(&mut self) {
+        self.progress.next_path += 1;
+        self.turns.turn_changed.notify_all();
+    }
+}
+
+/// Compile and run the tests in one file, then print the results when it's this file's turn.
+#[cfg(not(windows))]
+fn test_path(
+    path_index: usize,
+    path: &Path,
+    config: &TestConfig,
+    turns: &TestTurns,
+    runner: &mut roc_repl_expect::run::ExpectRunner,
+) {
+    use io::Write;
+    use roc_build::program::report_problems_monomorphized;
+    use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
+
+    let fail_fast = config.run_options.fail_fast;
+
+    // an earlier file failed, so we wouldn't print anything for this one anyway
+    if turns.is_stopped(fail_fast) {
+        drop(turns.take_turn(path_index));
+        return;
+    }
+
+    let arena = &Bump::new();
+    let function_kind = FunctionKind::from_env();
+
+    // Step 1: compile the app and generate the .o file
+    let load_config = LoadConfig {
+        target: config.target,
+        function_kind,
+        // TODO: expose this from CLI?
+        render: roc_reporting::report::RenderTarget::ColorTerminal,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        threading: config.threading,
+        exec_mode: if config.doc_tests {
+            ExecutionMode::TestDocs
+        } else {
+            ExecutionMode::Test
+        },
+    };
+    let load_result = roc_load::load_and_monomorphize(
+        arena,
+        path.to_path_buf(),
+        config.opt_main_path.cloned(),
+        config.roc_cache_dir,
+        load_config,
+    );
+
+    let mut loaded = match load_result {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            let mut turn = turns.take_turn(path_index);
+
+            if !turn.is_stopped(fail_fast) {
+                turn.exit = Some(match error {
+                    LoadMonomorphizedError::LoadingProblem(problem) => {
+                        handle_loading_problem(problem)
+                    }
+                    LoadMonomorphizedError::ErrorModule(module) => handle_error_module(
+                        module,
+                        config.start_time.elapsed(),
+                        path.as_os_str(),
+                        false,
+                    ),
+                });
             }
+
+            return;
         }
+    };
 
-        // Run the tests.
-        let arena = &bumpalo::Bump::new();
-        let interns = arena.alloc(interns);
+    if let Some(filter) = config.filter {
+        filter_toplevel_expects(&mut loaded, filter);
+    }
 
-        let mut writer = std::io::stdout();
+    if config.list_only {
+        let mut turn = turns.take_turn(path_index);
 
-        let mut total_failed_count = 0;
-        let mut total_passed_count = 0;
+        if !turn.is_stopped(fail_fast) {
+            report_problems_monomorphized(&mut loaded);
+            turn.listed_count += list_toplevel_expects(&loaded);
+        }
 
-        let mut results_by_module = Vec::new();
-        let global_layout_interner = layout_interner.into_global();
+        return;
+    }
 
-        let compilation_duration = start_time.elapsed();
+    // these are reported when it's our turn to print
+    let mut can_problems = std::mem::take(&mut loaded.can_problems);
+    let mut type_problems = std::mem::take(&mut loaded.type_problems);
 
-        for (module_id, expects) in expects_by_module.into_iter() {
-            let test_start_time = Instant::now();
+    let mut expectations = std::mem::take(&mut loaded.expectations);
 
-            let (failed_count, passed_count) = roc_repl_expect::run::run_toplevel_expects(
-                &mut writer,
-                roc_reporting::report::RenderTarget::ColorTerminal,
-                arena,
-                interns,
-                &global_layout_interner,
-                &dyn_lib,
-                &mut expectations,
-                expects,
-            )
-            .unwrap();
+    let interns = loaded.interns.clone();
+    let sources = loaded.sources.clone();
+
+    let (dylib, expects_by_module, layout_interner) =
+        roc_repl_expect::run::expect_mono_module_to_dylib(
+            arena,
+            config.target,
+            loaded,
+            config.opt_level,
+            LlvmBackendMode::CliTest,
+        );
 
-            let tests_duration = test_start_time.elapsed();
+    let compilation_duration = config.start_time.elapsed();
 
-            results_by_module.push(ModuleTestResults {
-                module_id,
-                failed_count,
-                passed_count,
-                tests_duration,
-            });
+    // Run the tests.
+    let arena = &bumpalo::Bump::new();
+    let interns = arena.alloc(interns);
+
+    runner.load(&dylib).unwrap();
+
+    // the failures are printed once it's our turn
+    let mut writer = Vec::new();
+
+    let mut total_failed_count = 0;
+    let mut total_passed_count = 0;
 
-            total_failed_count += failed_count;
-            total_passed_count += passed_count;
+    let mut results_by_module = Vec::new();
+    let global_layout_interner = layout_interner.into_global();
+
+    for (module_id, expects) in expects_by_module.into_iter() {
+        if fail_fast && (total_failed_count > 0 || turns.is_stopped(fail_fast)) {
+            break;
         }
 
-        let total_duration = start_time.elapsed();
-        all_files_total_failed_count += total_failed_count;
-        all_files_total_passed_count += total_passed_count;
-        if total_failed_count == 0 && total_passed_count == 0 {
-            // Only report no expectations found once.
-            continue;
-        } else if matches.get_flag(FLAG_VERBOSE) {
-            println!("Compiled in {} ms.", compilation_duration.as_millis());
-            for module_test_results in results_by_module {
-                print_test_results(module_test_results, &sources);
-            }
-        } else {
-            let test_summary_str =
-                test_summary(total_failed_count, total_passed_count, total_duration);
-            println!("{test_summary_str}");
+        let test_start_time = Instant::now();
+        let mut outcomes = Vec::new();
+
+        let (failed_count, passed_count) = roc_repl_expect::run::run_toplevel_expects(
+            &mut writer,
+            roc_reporting::report::RenderTarget::ColorTerminal,
+            arena,
+            interns,
+            &global_layout_interner,
+            runner,
+            &mut expectations,
+            expects,
+            config.run_options,
+            &mut outcomes,
+        )
+        .unwrap();
+
+        let tests_duration = test_start_time.elapsed();
+
+        results_by_module.push(ModuleTestResults {
+            module_id,
+            failed_count,
+            passed_count,
+            tests_duration,
+            outcomes,
+        });
+
+        total_failed_count += failed_count;
+        total_passed_count += passed_count;
+    }
+
+    let mut turn = turns.take_turn(path_index);
+
+    if turn.is_stopped(fail_fast) {
+        return;
+    }
+
+    // Print warnings before the test results.
+    {
+        let problems = roc_reporting::cli::report_problems(
+            &sources,
+            interns,
+            &mut can_problems,
+            &mut type_problems,
+        );
+
+        debug_assert_eq!(
+            problems.errors, 0,
+            "if there were errors, we would have already exited."
+        );
+        if problems.warnings > 0 {
+            problems.print_error_warning_count(config.start_time.elapsed());
+            println!(".\n\nRunning tests…\n\n\x1B[36m{}\x1B[39m", "─".repeat(80));
         }
     }
-    if all_files_total_failed_count == 0 && all_files_total_passed_count == 0 {
-        // TODO print this in a more nicely formatted way!
-        println!("No expectations were found.");
 
-        // If no tests ran, treat that as an error. This is perhaps
-        // briefly annoying at the very beginning of a project when
-        // you actually have zero tests, but it can save you from
-        // having a change to your CI script accidentally stop
-        // running tests altogether!
-        Ok(2)
+    io::stdout().write_all(&writer).unwrap();
+
+    if config.run_options.collect_reports {
+        turn.module_reports.extend(
+            results_by_module
+                .iter()
+                .map(|results| module_test_report(results, &sources, interns)),
+        );
+    }
+
+    let total_duration = config.start_time.elapsed();
+    turn.failed_count += total_failed_count;
+    turn.passed_count += total_passed_count;
+    if total_failed_count == 0 && total_passed_count == 0 {
+        // Only report no expectations found once.
+    } else if config.verbose {
+        println!("Compiled in {} ms.", compilation_duration.as_millis());
+        for module_test_results in results_by_module {
+            print_test_results(module_test_results, &sources);
+        }
     } else {
-        Ok((all_files_total_failed_count > 0) as i32)
+        let test_summary_str = test_summary(total_failed_count, total_passed_count, total_duration);
+        println!("{test_summary_str}");
+    }
+}
+
+/// Drop the top-level expects that `roc test --filter` doesn't select. The filter matches
+/// if it's a substring of the module name, of the name of the def the expect sits under,
+/// or of the two joined with a dot (e.g. `Str.trim`).
+#[cfg(not(windows))]
+fn filter_toplevel_expects(loaded: &mut roc_load::MonomorphizedModule, filter: &str) {
+    let interns = &loaded.interns;
+
+    for (module_id, expects) in loaded.toplevel_expects.iter_mut() {
+        let module_name = interns.module_name(*module_id).as_str();
+        let def_names = &expects.def_names;
+        let is_selected = |symbol: &roc_module::symbol::Symbol| {
+            let def_name = def_names.get(symbol).map(|def| def.as_str(interns));
+
+            module_name.contains(filter)
+                || def_name.map_or(false, |def_name| {
+                    def_name.contains(filter)
+                        || format!("{module_name}.{def_name}").contains(filter)
+                })
+        };
+
+        expects.pure = std::mem::take(&mut expects.pure)
+            .into_iter()
+            .filter(|(symbol, _)| is_selected(symbol))
+            .collect();
+        expects.fx = std::mem::take(&mut expects.fx)
+            .into_iter()
+            .filter(|(symbol, _)| is_selected(symbol))
+            .collect();
+    }
+
+    loaded
+        .toplevel_expects
+        .retain(|_, expects| !expects.pure.is_empty() || !expects.fx.is_empty());
+}
+
+/// Print every top-level expect with its source location, for `roc test --list`.
+/// Returns how many were printed.
+#[cfg(not(windows))]
+fn list_toplevel_expects(loaded: &roc_load::MonomorphizedModule) -> usize {
+    use roc_region::all::LineInfo;
+
+    let mut listed = Vec::new();
+
+    for (module_id, expects) in loaded.toplevel_expects.iter() {
+        let (path, source) = loaded.sources.get(module_id).unwrap();
+        let line_info = LineInfo::new(source);
+        let module_name = loaded.interns.module_name(*module_id).as_str();
+
+        for (symbol, region) in expects.pure.iter().chain(expects.fx.iter()) {
+            let position = line_info.convert_offset(test_report::expect_start(source, *region));
+
+            let name = match expects.def_names.get(symbol) {
+                Some(def) => format!("{module_name}.{}", def.as_str(&loaded.interns)),
+                None => module_name.to_string(),
+            };
+
+            listed.push((path.clone(), position.line, position.column, name));
+        }
+    }
+
+    listed.sort();
+
+    for (path, line, column, name) in listed.iter() {
+        println!("{}:{}:{}  {name}", path.display(), line + 1, column + 1);
+    }
+
+    listed.len()
+}
+
+#[cfg(not(windows))]
+fn test_report_request_parser(arg: &str) -> Result<test_report::ReportRequest, String> {
+    test_report::parse_report_request(arg)
+}
+
+#[cfg(windows)]
+fn test_report_request_parser(arg: &str) -> Result<String, String> {
+    Ok(arg.to_string())
+}
+
+/// `--timeout` is a number of seconds, where 0 (or `none`) means there is no timeout
+fn test_timeout_parser(arg: &str) -> Result<Option<Duration>, String> {
+    match arg {
+        "none" => Ok(None),
+        _ => match arg.parse::<u64>() {
+            Ok(0) => Ok(None),
+            Ok(seconds) => Ok(Some(Duration::from_secs(seconds))),
+            Err(_) => Err(format!(
+                "expected a number of seconds or `none`, but got `{arg}`"
+            )),
+        },
+    }
+}
+
+#[cfg(not(windows))]
+fn module_test_report(
+    results: &ModuleTestResults,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &roc_module::symbol::Interns,
+) -> test_report::ModuleReport {
+    use roc_region::all::LineInfo;
+
+    let (path, source) = sources.get(&results.module_id).unwrap();
+    let line_info = LineInfo::new(source);
+    let module_name = interns.module_name(results.module_id).as_str();
+
+    let expects = results
+        .outcomes
+        .iter()
+        .map(|outcome| {
+            let name = match outcome.def_name {
+                Some(def) => format!("{module_name}.{}", def.as_str(interns)),
+                None => module_name.to_string(),
+            };
+
+            test_report::expect_report(
+                source,
+                &line_info,
+                name,
+                outcome.region,
+                outcome.duration,
+                outcome.failure.clone(),
+            )
+        })
+        .collect();
+
+    test_report::ModuleReport {
+        module_name: module_name.to_string(),
+        path: path.clone(),
+        duration: results.tests_duration,
+        expects,
     }
 }
 
 fn find_all_roc_files(path: &PathBuf, flatten_paths: &mut Vec<PathBuf>) {
     if path.is_dir() {
         if let Ok(entries) = std::fs::read_dir(path) {
-            entries.for_each(|entry| {
-                if let Ok(entry) = entry {
-                    let entry_path = entry.path();
-                    find_all_roc_files(&entry_path, flatten_paths);
-                }
-            });
+            // read_dir's order depends on the file system, and we want the same order everywhere
+            let mut entry_paths: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+            entry_paths.sort();
+
+            for entry_path in entry_paths.iter() {
+                find_all_roc_files(entry_path, flatten_paths);
+            }
         } else {
             eprintln!(
                 "\nSomething went wrong opening the directory {}\n",
@@ -723,6 +1429,7 @@ fn print_test_results(
         failed_count,
         passed_count,
         tests_duration,
+        outcomes: _,
     } = module_test_results;
 
     let test_summary_str = test_summary(failed_count, passed_count, tests_duration);
@@ -849,11 +1556,14 @@ pub fn build(
                 format!("{total_time_ms} ms")
             };
             let created_path = path.with_file_name(&filename);
+            // file:// URLs need an absolute path
+            let absolute_path = env::current_dir()?.join(&created_path);
 
             println!(
-                "\nBundled \x1B[33m{}\x1B[39m and its dependent files into the following archive in {total_time}:\n\n\t\x1B[33m{}\x1B[39m\n\nTo distribute this archive as a package, upload this to some URL and then add it as a dependency with:\n\n\t\x1B[32m\"https://your-url-goes-here/{filename}\"\x1B[39m\n",
+                "\nBundled \x1B[33m{}\x1B[39m and its dependent files into the following archive in {total_time}:\n\n\t\x1B[33m{}\x1B[39m\n\nTo distribute this archive as a package, upload this to some URL and then add it as a dependency with:\n\n\t\x1B[32m\"https://your-url-goes-here/{filename}\"\x1B[39m\n\nTo try it out before uploading it, add it as a dependency with:\n\n\t\x1B[32m\"file://{}\"\x1B[39m\n",
                 path.to_string_lossy(),
-                created_path.to_string_lossy()
+                created_path.to_string_lossy(),
+                absolute_path.to_string_lossy()
             );
 
             return Ok(0);
@@ -876,7 +1586,14 @@ pub fn build(
         if matches!(target.architecture(), Architecture::Wasm32) {
             CodeGenBackend::Wasm
         } else {
-            CodeGenBackend::Assembly(AssemblyBackendMode::Binary)
+            let backend_mode = match opt_level {
+                OptLevel::Development => AssemblyBackendMode::BinaryDev,
+                OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
+                    AssemblyBackendMode::Binary
+                }
+            };
+
+            CodeGenBackend::Assembly(backend_mode)
         }
     } else {
         let backend_mode = match opt_level {
@@ -3,18 +3,19 @@ use bumpalo::Bump;
 use roc_build::link::LinkType;
 use roc_build::program::{check_file, CodeGenBackend};
 use roc_cli::{
-    build_app, format_files, format_src, test, BuildConfig, FormatMode, CMD_BUILD, CMD_CHECK,
-    CMD_DEV, CMD_DOCS, CMD_FORMAT, CMD_GLUE, CMD_PREPROCESS_HOST, CMD_REPL, CMD_RUN, CMD_TEST,
-    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_LIB, FLAG_MAIN, FLAG_NO_COLOR,
+    build_app, cache_command, format_files, format_src, roc_cache_dir, test, vendor, vendor_dir,
+    BuildConfig, FormatMode, CMD_BUILD, CMD_CACHE, CMD_CHECK, CMD_DEV, CMD_DOCS, CMD_FORMAT,
+    CMD_GLUE, CMD_PREPROCESS_HOST, CMD_REPL, CMD_RUN, CMD_TEST, CMD_VENDOR, CMD_VERSION,
+    DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_DOCS_FORMAT, FLAG_LIB, FLAG_MAIN, FLAG_NO_COLOR,
     FLAG_NO_HEADER, FLAG_NO_LINK, FLAG_OUTPUT, FLAG_PP_DYLIB, FLAG_PP_HOST, FLAG_PP_PLATFORM,
     FLAG_STDIN, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME, GLUE_DIR, GLUE_SPEC, ROC_FILE, VERSION,
 };
-use roc_docs::generate_docs_html;
+use roc_docs::{generate_docs_html, generate_docs_json};
 use roc_error_macros::user_error;
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
 use roc_load::{LoadingProblem, Threading};
-use roc_packaging::cache::{self, RocCacheDir};
+use roc_packaging::cache;
 use roc_target::Target;
 use std::fs::{self, FileType};
 use std::io::BufRead;
@@ -40,17 +41,20 @@ fn main() -> io::Result<()> {
         .map(|c| c.get_name().to_owned())
         .collect();
     let matches = app.get_matches();
+    let cache_dir = cache::roc_cache_packages_dir();
 
     let exit_code = match matches.subcommand() {
         None => {
             if matches.contains_id(ROC_FILE) {
+                let vendor_dir = vendor_dir(&matches);
+
                 build(
                     &matches,
                     &subcommands,
                     BuildConfig::BuildAndRunIfNoErrors,
                     Triple::host().into(),
                     None,
-                    RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                    roc_cache_dir(&matches, &cache_dir, &vendor_dir),
                     LinkType::Executable,
                 )
             } else {
@@ -59,13 +63,15 @@ fn main() -> io::Result<()> {
         }
         Some((CMD_RUN, matches)) => {
             if matches.contains_id(ROC_FILE) {
+                let vendor_dir = vendor_dir(matches);
+
                 build(
                     matches,
                     &subcommands,
                     BuildConfig::BuildAndRun,
                     Triple::host().into(),
                     None,
-                    RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                    roc_cache_dir(matches, &cache_dir, &vendor_dir),
                     LinkType::Executable,
                 )
             } else {
@@ -85,13 +91,15 @@ fn main() -> io::Result<()> {
         }
         Some((CMD_DEV, matches)) => {
             if matches.contains_id(ROC_FILE) {
+                let vendor_dir = vendor_dir(matches);
+
                 build(
                     matches,
                     &subcommands,
                     BuildConfig::BuildAndRunIfNoErrors,
                     Triple::host().into(),
                     None,
-                    RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                    roc_cache_dir(matches, &cache_dir, &vendor_dir),
                     LinkType::Executable,
                 )
             } else {
@@ -100,6 +108,8 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
+        Some((CMD_VENDOR, matches)) => vendor(matches),
+        Some((CMD_CACHE, matches)) => cache_command(matches),
         Some((CMD_GLUE, matches)) => {
             let input_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let output_path = matches.get_one::<PathBuf>(GLUE_DIR).unwrap();
@@ -182,6 +192,7 @@ fn main() -> io::Result<()> {
             let out_path = matches
                 .get_one::<OsString>(FLAG_OUTPUT)
                 .map(OsString::as_ref);
+            let vendor_dir = vendor_dir(matches);
 
             Ok(build(
                 matches,
@@ -189,7 +200,7 @@ fn main() -> io::Result<()> {
                 BuildConfig::BuildOnly,
                 target,
                 out_path,
-                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                roc_cache_dir(matches, &cache_dir, &vendor_dir),
                 link_type,
             )?)
         }
@@ -206,6 +217,7 @@ fn main() -> io::Result<()> {
             };
 
             let opt_main_path = matches.get_one::<PathBuf>(FLAG_MAIN);
+            let vendor_dir = vendor_dir(matches);
 
             match roc_file_path.extension().and_then(OsStr::to_str) {
                 Some("md") => {
@@ -242,7 +254,7 @@ fn main() -> io::Result<()> {
                             file.path().to_owned(),
                             opt_main_path.cloned(),
                             emit_timings,
-                            RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                            roc_cache_dir(matches, &cache_dir, &vendor_dir),
                             threading,
                         ) {
                             Ok((problems, total_time)) => {
@@ -273,7 +285,7 @@ fn main() -> io::Result<()> {
                         roc_file_path.to_owned(),
                         opt_main_path.cloned(),
                         emit_timings,
-                        RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                        roc_cache_dir(matches, &cache_dir, &vendor_dir),
                         threading,
                     ) {
                         Ok((problems, total_time)) => {
@@ -303,7 +315,13 @@ fn main() -> io::Result<()> {
             let root_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let out_dir = matches.get_one::<OsString>(FLAG_OUTPUT).unwrap();
 
-            generate_docs_html(root_path.to_owned(), out_dir.as_ref());
+            match matches
+                .get_one::<String>(FLAG_DOCS_FORMAT)
+                .map(|s| s.as_str())
+            {
+                Some("json") => generate_docs_json(root_path.to_owned(), out_dir.as_ref()),
+                _ => generate_docs_html(root_path.to_owned(), out_dir.as_ref()),
+            }
 
             Ok(0)
         }
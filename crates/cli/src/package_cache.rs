@@ -0,0 +1,205 @@
+//! `roc cache`, for looking after the packages downloaded into roc_cache_packages_dir()
+use crate::{
+    CMD_CACHE_LIST, CMD_CACHE_PRUNE, CMD_CACHE_VERIFY, FLAG_DRY_RUN, FLAG_MAX_SIZE_MB, FLAG_REMOVE,
+    FLAG_UNUSED_DAYS,
+};
+use clap::ArgMatches;
+use roc_error_macros::internal_error;
+use roc_packaging::inventory::{self, CachedPackage, PrunePolicy};
+use roc_packaging::manifest::{verify_package, Verification};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn cache_command(matches: &ArgMatches) -> io::Result<i32> {
+    let cache_dir = roc_packaging::cache::roc_cache_packages_dir();
+
+    match matches.subcommand() {
+        Some((CMD_CACHE_LIST, _)) => list(&cache_dir),
+        Some((CMD_CACHE_VERIFY, matches)) => verify(&cache_dir, matches.get_flag(FLAG_REMOVE)),
+        Some((CMD_CACHE_PRUNE, matches)) => {
+            let policy = PrunePolicy {
+                unused_for: matches
+                    .get_one::<u64>(FLAG_UNUSED_DAYS)
+                    .map(|days| Duration::from_secs(days * SECONDS_PER_DAY)),
+                max_total_bytes: matches
+                    .get_one::<u64>(FLAG_MAX_SIZE_MB)
+                    .map(|megabytes| megabytes * 1_000_000),
+            };
+
+            if policy.unused_for.is_none() && policy.max_total_bytes.is_none() {
+                eprintln!("\nWhich packages should I remove? Tell me with --{FLAG_UNUSED_DAYS} and/or --{FLAG_MAX_SIZE_MB}.\n");
+
+                return Ok(1);
+            }
+
+            prune(&cache_dir, policy, matches.get_flag(FLAG_DRY_RUN))
+        }
+        _ => internal_error!("clap should have required a `roc cache` subcommand"),
+    }
+}
+
+fn list(cache_dir: &Path) -> io::Result<i32> {
+    let packages = inventory::cached_packages(cache_dir)?;
+
+    if packages.is_empty() {
+        println!("The package cache at {} is empty.", cache_dir.display());
+
+        return Ok(0);
+    }
+
+    let now = SystemTime::now();
+
+    println!("Packages in {}:\n", cache_dir.display());
+
+    for package in packages.iter() {
+        println!(
+            "{:>10}  {:<16}  {}",
+            format_size(package.size_bytes),
+            format_last_used(package, now),
+            package_name(cache_dir, package)
+        );
+    }
+
+    let total_bytes = packages.iter().map(|package| package.size_bytes).sum();
+
+    println!(
+        "\n{} in total.",
+        count_and_size(packages.len(), total_bytes)
+    );
+
+    Ok(0)
+}
+
+fn verify(cache_dir: &Path, remove: bool) -> io::Result<i32> {
+    let packages = inventory::cached_packages(cache_dir)?;
+    let mut ok_count = 0;
+    let mut corrupted_count = 0;
+    let mut unverified_count = 0;
+
+    for package in packages.iter() {
+        let name = package_name(cache_dir, package);
+
+        match verify_package(&package.dir) {
+            Verification::Ok => ok_count += 1,
+            Verification::NoTarball => {
+                unverified_count += 1;
+                println!("Unverified {name}\n    (it was cached before roc kept the tarballs of cached packages, so there's nothing to rehash)");
+            }
+            Verification::Corrupted(problem) => {
+                corrupted_count += 1;
+                println!("\x1B[31mCorrupted\x1B[39m  {name}\n    {problem}");
+
+                if remove {
+                    inventory::remove_package(&package.dir)?;
+                    println!("    Removed it, so it will be downloaded again.");
+                }
+            }
+        }
+    }
+
+    println!("\nChecked {} cached packages: {ok_count} ok, {corrupted_count} corrupted, {unverified_count} unverified.", packages.len());
+
+    if corrupted_count > 0 && !remove {
+        println!("\nRun `roc cache verify --{FLAG_REMOVE}` to remove the corrupted packages, so they get downloaded again.");
+
+        Ok(1)
+    } else {
+        Ok(0)
+    }
+}
+
+fn prune(cache_dir: &Path, policy: PrunePolicy, dry_run: bool) -> io::Result<i32> {
+    let now = SystemTime::now();
+    let packages = inventory::cached_packages(cache_dir)?;
+    let to_prune = inventory::packages_to_prune(packages, policy, now);
+
+    if to_prune.is_empty() {
+        println!("There are no packages to remove.");
+
+        return Ok(0);
+    }
+
+    let action = if dry_run { "Would remove" } else { "Removed" };
+
+    for package in to_prune.iter() {
+        if !dry_run {
+            inventory::remove_package(&package.dir)?;
+        }
+
+        println!(
+            "{action} {} ({}, last used {})",
+            package_name(cache_dir, package),
+            format_size(package.size_bytes),
+            format_last_used(package, now)
+        );
+    }
+
+    if !dry_run {
+        for orphan in inventory::orphaned_files(cache_dir) {
+            std::fs::remove_file(orphan)?;
+        }
+    }
+
+    let total_bytes = to_prune.iter().map(|package| package.size_bytes).sum();
+
+    println!(
+        "\n{action} {}.",
+        count_and_size(to_prune.len(), total_bytes)
+    );
+
+    Ok(0)
+}
+
+/// The package's URL if we know it, or else where it is in the cache
+fn package_name(cache_dir: &Path, package: &CachedPackage) -> String {
+    match &package.url {
+        Some(url) => url.clone(),
+        None => package
+            .dir
+            .strip_prefix(cache_dir)
+            .unwrap_or(&package.dir)
+            .display()
+            .to_string(),
+    }
+}
+
+fn count_and_size(count: usize, total_bytes: u64) -> String {
+    let packages = if count == 1 { "package" } else { "packages" };
+
+    format!("{count} {packages}, {}", format_size(total_bytes))
+}
+
+fn format_size(bytes: u64) -> String {
+    let bytes = bytes as f64;
+
+    if bytes < 1_000.0 {
+        format!("{bytes} B")
+    } else if bytes < 1_000_000.0 {
+        format!("{:.1} KB", bytes / 1_000.0)
+    } else if bytes < 1_000_000_000.0 {
+        format!("{:.1} MB", bytes / 1_000_000.0)
+    } else {
+        format!("{:.1} GB", bytes / 1_000_000_000.0)
+    }
+}
+
+fn format_last_used(package: &CachedPackage, now: SystemTime) -> String {
+    let Some(last_used) = package.last_used else {
+        return "unknown".to_string();
+    };
+    // a last use in the future means the clock changed; it was recent, anyway
+    let unused_for = now.duration_since(last_used).unwrap_or_default();
+
+    let (amount, unit) = match unused_for.as_secs() {
+        secs if secs < 60 => return "just now".to_string(),
+        secs if secs < 60 * 60 => (secs / 60, "minute"),
+        secs if secs < SECONDS_PER_DAY => (secs / (60 * 60), "hour"),
+        secs => (secs / SECONDS_PER_DAY, "day"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+
+    format!("{amount} {unit}{plural} ago")
+}
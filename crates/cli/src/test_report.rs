@@ -0,0 +1,413 @@
+//! Machine-readable reports for `roc test --report`, so CI can ingest test results.
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use roc_region::all::{LineColumn, LineInfo, Region};
+use roc_repl_expect::run::{ExpectFailure, ExpectFailureKind};
+use serde_json::json;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// A `--report <format>=<path>` argument
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportRequest {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+pub fn parse_report_request(arg: &str) -> Result<ReportRequest, String> {
+    let (format_name, path) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected `junit=<path>` or `json=<path>`, but got `{arg}`"))?;
+
+    let format = match format_name {
+        "junit" => ReportFormat::Junit,
+        "json" => ReportFormat::Json,
+        other => {
+            return Err(format!(
+                "unknown report format `{other}`; use `junit` or `json`"
+            ))
+        }
+    };
+
+    if path.is_empty() {
+        return Err(format!("missing a path after `{format_name}=`"));
+    }
+
+    Ok(ReportRequest {
+        format,
+        path: PathBuf::from(path),
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct ExpectReport {
+    /// `Module.def` for the def the expect sits under, or just `Module`
+    pub name: String,
+    pub region: Region,
+    pub start: LineColumn,
+    pub end: LineColumn,
+    pub duration: Duration,
+    pub failure: Option<ExpectFailure>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleReport {
+    pub module_name: String,
+    pub path: PathBuf,
+    pub duration: Duration,
+    pub expects: Vec<ExpectReport>,
+}
+
+impl ModuleReport {
+    fn failed_count(&self) -> usize {
+        self.expects.iter().filter(|e| e.failure.is_some()).count()
+    }
+
+    fn count(&self, kind: ExpectFailureKind) -> usize {
+        self.expects
+            .iter()
+            .filter(|e| e.failure.as_ref().map(|f| f.kind) == Some(kind))
+            .count()
+    }
+}
+
+/// Where the `expect` keyword itself is. The region of a top-level expect starts at
+/// any comment above it.
+pub fn expect_start(source: &str, region: Region) -> u32 {
+    let start = region.start().offset as usize;
+    let comment_len: usize = source[start..]
+        .split_inclusive('\n')
+        .take_while(|line| line.trim_start().starts_with('#'))
+        .map(|line| line.len())
+        .sum();
+    let rest = &source[start + comment_len..];
+
+    (start + comment_len + (rest.len() - rest.trim_start().len())) as u32
+}
+
+pub fn expect_report(
+    source: &str,
+    line_info: &LineInfo,
+    name: String,
+    region: Region,
+    duration: Duration,
+    failure: Option<ExpectFailure>,
+) -> ExpectReport {
+    ExpectReport {
+        name,
+        region,
+        start: line_info.convert_offset(expect_start(source, region)),
+        end: line_info.convert_pos(region.end()),
+        duration,
+        failure,
+    }
+}
+
+pub fn write_report(request: &ReportRequest, modules: &[ModuleReport]) -> io::Result<()> {
+    let contents = match request.format {
+        ReportFormat::Junit => junit_report(modules),
+        ReportFormat::Json => json_report(modules),
+    };
+
+    if let Some(parent) = request.path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    std::fs::write(&request.path, contents)
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+fn path_str(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn kind_name(kind: ExpectFailureKind) -> &'static str {
+    match kind {
+        ExpectFailureKind::Failed => "failed",
+        ExpectFailureKind::Crashed => "crashed",
+        ExpectFailureKind::TimedOut => "timed_out",
+    }
+}
+
+pub fn junit_report(modules: &[ModuleReport]) -> String {
+    let tests: usize = modules.iter().map(|m| m.expects.len()).sum();
+    let failures: usize = modules
+        .iter()
+        .map(|m| m.count(ExpectFailureKind::Failed))
+        .sum();
+    let errors: usize = modules.iter().map(|m| m.failed_count()).sum::<usize>() - failures;
+    let duration: Duration = modules.iter().map(|m| m.duration).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    let _ = writeln!(
+        xml,
+        r#"<testsuites name="roc test" tests="{tests}" failures="{failures}" errors="{errors}" time="{}">"#,
+        seconds(duration)
+    );
+
+    for module in modules {
+        let module_name = xml_escape(&module.module_name);
+        let file = xml_escape(&path_str(&module.path));
+
+        let _ = writeln!(
+            xml,
+            r#"  <testsuite name="{module_name}" file="{file}" tests="{}" failures="{}" errors="{}" time="{}">"#,
+            module.expects.len(),
+            module.count(ExpectFailureKind::Failed),
+            module.failed_count() - module.count(ExpectFailureKind::Failed),
+            seconds(module.duration)
+        );
+
+        for expect in module.expects.iter() {
+            let _ = write!(
+                xml,
+                r#"    <testcase name="{}" classname="{module_name}" file="{file}" line="{}" time="{}""#,
+                xml_escape(&expect.name),
+                expect.start.line + 1,
+                seconds(expect.duration)
+            );
+
+            match &expect.failure {
+                None => xml.push_str("/>\n"),
+                Some(failure) => {
+                    // JUnit tells assertion failures apart from tests that could not finish
+                    let (element, message) = match failure.kind {
+                        ExpectFailureKind::Failed => ("failure", "expect failed"),
+                        ExpectFailureKind::Crashed => ("error", "expect crashed"),
+                        ExpectFailureKind::TimedOut => ("error", "expect timed out"),
+                    };
+
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <{element} type=\"{}\" message=\"{message}\">{}</{element}>\n    </testcase>",
+                        kind_name(failure.kind),
+                        xml_escape(&failure.report)
+                    );
+                }
+            }
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+pub fn json_report(modules: &[ModuleReport]) -> String {
+    let position = |lc: LineColumn| json!({ "line": lc.line + 1, "column": lc.column + 1 });
+
+    let modules_json: Vec<_> = modules
+        .iter()
+        .map(|module| {
+            let expects: Vec<_> = module
+                .expects
+                .iter()
+                .map(|expect| {
+                    json!({
+                        "name": expect.name,
+                        "passed": expect.failure.is_none(),
+                        "region": {
+                            "start": position(expect.start),
+                            "end": position(expect.end),
+                            "start_offset": expect.region.start().offset,
+                            "end_offset": expect.region.end().offset,
+                        },
+                        "duration_ms": expect.duration.as_secs_f64() * 1000.0,
+                        "failure_kind": expect.failure.as_ref().map(|f| kind_name(f.kind)),
+                        "failure": expect.failure.as_ref().map(|f| f.report.as_str()),
+                    })
+                })
+                .collect();
+
+            json!({
+                "module": module.module_name,
+                "path": path_str(&module.path),
+                "passed": module.expects.len() - module.failed_count(),
+                "failed": module.failed_count(),
+                "duration_ms": module.duration.as_secs_f64() * 1000.0,
+                "expects": expects,
+            })
+        })
+        .collect();
+
+    let failed: usize = modules.iter().map(|m| m.failed_count()).sum();
+    let total: usize = modules.iter().map(|m| m.expects.len()).sum();
+
+    let report = json!({
+        "passed": total - failed,
+        "failed": failed,
+        "modules": modules_json,
+    });
+
+    let mut json = serde_json::to_string_pretty(&report).unwrap();
+    json.push('\n');
+    json
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML 1.0 doesn't allow most control characters, even escaped
+            '\t' | '\n' | '\r' => escaped.push(ch),
+            ch if ch.is_control() => {}
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_region::all::Position;
+
+    const SOURCE: &str =
+        "module [add]\n\nadd = \\a, b -> a + b\n\n# adding works\nexpect add 1 2 == 3\n";
+
+    fn sample_modules() -> Vec<ModuleReport> {
+        let line_info = LineInfo::new(SOURCE);
+        let comment_start = SOURCE.find("# adding").unwrap() as u32;
+        let region = Region::new(
+            Position::new(comment_start),
+            Position::new(SOURCE.len() as u32 - 1),
+        );
+
+        vec![ModuleReport {
+            module_name: "Add".to_string(),
+            path: PathBuf::from("src/Add.roc"),
+            duration: Duration::from_millis(3),
+            expects: vec![
+                expect_report(
+                    SOURCE,
+                    &line_info,
+                    "Add.add".to_string(),
+                    region,
+                    Duration::from_millis(1),
+                    None,
+                ),
+                expect_report(
+                    SOURCE,
+                    &line_info,
+                    "Add.add".to_string(),
+                    region,
+                    Duration::from_millis(2),
+                    Some(ExpectFailure {
+                        kind: ExpectFailureKind::Failed,
+                        report: "This expectation failed:\n\n1 < 2 & \"x\"\n".to_string(),
+                    }),
+                ),
+                expect_report(
+                    SOURCE,
+                    &line_info,
+                    "Add.add".to_string(),
+                    region,
+                    Duration::from_millis(0),
+                    Some(ExpectFailure {
+                        kind: ExpectFailureKind::TimedOut,
+                        report: "This expectation did not finish within 1 second.\n".to_string(),
+                    }),
+                ),
+            ],
+        }]
+    }
+
+    #[test]
+    fn parse_requests() {
+        assert_eq!(
+            parse_report_request("junit=out/report.xml"),
+            Ok(ReportRequest {
+                format: ReportFormat::Junit,
+                path: PathBuf::from("out/report.xml"),
+            })
+        );
+        assert_eq!(
+            parse_report_request("json=report.json").map(|r| r.format),
+            Ok(ReportFormat::Json)
+        );
+        assert!(parse_report_request("report.xml").is_err());
+        assert!(parse_report_request("tap=report.tap").is_err());
+        assert!(parse_report_request("json=").is_err());
+    }
+
+    #[test]
+    fn expect_start_skips_comments() {
+        let modules = sample_modules();
+        let expect = &modules[0].expects[0];
+
+        // `expect` is on line 6, even though the region starts at the comment on line 5
+        assert_eq!(expect.start, LineColumn { line: 5, column: 0 });
+    }
+
+    #[test]
+    fn junit() {
+        let xml = junit_report(&sample_modules());
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(
+            r#"<testsuites name="roc test" tests="3" failures="1" errors="1" time="0.003">"#
+        ));
+        assert!(xml.contains(
+            r#"<testsuite name="Add" file="src/Add.roc" tests="3" failures="1" errors="1" time="0.003">"#
+        ));
+        assert!(xml.contains(
+            r#"<testcase name="Add.add" classname="Add" file="src/Add.roc" line="6" time="0.001"/>"#
+        ));
+        assert!(xml.contains(
+            "<failure type=\"failed\" message=\"expect failed\">This expectation failed:\n\n1 &lt; 2 &amp; &quot;x&quot;\n</failure>"
+        ));
+        assert!(xml.contains(
+            "<error type=\"timed_out\" message=\"expect timed out\">This expectation did not finish within 1 second.\n</error>"
+        ));
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+
+    #[test]
+    fn json() {
+        let report: serde_json::Value =
+            serde_json::from_str(&json_report(&sample_modules())).unwrap();
+
+        assert_eq!(report["passed"], 1);
+        assert_eq!(report["failed"], 2);
+
+        let module = &report["modules"][0];
+        assert_eq!(module["module"], "Add");
+        assert_eq!(module["path"], "src/Add.roc");
+
+        let expects = module["expects"].as_array().unwrap();
+        assert_eq!(expects.len(), 3);
+        assert_eq!(expects[0]["passed"], true);
+        assert_eq!(expects[0]["failure"], serde_json::Value::Null);
+        assert_eq!(expects[0]["region"]["start"]["line"], 6);
+        assert_eq!(expects[1]["passed"], false);
+        assert_eq!(expects[1]["failure_kind"], "failed");
+        assert_eq!(
+            expects[1]["failure"],
+            "This expectation failed:\n\n1 < 2 & \"x\"\n"
+        );
+        assert_eq!(expects[2]["failure_kind"], "timed_out");
+    }
+}
@@ -263,6 +263,296 @@ mod cli_tests {
         insta::assert_snapshot!(cli_test_out.normalize_stdout_and_stderr());
     }
 
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_filter_by_module() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .add_args(["--filter", "Transitive"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "0 failed and 1 passed in <ignored for test> ms.\n",
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_filter_by_def() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .add_args(["--filter", "Direct.addAndStringify"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "0 failed and 2 passed in <ignored for test> ms.\n",
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_list() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .arg("--list");
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "expects_transitive/Transitive.roc:7:1  Transitive.add\n",
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_directory_in_path_order() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root("crates/cli/tests/test-projects", "expects_transitive"),
+        )
+        .add_args(["--list", "--max-threads=4"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+
+        // each file is tested on its own, and they're printed in sorted order
+        // even though they're compiled at the same time
+        let output = cli_test_out.normalize_stdout_and_stderr();
+        let listed: Vec<_> = output
+            .lines()
+            .filter_map(|line| line.split("expects_transitive/").nth(1))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                "Direct.roc:10:1  Direct.addAndStringify",
+                "Direct.roc:12:1  Direct.addAndStringify",
+                "Transitive.roc:7:1  Transitive.add",
+                "Transitive.roc:7:1  Transitive.add",
+                "Direct.roc:10:1  Direct.addAndStringify",
+                "Direct.roc:12:1  Direct.addAndStringify",
+                "Transitive.roc:7:1  Transitive.add",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_isolation_timeout() {
+        let report_dir = tempfile::tempdir().unwrap();
+        let json_path = report_dir.path().join("report.json");
+
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_isolation",
+                "main.roc",
+            ),
+        )
+        .add_args([
+            "--timeout=1".to_string(),
+            format!("--report=json={}", json_path.display()),
+        ]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_nonzero_exit();
+
+        let output = cli_test_out.normalize_stdout_and_stderr();
+        assert!(output.contains("EXPECT PANICKED"));
+        assert!(output.contains("EXPECT TIMED OUT"));
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "2 failed and 1 passed in <ignored for test> ms.\n",
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        let kinds: Vec<_> = json["modules"][0]["expects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|expect| expect["failure_kind"].clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                serde_json::json!("crashed"),
+                serde_json::json!("timed_out"),
+                serde_json::Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_fail_fast() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_fail_fast",
+                "main.roc",
+            ),
+        )
+        .arg(concatcp!("--", roc_cli::FLAG_FAIL_FAST));
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_nonzero_exit();
+
+        let output = cli_test_out.normalize_stdout_and_stderr();
+        assert!(output.contains("expect double 1 == 3"), "{output}");
+        assert!(!output.contains("expect double 2 == 5"), "{output}");
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "1 failed and 0 passed in <ignored for test> ms.\n",
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_without_timeout() {
+        for timeout in ["--timeout=0", "--timeout=none"] {
+            let cli_test = ExecCli::new(
+                CMD_TEST,
+                file_from_root(
+                    "crates/cli/tests/test-projects/expects_transitive",
+                    "main.roc",
+                ),
+            )
+            .arg(timeout);
+
+            let cli_test_out = cli_test.run();
+            cli_test_out.assert_clean_success();
+            cli_test_out.assert_stdout_and_stderr_ends_with(
+                "0 failed and 3 passed in <ignored for test> ms.\n",
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_run_while_other_files_compile() {
+        use cli_test_utils::helpers::path_to_roc_binary;
+        use std::io::{BufRead, Write};
+        use std::os::unix::{ffi::OsStrExt, fs::OpenOptionsExt};
+        use std::process::{Command, Stdio};
+        use std::time::{Duration, Instant};
+
+        // Later.roc is a named pipe, so it can't finish compiling until we write to it
+        let dir = tempfile::tempdir().unwrap();
+        let later_path = dir.path().join("Later.roc");
+        let c_later_path = std::ffi::CString::new(later_path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_later_path.as_ptr(), 0o600) }, 0);
+
+        let mut roc = Command::new(path_to_roc_binary())
+            .arg(CMD_TEST)
+            .arg(file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ))
+            .arg(&later_path)
+            .arg("--max-threads=2")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let stdout = roc.stdout.take().unwrap();
+        let (lines_tx, lines_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::BufReader::new(stdout)
+                .lines()
+                .map_while(Result::ok)
+            {
+                if lines_tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // main.roc's expects have to run, and their results print, while Later.roc is compiling
+        let deadline = Instant::now() + Duration::from_secs(120);
+        let mut first_summary = None;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match lines_rx.recv_timeout(remaining) {
+                Ok(line) if line.contains(" passed in ") => {
+                    first_summary = Some(line);
+                    break;
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+
+        // roc is waiting for it, so this doesn't block
+        std::fs::OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&later_path)
+            .unwrap()
+            .write_all(b"module [double]\n\ndouble = \\n -> n * 2\n\nexpect double 2 == 4\n")
+            .unwrap();
+
+        let status = roc.wait().unwrap();
+        let rest: Vec<_> = lines_rx.iter().collect();
+
+        assert!(
+            first_summary.is_some(),
+            "main.roc's expects only ran after Later.roc compiled: {rest:?}"
+        );
+        assert!(status.success(), "{rest:?}");
+        assert!(
+            rest.iter().any(|line| line.contains(" passed in ")),
+            "{rest:?}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_reports() {
+        let report_dir = tempfile::tempdir().unwrap();
+        let json_path = report_dir.path().join("report.json");
+        let junit_path = report_dir.path().join("junit/report.xml");
+
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .add_args([
+            format!("--report=json={}", json_path.display()),
+            format!("--report=junit={}", junit_path.display()),
+        ]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["passed"], 3);
+        assert_eq!(json["failed"], 0);
+
+        let junit = std::fs::read_to_string(&junit_path).unwrap();
+        assert!(junit.contains(r#"<testsuites name="roc test" tests="3" failures="0""#));
+        assert!(junit.contains(r#"<testcase name="Transitive.add" classname="Transitive""#));
+    }
+
     #[test]
     #[cfg_attr(windows, ignore)]
     fn multiple_exposed() {
@@ -774,6 +1064,33 @@ mod cli_tests {
             insta::assert_snapshot!(cli_dev_out.normalize_stdout_and_stderr());
         }
 
+        #[test]
+        #[cfg_attr(
+            not(all(target_os = "linux", target_arch = "x86_64")),
+            ignore = "the dev backend only runs expects on linux x86_64 for now"
+        )]
+        fn expects_dev_backend_failure() {
+            build_platform_host();
+
+            let cli_dev = ExecCli::new(
+                CMD_DEV,
+                file_from_root(
+                    "crates/cli/tests/test-projects/expects",
+                    "expects_dev_backend.roc",
+                ),
+            )
+            .arg(concatcp!("--", roc_cli::FLAG_DEV));
+
+            let cli_dev_out = cli_dev.run();
+            let out = cli_dev_out.normalize_stdout_and_stderr();
+
+            assert!(out.contains("EXPECT FAILED"), "{out}");
+            assert!(out.contains(r#"expect name == "Rust""#), "{out}");
+            assert!(out.contains(r#"name = "Roc""#), "{out}");
+            assert!(out.contains("] x = 42"), "{out}");
+            assert!(out.contains("Program finished!"), "{out}");
+        }
+
         #[test]
         #[cfg_attr(windows, ignore = "running tests does not work on windows right now")]
         fn expects_test_failure() {
@@ -0,0 +1,10 @@
+app [main] { pf: platform "../test-platform-simple-zig/main.roc" }
+
+main =
+    x = 42
+    dbg x
+
+    name = "Roc"
+    expect name == "Rust"
+
+    "Program finished!\n"
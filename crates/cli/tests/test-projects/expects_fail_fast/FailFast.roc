@@ -0,0 +1,10 @@
+module [
+    double,
+]
+
+double : I64 -> I64
+double = \n -> n * 2
+
+expect double 1 == 3
+
+expect double 2 == 5
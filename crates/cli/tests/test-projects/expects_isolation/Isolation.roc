@@ -0,0 +1,16 @@
+module [
+    countUp,
+]
+
+countUp : U64 -> U64
+countUp = \n ->
+    if n == 0 then
+        0
+    else
+        countUp (n + 1)
+
+expect crash "this expect crashes"
+
+expect countUp 1 == 0
+
+expect countUp 0 == 0
@@ -281,10 +281,15 @@ where
 
                 let host_exposed: Vec<_> = symbols
                     .iter()
-                    .map(|symbol| {
+                    .map(|(symbol, arguments)| {
                         (
-                            func_name_bytes_help(*symbol, [], Niche::NONE, layout.result),
-                            [].as_slice(),
+                            func_name_bytes_help(
+                                *symbol,
+                                arguments.iter().copied(),
+                                Niche::NONE,
+                                layout.result,
+                            ),
+                            *arguments,
                         )
                     })
                     .collect();
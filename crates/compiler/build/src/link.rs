@@ -1274,6 +1274,36 @@ pub fn llvm_module_to_dylib(
     target: Target,
     opt_level: OptLevel,
 ) -> Result<Library, Error> {
+    let (_dir, dylib_path) = llvm_module_to_dylib_file(module, target, opt_level);
+
+    // Load the dylib
+    let path = dylib_path.as_path().to_str().unwrap();
+
+    if matches!(target.architecture(), Architecture::Aarch64) {
+        // On AArch64 darwin machines, calling `ldopen` on Roc-generated libs from multiple threads
+        // sometimes fails with
+        //   cannot dlopen until fork() handlers have completed
+        // This may be due to codesigning. In any case, spinning until we are able to dlopen seems
+        // to be okay.
+        loop {
+            match unsafe { Library::new(path) } {
+                Ok(lib) => return Ok(lib),
+                Err(Error::DlOpen { .. }) => continue,
+                Err(other) => return Err(other),
+            }
+        }
+    }
+
+    unsafe { Library::new(path) }
+}
+
+/// Links the module into a dylib without loading it, e.g. so another process can load it.
+/// The dylib is in the returned directory, and is deleted along with it.
+pub fn llvm_module_to_dylib_file(
+    module: &inkwell::module::Module,
+    target: Target,
+    opt_level: OptLevel,
+) -> (tempfile::TempDir, PathBuf) {
     use crate::target::{self, convert_opt_level};
     use inkwell::targets::{FileType, RelocMode};
 
@@ -1309,25 +1339,7 @@ pub fn llvm_module_to_dylib(
         "\n___________\nLinking command failed with status {exit_status:?}:\n\n  {child:?}\n___________\n"
     );
 
-    // Load the dylib
-    let path = dylib_path.as_path().to_str().unwrap();
-
-    if matches!(target.architecture(), Architecture::Aarch64) {
-        // On AArch64 darwin machines, calling `ldopen` on Roc-generated libs from multiple threads
-        // sometimes fails with
-        //   cannot dlopen until fork() handlers have completed
-        // This may be due to codesigning. In any case, spinning until we are able to dlopen seems
-        // to be okay.
-        loop {
-            match unsafe { Library::new(path) } {
-                Ok(lib) => return Ok(lib),
-                Err(Error::DlOpen { .. }) => continue,
-                Err(other) => return Err(other),
-            }
-        }
-    }
-
-    unsafe { Library::new(path) }
+    (dir, dylib_path)
 }
 
 pub fn preprocess_host_wasm32(host_input_path: &Path, host_output_path: &Path) {
@@ -113,13 +113,21 @@ pub fn gen_from_mono_module<'a>(
                 "Wasm backend needs a built host."
             );
 
+            // Like the other backends, only dev builds run dbg and inline expects
+            let backend_mode = match opt {
+                OptLevel::Development => AssemblyBackendMode::BinaryDev,
+                OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
+                    AssemblyBackendMode::Binary
+                }
+            };
+
             gen_from_mono_module_dev(
                 arena,
                 loaded,
                 target,
                 built_host_opt,
                 wasm_dev_stack_bytes,
-                AssemblyBackendMode::Binary, // dummy value, unused in practice
+                backend_mode,
             )
         }
         CodeGenBackend::Assembly(backend_mode) => gen_from_mono_module_dev(
@@ -449,7 +457,13 @@ fn gen_from_mono_module_dev<'a>(
         (BuiltHostOpt::Additive(host_path), Architecture::Wasm32) => {
             #[cfg(feature = "target-wasm32")]
             {
-                gen_from_mono_module_dev_wasm32(arena, loaded, host_path, wasm_dev_stack_bytes)
+                gen_from_mono_module_dev_wasm32(
+                    arena,
+                    loaded,
+                    host_path,
+                    wasm_dev_stack_bytes,
+                    backend_mode,
+                )
             }
 
             #[cfg(not(feature = "target-wasm32"))]
@@ -499,6 +513,7 @@ fn gen_from_mono_module_dev_wasm32<'a>(
     loaded: MonomorphizedModule<'a>,
     built_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    backend_mode: AssemblyBackendMode,
 ) -> GenFromMono<'a> {
     let all_code_gen_start = Instant::now();
     let MonomorphizedModule {
@@ -521,6 +536,7 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         module_id,
         exposed_to_host,
         stack_bytes: wasm_dev_stack_bytes.unwrap_or(roc_gen_wasm::Env::DEFAULT_STACK_BYTES),
+        runs_expects: matches!(backend_mode, AssemblyBackendMode::BinaryDev),
     };
 
     let host_bytes = std::fs::read(built_host_path).unwrap_or_else(|_| {
@@ -95,6 +95,10 @@ const FLOATS = [_]type{ f32, f64 };
 const NUMBERS = INTEGERS ++ FLOATS;
 
 comptime {
+    exportNumFn(num.shiftLeftByI128, "shift_left_by.i128");
+    exportNumFn(num.shiftLeftByU128, "shift_left_by.u128");
+    exportNumFn(num.shiftRightByI128, "shift_right_by.i128");
+    exportNumFn(num.shiftRightByU128, "shift_right_by.u128");
     exportNumFn(num.shiftRightZeroFillI128, "shift_right_zero_fill.i128");
     exportNumFn(num.shiftRightZeroFillU128, "shift_right_zero_fill.u128");
 
@@ -118,6 +122,8 @@ comptime {
     inline for (INTEGERS, 0..) |T, i| {
         num.exportPow(T, ROC_BUILTINS ++ "." ++ NUM ++ ".pow_int.");
         num.exportDivCeil(T, ROC_BUILTINS ++ "." ++ NUM ++ ".div_ceil.");
+        num.exportDivTrunc(T, ROC_BUILTINS ++ "." ++ NUM ++ ".div_trunc.");
+        num.exportRem(T, ROC_BUILTINS ++ "." ++ NUM ++ ".rem.");
 
         num.exportRound(f32, T, ROC_BUILTINS ++ "." ++ NUM ++ ".round_f32.");
         num.exportRound(f64, T, ROC_BUILTINS ++ "." ++ NUM ++ ".round_f64.");
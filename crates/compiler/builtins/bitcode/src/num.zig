@@ -310,6 +310,24 @@ fn isMultipleOf(comptime T: type, lhs: T, rhs: T) bool {
     }
 }
 
+pub fn exportDivTrunc(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(a: T, b: T) callconv(.C) T {
+            return @divTrunc(a, b);
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
+}
+
+pub fn exportRem(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(a: T, b: T) callconv(.C) T {
+            return @rem(a, b);
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
+}
+
 pub fn exportIsMultipleOf(comptime T: type, comptime name: []const u8) void {
     comptime var f = struct {
         fn func(self: T, other: T) callconv(.C) bool {
@@ -534,6 +552,35 @@ pub fn exportMulWrappedInt(comptime T: type, comptime name: []const u8) void {
     @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
 }
 
+pub fn shiftLeftByI128(self: i128, other: u8) callconv(.C) i128 {
+    if (other & 0b1000_0000 > 0) {
+        return 0;
+    } else {
+        return self << @as(u7, @intCast(other));
+    }
+}
+
+pub fn shiftLeftByU128(self: u128, other: u8) callconv(.C) u128 {
+    if (other & 0b1000_0000 > 0) {
+        return 0;
+    } else {
+        return self << @as(u7, @intCast(other));
+    }
+}
+
+pub fn shiftRightByI128(self: i128, other: u8) callconv(.C) i128 {
+    if (other & 0b1000_0000 > 0) {
+        return self >> 127;
+    } else {
+        return self >> @as(u7, @intCast(other));
+    }
+}
+
+// Roc's shiftRightBy extends the sign bit for unsigned integers too
+pub fn shiftRightByU128(self: u128, other: u8) callconv(.C) u128 {
+    return @as(u128, @bitCast(shiftRightByI128(@as(i128, @bitCast(self)), other)));
+}
+
 pub fn shiftRightZeroFillI128(self: i128, other: u8) callconv(.C) i128 {
     if (other & 0b1000_0000 > 0) {
         return 0;
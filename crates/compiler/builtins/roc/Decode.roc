@@ -23,6 +23,8 @@ module [
     list,
     record,
     tuple,
+    tag,
+    dict,
     custom,
     decodeWith,
     fromBytesPartial,
@@ -112,6 +114,24 @@ DecoderFormatting implements
     ## `finalizer` should produce the tuple value from the decoded `state`.
     tuple : state, (state, U64 -> [Next (Decoder state fmt), TooLong]), (state -> Result val DecodeError) -> Decoder val fmt where fmt implements DecoderFormatting
 
+    ## `tag stepTag` decodes a tag union value.
+    ##
+    ## The formatter decodes the name of the tag and passes it to `stepTag`,
+    ## which returns a decoder for that tag's payload, or `Skip` if the name is
+    ## not a tag of the decoded union.
+    ##
+    ## The payload decoder is built with [tuple], so a format should lay out the
+    ## payload of a tag the same way it lays out a tuple.
+    tag : (Str -> [Keep (Decoder val fmt), Skip]) -> Decoder val fmt where fmt implements DecoderFormatting
+
+    ## `dict state insert keyDecoder valueDecoder` decodes a map-shaped value,
+    ## such as a JSON object, by walking its key/value pairs.
+    ##
+    ## Each key is decoded with `keyDecoder` and each value with `valueDecoder`;
+    ## `insert` folds every decoded pair into the `state`, starting from the
+    ## given initial `state`.
+    dict : state, (state, k, v -> state), Decoder k fmt, Decoder v fmt -> Decoder state fmt where fmt implements DecoderFormatting
+
 ## Build a custom [Decoder] function. For example the implementation of
 ## `decodeBool` could be defined as follows;
 ##
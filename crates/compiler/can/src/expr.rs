@@ -3544,6 +3544,28 @@ pub fn toplevel_expect_to_inline_expect_fx(loc_expr: Loc<Expr>) -> Loc<Expr> {
 }
 
 fn toplevel_expect_to_inline_expect_help(mut loc_expr: Loc<Expr>, has_effects: bool) -> Loc<Expr> {
+    if let Expr::Closure(mut closure) = loc_expr.value {
+        // a property expect: the body is the condition, and the arguments are
+        // generated by the test runner, so they should be shown when it fails
+        let argument_lookups = closure
+            .arguments
+            .iter()
+            .filter_map(|(var, _, loc_pattern)| match loc_pattern.value {
+                Pattern::Identifier(symbol) => Some(ExpectLookup {
+                    symbol,
+                    var: *var,
+                    ability_info: None,
+                }),
+                _ => None,
+            });
+        let argument_lookups: Vec<_> = argument_lookups.collect();
+
+        let body = toplevel_expect_to_inline_expect_help(*closure.loc_body, has_effects);
+        closure.loc_body = Box::new(prepend_expect_lookups(body, argument_lookups));
+
+        return Loc::at(loc_expr.region, Expr::Closure(closure));
+    }
+
     enum StoredDef {
         NonRecursive(Region, Box<Def>),
         Recursive(Region, Vec<Def>, IllegalCycleMark),
@@ -3612,6 +3634,32 @@ fn toplevel_expect_to_inline_expect_help(mut loc_expr: Loc<Expr>, has_effects: b
     loc_expr
 }
 
+/// Add lookups to the `Expect` at the end of a chain of defs built by
+/// [toplevel_expect_to_inline_expect_help]
+fn prepend_expect_lookups(mut loc_expr: Loc<Expr>, lookups: Vec<ExpectLookup>) -> Loc<Expr> {
+    let mut current = &mut loc_expr.value;
+
+    loop {
+        match current {
+            Expr::LetNonRec(_, remainder) | Expr::LetRec(_, remainder, _) => {
+                current = &mut remainder.value;
+            }
+            Expr::Expect {
+                lookups_in_cond, ..
+            }
+            | Expr::ExpectFx {
+                lookups_in_cond, ..
+            } => {
+                lookups_in_cond.splice(0..0, lookups);
+                break;
+            }
+            _ => break,
+        }
+    }
+
+    loc_expr
+}
+
 pub struct ExpectCollector {
     pub expects: VecMap<Region, Vec<ExpectLookup>>,
     pub dbgs: VecMap<Symbol, DbgLookup>,
@@ -2779,21 +2779,56 @@ pub fn constrain_decls(
             Expectation => {
                 let loc_expr = &declarations.expressions[index];
 
-                let bool_type = constraints.push_variable(Variable::BOOL);
-                let expected = constraints.push_expected_type(Expected::ForReason(
-                    Reason::ExpectCondition,
-                    bool_type,
-                    loc_expr.region,
-                ));
+                let expect_constraint = match &loc_expr.value {
+                    Expr::Closure(closure) => {
+                        // a property, like `expect \xs -> List.reverse (List.reverse xs) == xs`.
+                        // The arguments can be anything; the body is the condition.
+                        let fn_type = constraints.push_variable(closure.function_type);
+                        let expected = constraints.push_expected_type(NoExpectation(fn_type));
+
+                        let closure_constraint = constrain_expr(
+                            types,
+                            constraints,
+                            &mut env,
+                            loc_expr.region,
+                            &loc_expr.value,
+                            expected,
+                        );
 
-                let expect_constraint = constrain_expr(
-                    types,
-                    constraints,
-                    &mut env,
-                    loc_expr.region,
-                    &loc_expr.value,
-                    expected,
-                );
+                        let body_region = closure.loc_body.region;
+                        let bool_type = constraints.push_variable(Variable::BOOL);
+                        let expected_bool = constraints.push_expected_type(Expected::ForReason(
+                            Reason::ExpectCondition,
+                            bool_type,
+                            body_region,
+                        ));
+                        let body_is_bool = constraints.equal_types_var(
+                            closure.return_type,
+                            expected_bool,
+                            Category::Return,
+                            body_region,
+                        );
+
+                        constraints.and_constraint([closure_constraint, body_is_bool])
+                    }
+                    _ => {
+                        let bool_type = constraints.push_variable(Variable::BOOL);
+                        let expected = constraints.push_expected_type(Expected::ForReason(
+                            Reason::ExpectCondition,
+                            bool_type,
+                            loc_expr.region,
+                        ));
+
+                        constrain_expr(
+                            types,
+                            constraints,
+                            &mut env,
+                            loc_expr.region,
+                            &loc_expr.value,
+                            expected,
+                        )
+                    }
+                };
 
                 constraint = constraints.let_constraint(
                     [],
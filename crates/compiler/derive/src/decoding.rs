@@ -14,8 +14,11 @@ use roc_types::subs::{
 use crate::util::Env;
 use crate::{synth_var, DerivedBody};
 
+mod dict;
 mod list;
 mod record;
+mod set;
+mod tag;
 mod tuple;
 
 pub(crate) fn derive_decoder(
@@ -25,8 +28,11 @@ pub(crate) fn derive_decoder(
 ) -> DerivedBody {
     let (body, body_type) = match key {
         FlatDecodableKey::List() => list::decoder(env, def_symbol),
+        FlatDecodableKey::Set() => set::decoder(env, def_symbol),
+        FlatDecodableKey::Dict() => dict::decoder(env, def_symbol),
         FlatDecodableKey::Record(fields) => record::decoder(env, def_symbol, fields),
         FlatDecodableKey::Tuple(arity) => tuple::decoder(env, def_symbol, arity),
+        FlatDecodableKey::TagUnion(tags) => tag::decoder(env, def_symbol, tags),
     };
 
     let specialization_lambda_sets =
@@ -48,6 +54,83 @@ fn wrap_in_decode_custom_decode_with(
     fmt: (Symbol, Variable),
     sorted_inner_decoder_captures: Vec<(Symbol, Variable)>,
     inner_decoder: (Expr, Variable),
+) -> (Expr, Variable) {
+    let decode_with_call = decode_with(env, bytes, inner_decoder, fmt);
+
+    wrap_in_decode_custom(
+        env,
+        bytes,
+        fmt,
+        sorted_inner_decoder_captures,
+        decode_with_call,
+    )
+}
+
+// Builds `Decode.decodeWith bytes myDecoder fmt : DecodeResult val`.
+fn decode_with(
+    env: &mut Env,
+    bytes_sym: Symbol,
+    inner_decoder: (Expr, Variable),
+    fmt: (Symbol, Variable),
+) -> (Expr, Variable) {
+    use Expr::*;
+
+    let bytes_var = Variable::LIST_U8;
+    let (fmt_sym, fmt_var) = fmt;
+    let (inner_decoder, inner_decoder_var) = inner_decoder;
+
+    // Decode.decodeWith : List U8, Decoder val fmt, fmt -> DecodeResult val where fmt implements DecoderFormatting
+    let decode_with_type = env.import_builtin_symbol_var(Symbol::DECODE_DECODE_WITH);
+
+    // Decode.decodeWith : bytes, inner_decoder, fmt -> DecoderResult (List val)
+    let this_decode_with_var_slice = env
+        .subs
+        .insert_into_vars([bytes_var, inner_decoder_var, fmt_var]);
+    let this_decode_with_clos_var = env.subs.fresh_unnamed_flex_var();
+    let this_decode_with_ret_var = env.subs.fresh_unnamed_flex_var();
+    let this_decode_with_fn_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Func(
+            this_decode_with_var_slice,
+            this_decode_with_clos_var,
+            this_decode_with_ret_var,
+            Variable::PURE,
+        )),
+    );
+
+    //   List U8, Decoder val fmt,         fmt -> DecodeResult val where fmt implements DecoderFormatting
+    // ~ bytes,   Decoder (List elem) fmt, fmt -> DecoderResult (List val)
+    env.unify(decode_with_type, this_decode_with_fn_var);
+
+    let decode_with_var = Var(Symbol::DECODE_DECODE_WITH, this_decode_with_fn_var);
+    let decode_with_fn = Box::new((
+        this_decode_with_fn_var,
+        Loc::at_zero(decode_with_var),
+        this_decode_with_clos_var,
+        this_decode_with_ret_var,
+        Variable::PURE,
+    ));
+    let decode_with_call = Call(
+        decode_with_fn,
+        vec![
+            // bytes inner_decoder fmt
+            (bytes_var, Loc::at_zero(Var(bytes_sym, bytes_var))),
+            (inner_decoder_var, Loc::at_zero(inner_decoder)),
+            (fmt_var, Loc::at_zero(Var(fmt_sym, fmt_var))),
+        ],
+        CalledVia::Space,
+    );
+
+    (decode_with_call, this_decode_with_ret_var)
+}
+
+// Wraps `body : DecodeResult val` in `Decode.custom \bytes, fmt -> body`.
+fn wrap_in_decode_custom(
+    env: &mut Env,
+    bytes: Symbol,
+    fmt: (Symbol, Variable),
+    sorted_inner_decoder_captures: Vec<(Symbol, Variable)>,
+    body: (Expr, Variable),
 ) -> (Expr, Variable) {
     use Expr::*;
 
@@ -59,56 +142,9 @@ fn wrap_in_decode_custom_decode_with(
 
     let (bytes_sym, bytes_var) = (bytes, Variable::LIST_U8);
     let (fmt_sym, fmt_var) = fmt;
-    let (inner_decoder, inner_decoder_var) = inner_decoder;
-
-    // Decode.decodeWith bytes inner_decoder fmt : DecodeResult val
-    let (decode_with_call, decode_with_result_var) = {
-        // Decode.decodeWith : List U8, Decoder val fmt, fmt -> DecodeResult val where fmt implements DecoderFormatting
-        let decode_with_type = env.import_builtin_symbol_var(Symbol::DECODE_DECODE_WITH);
-
-        // Decode.decodeWith : bytes, inner_decoder, fmt -> DecoderResult (List val)
-        let this_decode_with_var_slice =
-            env.subs
-                .insert_into_vars([bytes_var, inner_decoder_var, fmt_var]);
-        let this_decode_with_clos_var = env.subs.fresh_unnamed_flex_var();
-        let this_decode_with_ret_var = env.subs.fresh_unnamed_flex_var();
-        let this_decode_with_fn_var = synth_var(
-            env.subs,
-            Content::Structure(FlatType::Func(
-                this_decode_with_var_slice,
-                this_decode_with_clos_var,
-                this_decode_with_ret_var,
-                Variable::PURE,
-            )),
-        );
-
-        //   List U8, Decoder val fmt,         fmt -> DecodeResult val where fmt implements DecoderFormatting
-        // ~ bytes,   Decoder (List elem) fmt, fmt -> DecoderResult (List val)
-        env.unify(decode_with_type, this_decode_with_fn_var);
-
-        let decode_with_var = Var(Symbol::DECODE_DECODE_WITH, this_decode_with_fn_var);
-        let decode_with_fn = Box::new((
-            this_decode_with_fn_var,
-            Loc::at_zero(decode_with_var),
-            this_decode_with_clos_var,
-            this_decode_with_ret_var,
-            Variable::PURE,
-        ));
-        let decode_with_call = Call(
-            decode_with_fn,
-            vec![
-                // bytes inner_decoder fmt
-                (bytes_var, Loc::at_zero(Var(bytes_sym, bytes_var))),
-                (inner_decoder_var, Loc::at_zero(inner_decoder)),
-                (fmt_var, Loc::at_zero(Var(fmt_sym, fmt_var))),
-            ],
-            CalledVia::Space,
-        );
-
-        (decode_with_call, this_decode_with_ret_var)
-    };
+    let (body, body_var) = body;
 
-    // \bytes, fmt -> Decode.decodeWith bytes myDecoder fmt
+    // \bytes, fmt -> body
     let (custom_lambda, custom_var) = {
         let fn_name = env.new_symbol("custom");
 
@@ -140,16 +176,16 @@ fn wrap_in_decode_custom_decode_with(
             Content::Structure(FlatType::Func(
                 args_slice,
                 fn_clos_var,
-                decode_with_result_var,
+                body_var,
                 Variable::PURE,
             )),
         );
 
-        // \bytes, fmt -[[fn_name]]-> Decode.decodeWith bytes inner_decoder fmt
+        // \bytes, fmt -[[fn_name]]-> body
         let clos = Closure(ClosureData {
             function_type: fn_var,
             closure_type: fn_clos_var,
-            return_type: decode_with_result_var,
+            return_type: body_var,
             fx_type: Variable::PURE,
             early_returns: vec![],
             name: fn_name,
@@ -167,13 +203,13 @@ fn wrap_in_decode_custom_decode_with(
                     Loc::at_zero(Pattern::Identifier(fmt_sym)),
                 ),
             ],
-            loc_body: Box::new(Loc::at_zero(decode_with_call)),
+            loc_body: Box::new(Loc::at_zero(body)),
         });
 
         (clos, fn_var)
     };
 
-    // Decode.custom \bytes, fmt -> Decode.decodeWith bytes inner_decoder fmt
+    // Decode.custom \bytes, fmt -> body
     let (decode_custom_call, decoder_var) = {
         // (List U8, fmt -> DecodeResult val) -> Decoder val fmt where fmt implements DecoderFormatting
         let decode_custom_type = env.import_builtin_symbol_var(Symbol::DECODE_CUSTOM);
@@ -0,0 +1,139 @@
+use roc_can::expr::Expr;
+
+use roc_module::called_via::CalledVia;
+
+use roc_module::symbol::Symbol;
+use roc_region::all::Loc;
+use roc_types::subs::{Content, FlatType, Variable};
+
+use crate::decoding::wrap_in_decode_custom_decode_with;
+use crate::synth_var;
+use crate::util::Env;
+
+pub(crate) fn decoder(env: &mut Env<'_>, _def_symbol: Symbol) -> (Expr, Variable) {
+    // Build
+    //
+    //   def_symbol : Decoder (Dict k v) fmt where k implements Decoding & Hash & Eq, v implements Decoding, fmt implements DecoderFormatting
+    //   def_symbol = Decode.custom \bytes, fmt -> Decode.decodeWith bytes (Decode.dict (Dict.empty {}) Dict.insert Decode.decoder Decode.decoder) fmt
+    //
+    // NB: reduction to `Decode.dict (Dict.empty {}) Dict.insert Decode.decoder Decode.decoder` is not possible to the HRR.
+
+    use Expr::*;
+
+    // Dict.empty {} : Dict k v
+    let (empty_call, empty_var) = {
+        // {} -[clos]-> Dict k v where k implements Hash & Eq
+        let dict_empty_fn_var = env.import_builtin_symbol_var(Symbol::DICT_EMPTY);
+
+        // {} -a-> b
+        let empty_record_var_slice = env.subs.insert_into_vars([Variable::EMPTY_RECORD]);
+        let this_dict_empty_clos_var = env.subs.fresh_unnamed_flex_var();
+        let this_dict_empty_ret_var = env.subs.fresh_unnamed_flex_var();
+        let this_dict_empty_fn_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Func(
+                empty_record_var_slice,
+                this_dict_empty_clos_var,
+                this_dict_empty_ret_var,
+                Variable::PURE,
+            )),
+        );
+
+        //   {} -[clos]-> Dict k v
+        // ~ {} -a     -> b
+        env.unify(dict_empty_fn_var, this_dict_empty_fn_var);
+
+        let dict_empty_fn = Box::new((
+            this_dict_empty_fn_var,
+            Loc::at_zero(Var(Symbol::DICT_EMPTY, this_dict_empty_fn_var)),
+            this_dict_empty_clos_var,
+            this_dict_empty_ret_var,
+            Variable::PURE,
+        ));
+
+        let empty_call = Call(
+            dict_empty_fn,
+            vec![(Variable::EMPTY_RECORD, Loc::at_zero(EmptyRecord))],
+            CalledVia::Space,
+        );
+
+        (empty_call, this_dict_empty_ret_var)
+    };
+
+    // Dict.insert : Dict k v, k, v -> Dict k v where k implements Hash & Eq
+    let insert_var = env.import_builtin_symbol_var(Symbol::DICT_INSERT);
+    let insert = Var(Symbol::DICT_INSERT, insert_var);
+
+    // Decode.decoder : Decoder k fmt where k implements Decoding, fmt implements DecoderFormatting
+    let key_decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
+    let key_decoder = AbilityMember(Symbol::DECODE_DECODER, None, key_decoder_var);
+
+    // Decode.decoder : Decoder v fmt where v implements Decoding, fmt implements DecoderFormatting
+    let value_decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
+    let value_decoder = AbilityMember(Symbol::DECODE_DECODER, None, value_decoder_var);
+
+    // Decode.dict (Dict.empty {}) Dict.insert Decode.decoder Decode.decoder : Decoder (Dict k v) fmt
+    let (decode_dict_call, this_decode_dict_ret_var) = {
+        // Build `Decode.dict (Dict.empty {}) Dict.insert Decode.decoder Decode.decoder` type
+        // state, (state, k, v -> state), Decoder k fmt, Decoder v fmt -[uls]-> Decoder state fmt where fmt implements DecoderFormatting
+        let decode_dict_fn_var = env.import_builtin_symbol_var(Symbol::DECODE_DICT);
+
+        // Dict k v, (Dict k v, k, v -> Dict k v), Decoder k fmt, Decoder v fmt -a-> b
+        let decode_dict_args_slice = env.subs.insert_into_vars([
+            empty_var,
+            insert_var,
+            key_decoder_var,
+            value_decoder_var,
+        ]);
+        let this_decode_dict_clos_var = env.subs.fresh_unnamed_flex_var();
+        let this_decode_dict_ret_var = env.subs.fresh_unnamed_flex_var();
+        let this_decode_dict_fn_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Func(
+                decode_dict_args_slice,
+                this_decode_dict_clos_var,
+                this_decode_dict_ret_var,
+                Variable::PURE,
+            )),
+        );
+
+        //   state,    (state,    k, v -> state),    Decoder k fmt, Decoder v fmt -[uls]-> Decoder state fmt where fmt implements DecoderFormatting
+        // ~ Dict k v, (Dict k v, k, v -> Dict k v), Decoder k fmt, Decoder v fmt -a    -> b
+        env.unify(decode_dict_fn_var, this_decode_dict_fn_var);
+
+        let decode_dict_member = AbilityMember(Symbol::DECODE_DICT, None, this_decode_dict_fn_var);
+        let decode_dict_fn = Box::new((
+            decode_dict_fn_var,
+            Loc::at_zero(decode_dict_member),
+            this_decode_dict_clos_var,
+            this_decode_dict_ret_var,
+            Variable::PURE,
+        ));
+
+        let decode_dict_call = Call(
+            decode_dict_fn,
+            vec![
+                (empty_var, Loc::at_zero(empty_call)),
+                (insert_var, Loc::at_zero(insert)),
+                (key_decoder_var, Loc::at_zero(key_decoder)),
+                (value_decoder_var, Loc::at_zero(value_decoder)),
+            ],
+            CalledVia::Space,
+        );
+
+        (decode_dict_call, this_decode_dict_ret_var)
+    };
+
+    let bytes_sym = env.new_symbol("bytes");
+    let fmt_sym = env.new_symbol("fmt");
+    let fmt_var = env.subs.fresh_unnamed_flex_var();
+    let captures = vec![];
+
+    wrap_in_decode_custom_decode_with(
+        env,
+        bytes_sym,
+        (fmt_sym, fmt_var),
+        captures,
+        (decode_dict_call, this_decode_dict_ret_var),
+    )
+}
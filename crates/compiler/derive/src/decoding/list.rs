@@ -20,76 +20,7 @@ pub(crate) fn decoder(env: &mut Env<'_>, _def_symbol: Symbol) -> (Expr, Variable
     //
     // NB: reduction to `Decode.list Decode.decoder` is not possible to the HRR.
 
-    use Expr::*;
-
-    // Decode.list Decode.decoder : Decoder (List elem) fmt
-    let (decode_list_call, this_decode_list_ret_var) = {
-        // List elem
-        let elem_var = env.subs.fresh_unnamed_flex_var();
-
-        // Decode.decoder : Decoder elem fmt where elem implements Decoding, fmt implements EncoderFormatting
-        let (elem_decoder, elem_decoder_var) = {
-            // build `Decode.decoder : Decoder elem fmt` type
-            // Decoder val fmt where val implements Decoding, fmt implements EncoderFormatting
-            let elem_decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
-
-            // set val ~ elem
-            let val_var = match env.subs.get_content_without_compacting(elem_decoder_var) {
-                Content::Alias(Symbol::DECODE_DECODER_OPAQUE, vars, _, AliasKind::Opaque)
-                    if vars.type_variables_len == 2 =>
-                {
-                    env.subs.get_subs_slice(vars.type_variables())[0]
-                }
-                _ => internal_error!("Decode.decode not an opaque type"),
-            };
-
-            env.unify(val_var, elem_var);
-
-            (
-                AbilityMember(Symbol::DECODE_DECODER, None, elem_decoder_var),
-                elem_decoder_var,
-            )
-        };
-
-        // Build `Decode.list Decode.decoder` type
-        // Decoder val fmt -[uls]-> Decoder (List val) fmt where fmt implements DecoderFormatting
-        let decode_list_fn_var = env.import_builtin_symbol_var(Symbol::DECODE_LIST);
-
-        // Decoder elem fmt -a-> b
-        let elem_decoder_var_slice = env.subs.insert_into_vars([elem_decoder_var]);
-        let this_decode_list_clos_var = env.subs.fresh_unnamed_flex_var();
-        let this_decode_list_ret_var = env.subs.fresh_unnamed_flex_var();
-        let this_decode_list_fn_var = synth_var(
-            env.subs,
-            Content::Structure(FlatType::Func(
-                elem_decoder_var_slice,
-                this_decode_list_clos_var,
-                this_decode_list_ret_var,
-                Variable::PURE,
-            )),
-        );
-
-        //   Decoder val  fmt -[uls]-> Decoder (List val) fmt where fmt implements DecoderFormatting
-        // ~ Decoder elem fmt -a    -> b
-        env.unify(decode_list_fn_var, this_decode_list_fn_var);
-
-        let decode_list_member = AbilityMember(Symbol::DECODE_LIST, None, this_decode_list_fn_var);
-        let decode_list_fn = Box::new((
-            decode_list_fn_var,
-            Loc::at_zero(decode_list_member),
-            this_decode_list_clos_var,
-            this_decode_list_ret_var,
-            Variable::PURE,
-        ));
-
-        let decode_list_call = Call(
-            decode_list_fn,
-            vec![(elem_decoder_var, Loc::at_zero(elem_decoder))],
-            CalledVia::Space,
-        );
-
-        (decode_list_call, this_decode_list_ret_var)
-    };
+    let (decode_list_call, this_decode_list_ret_var) = decode_list_decoder(env);
 
     let bytes_sym = env.new_symbol("bytes");
     let fmt_sym = env.new_symbol("fmt");
@@ -104,3 +35,74 @@ pub(crate) fn decoder(env: &mut Env<'_>, _def_symbol: Symbol) -> (Expr, Variable
         (decode_list_call, this_decode_list_ret_var),
     )
 }
+
+// Builds `Decode.list Decode.decoder : Decoder (List elem) fmt`.
+pub(super) fn decode_list_decoder(env: &mut Env<'_>) -> (Expr, Variable) {
+    use Expr::*;
+
+    // List elem
+    let elem_var = env.subs.fresh_unnamed_flex_var();
+
+    // Decode.decoder : Decoder elem fmt where elem implements Decoding, fmt implements EncoderFormatting
+    let (elem_decoder, elem_decoder_var) = {
+        // build `Decode.decoder : Decoder elem fmt` type
+        // Decoder val fmt where val implements Decoding, fmt implements EncoderFormatting
+        let elem_decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
+
+        // set val ~ elem
+        let val_var = match env.subs.get_content_without_compacting(elem_decoder_var) {
+            Content::Alias(Symbol::DECODE_DECODER_OPAQUE, vars, _, AliasKind::Opaque)
+                if vars.type_variables_len == 2 =>
+            {
+                env.subs.get_subs_slice(vars.type_variables())[0]
+            }
+            _ => internal_error!("Decode.decode not an opaque type"),
+        };
+
+        env.unify(val_var, elem_var);
+
+        (
+            AbilityMember(Symbol::DECODE_DECODER, None, elem_decoder_var),
+            elem_decoder_var,
+        )
+    };
+
+    // Build `Decode.list Decode.decoder` type
+    // Decoder val fmt -[uls]-> Decoder (List val) fmt where fmt implements DecoderFormatting
+    let decode_list_fn_var = env.import_builtin_symbol_var(Symbol::DECODE_LIST);
+
+    // Decoder elem fmt -a-> b
+    let elem_decoder_var_slice = env.subs.insert_into_vars([elem_decoder_var]);
+    let this_decode_list_clos_var = env.subs.fresh_unnamed_flex_var();
+    let this_decode_list_ret_var = env.subs.fresh_unnamed_flex_var();
+    let this_decode_list_fn_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Func(
+            elem_decoder_var_slice,
+            this_decode_list_clos_var,
+            this_decode_list_ret_var,
+            Variable::PURE,
+        )),
+    );
+
+    //   Decoder val  fmt -[uls]-> Decoder (List val) fmt where fmt implements DecoderFormatting
+    // ~ Decoder elem fmt -a    -> b
+    env.unify(decode_list_fn_var, this_decode_list_fn_var);
+
+    let decode_list_member = AbilityMember(Symbol::DECODE_LIST, None, this_decode_list_fn_var);
+    let decode_list_fn = Box::new((
+        decode_list_fn_var,
+        Loc::at_zero(decode_list_member),
+        this_decode_list_clos_var,
+        this_decode_list_ret_var,
+        Variable::PURE,
+    ));
+
+    let decode_list_call = Call(
+        decode_list_fn,
+        vec![(elem_decoder_var, Loc::at_zero(elem_decoder))],
+        CalledVia::Space,
+    );
+
+    (decode_list_call, this_decode_list_ret_var)
+}
@@ -0,0 +1,91 @@
+use roc_can::expr::Expr;
+
+use roc_module::called_via::CalledVia;
+
+use roc_module::symbol::Symbol;
+use roc_region::all::Loc;
+use roc_types::subs::{Content, FlatType, Variable};
+
+use crate::decoding::list::decode_list_decoder;
+use crate::decoding::{decode_with, wrap_in_decode_custom};
+use crate::synth_var;
+use crate::util::Env;
+
+pub(crate) fn decoder(env: &mut Env<'_>, _def_symbol: Symbol) -> (Expr, Variable) {
+    // Build
+    //
+    //   def_symbol : Decoder (Set elem) fmt where elem implements Decoding & Hash & Eq, fmt implements DecoderFormatting
+    //   def_symbol = Decode.custom \bytes, fmt -> Decode.mapResult (Decode.decodeWith bytes (Decode.list Decode.decoder) fmt) Set.fromList
+    //
+    // That is, a set is decoded like a list of its elements.
+
+    use Expr::*;
+
+    let bytes_sym = env.new_symbol("bytes");
+    let fmt_sym = env.new_symbol("fmt");
+    let fmt_var = env.subs.fresh_unnamed_flex_var();
+
+    // Decode.list Decode.decoder : Decoder (List elem) fmt
+    let list_decoder = decode_list_decoder(env);
+
+    // Decode.decodeWith bytes (Decode.list Decode.decoder) fmt : DecodeResult (List elem)
+    let (decode_with_call, decode_with_result_var) =
+        decode_with(env, bytes_sym, list_decoder, (fmt_sym, fmt_var));
+
+    // Set.fromList : List elem -> Set elem where elem implements Hash & Eq
+    let from_list_var = env.import_builtin_symbol_var(Symbol::SET_FROM_LIST);
+    let from_list = Var(Symbol::SET_FROM_LIST, from_list_var);
+
+    // Decode.mapResult (Decode.decodeWith bytes (Decode.list Decode.decoder) fmt) Set.fromList : DecodeResult (Set elem)
+    let (map_result_call, this_map_result_ret_var) = {
+        // DecodeResult a, (a -> b) -> DecodeResult b
+        let map_result_fn_var = env.import_builtin_symbol_var(Symbol::DECODE_MAP_RESULT);
+
+        // DecodeResult (List elem), (List elem -> Set elem) -a-> c
+        let map_result_args_slice = env
+            .subs
+            .insert_into_vars([decode_with_result_var, from_list_var]);
+        let this_map_result_clos_var = env.subs.fresh_unnamed_flex_var();
+        let this_map_result_ret_var = env.subs.fresh_unnamed_flex_var();
+        let this_map_result_fn_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Func(
+                map_result_args_slice,
+                this_map_result_clos_var,
+                this_map_result_ret_var,
+                Variable::PURE,
+            )),
+        );
+
+        //   DecodeResult a,           (a         -> b)        -> DecodeResult b
+        // ~ DecodeResult (List elem), (List elem -> Set elem) -a-> c
+        env.unify(map_result_fn_var, this_map_result_fn_var);
+
+        let map_result_fn = Box::new((
+            this_map_result_fn_var,
+            Loc::at_zero(Var(Symbol::DECODE_MAP_RESULT, this_map_result_fn_var)),
+            this_map_result_clos_var,
+            this_map_result_ret_var,
+            Variable::PURE,
+        ));
+
+        let map_result_call = Call(
+            map_result_fn,
+            vec![
+                (decode_with_result_var, Loc::at_zero(decode_with_call)),
+                (from_list_var, Loc::at_zero(from_list)),
+            ],
+            CalledVia::Space,
+        );
+
+        (map_result_call, this_map_result_ret_var)
+    };
+
+    wrap_in_decode_custom(
+        env,
+        bytes_sym,
+        (fmt_sym, fmt_var),
+        vec![],
+        (map_result_call, this_map_result_ret_var),
+    )
+}
@@ -0,0 +1,288 @@
+use roc_can::expr::{AnnotatedMark, ClosureData, Expr, Recursive, WhenBranch, WhenBranchPattern};
+use roc_can::pattern::Pattern;
+use roc_module::called_via::CalledVia;
+use roc_module::ident::TagName;
+use roc_module::symbol::Symbol;
+use roc_region::all::{Loc, Region};
+use roc_types::subs::{
+    Content, ExhaustiveMark, FlatType, LambdaSet, OptVariable, RedundantMark, TagExt, UnionLambdas,
+    UnionTags, Variable,
+};
+
+use crate::synth_var;
+use crate::util::{Env, ExtensionKind};
+
+use super::tuple::decode_tuple_call;
+use super::wrap_in_decode_custom_decode_with;
+
+/// Implements decoding of a tag union. For example, for
+///
+/// ```text
+///   [A a b, B c]
+/// ```
+///
+/// we'd like to generate an impl like
+///
+/// ```roc
+/// decoder : Decoder [A a b, B c] fmt where a implements Decoding, b implements Decoding, c implements Decoding, fmt implements DecoderFormatting
+/// decoder =
+///     stepTag = \tag ->
+///         when tag is
+///             "A" ->
+///                 Keep (Decode.tuple
+///                     {e0: Err NoElem, e1: Err NoElem}
+///                     (\state, index -> ...)
+///                     (\st ->
+///                         when st.e0 is
+///                             Ok e0 ->
+///                                 when st.e1 is
+///                                     Ok e1 -> Ok (A e0 e1)
+///                                     _ -> Err TooShort
+///                             _ -> Err TooShort))
+///             "B" ->
+///                 Keep (Decode.tuple
+///                     {e0: Err NoElem}
+///                     (\state, index -> ...)
+///                     (\st ->
+///                         when st.e0 is
+///                             Ok e0 -> Ok (B e0)
+///                             _ -> Err TooShort))
+///             _ -> Skip
+///
+///     Decode.custom \bytes, fmt -> Decode.decodeWith bytes (Decode.tag stepTag) fmt
+/// ```
+///
+/// The payload of each tag is decoded exactly like a tuple (see the tuple deriver), except that
+/// the finalizer applies the tag to the decoded elements.
+pub(crate) fn decoder(
+    env: &mut Env,
+    _def_symbol: Symbol,
+    tags: Vec<(TagName, u16)>,
+) -> (Expr, Variable) {
+    // Generalized tag union var so we can reuse this impl between many unions:
+    // if tags = [ A arity=2, B arity=1 ], this is [ A t1 t2, B t3 ] for fresh t1, t2, t3
+    let payload_vars: Vec<Vec<Variable>> = tags
+        .iter()
+        .map(|(_, arity)| {
+            (0..*arity)
+                .map(|_| env.subs.fresh_unnamed_flex_var())
+                .collect()
+        })
+        .collect();
+
+    let tag_union_var = {
+        let union_tags = UnionTags::insert_into_subs(
+            env.subs,
+            tags.iter()
+                .zip(payload_vars.iter())
+                .map(|((tag_name, _), vars)| (tag_name.clone(), vars.iter().copied())),
+        );
+
+        synth_var(
+            env.subs,
+            Content::Structure(FlatType::TagUnion(
+                union_tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        )
+    };
+
+    // stepTag = ...
+    let (step_tag, step_tag_var) = step_tag(env, tag_union_var, tags, &payload_vars);
+
+    // Build up the type of `Decode.tag` we expect
+    let tag_decoder_var = env.subs.fresh_unnamed_flex_var();
+    let decode_tag_lambda_set = env.subs.fresh_unnamed_flex_var();
+    let decode_tag_var = env.import_builtin_symbol_var(Symbol::DECODE_TAG);
+    let this_decode_tag_var = {
+        let flat_type = FlatType::Func(
+            env.subs.insert_into_vars([step_tag_var]),
+            decode_tag_lambda_set,
+            tag_decoder_var,
+            Variable::PURE,
+        );
+
+        synth_var(env.subs, Content::Structure(flat_type))
+    };
+
+    env.unify(decode_tag_var, this_decode_tag_var);
+
+    // Decode.tag stepTag
+    let call_decode_tag = Expr::Call(
+        Box::new((
+            this_decode_tag_var,
+            Loc::at_zero(Expr::AbilityMember(
+                Symbol::DECODE_TAG,
+                None,
+                this_decode_tag_var,
+            )),
+            decode_tag_lambda_set,
+            tag_decoder_var,
+            Variable::PURE,
+        )),
+        vec![(step_tag_var, Loc::at_zero(step_tag))],
+        CalledVia::Space,
+    );
+
+    let bytes_sym = env.new_symbol("bytes");
+    let fmt_sym = env.new_symbol("fmt");
+    let fmt_var = env.subs.fresh_unnamed_flex_var();
+
+    wrap_in_decode_custom_decode_with(
+        env,
+        bytes_sym,
+        (fmt_sym, fmt_var),
+        vec![],
+        (call_decode_tag, tag_decoder_var),
+    )
+}
+
+// Example:
+// stepTag = \tag ->
+//     when tag is
+//         "A" -> Keep (Decode.tuple {e0: Err NoElem, e1: Err NoElem} stepElem finalizer)
+//         "B" -> Keep (Decode.tuple {e0: Err NoElem} stepElem finalizer)
+//         _ -> Skip
+fn step_tag(
+    env: &mut Env,
+    tag_union_var: Variable,
+    tags: Vec<(TagName, u16)>,
+    payload_vars: &[Vec<Variable>],
+) -> (Expr, Variable) {
+    let tag_arg_symbol = env.new_symbol("tag");
+
+    // +1 because of the default branch.
+    let mut branches = Vec::with_capacity(tags.len() + 1);
+    let keep_payload_var = env.subs.fresh_unnamed_flex_var();
+    let keep_or_skip_var = {
+        let keep_payload_subs_slice = env.subs.insert_into_vars([keep_payload_var]);
+        let flat_type = FlatType::TagUnion(
+            UnionTags::insert_slices_into_subs(
+                env.subs,
+                [
+                    ("Keep".into(), keep_payload_subs_slice),
+                    ("Skip".into(), Default::default()),
+                ],
+            ),
+            TagExt::Any(Variable::EMPTY_TAG_UNION),
+        );
+
+        synth_var(env.subs, Content::Structure(flat_type))
+    };
+
+    for ((tag_name, _), tag_payload_vars) in tags.into_iter().zip(payload_vars) {
+        // Decode.tuple initialState stepElem finalizer, where the finalizer produces e.g. `A e0 e1`
+        let (payload_decoder, payload_decoder_var) =
+            decode_tuple_call(env, tag_payload_vars, |env, elems| {
+                let done_tag = Expr::Tag {
+                    tag_union_var,
+                    ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                    name: tag_name.clone(),
+                    arguments: elems
+                        .into_iter()
+                        .map(|(var, expr)| (var, Loc::at_zero(expr)))
+                        .collect(),
+                };
+
+                (done_tag, tag_union_var)
+            });
+
+        env.unify(keep_payload_var, payload_decoder_var);
+
+        // Keep (Decode.tuple initialState stepElem finalizer)
+        let keep = Expr::Tag {
+            tag_union_var: keep_or_skip_var,
+            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+            name: "Keep".into(),
+            arguments: vec![(payload_decoder_var, Loc::at_zero(payload_decoder))],
+        };
+
+        // "A" -> Keep (Decode.tuple initialState stepElem finalizer)
+        let branch = WhenBranch {
+            patterns: vec![WhenBranchPattern {
+                pattern: Loc::at_zero(Pattern::StrLiteral(tag_name.0.as_str().into())),
+                degenerate: false,
+            }],
+            value: Loc::at_zero(keep),
+            guard: None,
+            redundant: RedundantMark::known_non_redundant(),
+        };
+
+        branches.push(branch);
+    }
+
+    // Example: `_ -> Skip`
+    let default_branch = WhenBranch {
+        patterns: vec![WhenBranchPattern {
+            pattern: Loc::at_zero(Pattern::Underscore),
+            degenerate: false,
+        }],
+        value: Loc::at_zero(Expr::Tag {
+            tag_union_var: keep_or_skip_var,
+            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+            name: "Skip".into(),
+            arguments: Vec::new(),
+        }),
+        guard: None,
+        redundant: RedundantMark::known_non_redundant(),
+    };
+
+    branches.push(default_branch);
+
+    // when tag is
+    let body = Expr::When {
+        loc_cond: Box::new(Loc::at_zero(Expr::Var(tag_arg_symbol, Variable::STR))),
+        cond_var: Variable::STR,
+        expr_var: keep_or_skip_var,
+        region: Region::zero(),
+        branches,
+        branches_cond_var: Variable::STR,
+        exhaustive: ExhaustiveMark::known_exhaustive(),
+    };
+
+    let step_tag_closure = env.new_symbol("stepTag");
+    let function_type = env.subs.fresh_unnamed_flex_var();
+    let closure_type = {
+        let lambda_set = LambdaSet {
+            solved: UnionLambdas::tag_without_arguments(env.subs, step_tag_closure),
+            recursion_var: OptVariable::NONE,
+            unspecialized: Default::default(),
+            ambient_function: function_type,
+        };
+
+        synth_var(env.subs, Content::LambdaSet(lambda_set))
+    };
+
+    {
+        let args_slice = env.subs.insert_into_vars([Variable::STR]);
+
+        env.subs.set_content(
+            function_type,
+            Content::Structure(FlatType::Func(
+                args_slice,
+                closure_type,
+                keep_or_skip_var,
+                Variable::PURE,
+            )),
+        )
+    };
+
+    let expr = Expr::Closure(ClosureData {
+        function_type,
+        closure_type,
+        return_type: keep_or_skip_var,
+        fx_type: Variable::PURE,
+        early_returns: vec![],
+        name: step_tag_closure,
+        captured_symbols: Vec::new(),
+        recursive: Recursive::NotRecursive,
+        arguments: vec![(
+            Variable::STR,
+            AnnotatedMark::known_exhaustive(),
+            Loc::at_zero(Pattern::Identifier(tag_arg_symbol)),
+        )],
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    (expr, function_type)
+}
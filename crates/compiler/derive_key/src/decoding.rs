@@ -1,8 +1,11 @@
-use roc_module::{ident::Lowercase, symbol::Symbol};
-use roc_types::subs::{Content, FlatType, Subs, Variable};
+use roc_module::{
+    ident::{Lowercase, TagName},
+    symbol::Symbol,
+};
+use roc_types::subs::{Content, FlatType, GetSubsSlice, Subs, Variable};
 
 use crate::{
-    util::{check_derivable_ext_var, debug_name_record, debug_name_tuple},
+    util::{check_derivable_ext_var, debug_name_record, debug_name_tag, debug_name_tuple},
     DeriveError,
 };
 
@@ -15,18 +18,24 @@ pub enum FlatDecodable {
 #[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub enum FlatDecodableKey {
     List(/* takes one variable */),
+    Set(/* takes one variable */),
+    Dict(/* takes two variables */),
 
     // Unfortunate that we must allocate here, c'est la vie
     Record(Vec<Lowercase>),
     Tuple(u32),
+    TagUnion(Vec<(TagName, u16)>),
 }
 
 impl FlatDecodableKey {
     pub(crate) fn debug_name(&self) -> String {
         match self {
             FlatDecodableKey::List() => "list".to_string(),
+            FlatDecodableKey::Set() => "set".to_string(),
+            FlatDecodableKey::Dict() => "dict".to_string(),
             FlatDecodableKey::Record(fields) => debug_name_record(fields),
             FlatDecodableKey::Tuple(arity) => debug_name_tuple(*arity),
+            FlatDecodableKey::TagUnion(tags) => debug_name_tag(tags),
         }
     }
 }
@@ -39,6 +48,8 @@ impl FlatDecodable {
             Content::Structure(flat_type) => match flat_type {
                 FlatType::Apply(sym, _) => match sym {
                     Symbol::LIST_LIST => Ok(Key(FlatDecodableKey::List())),
+                    Symbol::SET_SET => Ok(Key(FlatDecodableKey::Set())),
+                    Symbol::DICT_DICT => Ok(Key(FlatDecodableKey::Dict())),
                     Symbol::STR_STR => Ok(Immediate(Symbol::DECODE_STRING)),
                     _ => Err(Underivable),
                 },
@@ -70,16 +81,39 @@ impl FlatDecodable {
 
                     Ok(Key(FlatDecodableKey::Tuple(elems_iter.count() as _)))
                 }
-                FlatType::TagUnion(_tags, _ext) | FlatType::RecursiveTagUnion(_, _tags, _ext) => {
-                    Err(Underivable) // yet
+                FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext) => {
+                    // As with encoding, the recursion var doesn't matter: the derived decoder only
+                    // looks at the surface of the tag union, and decodes each payload with the
+                    // `Decoding` implementation of its (possibly recursive) type.
+                    let (tags_iter, ext) = tags.unsorted_tags_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext.var(), |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyTagUnion))
+                    })?;
+
+                    let mut tag_names_and_payload_sizes: Vec<_> = tags_iter
+                        .tags
+                        .into_iter()
+                        .map(|(name, payload_slice)| {
+                            let payload_size = payload_slice.len();
+                            (name.clone(), payload_size as _)
+                        })
+                        .collect();
+
+                    tag_names_and_payload_sizes.sort_by(|(t1, _), (t2, _)| t1.cmp(t2));
+
+                    Ok(Key(FlatDecodableKey::TagUnion(tag_names_and_payload_sizes)))
                 }
-                FlatType::FunctionOrTagUnion(_name_index, _, _) => {
-                    Err(Underivable) // yet
+                FlatType::FunctionOrTagUnion(names_index, _, _) => {
+                    Ok(Key(FlatDecodableKey::TagUnion(
+                        subs.get_subs_slice(names_index)
+                            .iter()
+                            .map(|t| (t.clone(), 0))
+                            .collect(),
+                    )))
                 }
                 FlatType::EmptyRecord => Ok(Key(FlatDecodableKey::Record(vec![]))),
-                FlatType::EmptyTagUnion => {
-                    Err(Underivable) // yet
-                }
+                FlatType::EmptyTagUnion => Ok(Key(FlatDecodableKey::TagUnion(vec![]))),
                 //
                 FlatType::Func(..) | FlatType::EffectfulFunc => Err(Underivable),
             },
@@ -113,6 +147,10 @@ impl FlatDecodable {
 const fn from_builtin_symbol(symbol: Symbol) -> Option<Result<FlatDecodable, DeriveError>> {
     use FlatDecodable::*;
     match symbol {
+        // `Dict` and `Set` are opaque, but unlike user-defined opaques they don't list a
+        // `Decoding` implementation; their decoders are derived from their type arguments.
+        Symbol::SET_SET => Some(Ok(Key(FlatDecodableKey::Set()))),
+        Symbol::DICT_DICT => Some(Ok(Key(FlatDecodableKey::Dict()))),
         Symbol::BOOL_BOOL => Some(Ok(Immediate(Symbol::DECODE_BOOL))),
         Symbol::NUM_U8 | Symbol::NUM_UNSIGNED8 => Some(Ok(Immediate(Symbol::DECODE_U8))),
         Symbol::NUM_U16 | Symbol::NUM_UNSIGNED16 => Some(Ok(Immediate(Symbol::DECODE_U16))),
@@ -35,6 +35,8 @@ mod run_roc;
 pub enum AssemblyBackendMode {
     /// Assumes primitives (roc_alloc, roc_panic, etc) are provided by the host
     Binary,
+    /// Like `Binary`, but also runs `dbg` and inline `expect`s, reporting failures to `roc dev`
+    BinaryDev,
     /// Provides a testing implementation of primitives (roc_alloc, roc_panic, etc)
     Test,
     /// Provides a testing implementation of primitives (roc_alloc, roc_panic, etc)
@@ -45,6 +47,7 @@ impl AssemblyBackendMode {
     fn generate_allocators(self) -> bool {
         match self {
             AssemblyBackendMode::Binary => false,
+            AssemblyBackendMode::BinaryDev => false,
             AssemblyBackendMode::Test => true,
             AssemblyBackendMode::Repl => true,
         }
@@ -53,6 +56,7 @@ impl AssemblyBackendMode {
     fn generate_roc_panic(self) -> bool {
         match self {
             AssemblyBackendMode::Binary => false,
+            AssemblyBackendMode::BinaryDev => false,
             AssemblyBackendMode::Test => true,
             AssemblyBackendMode::Repl => true,
         }
@@ -61,10 +65,20 @@ impl AssemblyBackendMode {
     fn generate_roc_dbg(self) -> bool {
         match self {
             AssemblyBackendMode::Binary => false,
+            AssemblyBackendMode::BinaryDev => false,
             AssemblyBackendMode::Test => true,
             AssemblyBackendMode::Repl => true,
         }
     }
+
+    fn runs_expects(self) -> bool {
+        match self {
+            AssemblyBackendMode::Binary => false,
+            AssemblyBackendMode::BinaryDev => true,
+            AssemblyBackendMode::Test => false,
+            AssemblyBackendMode::Repl => false,
+        }
+    }
 }
 
 pub struct Env<'a> {
@@ -277,9 +291,30 @@ impl<'a> LastSeenMap<'a> {
                 }
             }
 
-            Stmt::Dbg { .. } => todo!("dbg not implemented in the dev backend"),
-            Stmt::Expect { .. } => todo!("expect is not implemented in the dev backend"),
-            Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the dev backend"),
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                self.set_last_seen(*symbol, stmt);
+                self.scan_ast_help(remainder);
+            }
+            Stmt::Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            }
+            | Stmt::ExpectFx {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                self.set_last_seen(*condition, stmt);
+                for sym in *lookups {
+                    self.set_last_seen(*sym, stmt);
+                }
+                self.scan_ast_help(remainder);
+            }
 
             Stmt::Crash(msg, _crash_tag) => {
                 self.set_last_seen(*msg, stmt);
@@ -706,7 +741,87 @@ trait Backend<'a> {
                 self.free_symbols(stmt);
             }
             Stmt::Crash(msg, crash_tag) => self.roc_panic(*msg, *crash_tag),
-            x => todo!("the statement, {:?}", x),
+            Stmt::Dbg {
+                source_location,
+                source,
+                symbol,
+                remainder,
+                ..
+            } => {
+                if !self.env().mode.runs_expects() {
+                    return self.build_stmt(layout_ids, remainder, ret_layout);
+                }
+
+                // Expand the Dbg statement into a call to roc_dbg
+                let dbg_stmt = {
+                    let (module_id, layout_interner, interns, rc_proc_gen, _) =
+                        self.module_interns_helpers_mut();
+                    let ident_ids = interns.all_ident_ids.get_mut(&module_id).unwrap();
+
+                    rc_proc_gen.expand_dbg_stmt(
+                        ident_ids,
+                        layout_interner,
+                        source_location,
+                        source,
+                        *symbol,
+                        remainder,
+                    )
+                };
+
+                self.build_stmt(layout_ids, dbg_stmt, ret_layout)
+            }
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            }
+            | Stmt::ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                if !self.env().mode.runs_expects() {
+                    return self.build_stmt(layout_ids, remainder, ret_layout);
+                }
+
+                let arena = self.env().arena;
+                let layout_map = self.layout_map();
+                let lookups = Vec::from_iter_in(
+                    lookups
+                        .iter()
+                        .map(|sym| (*sym, *layout_map.get(sym).unwrap())),
+                    arena,
+                );
+
+                // Expand the Expect statement into IR that writes failures to shared memory.
+                // Cloning data structures may need new helper procs.
+                let (expect_stmt, new_specializations) = {
+                    let (module_id, layout_interner, interns, rc_proc_gen, _) =
+                        self.module_interns_helpers_mut();
+                    let ident_ids = interns.all_ident_ids.get_mut(&module_id).unwrap();
+
+                    rc_proc_gen.expand_expect_stmt(
+                        ident_ids,
+                        layout_interner,
+                        *condition,
+                        *region,
+                        &lookups,
+                        variables,
+                        *ret_layout,
+                        remainder,
+                    )
+                };
+
+                for spec in new_specializations.into_iter() {
+                    self.helper_proc_symbols_mut().push(spec);
+                }
+
+                self.build_stmt(layout_ids, expect_stmt, ret_layout)
+            }
         }
     }
 
@@ -1000,6 +1115,9 @@ trait Backend<'a> {
                 initializer,
                 element_layout,
             } => {
+                if let Some(initializer) = initializer {
+                    self.load_literal_symbols(&[*initializer]);
+                }
                 self.build_alloca(*sym, *initializer, *element_layout);
             }
             Expr::RuntimeErrorFunction(_) => todo!(),
@@ -586,7 +586,7 @@ fn build_object<'a, B: Backend<'a>>(
                         Exposed::TestMain,
                     );
                 }
-                AssemblyBackendMode::Binary => { /* do nothing */ }
+                AssemblyBackendMode::Binary | AssemblyBackendMode::BinaryDev => { /* do nothing */ }
             }
 
             build_proc_symbol(
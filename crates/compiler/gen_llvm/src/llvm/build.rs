@@ -5636,11 +5636,11 @@ pub fn build_procedures_expose_expects<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &STLayoutInterner<'a>,
     opt_level: OptLevel,
-    expects_by_module: MutMap<ModuleId, Vec<'a, Symbol>>,
+    expects_by_module: MutMap<ModuleId, Vec<'a, (Symbol, &'a [InLayout<'a>])>>,
     procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
 ) -> MutMap<ModuleId, Vec<'a, &'a str>> {
     // converts Vec<Vec<Symbol>> into Vec<Symbol>
-    let flattened_symbols: Vec<Symbol> =
+    let flattened_symbols: Vec<(Symbol, &'a [InLayout<'a>])> =
         Vec::from_iter_in(expects_by_module.values().flatten().copied(), env.arena);
 
     let entry_point = EntryPoint::Expects {
@@ -5659,18 +5659,18 @@ pub fn build_procedures_expose_expects<'a>(
 
     let captures_niche = Niche::NONE;
 
-    let top_level = ProcLayout {
-        arguments: &[],
-        result: Layout::UNIT,
-        niche: captures_niche,
-    };
-
     let mut expect_names_by_module = MutMap::default();
 
     for (module_id, expects) in expects_by_module {
         let mut expect_names = Vec::with_capacity_in(expects.len(), env.arena);
 
-        for symbol in expects.iter().copied() {
+        for (symbol, arguments) in expects.iter().copied() {
+            let top_level = ProcLayout {
+                arguments,
+                result: Layout::UNIT,
+                niche: captures_niche,
+            };
+
             let args_iter = top_level.arguments.iter().copied();
 
             let func_name_bytes = roc_alias_analysis::func_name_bytes_help(
@@ -5705,16 +5705,26 @@ pub fn build_procedures_expose_expects<'a>(
             let expect_name_str = env.arena.alloc_str(expect_name);
             expect_names.push(&*expect_name_str);
 
-            // Add main to the module.
-            let _ = expose_function_to_host_help_c_abi(
-                env,
-                layout_interner,
-                name,
-                roc_main_fn,
-                top_level.arguments,
-                top_level.result,
-                &format!("Expect_{name}"),
-            );
+            if arguments.is_empty() {
+                // Add main to the module.
+                let _ = expose_function_to_host_help_c_abi(
+                    env,
+                    layout_interner,
+                    name,
+                    roc_main_fn,
+                    top_level.arguments,
+                    top_level.result,
+                    &format!("Expect_{name}"),
+                );
+            } else {
+                expose_property_expect_to_host(
+                    env,
+                    layout_interner,
+                    roc_main_fn,
+                    arguments,
+                    expect_name,
+                );
+            }
         }
         expect_names_by_module.insert(module_id, expect_names);
     }
@@ -5722,6 +5732,113 @@ pub fn build_procedures_expose_expects<'a>(
     expect_names_by_module
 }
 
+/// A property expect takes arguments that the test runner generates at runtime, so it can't
+/// know their C types up front. Instead, the wrapper takes a pointer to a struct with one field
+/// per argument, and a pointer to write the `RocCallResult<{}>` into.
+fn expose_property_expect_to_host<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    roc_function: FunctionValue<'ctx>,
+    arguments: &[InLayout<'a>],
+    c_function_name: &str,
+) -> FunctionValue<'ctx> {
+    let builder = env.builder;
+    let context = env.context;
+
+    let return_layout = Layout::UNIT;
+    let return_type = roc_call_result_type(
+        env,
+        basic_type_from_layout(
+            env,
+            layout_interner,
+            layout_interner.get_repr(return_layout),
+        ),
+    );
+
+    let field_types = Vec::from_iter_in(
+        arguments.iter().map(|layout| {
+            basic_type_from_layout(env, layout_interner, layout_interner.get_repr(*layout))
+        }),
+        env.arena,
+    );
+    let arguments_type = context.struct_type(&field_types, false);
+
+    let c_function_spec = FunctionSpec::cconv(
+        env,
+        CCReturn::Void,
+        None,
+        &[
+            arguments_type.ptr_type(AddressSpace::default()).into(),
+            return_type.ptr_type(AddressSpace::default()).into(),
+        ],
+    );
+
+    let c_function = add_func(
+        context,
+        env.module,
+        c_function_name,
+        c_function_spec,
+        Linkage::External,
+    );
+
+    let subprogram = env.new_subprogram(c_function_name);
+    c_function.set_subprogram(subprogram);
+
+    debug_info_init!(env, c_function);
+
+    let entry = context.append_basic_block(c_function, "entry");
+    builder.position_at_end(entry);
+
+    let arguments_ptr = c_function.get_nth_param(0).unwrap().into_pointer_value();
+    let output_ptr = c_function.get_nth_param(1).unwrap().into_pointer_value();
+
+    let mut arguments_for_call = Vec::with_capacity_in(arguments.len(), env.arena);
+
+    for (index, layout) in arguments.iter().enumerate() {
+        let field_ptr = builder.new_build_struct_gep(
+            arguments_type,
+            arguments_ptr,
+            index as u32,
+            "property_argument_ptr",
+        );
+
+        arguments_for_call.push(load_roc_value(
+            env,
+            layout_interner,
+            layout_interner.get_repr(*layout),
+            field_ptr,
+            "property_argument",
+        ));
+    }
+
+    let dbg_loc = builder.get_current_debug_location().unwrap();
+    let roc_wrapper_function =
+        make_exception_catcher(env, layout_interner, roc_function, return_layout);
+
+    builder.position_at_end(entry);
+    builder.set_current_debug_location(dbg_loc);
+
+    let call_result_layout = roc_call_result_layout(env.arena, return_layout);
+    let call_result = call_direct_roc_function(
+        env,
+        layout_interner,
+        roc_wrapper_function,
+        call_result_layout,
+        arguments_for_call.into_bump_slice(),
+    );
+
+    store_roc_value(
+        env,
+        layout_interner,
+        call_result_layout,
+        output_ptr,
+        call_result,
+    );
+    builder.new_build_return(None);
+
+    c_function
+}
+
 fn build_procedures_help<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &STLayoutInterner<'a>,
@@ -15,6 +15,7 @@ use roc_mono::layout::{
     Builtin, InLayout, Layout, LayoutIds, LayoutInterner, LayoutRepr, STLayoutInterner,
     TagIdIntType, UnionLayout,
 };
+use roc_region::all::Region;
 use roc_std::RocDec;
 
 use roc_wasm_module::linking::{DataSymbol, WasmObjectSymbol};
@@ -605,9 +606,26 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 _ => self.stmt_refcounting(modify, following),
             },
 
-            Stmt::Dbg { .. } => todo!("dbg is not implemented in the wasm backend"),
-            Stmt::Expect { .. } => todo!("expect is not implemented in the wasm backend"),
-            Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the wasm backend"),
+            Stmt::Dbg {
+                source_location,
+                source,
+                symbol,
+                variable: _,
+                remainder,
+            } => self.stmt_dbg(source_location, source, *symbol, remainder),
+
+            Stmt::Expect {
+                condition,
+                region,
+                remainder,
+                ..
+            }
+            | Stmt::ExpectFx {
+                condition,
+                region,
+                remainder,
+                ..
+            } => self.stmt_expect(*condition, *region, remainder),
 
             Stmt::Crash(sym, tag) => self.stmt_crash(*sym, *tag),
         }
@@ -899,6 +917,105 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         self.stmt(following);
     }
 
+    fn stmt_dbg(
+        &mut self,
+        source_location: &'a str,
+        source: &'a str,
+        symbol: Symbol,
+        remainder: &'a Stmt<'a>,
+    ) {
+        if !self.env.runs_expects {
+            return self.stmt(remainder);
+        }
+
+        let ident_ids = self
+            .interns
+            .all_ident_ids
+            .get_mut(&self.env.module_id)
+            .unwrap();
+
+        // Becomes a call to the platform's `roc_dbg`, with the strings passed by reference
+        let dbg_stmt = self.helper_proc_gen.expand_dbg_stmt(
+            ident_ids,
+            self.layout_interner,
+            source_location,
+            source,
+            symbol,
+            remainder,
+        );
+
+        self.stmt(dbg_stmt);
+    }
+
+    /// Inline expects call `roc_expect_failed(module_name: *RocStr, region_start: u32, region_end: u32)`
+    /// when the condition is false. There's no shared memory for the looked-up values in Wasm,
+    /// so the host only gets told where the failure was. Platforms that don't provide
+    /// `roc_expect_failed` get a `roc_panic` instead, so the failure isn't lost.
+    fn stmt_expect(&mut self, condition: Symbol, region: Region, remainder: &'a Stmt<'a>) {
+        if !self.env.runs_expects {
+            return self.stmt(remainder);
+        }
+
+        let module_name = self.interns.module_name(self.env.module_id).as_str();
+        let module_name = self.env.arena.alloc_str(module_name);
+        let host_has_expect_failed = self
+            .host_lookup
+            .iter()
+            .any(|(fn_name, _)| *fn_name == "roc_expect_failed");
+
+        self.start_block();
+
+        // skip the failure report if the condition is true
+        self.storage
+            .load_symbols(&mut self.code_builder, &[condition]);
+        self.code_builder.br_if(0);
+
+        if host_has_expect_failed {
+            let name_sym = self.str_literal_var("expect_module_name", module_name);
+
+            // load the pointer
+            self.storage
+                .load_symbols(&mut self.code_builder, &[name_sym]);
+            self.code_builder.i32_const(region.start().offset as i32);
+            self.code_builder.i32_const(region.end().offset as i32);
+            self.call_host_fn_after_loading_args("roc_expect_failed");
+        } else {
+            let msg = bumpalo::format!(
+                in self.env.arena,
+                "An expect failed in {} at bytes {}..{}",
+                module_name,
+                region.start().offset,
+                region.end().offset
+            );
+            let msg_sym = self.str_literal_var("expect_failed_msg", msg.into_bump_str());
+            self.stmt_crash(msg_sym, CrashTag::User);
+        }
+
+        self.end_block();
+
+        self.stmt(remainder);
+    }
+
+    /// Store a string literal in a new variable on the stack
+    fn str_literal_var(&mut self, name: &str, string: &'a str) -> Symbol {
+        let sym = self.create_symbol(name);
+        let storage = self.storage.allocate_var(
+            self.layout_interner,
+            Layout::STR,
+            sym,
+            StoredVarKind::Variable,
+        );
+        let (local_id, offset) = match storage {
+            StoredValue::StackMemory { location, .. } => {
+                location.local_and_offset(self.storage.stack_frame_pointer)
+            }
+            _ => internal_error!("String must always have stack memory"),
+        };
+        self.expr_string_literal(string, local_id, offset);
+
+        sym
+    }
+
     pub fn stmt_internal_error(&mut self, msg: &'a str) {
         let msg_sym = self.create_symbol("panic_str");
         let msg_storage = self.storage.allocate_var(
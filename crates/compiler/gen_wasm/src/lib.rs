@@ -46,6 +46,9 @@ pub struct Env<'a> {
     pub module_id: ModuleId,
     pub exposed_to_host: MutSet<Symbol>,
     pub stack_bytes: u32,
+    /// Run `dbg` and inline `expect`s, like the other backends do in dev builds.
+    /// Otherwise they're left out of the app.
+    pub runs_expects: bool,
 }
 
 impl Env<'_> {
@@ -10,6 +10,7 @@ use roc_mono::layout::{Builtin, InLayout, Layout, LayoutInterner, LayoutRepr, Un
 use roc_mono::low_level::HigherOrder;
 
 use crate::backend::{ProcLookupData, ProcSource, WasmBackend};
+use crate::code_builder::CodeBuilder;
 use crate::layout::{StackMemoryFormat, WasmLayout};
 use crate::storage::{AddressValue, StackMemoryLocation, StoredValue};
 use crate::PTR_TYPE;
@@ -120,6 +121,24 @@ fn symbol_is_signed_int(backend: &WasmBackend<'_, '_>, symbol: Symbol) -> bool {
     layout_is_signed_int(backend.storage.symbol_layouts[&symbol])
 }
 
+fn symbol_int_width(backend: &WasmBackend<'_, '_>, symbol: Symbol) -> IntWidth {
+    let layout = backend.storage.symbol_layouts[&symbol];
+    match backend.layout_interner.get_repr(layout) {
+        LayoutRepr::Builtin(Builtin::Int(width)) => width,
+        x => internal_error!("Expected an integer but got {:?}", x),
+    }
+}
+
+/// The pointer and offset of a 128-bit number, which is always in stack memory
+fn num128_location(backend: &WasmBackend<'_, '_>, storage: &StoredValue) -> (LocalId, u32) {
+    match storage {
+        StoredValue::StackMemory { location, .. } => {
+            location.local_and_offset(backend.storage.stack_frame_pointer)
+        }
+        _ => internal_error!("128-bit numbers should be in stack memory"),
+    }
+}
+
 pub struct LowLevelCall<'a> {
     pub lowlevel: LowLevel,
     pub arguments: &'a [Symbol],
@@ -148,6 +167,13 @@ impl<'a> LowLevelCall<'a> {
         backend.call_host_fn_after_loading_args(name);
     }
 
+    fn ret_int_width(&self) -> IntWidth {
+        match self.ret_layout_raw {
+            LayoutRepr::Builtin(Builtin::Int(width)) => width,
+            x => internal_error!("{:?} should return an integer, not {:?}", self.lowlevel, x),
+        }
+    }
+
     /// Wrap an integer that should have less than 32 bits, but is represented in Wasm as i32.
     /// This may seem like deliberately introducing an error!
     /// But we want all targets to behave the same, and hash algos rely on wrapping.
@@ -1380,8 +1406,15 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.f64_lt();
                         backend.code_builder.i32_add();
                     }
-                    I128 | Decimal => {
-                        self.load_args(backend);
+                    I128 => {
+                        let width = if is_signed {
+                            IntWidth::I128
+                        } else {
+                            IntWidth::U128
+                        };
+                        self.load_args_and_call_zig(backend, &bitcode::NUM_COMPARE[width]);
+                    }
+                    Decimal => {
                         self.load_args_and_call_zig(backend, &bitcode::NUM_COMPARE[IntWidth::I128]);
                     }
                 }
@@ -1413,6 +1446,10 @@ impl<'a> LowLevelCall<'a> {
                             backend.code_builder.i64_div_u()
                         }
                     }
+                    I128 => {
+                        let width = self.ret_int_width();
+                        backend.call_host_fn_after_loading_args(&bitcode::NUM_DIV_TRUNC[width]);
+                    }
                     x => todo!("{:?} for {:?}", self.lowlevel, x),
                 }
             }
@@ -1428,6 +1465,10 @@ impl<'a> LowLevelCall<'a> {
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => backend.code_builder.i32_rem_s(),
                     I64 => backend.code_builder.i64_rem_s(),
+                    I128 => {
+                        let width = self.ret_int_width();
+                        backend.call_host_fn_after_loading_args(&bitcode::NUM_REM[width]);
+                    }
                     _ => todo!("{:?} for {:?}", self.lowlevel, self.ret_layout),
                 }
             }
@@ -1527,6 +1568,11 @@ impl<'a> LowLevelCall<'a> {
                         code_builder.get_local(tmp);
                     }
 
+                    I128 => {
+                        let width = symbol_int_width(backend, lhs);
+                        self.load_args_and_call_zig(backend, &bitcode::NUM_IS_MULTIPLE_OF[width]);
+                    }
+
                     _ => panic_ret_type(),
                 }
             }
@@ -1534,6 +1580,10 @@ impl<'a> LowLevelCall<'a> {
                 const PANIC_MSG: &str =
                     "Integer absolute overflowed because its argument is the minimum value";
 
+                if CodeGenNumType::from(self.ret_layout) == I128 {
+                    return self.num128_abs_or_neg(backend, PANIC_MSG);
+                }
+
                 self.load_args(backend);
 
                 match CodeGenNumType::from(self.ret_layout) {
@@ -1598,6 +1648,10 @@ impl<'a> LowLevelCall<'a> {
                 const PANIC_MSG: &str =
                     "Integer negation overflowed because its argument is the minimum value";
 
+                if CodeGenNumType::from(self.ret_layout) == I128 {
+                    return self.num128_abs_or_neg(backend, PANIC_MSG);
+                }
+
                 self.load_args(backend);
                 match CodeGenNumType::from(self.ret_layout) {
                     I32 => {
@@ -1688,6 +1742,19 @@ impl<'a> LowLevelCall<'a> {
                     (F64, F32) => backend.code_builder.f64_promote_f32(),
                     (F64, F64) => {}
 
+                    (F32, I128) => {
+                        let int_width = symbol_int_width(backend, self.arguments[0]);
+                        backend.call_host_fn_after_loading_args(
+                            &bitcode::INT_TO_FLOAT_CAST_F32[int_width],
+                        );
+                    }
+                    (F64, I128) => {
+                        let int_width = symbol_int_width(backend, self.arguments[0]);
+                        backend.call_host_fn_after_loading_args(
+                            &bitcode::INT_TO_FLOAT_CAST_F64[int_width],
+                        );
+                    }
+
                     (Decimal, I32) => {
                         let int_width = match arg_is_signed {
                             true => IntWidth::I32,
@@ -1704,6 +1771,14 @@ impl<'a> LowLevelCall<'a> {
 
                         self.load_args_and_call_zig(backend, &bitcode::DEC_FROM_INT[int_width]);
                     }
+                    (Decimal, I128) => {
+                        let int_width = match arg_is_signed {
+                            true => IntWidth::I128,
+                            false => IntWidth::U128,
+                        };
+
+                        self.load_args_and_call_zig(backend, &bitcode::DEC_FROM_INT[int_width]);
+                    }
                     (Decimal, F32) => {
                         self.load_args_and_call_zig(
                             backend,
@@ -1773,7 +1848,7 @@ impl<'a> LowLevelCall<'a> {
                 let width = match ret_type {
                     CodeGenNumType::I32 => IntWidth::I32,
                     CodeGenNumType::I64 => IntWidth::I64,
-                    CodeGenNumType::I128 => todo!("{:?} for I128", self.lowlevel),
+                    CodeGenNumType::I128 => self.ret_int_width(),
                     _ => internal_error!("Invalid return type for round: {:?}", ret_type),
                 };
 
@@ -1792,11 +1867,28 @@ impl<'a> LowLevelCall<'a> {
                 let width = match ret_type {
                     CodeGenNumType::I32 => IntWidth::I32,
                     CodeGenNumType::I64 => IntWidth::I64,
-                    CodeGenNumType::I128 => todo!("{:?} for I128", self.lowlevel),
+                    CodeGenNumType::I128 => self.ret_int_width(),
                     _ => internal_error!("Invalid return type for round: {:?}", ret_type),
                 };
 
                 match (arg_type, self.lowlevel) {
+                    // Wasm has no instructions to convert floats to 128-bit integers
+                    (F32, NumCeiling) if ret_type == I128 => {
+                        return backend
+                            .call_host_fn_after_loading_args(&bitcode::NUM_CEILING_F32[width]);
+                    }
+                    (F64, NumCeiling) if ret_type == I128 => {
+                        return backend
+                            .call_host_fn_after_loading_args(&bitcode::NUM_CEILING_F64[width]);
+                    }
+                    (F32, NumFloor) if ret_type == I128 => {
+                        return backend
+                            .call_host_fn_after_loading_args(&bitcode::NUM_FLOOR_F32[width]);
+                    }
+                    (F64, NumFloor) if ret_type == I128 => {
+                        return backend
+                            .call_host_fn_after_loading_args(&bitcode::NUM_FLOOR_F64[width]);
+                    }
                     (F32, NumCeiling) => {
                         backend.code_builder.f32_ceil();
                     }
@@ -1824,7 +1916,6 @@ impl<'a> LowLevelCall<'a> {
                     (I32, F64) => backend.code_builder.i32_trunc_s_f64(),
                     (I64, F32) => backend.code_builder.i64_trunc_s_f32(),
                     (I64, F64) => backend.code_builder.i64_trunc_s_f64(),
-                    (I128, _) => todo!("{:?} for I128", self.lowlevel),
                     _ => panic_ret_type(),
                 }
             }
@@ -1840,7 +1931,7 @@ impl<'a> LowLevelCall<'a> {
                 let width = match ret_type {
                     CodeGenNumType::I32 => IntWidth::I32,
                     CodeGenNumType::I64 => IntWidth::I64,
-                    CodeGenNumType::I128 => todo!("{:?} for I128", self.lowlevel),
+                    CodeGenNumType::I128 => self.ret_int_width(),
                     _ => internal_error!("Invalid return type for pow: {:?}", ret_type),
                 };
 
@@ -1879,33 +1970,44 @@ impl<'a> LowLevelCall<'a> {
                 _ => panic_ret_type(),
             },
             NumBitwiseAnd => {
+                if CodeGenNumType::from(self.ret_layout) == I128 {
+                    return self.num128_bitwise(backend, CodeBuilder::i64_and);
+                }
                 self.load_args(backend);
                 match CodeGenNumType::from(self.ret_layout) {
                     I32 => backend.code_builder.i32_and(),
                     I64 => backend.code_builder.i64_and(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
                     _ => panic_ret_type(),
                 }
             }
             NumBitwiseXor => {
+                if CodeGenNumType::from(self.ret_layout) == I128 {
+                    return self.num128_bitwise(backend, CodeBuilder::i64_xor);
+                }
                 self.load_args(backend);
                 match CodeGenNumType::from(self.ret_layout) {
                     I32 => backend.code_builder.i32_xor(),
                     I64 => backend.code_builder.i64_xor(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
                     _ => panic_ret_type(),
                 }
             }
             NumBitwiseOr => {
+                if CodeGenNumType::from(self.ret_layout) == I128 {
+                    return self.num128_bitwise(backend, CodeBuilder::i64_or);
+                }
                 self.load_args(backend);
                 match CodeGenNumType::from(self.ret_layout) {
                     I32 => backend.code_builder.i32_or(),
                     I64 => backend.code_builder.i64_or(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
                     _ => panic_ret_type(),
                 }
             }
             NumShiftLeftBy => {
+                if CodeGenNumType::from(self.ret_layout) == I128 {
+                    let width = self.ret_int_width();
+                    return self
+                        .load_args_and_call_zig(backend, &bitcode::NUM_SHIFT_LEFT_BY[width]);
+                }
                 let num = self.arguments[0];
                 let bits = self.arguments[1];
                 backend
@@ -1917,7 +2019,6 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shl();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
                     _ => panic_ret_type(),
                 }
             }
@@ -1964,7 +2065,10 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shr_s();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
+                    I128 => {
+                        let width = self.ret_int_width();
+                        self.load_args_and_call_zig(backend, &bitcode::NUM_SHIFT_RIGHT_BY[width]);
+                    }
                     _ => panic_ret_type(),
                 }
             }
@@ -2035,8 +2139,11 @@ impl<'a> LowLevelCall<'a> {
                         self.wrap_small_int(backend, ret_width);
                     }
                     (I32, I128) => {
-                        self.load_args(backend);
-                        backend.code_builder.i32_load(Align::Bytes4, 0);
+                        let (frame_ptr, offset) =
+                            num128_location(backend, backend.storage.get(&self.arguments[0]));
+                        backend.code_builder.get_local(frame_ptr);
+                        backend.code_builder.i32_load(Align::Bytes4, offset);
+                        self.wrap_small_int(backend, ret_width);
                     }
                     (I64, I32) => {
                         self.load_args(backend);
@@ -2050,31 +2157,54 @@ impl<'a> LowLevelCall<'a> {
                         self.load_args(backend);
                     }
                     (I64, I128) => {
-                        let (frame_ptr, offset) = match backend.storage.get(&self.arguments[0]) {
-                            StoredValue::StackMemory { location, .. } => {
-                                location.local_and_offset(backend.storage.stack_frame_pointer)
-                            }
-                            _ => internal_error!("I128 should be in stack memory"),
-                        };
+                        let (frame_ptr, offset) =
+                            num128_location(backend, backend.storage.get(&self.arguments[0]));
                         backend.code_builder.get_local(frame_ptr);
                         backend.code_builder.i64_load(Align::Bytes8, offset);
                     }
-                    (I128, I64) => {
-                        // Symbols are loaded as if for a call, so the i128 "return address" and i64 value are on the value stack
+                    (I128, I32 | I64) => {
+                        let arg = self.arguments[0];
+                        let extend_to_i64 = |backend: &mut WasmBackend<'a, '_>| {
+                            if arg_type != I32 {
+                                return;
+                            }
+                            if arg_width.is_signed() {
+                                backend.code_builder.i64_extend_s_i32()
+                            } else {
+                                backend.code_builder.i64_extend_u_i32()
+                            }
+                        };
+
+                        // Symbols are loaded as if for a call, so the i128 "return address" and the value are on the value stack
                         self.load_args(backend);
+                        extend_to_i64(backend);
                         backend.code_builder.i64_store(Align::Bytes8, 0);
 
-                        // Zero the most significant 64 bits
-                        let (frame_ptr, offset) = match &self.ret_storage {
-                            StoredValue::StackMemory { location, .. } => {
-                                location.local_and_offset(backend.storage.stack_frame_pointer)
-                            }
-                            _ => internal_error!("I128 should be in stack memory"),
-                        };
+                        // Sign-extend or zero the most significant 64 bits
+                        let (frame_ptr, offset) = num128_location(backend, &self.ret_storage);
                         backend.code_builder.get_local(frame_ptr);
-                        backend.code_builder.i64_const(0);
+                        if arg_width.is_signed() {
+                            backend
+                                .storage
+                                .load_symbols(&mut backend.code_builder, &[arg]);
+                            extend_to_i64(backend);
+                            backend.code_builder.i64_const(63);
+                            backend.code_builder.i64_shr_s();
+                        } else {
+                            backend.code_builder.i64_const(0);
+                        }
                         backend.code_builder.i64_store(Align::Bytes8, offset + 8);
                     }
+                    (I128, I128) => {
+                        // Same bits, only the signedness changes
+                        let (frame_ptr, offset) = num128_location(backend, &self.ret_storage);
+                        backend.storage.copy_value_to_memory(
+                            &mut backend.code_builder,
+                            frame_ptr,
+                            offset,
+                            self.arguments[0],
+                        );
+                    }
 
                     _ => todo!("{:?}: {:?} -> {:?}", self.lowlevel, arg_type, ret_type),
                 }
@@ -2123,6 +2253,19 @@ impl<'a> LowLevelCall<'a> {
                             backend.code_builder.f64_convert_u_i64()
                         }
                     }
+                    (F64, F32) => backend.code_builder.f64_promote_f32(),
+                    (F32, I128) => {
+                        let int_width = symbol_int_width(backend, self.arguments[0]);
+                        backend.call_host_fn_after_loading_args(
+                            &bitcode::INT_TO_FLOAT_CAST_F32[int_width],
+                        );
+                    }
+                    (F64, I128) => {
+                        let int_width = symbol_int_width(backend, self.arguments[0]);
+                        backend.call_host_fn_after_loading_args(
+                            &bitcode::INT_TO_FLOAT_CAST_F64[int_width],
+                        );
+                    }
                     _ => todo!("{:?}: {:?} -> {:?}", self.lowlevel, arg_type, ret_type),
                 }
             }
@@ -2167,9 +2310,7 @@ impl<'a> LowLevelCall<'a> {
                     )
                 }
             }
-            NumToFloatChecked => {
-                todo!("implement toF32Checked and toF64Checked");
-            }
+            NumToFloatChecked => self.num_to_float_checked(backend),
             NumWithoutDecimalPoint => self.load_args_and_call_zig(backend, bitcode::DEC_TO_I128),
             NumWithDecimalPoint => self.load_args_and_call_zig(backend, bitcode::DEC_FROM_I128),
             NumF32ToParts => self.load_args_and_call_zig(backend, bitcode::NUM_F32_TO_PARTS),
@@ -2443,6 +2584,198 @@ impl<'a> LowLevelCall<'a> {
         backend.code_builder.i32_and();
     }
 
+    /// Negation or absolute value of a 128-bit integer, computed one 64-bit half at a time
+    fn num128_abs_or_neg(&self, backend: &mut WasmBackend<'a, '_>, panic_msg: &'a str) {
+        let arg = self.arguments[0];
+        let (arg_local, arg_offset) = num128_location(backend, backend.storage.get(&arg));
+        let (ret_local, ret_offset) = num128_location(backend, &self.ret_storage);
+        let is_signed = layout_is_signed_int(self.ret_layout);
+        let is_abs = self.lowlevel == LowLevel::NumAbs;
+
+        if is_abs && !is_signed {
+            backend.storage.copy_value_to_memory(
+                &mut backend.code_builder,
+                ret_local,
+                ret_offset,
+                arg,
+            );
+            return;
+        }
+
+        let load_low = |backend: &mut WasmBackend<'a, '_>| {
+            backend.code_builder.get_local(arg_local);
+            backend.code_builder.i64_load(Align::Bytes8, arg_offset);
+        };
+        let load_high = |backend: &mut WasmBackend<'a, '_>| {
+            backend.code_builder.get_local(arg_local);
+            backend.code_builder.i64_load(Align::Bytes8, arg_offset + 8);
+        };
+
+        if is_signed {
+            // x == MIN
+            load_high(backend);
+            backend.code_builder.i64_const(i64::MIN);
+            backend.code_builder.i64_eq();
+            load_low(backend);
+            backend.code_builder.i64_eqz();
+            backend.code_builder.i32_and();
+            backend.code_builder.if_();
+            backend.stmt_internal_error(panic_msg);
+            backend.code_builder.end();
+        }
+
+        // Low half of -x
+        backend.code_builder.get_local(ret_local);
+        if is_abs {
+            load_low(backend);
+        }
+        backend.code_builder.i64_const(0);
+        load_low(backend);
+        backend.code_builder.i64_sub();
+        if is_abs {
+            // (x >= 0) ? x : -x
+            load_high(backend);
+            backend.code_builder.i64_const(0);
+            backend.code_builder.i64_ge_s();
+            backend.code_builder.select();
+        }
+        backend.code_builder.i64_store(Align::Bytes8, ret_offset);
+
+        // High half of -x, borrowing 1 if the low half is non-zero
+        backend.code_builder.get_local(ret_local);
+        if is_abs {
+            load_high(backend);
+        }
+        backend.code_builder.i64_const(0);
+        load_high(backend);
+        backend.code_builder.i64_sub();
+        load_low(backend);
+        backend.code_builder.i64_const(0);
+        backend.code_builder.i64_ne();
+        backend.code_builder.i64_extend_u_i32();
+        backend.code_builder.i64_sub();
+        if is_abs {
+            load_high(backend);
+            backend.code_builder.i64_const(0);
+            backend.code_builder.i64_ge_s();
+            backend.code_builder.select();
+        }
+        backend
+            .code_builder
+            .i64_store(Align::Bytes8, ret_offset + 8);
+    }
+
+    /// Bitwise operation on 128-bit integers, computed one 64-bit half at a time
+    fn num128_bitwise(&self, backend: &mut WasmBackend<'a, '_>, i64_op: fn(&mut CodeBuilder<'a>)) {
+        let (ret_local, ret_offset) = num128_location(backend, &self.ret_storage);
+        let (x_local, x_offset) = num128_location(backend, backend.storage.get(&self.arguments[0]));
+        let (y_local, y_offset) = num128_location(backend, backend.storage.get(&self.arguments[1]));
+
+        for half in [0, 8] {
+            backend.code_builder.get_local(ret_local);
+            backend.code_builder.get_local(x_local);
+            backend
+                .code_builder
+                .i64_load(Align::Bytes8, x_offset + half);
+            backend.code_builder.get_local(y_local);
+            backend
+                .code_builder
+                .i64_load(Align::Bytes8, y_offset + half);
+            i64_op(&mut backend.code_builder);
+            backend
+                .code_builder
+                .i64_store(Align::Bytes8, ret_offset + half);
+        }
+    }
+
+    /// toF32Checked and toF64Checked
+    /// The return value is a struct like { a: F32 or F64, b: Bool }, where `b` means "out of bounds"
+    fn num_to_float_checked(&self, backend: &mut WasmBackend<'a, '_>) {
+        let arg = self.arguments[0];
+        let arg_layout = backend.storage.symbol_layouts[&arg];
+        let arg_repr = backend.layout_interner.get_repr(arg_layout);
+
+        let float_width = match self.ret_layout_raw {
+            LayoutRepr::Struct(&[value, ..]) => match backend.layout_interner.get_repr(value) {
+                LayoutRepr::Builtin(Builtin::Float(width)) => width,
+                x => internal_error!("Invalid return layout for NumToFloatChecked: {:?}", x),
+            },
+            x => internal_error!("Invalid return layout for NumToFloatChecked: {:?}", x),
+        };
+
+        let (ret_local, ret_offset) = match &self.ret_storage {
+            StoredValue::StackMemory { location, .. } => {
+                location.local_and_offset(backend.storage.stack_frame_pointer)
+            }
+            _ => internal_error!("Invalid return value storage for NumToFloatChecked"),
+        };
+
+        // Convert the number and store it in the first field
+        backend.code_builder.get_local(ret_local);
+        backend
+            .storage
+            .load_symbols(&mut backend.code_builder, &[arg]);
+        match (arg_repr, float_width) {
+            (LayoutRepr::Builtin(Builtin::Int(int_width)), FloatWidth::F32) => {
+                backend.call_host_fn_after_loading_args(&bitcode::INT_TO_FLOAT_CAST_F32[int_width]);
+            }
+            (LayoutRepr::Builtin(Builtin::Int(int_width)), FloatWidth::F64) => {
+                backend.call_host_fn_after_loading_args(&bitcode::INT_TO_FLOAT_CAST_F64[int_width]);
+            }
+            (LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32)), FloatWidth::F32) => {}
+            (LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64)), FloatWidth::F64) => {}
+            (LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64)), FloatWidth::F32) => {
+                backend.code_builder.f32_demote_f64();
+            }
+            (LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32)), FloatWidth::F64) => {
+                backend.code_builder.f64_promote_f32();
+            }
+            (LayoutRepr::Builtin(Builtin::Decimal), FloatWidth::F64) => {
+                backend.call_host_fn_after_loading_args(bitcode::DEC_TO_F64);
+            }
+            (LayoutRepr::Builtin(Builtin::Decimal), FloatWidth::F32) => {
+                backend.call_host_fn_after_loading_args(bitcode::DEC_TO_F64);
+                backend.code_builder.f32_demote_f64();
+            }
+            (x, _) => internal_error!("Invalid argument layout for {:?}: {:?}", self.lowlevel, x),
+        }
+        match float_width {
+            FloatWidth::F32 => backend.code_builder.f32_store(Align::Bytes4, ret_offset),
+            FloatWidth::F64 => backend.code_builder.f64_store(Align::Bytes8, ret_offset),
+        }
+
+        // The conversion is out of bounds if it overflowed to infinity
+        backend.code_builder.get_local(ret_local);
+        backend.code_builder.get_local(ret_local);
+        match float_width {
+            FloatWidth::F32 => {
+                backend.code_builder.f32_load(Align::Bytes4, ret_offset);
+                backend.code_builder.i32_reinterpret_f32();
+                backend.code_builder.i32_const(0x7fff_ffff);
+                backend.code_builder.i32_and();
+                backend.code_builder.i32_const(0x7f80_0000);
+                backend.code_builder.i32_eq();
+            }
+            FloatWidth::F64 => {
+                backend.code_builder.f64_load(Align::Bytes8, ret_offset);
+                backend.code_builder.i64_reinterpret_f64();
+                backend.code_builder.i64_const(0x7fff_ffff_ffff_ffff);
+                backend.code_builder.i64_and();
+                backend.code_builder.i64_const(0x7ff0_0000_0000_0000);
+                backend.code_builder.i64_eq();
+            }
+        }
+        if matches!(arg_repr, LayoutRepr::Builtin(Builtin::Float(_))) {
+            // Infinity converts to infinity without being out of bounds
+            num_is_infinite(backend, arg);
+            backend.code_builder.i32_eqz();
+            backend.code_builder.i32_and();
+        }
+        backend
+            .code_builder
+            .i32_store8(Align::Bytes1, ret_offset + float_width.stack_size());
+    }
+
     fn num_to_str(&self, backend: &mut WasmBackend<'a, '_>) {
         let arg_layout = backend.storage.symbol_layouts[&self.arguments[0]];
         match backend.layout_interner.runtime_representation(arg_layout) {
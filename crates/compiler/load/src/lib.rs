@@ -24,6 +24,7 @@ pub use roc_load_internal::file::{
 pub use roc_load_internal::module::{
     CheckedModule, EntryPoint, Expectations, ExposedToHost, LoadedModule, MonomorphizedModule,
 };
+pub use roc_load_internal::solved_cache::SolvedModuleCache;
 pub use roc_solve::FunctionKind;
 
 #[allow(clippy::too_many_arguments)]
@@ -11385,6 +11385,8 @@ All branches in an `if` must have the same type!
                 list: decodeList,
                 record: decodeRecord,
                 tuple: decodeTuple,
+                tag: decodeTag,
+                dict: decodeDict,
             }]
             decodeU8 = Decode.custom \rest, @ErrDecoder {} -> {result: Err TooShort, rest}
             decodeU16 = Decode.custom \rest, @ErrDecoder {} -> {result: Err TooShort, rest}
@@ -11407,6 +11409,10 @@ All branches in an `if` must have the same type!
             decodeRecord =\_, _, _ ->  Decode.custom \rest, @ErrDecoder {} -> {result: Err TooShort, rest}
             decodeTuple : state, (state, U64 -> [Next (Decoder state (ErrDecoder)), TooLong]), (state -> Result val DecodeError) -> Decoder val (ErrDecoder)
             decodeTuple = \_, _, _ -> Decode.custom \rest, @ErrDecoder {} -> {result: Err TooShort, rest}
+            decodeTag : (Str -> [Keep (Decoder val (ErrDecoder)), Skip]) -> Decoder val (ErrDecoder)
+            decodeTag = \_ -> Decode.custom \rest, @ErrDecoder {} -> {result: Err TooShort, rest}
+            decodeDict : state, (state, k, v -> state), Decoder k (ErrDecoder), Decoder v (ErrDecoder) -> Decoder state (ErrDecoder)
+            decodeDict = \_, _, _, _ -> Decode.custom \rest, @ErrDecoder {} -> {result: Err TooShort, rest}
 
             main =
                 decoded = Str.toUtf8 "{\"first\":\"ab\",\"second\":\"cd\"}" |> Decode.fromBytes (@ErrDecoder {})
@@ -11420,7 +11426,7 @@ All branches in an `if` must have the same type!
 
     This expression has a type that does not implement the abilities it's expected to:
 
-    48│          Ok rcd -> rcd.first rcd.second
+    54│          Ok rcd -> rcd.first rcd.second
                            ^^^^^^^^^
 
     I can't generate an implementation of the `Decoding` ability for
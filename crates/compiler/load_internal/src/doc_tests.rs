@@ -0,0 +1,156 @@
+//! `roc test --doc` also runs the code examples in doc comments. Every ```` ```roc ```` block
+//! with an `expect` in it becomes a top-level expect of the module whose docs it's in,
+//! so the example can use anything in that module's scope.
+use bumpalo::Bump;
+use roc_parse::ast::{CommentOrNewline, Defs, Expr, ValueDef};
+use roc_parse::expr::parse_repl_defs_and_optional_expr;
+use roc_parse::parser::SyntaxError;
+use roc_parse::state::State;
+use roc_region::all::{Loc, Position, Region};
+use std::ops::Range;
+
+use crate::docs::doc_comments;
+
+const ROC_FENCE: &str = "```roc";
+const FENCE: &str = "```";
+
+/// A ```` ```roc ```` block in a doc comment
+#[derive(Debug)]
+struct DocExample {
+    /// Where each line's code is in the source, i.e. everything after its `##`
+    lines: Vec<Range<usize>>,
+}
+
+impl DocExample {
+    fn has_expect(&self, src: &str) -> bool {
+        self.lines.iter().any(|line| {
+            let code = src[line.clone()].trim_start();
+
+            code == "expect" || code.starts_with("expect ")
+        })
+    }
+
+    fn region(&self) -> Region {
+        let start = self.lines.first().map_or(0, |line| line.start);
+        let end = self.lines.last().map_or(start, |line| line.end);
+
+        Region::new(Position::new(start as u32), Position::new(end as u32))
+    }
+
+    /// The source with everything but the example's code blanked out, and newlines kept,
+    /// so parsing it gives regions in the original source.
+    fn masked_src<'a>(&self, arena: &'a Bump, src: &str) -> &'a str {
+        let mut masked: Vec<u8> = src
+            .bytes()
+            .map(|byte| if byte == b'\n' { b'\n' } else { b' ' })
+            .collect();
+
+        for line in self.lines.iter() {
+            masked[line.clone()].copy_from_slice(src[line.clone()].as_bytes());
+        }
+
+        // Every line we kept is whole, and everything else is ASCII now
+        arena.alloc_str(std::str::from_utf8(&masked).unwrap())
+    }
+}
+
+/// Add a top-level expect for every doc comment example with an `expect` in it. The example's
+/// defs and all but its last `expect` go in the body of that expect, so this example
+///
+/// ```roc
+/// greeting = "hi"
+/// expect !(Str.isEmpty greeting)
+/// expect Str.startsWith greeting "h"
+/// ```
+///
+/// is tested like `expect (greeting = "hi" ... Str.startsWith greeting "h")`, with the first
+/// `expect` as an inline expect. Examples without an `expect` are just examples, but the ones
+/// with an `expect` that don't end in one can't be tested, so this returns their regions.
+pub fn add_doc_tests<'a>(
+    arena: &'a Bump,
+    src: &'a str,
+    header_comments: &[CommentOrNewline<'a>],
+    defs: &mut Defs<'a>,
+) -> Result<Vec<Region>, SyntaxError<'a>> {
+    let examples = doc_examples(src, &doc_comments(header_comments, defs));
+    let mut skipped = Vec::new();
+
+    for example in examples.iter().filter(|example| example.has_expect(src)) {
+        let masked_src = example.masked_src(arena, src);
+
+        let mut example_defs =
+            match parse_repl_defs_and_optional_expr(arena, State::new(masked_src.as_bytes())) {
+                Ok((_, (example_defs, None), _)) => example_defs,
+                Ok((_, (_, Some(_)), _)) => {
+                    skipped.push(example.region());
+                    continue;
+                }
+                Err((_, fail)) => return Err(SyntaxError::Expr(fail, example.region().start())),
+            };
+
+        let last_condition = match example_defs.last() {
+            Some(Err(ValueDef::Expect { condition, .. })) => *condition,
+            _ => {
+                skipped.push(example.region());
+                continue;
+            }
+        };
+
+        example_defs.remove_tag(example_defs.len() - 1);
+
+        let condition = if example_defs.is_empty() {
+            last_condition
+        } else {
+            arena.alloc(Loc::at(
+                last_condition.region,
+                Expr::Defs(arena.alloc(example_defs), last_condition),
+            ))
+        };
+
+        defs.push_value_def(
+            ValueDef::Expect {
+                condition,
+                // so failures show the whole example
+                preceding_comment: example.region(),
+            },
+            example.region(),
+            &[],
+            &[],
+        );
+    }
+
+    Ok(skipped)
+}
+
+/// The ```` ```roc ```` blocks in the doc comments. A block that the comment ends in the
+/// middle of wasn't one after all.
+fn doc_examples(src: &str, doc_comments: &[Vec<&str>]) -> Vec<DocExample> {
+    let mut examples = Vec::new();
+
+    for lines in doc_comments {
+        let mut opt_example: Option<DocExample> = None;
+
+        for line in lines {
+            let Some(code) = range_in(src, line) else {
+                break;
+            };
+
+            match (line.trim(), opt_example.as_mut()) {
+                (ROC_FENCE, None) => opt_example = Some(DocExample { lines: Vec::new() }),
+                (_, None) => {}
+                (FENCE, Some(_)) => examples.extend(opt_example.take()),
+                (_, Some(example)) => example.lines.push(code),
+            }
+        }
+    }
+
+    examples
+}
+
+/// Where a line of a doc comment is in the source it was parsed from
+fn range_in(src: &str, line: &str) -> Option<Range<usize>> {
+    let start = (line.as_ptr() as usize).checked_sub(src.as_ptr() as usize)?;
+    let end = start + line.len();
+
+    (end <= src.len()).then_some(start..end)
+}
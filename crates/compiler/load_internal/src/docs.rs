@@ -7,6 +7,8 @@ use roc_module::symbol::{IdentIds, ModuleId, ModuleIds, Symbol};
 use roc_parse::ast::{self, ExtractSpaces, TypeHeader};
 use roc_parse::ast::{AssignedField, FunctionArrow};
 use roc_parse::ast::{CommentOrNewline, TypeDef, ValueDef};
+use roc_parse::ast::{ImplementsAbilities, ImplementsAbility};
+use roc_region::all::{Loc, Region};
 
 // Documentation generation requirements
 
@@ -43,6 +45,11 @@ pub struct DocDef {
     pub type_vars: Vec<String>,
     pub type_annotation: TypeAnnotation,
     pub docs: Option<String>,
+    /// Where the def is in its module's source, not counting its doc comment
+    pub region: Region,
+    /// The abilities an opaque type implements, like `["Eq", "Hash"]` for
+    /// `implements [Eq, Hash]`. Empty for everything else.
+    pub implements: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -177,6 +184,49 @@ fn detached_docs_from_comments_and_new_lines<'a>(
     detached_docs
 }
 
+/// The doc comments the docs are made of: the module's own, and those around its defs.
+/// Each is a list of lines, and each line is a slice of the module's source.
+pub(crate) fn doc_comments<'a>(
+    header_comments: &[CommentOrNewline<'a>],
+    defs: &roc_parse::ast::Defs<'a>,
+) -> Vec<Vec<&'a str>> {
+    let spaces_around_defs = (0..defs.tags.len()).flat_map(|index| {
+        defs.spaces[defs.space_before[index].indices()]
+            .iter()
+            .chain(&defs.spaces[defs.space_after[index].indices()])
+    });
+
+    let mut comments = Vec::new();
+
+    push_doc_comments(&mut comments, header_comments.iter());
+    push_doc_comments(&mut comments, spaces_around_defs);
+
+    comments
+}
+
+/// Add the doc comments in these spaces. A blank line or a regular comment ends a doc comment.
+fn push_doc_comments<'a: 'b, 'b>(
+    comments: &mut Vec<Vec<&'a str>>,
+    spaces: impl Iterator<Item = &'b CommentOrNewline<'a>>,
+) {
+    let mut lines = Vec::new();
+
+    for comment_or_new_line in spaces {
+        match comment_or_new_line {
+            CommentOrNewline::DocComment(line) => lines.push(*line),
+            CommentOrNewline::Newline | CommentOrNewline::LineComment(_) => {
+                if !lines.is_empty() {
+                    comments.push(std::mem::take(&mut lines));
+                }
+            }
+        }
+    }
+
+    if !lines.is_empty() {
+        comments.push(lines);
+    }
+}
+
 fn generate_entry_docs(
     home: ModuleId,
     ident_ids: &IdentIds,
@@ -198,6 +248,7 @@ fn generate_entry_docs(
 
     for (index, either_index) in defs.tags.iter().enumerate() {
         let spaces_before = &defs.spaces[defs.space_before[index].indices()];
+        let region = defs.regions[index];
 
         scratchpad.clear();
         scratchpad.extend(
@@ -223,6 +274,8 @@ fn generate_entry_docs(
                                 type_annotation: type_to_docs(false, loc_ann.value),
                                 type_vars: Vec::new(),
                                 docs,
+                                region,
+                                implements: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -243,6 +296,8 @@ fn generate_entry_docs(
                                 type_vars: Vec::new(),
                                 symbol: Symbol::new(home, ident_id),
                                 docs,
+                                region,
+                                implements: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -259,6 +314,8 @@ fn generate_entry_docs(
                                 type_vars: Vec::new(),
                                 symbol: Symbol::new(home, ident_id),
                                 docs,
+                                region,
+                                implements: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -300,6 +357,8 @@ fn generate_entry_docs(
                                 type_vars: Vec::new(),
                                 symbol: Symbol::new(home, ident_id),
                                 docs,
+                                region,
+                                implements: Vec::new(),
                             };
                             doc_entries.push(DocEntry::DocDef(doc_def));
                         }
@@ -337,6 +396,8 @@ fn generate_entry_docs(
                         type_annotation,
                         type_vars,
                         docs,
+                        region,
+                        implements: Vec::new(),
                         symbol: Symbol::new(home, ident_id),
                     };
                     doc_entries.push(DocEntry::DocDef(doc_def));
@@ -344,6 +405,7 @@ fn generate_entry_docs(
 
                 TypeDef::Opaque {
                     header: TypeHeader { name, vars },
+                    derived,
                     ..
                 } => {
                     let mut type_vars = Vec::new();
@@ -360,6 +422,8 @@ fn generate_entry_docs(
                         type_annotation: TypeAnnotation::NoTypeAnn,
                         type_vars,
                         docs,
+                        region,
+                        implements: implemented_abilities(derived.as_ref()),
                         symbol: Symbol::new(home, ident_id),
                     };
                     doc_entries.push(DocEntry::DocDef(doc_def));
@@ -401,6 +465,8 @@ fn generate_entry_docs(
                         symbol: Symbol::new(home, ident_id),
                         type_vars,
                         docs,
+                        region,
+                        implements: Vec::new(),
                     };
                     doc_entries.push(DocEntry::DocDef(doc_def));
                 }
@@ -420,6 +486,30 @@ fn generate_entry_docs(
     doc_entries
 }
 
+/// The names of the abilities in an opaque type's `implements [...]`
+fn implemented_abilities(derived: Option<&Loc<ImplementsAbilities>>) -> Vec<String> {
+    let Some(derived) = derived else {
+        return Vec::new();
+    };
+
+    derived
+        .value
+        .collection()
+        .iter()
+        .filter_map(
+            |loc_implements| match loc_implements.value.extract_spaces().item {
+                ImplementsAbility::ImplementsAbility { ability, .. } => {
+                    match type_to_docs(false, ability.value) {
+                        Apply { name, .. } => Some(name),
+                        _ => None,
+                    }
+                }
+                ImplementsAbility::SpaceBefore(..) | ImplementsAbility::SpaceAfter(..) => None,
+            },
+        )
+        .collect()
+}
+
 /// Does this type contain any types which are not exposed outside the package?
 /// (If so, we shouldn't try to render a type annotation for it.)
 fn contains_unexposed_type(
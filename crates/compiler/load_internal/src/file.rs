@@ -7,6 +7,7 @@ use crate::module::{
     ModuleTiming, MonomorphizedModule, ParsedModule, ToplevelExpects, TypeCheckedModule,
 };
 use crate::module_cache::ModuleCache;
+use crate::solved_cache::SolvedModuleCache;
 use bumpalo::{collections::CollectIn, Bump};
 use crossbeam::channel::{bounded, Sender};
 use crossbeam::deque::{Injector, Worker};
@@ -48,7 +49,9 @@ use roc_mono::layout::{
 };
 use roc_mono::reset_reuse;
 use roc_mono::{drop_specialization, inc_dec};
-use roc_packaging::cache::RocCacheDir;
+use roc_packaging::cache::{self, RocCacheDir};
+#[cfg(not(target_family = "wasm"))]
+use roc_packaging::https::{PackageMetadata, Problem};
 use roc_parse::ast::{self, CommentOrNewline, ExtractSpaces, Spaced, ValueDef};
 use roc_parse::header::parse_module_defs;
 use roc_parse::header::{
@@ -77,11 +80,6 @@ use std::path::{Path, PathBuf};
 use std::str::from_utf8_unchecked;
 use std::sync::Arc;
 use std::{env, fs};
-#[cfg(not(target_family = "wasm"))]
-use {
-    roc_packaging::cache::{self},
-    roc_packaging::https::{PackageMetadata, Problem},
-};
 
 pub use roc_work::Phase;
 use roc_work::{DepCycle, Dependencies};
@@ -125,6 +123,12 @@ pub enum ExecutionMode {
     /// Test is like [`ExecutionMode::ExecutableIfCheck`], but rather than producing a proper
     /// executable, run tests.
     Test,
+    /// Like [`ExecutionMode::Test`], but the code examples with an `expect` in the doc comments
+    /// of the root package's modules are tests too (`roc test --doc`).
+    TestDocs,
+    /// Like [`ExecutionMode::TestDocs`], but for the builtins instead of the root package,
+    /// so the examples in the docs of `Str`, `List` and the rest get checked.
+    TestBuiltinDocs,
 }
 
 impl ExecutionMode {
@@ -133,12 +137,23 @@ impl ExecutionMode {
 
         match self {
             Executable => Phase::MakeSpecializations,
-            Check | ExecutableIfCheck | Test => Phase::SolveTypes,
+            Check | ExecutableIfCheck | Test | TestDocs | TestBuiltinDocs => Phase::SolveTypes,
         }
     }
 
     fn build_if_checks(&self) -> bool {
-        matches!(self, Self::ExecutableIfCheck | Self::Test)
+        matches!(
+            self,
+            Self::ExecutableIfCheck | Self::Test | Self::TestDocs | Self::TestBuiltinDocs
+        )
+    }
+
+    fn is_test(&self) -> bool {
+        matches!(self, Self::Test | Self::TestDocs | Self::TestBuiltinDocs)
+    }
+
+    fn has_doc_tests(&self) -> bool {
+        matches!(self, Self::TestDocs | Self::TestBuiltinDocs)
     }
 }
 
@@ -197,12 +212,16 @@ fn start_phase<'a>(
                 // parse the file
                 let header = state.module_cache.headers.remove(&module_id).unwrap();
 
+                // Like other expects, only the doc examples of the package under test are tests.
+                let doc_tests = state.exec_mode.has_doc_tests() && state.is_under_test(module_id);
+
                 BuildTask::Parse {
                     header,
                     arc_shorthands: Arc::clone(&state.arc_shorthands),
                     module_ids: Arc::clone(&state.arc_modules),
                     ident_ids_by_module: Arc::clone(&state.ident_ids_by_module),
                     root_type: state.root_type.clone(),
+                    doc_tests,
                 }
             }
             Phase::CanonicalizeAndConstrain => {
@@ -302,6 +321,11 @@ fn start_phase<'a>(
                     }
                 }
 
+                if let Some(type_state) = reusable_solved_types(&state, &parsed) {
+                    state.cached_types.lock().insert(module_id, type_state);
+                    state.reused_modules.insert(module_id);
+                }
+
                 let skip_constraint_gen = {
                     // Give this its own scope to make sure that the Guard from the lock() is dropped
                     // immediately after contains_key returns
@@ -415,8 +439,7 @@ fn start_phase<'a>(
 
                 let derived_module = SharedDerivedModule::clone(&state.derived_module);
 
-                let build_expects =
-                    matches!(state.exec_mode, ExecutionMode::Test) && expectations.is_some();
+                let build_expects = state.exec_mode.is_test() && expectations.is_some();
 
                 BuildTask::BuildPendingSpecializations {
                     layout_cache,
@@ -705,6 +728,8 @@ struct State<'a> {
     pub root_path: PathBuf,
     pub root_type: RootType,
     pub cache_dir: PathBuf,
+    /// When offline, URL packages are looked up in here before the cache
+    pub vendor_dir: Option<PathBuf>,
     /// If the root is an app module, the shorthand specified in its header's `to` field
     pub opt_platform_shorthand: Option<&'a str>,
     pub platform_data: Option<PlatformData<'a>>,
@@ -761,6 +786,11 @@ struct State<'a> {
     // cached types (used for builtin modules, could include packages in the future too)
     cached_types: CachedTypeState,
 
+    /// Solved modules from earlier loads; see [`LoadStart::with_solved_cache`]
+    solved_cache: Option<SolvedModuleCache>,
+    /// The modules whose solved types came from `solved_cache`
+    reused_modules: MutSet<ModuleId>,
+
     layout_interner: GlobalLayoutInterner<'a>,
 }
 
@@ -783,10 +813,12 @@ impl<'a> State<'a> {
         ident_ids_by_module: SharedIdentIdsByModule,
         arc_shorthands: Arc<Mutex<MutMap<&'a str, ShorthandPath>>>,
         cached_types: MutMap<ModuleId, TypeState>,
+        solved_cache: Option<SolvedModuleCache>,
         render: RenderTarget,
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        vendor_dir: Option<PathBuf>,
     ) -> Self {
         let cache_dir = roc_packaging::cache::roc_cache_packages_dir();
         let dependencies = Dependencies::new(exec_mode.goal_phase());
@@ -798,6 +830,7 @@ impl<'a> State<'a> {
             root_type,
             opt_platform_shorthand,
             cache_dir,
+            vendor_dir,
             target,
             function_kind,
             fx_mode: FxMode::Task,
@@ -820,7 +853,13 @@ impl<'a> State<'a> {
             exposed_symbols_by_module: MutMap::default(),
             timings: MutMap::default(),
             layout_caches: std::vec::Vec::with_capacity(number_of_workers),
-            cached_types: Arc::new(Mutex::new(cached_types)),
+            // Cached builtins skip constraint generation, which their doc tests need.
+            cached_types: Arc::new(Mutex::new(match exec_mode {
+                ExecutionMode::TestBuiltinDocs => MutMap::default(),
+                _ => cached_types,
+            })),
+            solved_cache,
+            reused_modules: MutSet::default(),
             render,
             palette,
             exec_mode,
@@ -829,6 +868,54 @@ impl<'a> State<'a> {
             layout_interner: GlobalLayoutInterner::with_capacity(128, target),
         }
     }
+
+    /// Whether the module's expects are tests: the root package's, or the builtins'
+    /// with [`ExecutionMode::TestBuiltinDocs`]
+    fn is_under_test(&self, module_id: ModuleId) -> bool {
+        match self.exec_mode {
+            ExecutionMode::TestBuiltinDocs => module_id.is_builtin(),
+            _ => self
+                .arc_modules
+                .lock()
+                .package_eq(module_id, self.root_id)
+                .expect("root or this module is not yet known - that's a bug!"),
+        }
+    }
+}
+
+/// The solved types an earlier load found for this module, if they're still right. They are if
+/// it has the same source, and so do all the modules it imports, because we reused theirs too.
+/// The root module is the one being edited, so we always solve it.
+fn reusable_solved_types(state: &State, parsed: &ParsedModule) -> Option<TypeState> {
+    let solved_cache = state.solved_cache.as_ref()?;
+    let module_id = parsed.module_id;
+
+    if module_id == state.root_id || !matches!(parsed.header_type, HeaderType::Module { .. }) {
+        return None;
+    }
+
+    let imports = state.module_cache.imports.get(&module_id)?;
+    let imports_reused = imports
+        .iter()
+        .all(|import| import.is_builtin() || state.reused_modules.contains(import));
+
+    if !imports_reused {
+        return None;
+    }
+
+    solved_cache.get(
+        &parsed.module_path,
+        module_id,
+        parsed.src,
+        imports,
+        |import| {
+            state
+                .module_cache
+                .sources
+                .get(&import)
+                .map(|(path, _)| path.as_path())
+        },
+    )
 }
 
 fn report_timing(
@@ -892,6 +979,8 @@ enum BuildTask<'a> {
         module_ids: Arc<Mutex<PackageModuleIds<'a>>>,
         ident_ids_by_module: SharedIdentIdsByModule,
         root_type: RootType,
+        /// Turn the module's doc comment examples into expects, for `roc test --doc`
+        doc_tests: bool,
     },
     CanonicalizeAndConstrain {
         parsed: ParsedModule<'a>,
@@ -1116,6 +1205,7 @@ pub struct LoadStart<'a> {
     root_type: RootType,
     opt_platform_shorthand: Option<&'a str>,
     src_dir: PathBuf,
+    solved_cache: Option<SolvedModuleCache>,
 }
 
 #[derive(Debug, Clone)]
@@ -1197,6 +1287,7 @@ impl<'a> LoadStart<'a> {
             root_msg: header_output.msg,
             root_type,
             opt_platform_shorthand: header_output.opt_platform_shorthand,
+            solved_cache: None,
         })
     }
 
@@ -1253,8 +1344,16 @@ impl<'a> LoadStart<'a> {
             root_msg,
             root_type,
             opt_platform_shorthand: opt_platform_id,
+            solved_cache: None,
         })
     }
+
+    /// Reuse the solved types of the modules in `solved_cache` where they're still valid, and
+    /// add the modules this load solves to it. Only for type-checking loads.
+    pub fn with_solved_cache(mut self, solved_cache: SolvedModuleCache) -> Self {
+        self.solved_cache = Some(solved_cache);
+        self
+    }
 }
 
 fn handle_root_type<'a>(
@@ -1283,7 +1382,7 @@ fn handle_root_type<'a>(
 
                 let cache_dir = roc_cache_dir.as_persistent_path();
 
-                if let (Some(main_path), Some(cache_dir)) = (main_path.clone(), cache_dir) {
+                if let (Some(main_path), Some(_)) = (main_path.clone(), cache_dir) {
                     let mut messages = Vec::with_capacity(4);
                     messages.push(header_output.msg);
 
@@ -1295,7 +1394,7 @@ fn handle_root_type<'a>(
                         Arc::clone(&arc_modules),
                         Arc::clone(&ident_ids_by_module),
                         Arc::clone(&arc_shorthands),
-                        cache_dir,
+                        roc_cache_dir,
                     )?;
 
                     header_output.msg = Msg::Many(messages);
@@ -1335,7 +1434,7 @@ fn load_packages_from_main<'a>(
     module_ids: Arc<Mutex<PackageModuleIds<'a>>>,
     ident_ids_by_module: SharedIdentIdsByModule,
     arc_shorthands: Arc<Mutex<MutMap<&'a str, ShorthandPath>>>,
-    cache_dir: &Path,
+    roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<(), LoadingProblem<'a>> {
     let src_bytes = fs::read(&filename).map_err(|err| LoadingProblem::FileProblem {
         filename: filename.clone(),
@@ -1365,7 +1464,7 @@ fn load_packages_from_main<'a>(
     load_packages(
         packages,
         messages,
-        RocCacheDir::Persistent(cache_dir),
+        roc_cache_dir,
         src_dir.clone(),
         arena,
         None,
@@ -1381,12 +1480,18 @@ fn load_packages_from_main<'a>(
 
     let mut shorthands = arc_shorthands.lock();
 
+    // handle_root_type only calls this with a persistent cache dir
+    let cache_dir = roc_cache_dir.as_persistent_path().unwrap_or_else(|| {
+        internal_error!("Tried to load packages from main without a cache dir");
+    });
+
     register_package_shorthands(
         &mut shorthands,
         &package_entries,
         &filename,
         &src_dir,
         cache_dir,
+        roc_cache_dir.vendor_dir(),
     )
 }
 
@@ -1538,7 +1643,7 @@ pub fn load_single_threaded<'a>(
         root_type,
         src_dir,
         opt_platform_shorthand,
-        ..
+        solved_cache,
     } = load_start;
 
     let (msg_tx, msg_rx) = bounded(1024);
@@ -1560,10 +1665,12 @@ pub fn load_single_threaded<'a>(
         ident_ids_by_module,
         arc_shorthands,
         cached_types,
+        solved_cache,
         render,
         palette,
         number_of_workers,
         exec_mode,
+        roc_cache_dir.vendor_dir().map(Path::to_path_buf),
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1916,7 +2023,7 @@ fn load_multi_threaded<'a>(
         root_type,
         src_dir,
         opt_platform_shorthand,
-        ..
+        solved_cache,
     } = load_start;
 
     let (msg_tx, msg_rx) = bounded(1024);
@@ -1953,10 +2060,12 @@ fn load_multi_threaded<'a>(
         ident_ids_by_module,
         arc_shorthands,
         cached_types,
+        solved_cache,
         render,
         palette,
         num_workers,
         exec_mode,
+        roc_cache_dir.vendor_dir().map(Path::to_path_buf),
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2199,6 +2308,7 @@ fn update<'a>(
                     &header.module_path,
                     &parent_dir,
                     &state.cache_dir,
+                    state.vendor_dir.as_deref(),
                 )?;
             }
 
@@ -2453,6 +2563,42 @@ fn update<'a>(
             log!("solved types for {:?}", module_id);
             module_timing.end_time = Instant::now();
 
+            if let Some(solved_cache) = &state.solved_cache {
+                let cacheable = !module_id.is_builtin()
+                    && !state.reused_modules.contains(&module_id)
+                    && solved_module.problems.is_empty();
+
+                let sources = &state.module_cache.sources;
+                // builtins have the same ids in every load, so they don't need a path
+                let import_paths = state
+                    .module_cache
+                    .imports
+                    .get(&module_id)
+                    .and_then(|imports| {
+                        imports
+                            .iter()
+                            .map(|import| match sources.get(import) {
+                                _ if import.is_builtin() => Some((*import, None)),
+                                Some((path, _)) => Some((*import, Some(path.clone()))),
+                                None => None,
+                            })
+                            .collect::<Option<MutMap<_, _>>>()
+                    });
+
+                if let (true, Some((path, src)), Some(import_paths)) =
+                    (cacheable, sources.get(&module_id), import_paths)
+                {
+                    let type_state = TypeState {
+                        subs: solved_subs.clone().into_inner(),
+                        exposed_vars_by_symbol: solved_module.exposed_vars_by_symbol.clone(),
+                        abilities: abilities_store.clone(),
+                        solved_implementations: solved_module.solved_implementations.clone(),
+                    };
+
+                    solved_cache.insert(path.clone(), module_id, src, import_paths, type_state);
+                }
+            }
+
             state
                 .module_cache
                 .type_problems
@@ -2462,12 +2608,8 @@ fn update<'a>(
                 .exposes
                 .insert(module_id, solved_module.exposed_vars_by_symbol.clone());
 
-            let should_include_expects = (!loc_expects.is_empty() || !loc_dbgs.is_empty()) && {
-                let modules = state.arc_modules.lock();
-                modules
-                    .package_eq(module_id, state.root_id)
-                    .expect("root or this module is not yet known - that's a bug!")
-            };
+            let should_include_expects =
+                (!loc_expects.is_empty() || !loc_dbgs.is_empty()) && state.is_under_test(module_id);
 
             let opt_expectations = if should_include_expects {
                 let (path, _) = state.module_cache.sources.get(&module_id).unwrap();
@@ -2494,7 +2636,7 @@ fn update<'a>(
 
             let add_to_host_exposed = is_host_exposed &&
                 // During testing, we don't need to expose anything to the host.
-                !matches!(state.exec_mode, ExecutionMode::Test);
+                !state.exec_mode.is_test();
 
             if add_to_host_exposed {
                 state.exposed_to_host.top_level_values.extend(
@@ -3018,19 +3160,27 @@ fn register_package_shorthands<'a>(
     src_dir: &Path,
     #[allow(unused_variables)] // for wasm
     cache_dir: &Path,
+    #[allow(unused_variables)] // for wasm
+    vendor_dir: Option<&Path>,
 ) -> Result<(), LoadingProblem<'a>> {
     for (shorthand, package_name) in package_entries.iter() {
         let package_str = package_name.as_str();
-        let shorthand_path = if package_str.starts_with("https://") {
+        let shorthand_path = if cache::is_package_url(package_str) {
             #[cfg(not(target_family = "wasm"))]
             {
                 let url = package_str;
                 match PackageMetadata::try_from(url) {
                     Ok(url_metadata) => {
-                        // This was a valid URL
-                        let root_module_dir = cache_dir
-                            .join(url_metadata.cache_subdir)
-                            .join(url_metadata.content_hash);
+                        // This was a valid URL. When offline, a vendored copy wins over the
+                        // cache, same as in cache::install_package.
+                        let root_module_dir = vendor_dir
+                            .map(|vendor_dir| vendor_dir.join(url_metadata.content_hash))
+                            .filter(|dir| dir.is_dir())
+                            .unwrap_or_else(|| {
+                                cache_dir
+                                    .join(url_metadata.cache_subdir)
+                                    .join(url_metadata.content_hash)
+                            });
                         let root_module = root_module_dir.join(
                             url_metadata
                                 .root_module_filename
@@ -3152,7 +3302,9 @@ fn finish_specialization<'a>(
     let entry_point = {
         let interns: &mut Interns = &mut interns;
         match state.exec_mode {
-            ExecutionMode::Test => Ok(EntryPoint::Test),
+            ExecutionMode::Test | ExecutionMode::TestDocs | ExecutionMode::TestBuiltinDocs => {
+                Ok(EntryPoint::Test)
+            }
             ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck => {
                 use PlatformPath::*;
 
@@ -4111,11 +4263,11 @@ fn load_packages<'a>(
 
         // find the `package` or `platform` module on disk,
         // downloading it into a cache dir first if necessary.
-        let root_module_path = if src.starts_with("https://") {
+        let root_module_path = if cache::is_package_url(src) {
             #[cfg(not(target_family = "wasm"))]
             {
-                // If this is a HTTPS package, synchronously download it
-                // to the cache before proceeding.
+                // If this is a URL package, synchronously download it
+                // (or copy it, for a file:// URL) to the cache before proceeding.
 
                 // TODO we should do this async; however, with the current
                 // architecture of file.rs (which doesn't use async/await),
@@ -4818,56 +4970,40 @@ fn run_solve<'a>(
     let loc_dbgs = std::mem::take(&mut module.loc_dbgs);
     let module = module;
 
-    let solve_result = {
-        if module_id.is_builtin() {
-            match cached_types.lock().remove(&module_id) {
-                None => run_solve_solve(
-                    exposed_for_module,
-                    types,
-                    constraints,
-                    constraint,
-                    function_kind,
-                    pending_derives,
-                    var_store,
-                    module,
-                    derived_module,
-                    //
-                    #[cfg(debug_assertions)]
-                    checkmate,
-                ),
-                Some(TypeState {
-                    subs,
-                    exposed_vars_by_symbol,
-                    abilities,
-                    solved_implementations,
-                }) => SolveResult {
-                    solved: Solved(subs),
-                    solved_implementations,
-                    exposed_vars_by_symbol,
-                    problems: vec![],
-                    abilities_store: abilities,
-                    imported_modules_with_params: vec![],
+    // The builtins, and the modules reused from a `SolvedModuleCache`, are already solved
+    let cached_type_state = cached_types.lock().remove(&module_id);
 
-                    #[cfg(debug_assertions)]
-                    checkmate: None,
-                },
-            }
-        } else {
-            run_solve_solve(
-                exposed_for_module,
-                types,
-                constraints,
-                constraint,
-                function_kind,
-                pending_derives,
-                var_store,
-                module,
-                derived_module,
-                //
-                #[cfg(debug_assertions)]
-                checkmate,
-            )
-        }
+    let solve_result = match cached_type_state {
+        None => run_solve_solve(
+            exposed_for_module,
+            types,
+            constraints,
+            constraint,
+            function_kind,
+            pending_derives,
+            var_store,
+            module,
+            derived_module,
+            //
+            #[cfg(debug_assertions)]
+            checkmate,
+        ),
+        Some(TypeState {
+            subs,
+            exposed_vars_by_symbol,
+            abilities,
+            solved_implementations,
+        }) => SolveResult {
+            solved: Solved(subs),
+            solved_implementations,
+            exposed_vars_by_symbol,
+            problems: vec![],
+            abilities_store: abilities,
+            imported_modules_with_params: vec![],
+
+            #[cfg(debug_assertions)]
+            checkmate: None,
+        },
     };
 
     let SolveResult {
@@ -4895,7 +5031,11 @@ fn run_solve<'a>(
         ExecutionMode::Check => {
             // Params are not lowered in check mode
         }
-        ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck | ExecutionMode::Test => {
+        ExecutionMode::Executable
+        | ExecutionMode::ExecutableIfCheck
+        | ExecutionMode::Test
+        | ExecutionMode::TestDocs
+        | ExecutionMode::TestBuiltinDocs => {
             roc_lower_params::type_error::remove_module_param_arguments(
                 &mut problems,
                 home_has_params,
@@ -5073,6 +5213,7 @@ fn canonicalize_and_constrain<'a>(
         mut module_timing,
         symbols_from_requires,
         opt_shorthand,
+        skipped_doc_tests,
         ..
     } = parsed;
 
@@ -5112,6 +5253,12 @@ fn canonicalize_and_constrain<'a>(
         fx_mode,
     );
 
+    module_output.problems.extend(
+        skipped_doc_tests
+            .into_iter()
+            .map(roc_problem::can::Problem::SkippedDocTest),
+    );
+
     let mut types = Types::new();
 
     // _after has an underscore because it's unused in --release builds
@@ -5157,7 +5304,11 @@ fn canonicalize_and_constrain<'a>(
             // No need to lower params for `roc check` and lang server
             // If we did, we'd have to update the language server to exclude the extra arguments
         }
-        ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck | ExecutionMode::Test => {
+        ExecutionMode::Executable
+        | ExecutionMode::ExecutableIfCheck
+        | ExecutionMode::Test
+        | ExecutionMode::TestDocs
+        | ExecutionMode::TestBuiltinDocs => {
             // We need to lower params only if the current module has any or imports at least one with params
             if module_output.module_params.is_some() || !imported_module_params.is_empty() {
                 roc_lower_params::lower::lower(
@@ -5272,6 +5423,7 @@ fn parse<'a>(
     module_ids: Arc<Mutex<PackageModuleIds<'a>>>,
     ident_ids_by_module: SharedIdentIdsByModule,
     root_type: RootType,
+    doc_tests: bool,
 ) -> Result<Msg<'a>, LoadingProblem<'a>> {
     let mut module_timing = header.module_timing;
     let parse_start = Instant::now();
@@ -5281,7 +5433,7 @@ fn parse<'a>(
     let header_import_defs =
         roc_parse::ast::Header::header_imports_to_defs(arena, header.header_imports);
 
-    let parsed_defs = match parse_module_defs(arena, parse_state.clone(), header_import_defs) {
+    let mut parsed_defs = match parse_module_defs(arena, parse_state.clone(), header_import_defs) {
         Ok(success) => success,
         Err(fail) => {
             return Err(LoadingProblem::ParsingFailed(
@@ -5295,6 +5447,20 @@ fn parse<'a>(
     // we'd have bailed out before now.
     let src = unsafe { from_utf8_unchecked(source) };
 
+    let skipped_doc_tests = if doc_tests {
+        match crate::doc_tests::add_doc_tests(arena, src, header.header_comments, &mut parsed_defs)
+        {
+            Ok(skipped) => skipped,
+            Err(fail) => {
+                return Err(LoadingProblem::ParsingFailed(
+                    fail.into_file_error(header.module_path, &parse_state),
+                ));
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
     // Record the parse end time once, to avoid checking the time a second time
     // immediately afterward (for the beginning of canonicalization).
     let parse_end = Instant::now();
@@ -5563,6 +5729,7 @@ fn parse<'a>(
         header_type,
         header_comments: header_docs,
         opt_shorthand: header.opt_shorthand,
+        skipped_doc_tests,
     };
 
     Ok(Msg::Parsed(parsed))
@@ -5944,10 +6111,23 @@ fn build_pending_specializations<'a>(
                     continue;
                 }
 
-                // mark this symbol as a top-level thunk before any other work on the procs
-                module_thunks.push(symbol);
+                let body = roc_can::expr::toplevel_expect_to_inline_expect_pure(body);
 
-                let expr_var = Variable::EMPTY_RECORD;
+                let expr_var = match &body.value {
+                    // a property like `expect \xs -> ...`, which the test runner calls with
+                    // generated arguments
+                    roc_can::expr::Expr::Closure(closure) => {
+                        default_property_expect_arguments(&mut mono_env, closure);
+
+                        property_expect_function_var(mono_env.subs, closure)
+                    }
+                    _ => {
+                        // mark this symbol as a top-level thunk before any other work on the procs
+                        module_thunks.push(symbol);
+
+                        Variable::EMPTY_RECORD
+                    }
+                };
 
                 let is_host_exposed = true;
 
@@ -5987,20 +6167,6 @@ fn build_pending_specializations<'a>(
                     );
                 }
 
-                let body = roc_can::expr::toplevel_expect_to_inline_expect_pure(body);
-
-                let proc = PartialProc {
-                    annotation: expr_var,
-                    // This is a 0-arity thunk, so it has no arguments.
-                    pattern_symbols: &[],
-                    // This is a top-level definition, so it cannot capture anything
-                    captured_symbols: CapturedSymbols::None,
-                    body: body.value,
-                    body_var: expr_var,
-                    // This is a 0-arity thunk, so it cannot be recursive
-                    is_self_recursive: false,
-                };
-
                 // extend the region of the expect expression with the region of the preceding
                 // comment, so it is shown in failure/panic messages
                 let name_region = declarations.symbols[index].region;
@@ -6008,7 +6174,50 @@ fn build_pending_specializations<'a>(
                 let region = Region::span_across(&name_region, &expr_region);
 
                 toplevel_expects.pure.insert(symbol, region);
-                procs_base.partial_procs.insert(symbol, proc);
+
+                match body.value {
+                    roc_can::expr::Expr::Closure(closure) => {
+                        let arguments =
+                            closure
+                                .arguments
+                                .iter()
+                                .map(|(var, _, loc_pattern)| match loc_pattern.value {
+                                    roc_can::pattern::Pattern::Identifier(name) => {
+                                        (Some(name), *var)
+                                    }
+                                    _ => (None, *var),
+                                });
+                        toplevel_expects
+                            .property_arguments
+                            .insert(symbol, arguments.collect());
+
+                        register_toplevel_function_into_procs_base(
+                            &mut mono_env,
+                            &mut procs_base,
+                            symbol,
+                            expr_var,
+                            closure.arguments,
+                            Variable::EMPTY_RECORD,
+                            *closure.loc_body,
+                            false,
+                        );
+                    }
+                    body => {
+                        let proc = PartialProc {
+                            annotation: expr_var,
+                            // This is a 0-arity thunk, so it has no arguments.
+                            pattern_symbols: &[],
+                            // This is a top-level definition, so it cannot capture anything
+                            captured_symbols: CapturedSymbols::None,
+                            body,
+                            body_var: expr_var,
+                            // This is a 0-arity thunk, so it cannot be recursive
+                            is_self_recursive: false,
+                        };
+
+                        procs_base.partial_procs.insert(symbol, proc);
+                    }
+                }
             }
             ExpectationFx => {
                 // skip expectations if we're not going to run them
@@ -6085,6 +6294,8 @@ fn build_pending_specializations<'a>(
         }
     }
 
+    toplevel_expects.def_names = expect_def_names(&declarations, &toplevel_expects);
+
     layout_cache.rollback_to(layout_cache_snapshot);
 
     procs_base.module_thunks = module_thunks.into_bump_slice();
@@ -6105,6 +6316,163 @@ fn build_pending_specializations<'a>(
     }
 }
 
+/// For each top-level expect, find the closest top-level def that starts before it in the source
+fn expect_def_names(
+    declarations: &Declarations,
+    toplevel_expects: &ToplevelExpects,
+) -> VecMap<Symbol, Symbol> {
+    use roc_can::expr::DeclarationTag::*;
+
+    let mut def_starts: Vec<(u32, Symbol)> = (0..declarations.len())
+        .filter(|index| {
+            matches!(
+                declarations.declarations[*index],
+                Value | Function(_) | Recursive(_) | TailRecursive(_)
+            )
+        })
+        .map(|index| {
+            let loc_symbol = declarations.symbols[index];
+            (loc_symbol.region.start().offset, loc_symbol.value)
+        })
+        .collect();
+    def_starts.sort_unstable_by_key(|(start, _)| *start);
+
+    let mut def_names = VecMap::default();
+
+    for (expect_symbol, region) in toplevel_expects
+        .pure
+        .iter()
+        .chain(toplevel_expects.fx.iter())
+    {
+        let expect_start = region.start().offset;
+        let preceding = def_starts.partition_point(|(start, _)| *start < expect_start);
+
+        if let Some((_, def_symbol)) = preceding.checked_sub(1).map(|i| def_starts[i]) {
+            def_names.insert(*expect_symbol, def_symbol);
+        }
+    }
+
+    def_names
+}
+
+/// Parts of a property expect's arguments that nothing constrains, like the elements of `xs` in
+/// `expect \xs -> List.reverse (List.reverse xs) == xs`, would get a zero-sized layout, and then
+/// `roc test` could only ever generate empty lists. Like unconstrained number literals, they
+/// default to `I64` instead, unless they have to implement an ability that `I64` doesn't. Those
+/// stay unbound, and `roc test` reports that it can't generate them.
+fn default_property_expect_arguments(
+    mono_env: &mut roc_mono::ir::Env,
+    closure: &roc_can::expr::ClosureData,
+) {
+    let mut unbound = Vec::new();
+    let mut seen = Vec::new();
+
+    for (var, _, _) in closure.arguments.iter() {
+        collect_unbound_vars(mono_env.subs, *var, &mut seen, &mut unbound);
+    }
+
+    for var in unbound {
+        // unify like mono does when it specializes, so that abilities used on these
+        // variables (like `==`) get resolved
+        let result = roc_late_solve::unify(
+            mono_env.home,
+            mono_env.arena,
+            mono_env.subs,
+            &mono_env.abilities,
+            mono_env.derived_module,
+            mono_env.exposed_by_module,
+            var,
+            Variable::I64,
+        );
+
+        if result.is_err() {
+            // leave it unbound, so `roc test` reports that it can't generate it
+            continue;
+        }
+
+        if let Some(expectation_subs) = mono_env.expectation_subs.as_deref_mut() {
+            let i64_descriptor = expectation_subs.get(Variable::I64);
+            expectation_subs.union(var, Variable::I64, i64_descriptor);
+        }
+    }
+}
+
+fn collect_unbound_vars(
+    subs: &Subs,
+    var: Variable,
+    seen: &mut Vec<Variable>,
+    unbound: &mut Vec<Variable>,
+) {
+    use roc_types::subs::{Content, FlatType, GetSubsSlice};
+
+    let var = subs.get_root_key_without_compacting(var);
+    if seen.contains(&var) {
+        return;
+    }
+    seen.push(var);
+
+    let mut children = Vec::new();
+
+    match subs.get_content_without_compacting(var) {
+        Content::FlexVar(_) => unbound.push(var),
+        Content::FlexAbleVar(_, abilities) => {
+            // every builtin ability can be derived for `I64`, and no other ability can
+            if subs
+                .get_subs_slice(*abilities)
+                .iter()
+                .all(|ability| ability.is_derivable_ability())
+            {
+                unbound.push(var);
+            }
+        }
+        Content::Alias(symbol, _, real_var, _) => {
+            // numbers without a concrete type already default to I64 when we pick their layout
+            if symbol.module_id() != ModuleId::NUM {
+                children.push(*real_var);
+            }
+        }
+        Content::Structure(FlatType::Apply(_, arguments)) => {
+            children.extend(subs.get_subs_slice(*arguments));
+        }
+        Content::Structure(FlatType::Record(fields, _)) => {
+            children.extend(subs.get_subs_slice(fields.variables()));
+        }
+        Content::Structure(FlatType::Tuple(elems, _)) => {
+            children.extend(subs.get_subs_slice(elems.variables()));
+        }
+        Content::Structure(FlatType::TagUnion(tags, _)) => {
+            for payload in tags.variables() {
+                children.extend(subs.get_subs_slice(subs[payload]));
+            }
+        }
+        Content::Structure(FlatType::Func(arguments, _, ret, _)) => {
+            children.extend(subs.get_subs_slice(*arguments));
+            children.push(*ret);
+        }
+        _ => { /* nothing we could generate values for anyway */ }
+    }
+
+    for child in children {
+        collect_unbound_vars(subs, child, seen, unbound);
+    }
+}
+
+/// The type of a property expect once it has been turned into an inline expect:
+/// it takes the same arguments, but returns `{}` rather than a `Bool`.
+fn property_expect_function_var(subs: &mut Subs, closure: &roc_can::expr::ClosureData) -> Variable {
+    use roc_types::subs::{Content, FlatType};
+
+    let arguments = subs.insert_into_vars(closure.arguments.iter().map(|(var, _, _)| *var));
+    let function = FlatType::Func(
+        arguments,
+        closure.closure_type,
+        Variable::EMPTY_RECORD,
+        closure.fx_type,
+    );
+
+    subs.fresh(Content::Structure(function).into())
+}
+
 fn register_toplevel_function_into_procs_base<'a>(
     mono_env: &mut roc_mono::ir::Env<'a, '_>,
     procs_base: &mut ProcsBase<'a>,
@@ -6274,6 +6642,7 @@ fn run_task<'a>(
             module_ids,
             ident_ids_by_module,
             root_type,
+            doc_tests,
         } => parse(
             arena,
             header,
@@ -6281,6 +6650,7 @@ fn run_task<'a>(
             module_ids,
             ident_ids_by_module,
             root_type,
+            doc_tests,
         ),
         CanonicalizeAndConstrain {
             parsed,
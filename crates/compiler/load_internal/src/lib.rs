@@ -4,10 +4,12 @@
 #![allow(clippy::large_enum_variant)]
 
 use roc_module::symbol::ModuleId;
+mod doc_tests;
 pub mod docs;
 pub mod file;
 pub mod module;
 mod module_cache;
+pub mod solved_cache;
 
 #[cfg(target_family = "wasm")]
 mod wasm_instant;
@@ -168,6 +168,12 @@ pub(crate) struct LateSpecializationsModule<'a> {
 pub struct ToplevelExpects {
     pub pure: VecMap<Symbol, Region>,
     pub fx: VecMap<Symbol, Region>,
+    /// The closest top-level def above each expect. Expects usually sit right below
+    /// the def they test, so this is what `roc test --filter` matches on.
+    pub def_names: VecMap<Symbol, Symbol>,
+    /// The arguments of property expects, like `expect \xs -> ...`: their names, unless they
+    /// are destructured, and their types. `roc test` generates values to call them with.
+    pub property_arguments: VecMap<Symbol, Vec<(Option<Symbol>, Variable)>>,
 }
 
 #[derive(Debug)]
@@ -208,6 +214,8 @@ pub struct ParsedModule<'a> {
     pub initial_scope: MutMap<Ident, (Symbol, Region)>,
     pub exposes: Vec<Symbol>,
     pub opt_shorthand: Option<&'a str>,
+    /// The doc comment examples `roc test --doc` can't run, because they don't end in an `expect`
+    pub skipped_doc_tests: Vec<Region>,
 }
 
 #[derive(Debug)]
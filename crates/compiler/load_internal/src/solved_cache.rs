@@ -0,0 +1,106 @@
+//! Lets a load reuse the solved types of modules an earlier load already solved.
+//!
+//! Editors load the same module graph again every time a module in it changes. The modules
+//! that didn't change solve to the same types as last time, as long as none of the modules
+//! they import changed either, so solving them again is wasted work.
+use parking_lot::Mutex;
+use roc_can::abilities::AbilitiesStore;
+use roc_can::module::{ResolvedImplementations, TypeState};
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_types::subs::{Subs, Variable};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The solved types of the modules of earlier loads, by path. Pass one to
+/// [`LoadStart::with_solved_cache`](crate::file::LoadStart::with_solved_cache) to reuse them,
+/// and to add the modules that load solves.
+///
+/// Symbols and variables are only meaningful within a load, so an entry is only reused by a load
+/// that gives the module and everything it imports the same [`ModuleId`]s, and has the same
+/// source for all of them.
+#[derive(Debug, Clone, Default)]
+pub struct SolvedModuleCache {
+    modules: Arc<Mutex<MutMap<PathBuf, SolvedModule>>>,
+}
+
+#[derive(Debug)]
+struct SolvedModule {
+    module_id: ModuleId,
+    src: Box<str>,
+    /// The path of each module it imports, except for the builtins, whose ids never change
+    imports: MutMap<ModuleId, Option<PathBuf>>,
+    subs: Subs,
+    exposed_vars_by_symbol: Vec<(Symbol, Variable)>,
+    abilities: AbilitiesStore,
+    solved_implementations: ResolvedImplementations,
+}
+
+impl SolvedModuleCache {
+    /// The solved types of a module, if they were cached with the same source, and the same ids
+    /// for it and for the modules it imports. `import_path` is where this load found an import.
+    /// The caller must check that the imports were reused too.
+    pub(crate) fn get<'p>(
+        &self,
+        path: &Path,
+        module_id: ModuleId,
+        src: &str,
+        imports: &MutSet<ModuleId>,
+        import_path: impl Fn(ModuleId) -> Option<&'p Path>,
+    ) -> Option<TypeState> {
+        let modules = self.modules.lock();
+        let solved = modules.get(path)?;
+
+        // The types refer to these modules by id, so the ids have to mean the same modules
+        let same_imports = solved.imports.len() == imports.len()
+            && imports
+                .iter()
+                .all(|import| match solved.imports.get(import) {
+                    Some(Some(cached_path)) => import_path(*import) == Some(cached_path.as_path()),
+                    Some(None) => import.is_builtin(),
+                    None => false,
+                });
+
+        if solved.module_id != module_id || &*solved.src != src || !same_imports {
+            return None;
+        }
+
+        Some(TypeState {
+            subs: solved.subs.clone(),
+            exposed_vars_by_symbol: solved.exposed_vars_by_symbol.clone(),
+            abilities: solved.abilities.clone(),
+            solved_implementations: solved.solved_implementations.clone(),
+        })
+    }
+
+    /// Cache a module's solved types, replacing the ones cached for its path before, even if that
+    /// load gave it another id. `imports` has the path of each import, except for builtins.
+    pub(crate) fn insert(
+        &self,
+        path: PathBuf,
+        module_id: ModuleId,
+        src: &str,
+        imports: MutMap<ModuleId, Option<PathBuf>>,
+        type_state: TypeState,
+    ) {
+        let TypeState {
+            subs,
+            exposed_vars_by_symbol,
+            abilities,
+            solved_implementations,
+        } = type_state;
+
+        self.modules.lock().insert(
+            path,
+            SolvedModule {
+                module_id,
+                src: src.into(),
+                imports,
+                subs,
+                exposed_vars_by_symbol,
+                abilities,
+                solved_implementations,
+            },
+        );
+    }
+}
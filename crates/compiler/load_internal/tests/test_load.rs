@@ -22,6 +22,7 @@ use roc_load_internal::file::{
     ExecutionMode, LoadConfig, LoadResult, LoadStart, LoadingProblem, Threading,
 };
 use roc_load_internal::module::LoadedModule;
+use roc_load_internal::solved_cache::SolvedModuleCache;
 use roc_module::ident::ModuleName;
 use roc_module::symbol::{Interns, ModuleId};
 use roc_packaging::cache::RocCacheDir;
@@ -37,7 +38,7 @@ use roc_test_utils_dir::TmpDir;
 use roc_types::pretty_print::name_and_print_var;
 use roc_types::pretty_print::DebugPrint;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn load_and_typecheck(
     arena: &Bump,
@@ -2135,3 +2136,394 @@ fn roc_file_no_extension() {
 
     assert_eq!(err, expected, "\n{}", err);
 }
+
+#[test]
+fn property_expect() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r"
+            module [double]
+
+            double = \n -> n * 2
+
+            expect \n -> double n == n + n
+
+            expect \xs -> List.reverse (List.reverse xs) == xs
+            "
+        ),
+    )];
+
+    let result = multiple_modules("property_expect", modules);
+    assert!(result.is_ok(), "{}", result.unwrap_err());
+}
+
+#[test]
+fn property_expect_body_must_be_bool() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r#"
+            module [double]
+
+            double = \n -> n * 2
+
+            expect \n -> Num.toStr (double n)
+            "#
+        ),
+    )];
+
+    let expected = indoc!(
+        r"
+        ── TYPE MISMATCH in tmp/property_expect_body_must_be_bool/Main.roc ─────────────
+
+        This `expect` condition needs to be a Bool:
+
+        5│  expect \n -> Num.toStr (double n)
+                         ^^^^^^^^^^^^^^^^^^^^
+
+        This returns a value of type:
+
+            Str
+
+        But I need every `expect` condition to evaluate to a Bool—either
+        `Bool.true` or `Bool.false`.
+        "
+    );
+
+    let err =
+        strip_colors(&multiple_modules("property_expect_body_must_be_bool", modules).unwrap_err());
+
+    assert_eq!(err, expected, "\n{}", err);
+}
+
+/// Load a single module like `roc test --doc` does, and count its top-level expects.
+/// Any problems, warnings included, are the error.
+fn count_doc_tests(subdir: &str, src: &str) -> Result<usize, String> {
+    let arena = Bump::new();
+    let dir = TmpDir::new(&format!("tmp/{subdir}"));
+    let filename = dir.path().join("Main.roc");
+
+    std::fs::write(&filename, src).unwrap();
+
+    let load_start = LoadStart::from_path(
+        &arena,
+        filename,
+        None,
+        RenderTarget::Generic,
+        RocCacheDir::Disallowed,
+        DEFAULT_PALETTE,
+    )
+    .map_err(|problem| format!("{problem:?}"))?;
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode: ExecutionMode::TestDocs,
+    };
+
+    let loaded = roc_load_internal::file::load(
+        &arena,
+        load_start,
+        Default::default(),
+        Default::default(),
+        RocCacheDir::Disallowed,
+        load_config,
+    );
+
+    match loaded {
+        Ok(LoadResult::Monomorphized(mut module)) => {
+            let home = module.module_id;
+            let can_problems = module.can_problems.remove(&home).unwrap_or_default();
+
+            if can_problems.is_empty() {
+                Ok(module.toplevel_expects[&home].pure.len())
+            } else {
+                let (filepath, src) = module.sources.get(&home).unwrap();
+
+                Err(format_can_problems(
+                    can_problems,
+                    home,
+                    &module.interns,
+                    filepath.clone(),
+                    src,
+                ))
+            }
+        }
+        Ok(LoadResult::TypeChecked(mut module)) => {
+            let home = module.module_id;
+            let (filepath, src) = module.sources.get(&home).unwrap();
+            let can_problems = module.can_problems.remove(&home).unwrap_or_default();
+            let type_problems = module.type_problems.remove(&home).unwrap_or_default();
+
+            Err(
+                format_can_problems(can_problems, home, &module.interns, filepath.clone(), src)
+                    + &format_type_problems(
+                        type_problems,
+                        home,
+                        &module.interns,
+                        filepath.clone(),
+                        src,
+                    ),
+            )
+        }
+        Err(LoadingProblem::FormattedReport(report)) => Err(report),
+        Err(problem) => Err(format!("{problem:?}")),
+    }
+}
+
+#[test]
+fn doc_tests() {
+    let src = indoc!(
+        r"
+        module [double]
+
+        ## Doubles a number.
+        ## ```roc
+        ## expect double 2 == 4
+        ## ```
+        ##
+        ## The defs in an example are in scope for its expects:
+        ## ```roc
+        ## four = double 2
+        ## expect four == 4
+        ## expect double four == 8
+        ## ```
+        ##
+        ## Examples without an expect aren't tests:
+        ## ```roc
+        ## double 21
+        ## ```
+        double = \n -> n * 2
+
+        expect double 0 == 0
+        "
+    );
+
+    assert_eq!(count_doc_tests("doc_tests", src), Ok(3));
+}
+
+#[test]
+fn doc_test_without_final_expect() {
+    let src = indoc!(
+        r"
+        module [double]
+
+        ## ```roc
+        ## expect double 2 == 4
+        ## double 3
+        ## ```
+        double = \n -> n * 2
+        "
+    );
+
+    let err = strip_colors(&count_doc_tests("doc_test_without_final_expect", src).unwrap_err());
+
+    // It's a warning rather than a test that silently never runs
+    assert!(err.contains("SKIPPED DOC TEST"), "\n{}", err);
+    assert!(err.contains("4│  ## expect double 2 == 4"), "\n{}", err);
+}
+
+#[test]
+fn doc_test_problem_in_comment() {
+    let src = indoc!(
+        r#"
+        module [double]
+
+        ## ```roc
+        ## expect double "two" == 4
+        ## ```
+        double = \n -> n * 2
+        "#
+    );
+
+    let err = strip_colors(&count_doc_tests("doc_test_problem_in_comment", src).unwrap_err());
+
+    // The problem is reported in the doc comment the example is in
+    assert!(err.contains("TYPE MISMATCH"), "\n{}", err);
+    assert!(
+        err.contains(r#"4│  ## expect double "two" == 4"#),
+        "\n{}",
+        err
+    );
+}
+
+#[test]
+fn docs_opaque_implements_and_regions() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r"
+            module [Id, zero]
+
+            ## An identifier
+            Id := U64 implements [Eq, Hash]
+
+            zero : Id
+            zero = @Id 0
+            "
+        ),
+    )];
+
+    let loaded_module = multiple_modules("docs_opaque_implements_and_regions", modules).unwrap();
+    let module_docs = loaded_module
+        .docs_by_module
+        .get(&loaded_module.module_id)
+        .expect("module should have docs");
+
+    let doc_defs: Vec<_> = module_docs
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            roc_load_internal::docs::DocEntry::DocDef(DocDef {
+                name,
+                region,
+                implements,
+                ..
+            }) => Some((name.as_str(), region.start().offset, implements.clone())),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        doc_defs,
+        vec![
+            ("Id", 36, vec!["Eq".to_string(), "Hash".to_string()]),
+            ("zero", 69, Vec::new()),
+        ]
+    );
+}
+
+fn load_with_solved_cache(path: &Path, solved_cache: &SolvedModuleCache) -> LoadedModule {
+    let arena = Bump::new();
+    let load_start = LoadStart::from_path(
+        &arena,
+        path.to_path_buf(),
+        None,
+        RenderTarget::Generic,
+        RocCacheDir::Disallowed,
+        DEFAULT_PALETTE,
+    )
+    .unwrap()
+    .with_solved_cache(solved_cache.clone());
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode: ExecutionMode::Check,
+    };
+
+    match roc_load_internal::file::load(
+        &arena,
+        load_start,
+        Default::default(),
+        Default::default(),
+        RocCacheDir::Disallowed,
+        load_config,
+    ) {
+        Ok(LoadResult::TypeChecked(module)) => module,
+        _ => panic!("{} should type-check", path.display()),
+    }
+}
+
+#[test]
+fn solved_cache_rechecks_changed_imports() {
+    let dir = TmpDir::new("tmp/solved_cache_rechecks_changed_imports");
+    let main_path = dir.path().join("Main.roc");
+    let dep_path = dir.path().join("Dep.roc");
+    let solved_cache = SolvedModuleCache::default();
+
+    std::fs::write(
+        &main_path,
+        indoc!(
+            r"
+            module [main]
+
+            import Dep
+
+            main = Dep.value
+            "
+        ),
+    )
+    .unwrap();
+    std::fs::write(&dep_path, "module [value]\n\nvalue = \"one\"\n").unwrap();
+
+    expect_types(
+        load_with_solved_cache(&main_path, &solved_cache),
+        hashmap! { "main" => "Str" },
+    );
+    // Dep didn't change, so it's reused this time
+    expect_types(
+        load_with_solved_cache(&main_path, &solved_cache),
+        hashmap! { "main" => "Str" },
+    );
+
+    std::fs::write(&dep_path, "module [value]\n\nvalue = 1u8\n").unwrap();
+
+    expect_types(
+        load_with_solved_cache(&main_path, &solved_cache),
+        hashmap! { "main" => "U8" },
+    );
+}
+
+#[test]
+fn solved_cache_handles_changed_module_ids() {
+    let dir = TmpDir::new("tmp/solved_cache_handles_changed_module_ids");
+    let main_path = dir.path().join("Main.roc");
+    let solved_cache = SolvedModuleCache::default();
+
+    std::fs::write(
+        dir.path().join("A.roc"),
+        "module [value]\n\nimport B\n\nvalue = B.value\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("B.roc"),
+        "module [value]\n\nvalue = \"b\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("C.roc"), "module [value]\n\nvalue = 1u8\n").unwrap();
+    std::fs::write(
+        &main_path,
+        indoc!(
+            r"
+            module [main]
+
+            import A
+
+            main = A.value
+            "
+        ),
+    )
+    .unwrap();
+
+    expect_types(
+        load_with_solved_cache(&main_path, &solved_cache),
+        hashmap! { "main" => "Str" },
+    );
+
+    // C gets loaded first now, so A and B get different ids than last time
+    std::fs::write(
+        &main_path,
+        indoc!(
+            r"
+            module [main]
+
+            import C
+            import A
+
+            main = { a: A.value, c: C.value }
+            "
+        ),
+    )
+    .unwrap();
+
+    expect_types(
+        load_with_solved_cache(&main_path, &solved_cache),
+        hashmap! { "main" => "{ a : Str, c : U8 }" },
+    );
+}
@@ -1651,11 +1651,13 @@ define_builtins! {
         21 DECODE_LIST: "list"
         22 DECODE_RECORD: "record"
         23 DECODE_TUPLE: "tuple"
-        24 DECODE_CUSTOM: "custom"
-        25 DECODE_DECODE_WITH: "decodeWith"
-        26 DECODE_FROM_BYTES_PARTIAL: "fromBytesPartial"
-        27 DECODE_FROM_BYTES: "fromBytes"
-        28 DECODE_MAP_RESULT: "mapResult"
+        24 DECODE_TAG: "tag"
+        25 DECODE_DICT: "dict"
+        26 DECODE_CUSTOM: "custom"
+        27 DECODE_DECODE_WITH: "decodeWith"
+        28 DECODE_FROM_BYTES_PARTIAL: "fromBytesPartial"
+        29 DECODE_FROM_BYTES: "fromBytes"
+        30 DECODE_MAP_RESULT: "mapResult"
     }
     13 HASH: "Hash" => {
         0 HASH_HASH_ABILITY: "Hash" exposed_type=true
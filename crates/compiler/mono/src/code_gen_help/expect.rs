@@ -0,0 +1,1095 @@
+//! Lower `dbg` and `expect` statements to plain IR, for the backends that don't have
+//! their own lowering of these statements.
+//!
+//! A failed `expect` writes a frame to shared memory, in the same format as
+//! `roc_gen_llvm::llvm::expect`, so that `roc_repl_expect` can read it back.
+
+use bumpalo::collections::vec::Vec;
+use bumpalo::Bump;
+use roc_builtins::bitcode;
+use roc_error_macros::{internal_error, todo_lambda_erasure};
+use roc_module::ident::ForeignSymbol;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{IdentIds, Symbol};
+use roc_region::all::Region;
+
+use crate::ir::{
+    BranchInfo, Call, CallType, Expr, JoinPointId, Literal, LookupType, Param, Stmt, UpdateModeId,
+};
+use crate::layout::{
+    Builtin, InLayout, Layout, LayoutInterner, LayoutRepr, STLayoutInterner, TagIdIntType,
+    UnionLayout,
+};
+
+use super::{CodeGenHelp, Context, LAYOUT_BOOL, LAYOUT_UNIT};
+
+const ARG_1: Symbol = Symbol::ARG_1;
+const ARG_2: Symbol = Symbol::ARG_2;
+const ARG_3: Symbol = Symbol::ARG_3;
+const ARG_4: Symbol = Symbol::ARG_4;
+
+/// Straight-line IR. The `let`s are pushed in program order, and wrapped around the
+/// final statement at the end.
+struct Block<'a> {
+    lets: Vec<'a, (Symbol, Expr<'a>, InLayout<'a>)>,
+}
+
+impl<'a> Block<'a> {
+    fn new(arena: &'a Bump) -> Self {
+        Block {
+            lets: Vec::new_in(arena),
+        }
+    }
+
+    fn push(&mut self, symbol: Symbol, expr: Expr<'a>, layout: InLayout<'a>) -> Symbol {
+        self.lets.push((symbol, expr, layout));
+        symbol
+    }
+
+    fn finish(self, arena: &'a Bump, next: &'a Stmt<'a>) -> Stmt<'a> {
+        let mut stmt = next;
+
+        for (symbol, expr, layout) in self.lets.into_iter().rev() {
+            stmt = arena.alloc(Stmt::Let(symbol, expr, layout, stmt));
+        }
+
+        stmt.clone()
+    }
+}
+
+fn lowlevel<'a>(arena: &'a Bump, op: LowLevel, arguments: &[Symbol]) -> Expr<'a> {
+    Expr::Call(Call {
+        call_type: CallType::LowLevel {
+            op,
+            update_mode: UpdateModeId::BACKEND_DUMMY,
+        },
+        arguments: arena.alloc_slice_copy(arguments),
+    })
+}
+
+fn foreign_call<'a>(
+    arena: &'a Bump,
+    name: &str,
+    ret_layout: InLayout<'a>,
+    arguments: &[Symbol],
+) -> Expr<'a> {
+    Expr::Call(Call {
+        call_type: CallType::Foreign {
+            foreign_symbol: ForeignSymbol::from(name),
+            ret_layout,
+        },
+        arguments: arena.alloc_slice_copy(arguments),
+    })
+}
+
+fn int_literal<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    block: &mut Block<'a>,
+    layout: InLayout<'a>,
+    value: i128,
+) -> Symbol {
+    let symbol = root.create_symbol(ident_ids, "literal");
+    let expr = Expr::Literal(Literal::Int(value.to_ne_bytes()));
+    block.push(symbol, expr, layout)
+}
+
+/// `offset + amount`, where `offset` is an isize
+fn add_offset<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    block: &mut Block<'a>,
+    offset: Symbol,
+    amount: u32,
+) -> Symbol {
+    let layout_isize = root.layout_isize;
+    let amount = int_literal(root, ident_ids, block, layout_isize, amount as i128);
+
+    let sum = root.create_symbol(ident_ids, "offset");
+    let expr = lowlevel(root.arena, LowLevel::NumAdd, &[offset, amount]);
+    block.push(sum, expr, layout_isize)
+}
+
+/// Cast `buffer + offset` to a pointer to `layout`
+fn pointer_at_offset<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    layout_interner: &mut STLayoutInterner<'a>,
+    block: &mut Block<'a>,
+    buffer: Symbol,
+    offset: Symbol,
+    layout: InLayout<'a>,
+) -> Symbol {
+    let arena = root.arena;
+    let ptr_layout = layout_interner.insert_direct_no_semantic(LayoutRepr::Ptr(layout));
+
+    let addr = root.create_symbol(ident_ids, "addr");
+    let addr_expr = lowlevel(arena, LowLevel::NumAdd, &[buffer, offset]);
+    block.push(addr, addr_expr, root.layout_isize);
+
+    let ptr = root.create_symbol(ident_ids, "ptr");
+    block.push(ptr, lowlevel(arena, LowLevel::PtrCast, &[addr]), ptr_layout)
+}
+
+fn load_at<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    layout_interner: &mut STLayoutInterner<'a>,
+    block: &mut Block<'a>,
+    buffer: Symbol,
+    offset: Symbol,
+    layout: InLayout<'a>,
+) -> Symbol {
+    let ptr = pointer_at_offset(
+        root,
+        ident_ids,
+        layout_interner,
+        block,
+        buffer,
+        offset,
+        layout,
+    );
+
+    let loaded = root.create_symbol(ident_ids, "loaded");
+    block.push(loaded, Expr::ptr_load(root.arena.alloc(ptr)), layout)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn store_at<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    layout_interner: &mut STLayoutInterner<'a>,
+    block: &mut Block<'a>,
+    buffer: Symbol,
+    offset: Symbol,
+    value: Symbol,
+    layout: InLayout<'a>,
+) {
+    if layout_interner.stack_size(layout) == 0 {
+        return;
+    }
+
+    let ptr = pointer_at_offset(
+        root,
+        ident_ids,
+        layout_interner,
+        block,
+        buffer,
+        offset,
+        layout,
+    );
+
+    let unit = root.create_symbol(ident_ids, "unit");
+    let store_expr = Expr::ptr_store(root.arena.alloc([ptr, value]));
+    block.push(unit, store_expr, LAYOUT_UNIT);
+}
+
+// ============================================================================
+//
+//              DBG
+//
+// ============================================================================
+
+/// `roc_dbg(&location, &message, &source)`, followed by the remainder
+pub fn dbg_stmt<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    layout_interner: &mut STLayoutInterner<'a>,
+    source_location: &'a str,
+    source: &'a str,
+    symbol: Symbol,
+    remainder: &'a Stmt<'a>,
+) -> Stmt<'a> {
+    let arena = root.arena;
+    let ptr_str = layout_interner.insert_direct_no_semantic(LayoutRepr::Ptr(Layout::STR));
+    let mut block = Block::new(arena);
+
+    let location = root.create_symbol(ident_ids, "dbg_location");
+    block.push(
+        location,
+        Expr::Literal(Literal::Str(source_location)),
+        Layout::STR,
+    );
+
+    let source_str = root.create_symbol(ident_ids, "dbg_source");
+    block.push(source_str, Expr::Literal(Literal::Str(source)), Layout::STR);
+
+    // roc_dbg takes its strings by reference
+    let mut by_reference = |name, value| {
+        let ptr = root.create_symbol(ident_ids, name);
+        let alloca = Expr::Alloca {
+            element_layout: Layout::STR,
+            initializer: Some(value),
+        };
+        block.push(ptr, alloca, ptr_str)
+    };
+
+    let location_ptr = by_reference("location_ptr", location);
+    let message_ptr = by_reference("message_ptr", symbol);
+    let source_ptr = by_reference("source_ptr", source_str);
+
+    let unit = root.create_symbol(ident_ids, "unit");
+    let call = foreign_call(
+        arena,
+        "roc_dbg",
+        LAYOUT_UNIT,
+        &[location_ptr, message_ptr, source_ptr],
+    );
+    block.push(unit, call, LAYOUT_UNIT);
+
+    block.finish(arena, remainder)
+}
+
+// ============================================================================
+//
+//              EXPECT
+//
+// ============================================================================
+
+// Shape of expect frame:
+//
+//     ===
+//     Fixed-size header
+//     ===
+// /-- ptr_lookup_1  (ptr_size)
+// |   var_lookup_1  (u32)
+// |   ..
+// |   ptr_lookup_n  (ptr_size)
+// |   var_lookup_n  (u32)
+// \-> lookup_val_1  (varsize)
+//     ..
+//     lookup_val_n  (varsize)
+//
+#[allow(clippy::too_many_arguments)]
+pub fn expect_stmt<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    condition: Symbol,
+    region: Region,
+    lookups: &[(Symbol, InLayout<'a>)],
+    variables: &[LookupType],
+    ret_layout: InLayout<'a>,
+    remainder: &'a Stmt<'a>,
+) -> Stmt<'a> {
+    let arena = root.arena;
+    let layout_isize = root.layout_isize;
+    let ptr_size = root.target.ptr_size() as u32;
+
+    let expect_done = JoinPointId(root.create_symbol(ident_ids, "expect_done"));
+    let jump_done = || Stmt::Jump(expect_done, &[]);
+
+    let mut block = Block::new(arena);
+
+    // The frame goes in the memory shared with `roc dev`, which is notified after every failure
+    let buffer = root.create_symbol(ident_ids, "shared_memory");
+    let start_call = foreign_call(
+        arena,
+        bitcode::UTILS_EXPECT_FAILED_START_SHARED_FILE,
+        layout_isize,
+        &[],
+    );
+    block.push(buffer, start_call, layout_isize);
+
+    // Read the state: the number of frames so far, and the next free position
+    let count_offset = int_literal(root, ident_ids, &mut block, layout_isize, 0);
+    let state_offset = int_literal(root, ident_ids, &mut block, layout_isize, ptr_size as _);
+    let count = load_at(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        buffer,
+        count_offset,
+        layout_isize,
+    );
+    let mut offset = load_at(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        buffer,
+        state_offset,
+        layout_isize,
+    );
+
+    // Write the module and region
+    let module_id: u32 = unsafe { std::mem::transmute(condition.module_id()) };
+    let header = [region.start().offset, region.end().offset, module_id];
+    for value in header {
+        let value = int_literal(root, ident_ids, &mut block, Layout::U32, value as i128);
+        store_at(
+            root,
+            ident_ids,
+            layout_interner,
+            &mut block,
+            buffer,
+            offset,
+            value,
+            Layout::U32,
+        );
+        offset = add_offset(root, ident_ids, &mut block, offset, 4);
+    }
+
+    let after_header = offset;
+    let table_size = lookups.len() as u32 * (ptr_size + 4);
+    offset = add_offset(root, ident_ids, &mut block, offset, table_size);
+
+    // Write the values
+    let mut lookup_starts = Vec::with_capacity_in(lookups.len(), arena);
+    for (lookup, layout) in lookups.iter().copied() {
+        lookup_starts.push(offset);
+
+        let stack_size = layout_interner.stack_size(layout);
+        let extra_offset = add_offset(root, ident_ids, &mut block, offset, stack_size);
+
+        offset = clone_value(
+            root,
+            ident_ids,
+            ctx,
+            layout_interner,
+            &mut block,
+            lookup,
+            layout,
+            buffer,
+            offset,
+            extra_offset,
+        );
+    }
+
+    // Write the lookup table
+    let mut table_offset = after_header;
+    for (lookup_start, variable) in lookup_starts.into_iter().zip(variables) {
+        store_at(
+            root,
+            ident_ids,
+            layout_interner,
+            &mut block,
+            buffer,
+            table_offset,
+            lookup_start,
+            layout_isize,
+        );
+        table_offset = add_offset(root, ident_ids, &mut block, table_offset, ptr_size);
+
+        let index = variable.index() as i128;
+        let variable = int_literal(root, ident_ids, &mut block, Layout::U32, index);
+        store_at(
+            root,
+            ident_ids,
+            layout_interner,
+            &mut block,
+            buffer,
+            table_offset,
+            variable,
+            Layout::U32,
+        );
+        table_offset = add_offset(root, ident_ids, &mut block, table_offset, 4);
+    }
+
+    // Write the new state
+    let new_count = add_offset(root, ident_ids, &mut block, count, 1);
+    store_at(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        buffer,
+        count_offset,
+        new_count,
+        layout_isize,
+    );
+    store_at(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        buffer,
+        state_offset,
+        offset,
+        layout_isize,
+    );
+
+    let unit = root.create_symbol(ident_ids, "unit");
+    let notify = foreign_call(arena, bitcode::NOTIFY_PARENT_EXPECT, LAYOUT_UNIT, &[buffer]);
+    block.push(unit, notify, LAYOUT_UNIT);
+
+    let failed = block.finish(arena, arena.alloc(jump_done()));
+
+    let check = Stmt::if_then_else(
+        arena,
+        condition,
+        ret_layout,
+        jump_done(),
+        arena.alloc(failed),
+    );
+
+    Stmt::Join {
+        id: expect_done,
+        parameters: &[],
+        body: remainder,
+        remainder: arena.alloc(check),
+    }
+}
+
+// ============================================================================
+//
+//              CLONE TO SHARED MEMORY
+//
+// ============================================================================
+
+/// Clone `value` into `buffer` at `offset`. Any heap data it refers to goes at `extra_offset`.
+/// Returns the symbol for the next free `extra_offset`.
+#[allow(clippy::too_many_arguments)]
+fn clone_value<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    block: &mut Block<'a>,
+    value: Symbol,
+    layout: InLayout<'a>,
+    buffer: Symbol,
+    offset: Symbol,
+    extra_offset: Symbol,
+) -> Symbol {
+    let arena = root.arena;
+    let layout_isize = root.layout_isize;
+
+    match layout_interner.get_repr(layout) {
+        LayoutRepr::Builtin(Builtin::Int(_) | Builtin::Float(_) | Builtin::Bool)
+        | LayoutRepr::Builtin(Builtin::Decimal) => {
+            store_at(
+                root,
+                ident_ids,
+                layout_interner,
+                block,
+                buffer,
+                offset,
+                value,
+                layout,
+            );
+            extra_offset
+        }
+
+        LayoutRepr::Builtin(Builtin::Str) => {
+            let new_extra = root.create_symbol(ident_ids, "extra_offset");
+            let call = foreign_call(
+                arena,
+                bitcode::STR_CLONE_TO,
+                layout_isize,
+                &[value, buffer, offset, extra_offset],
+            );
+            block.push(new_extra, call, layout_isize)
+        }
+
+        // Since we will never actually display functions (and hence lambda sets)
+        // we just write nothing to the buffer
+        LayoutRepr::LambdaSet(_) => extra_offset,
+
+        LayoutRepr::Ptr(_) => internal_error!("for internal use only"),
+        LayoutRepr::FunctionPointer(_) | LayoutRepr::Erased(_) => todo_lambda_erasure!(),
+
+        LayoutRepr::Builtin(Builtin::List(_))
+        | LayoutRepr::Struct(_)
+        | LayoutRepr::Union(_)
+        | LayoutRepr::RecursivePointer(_) => {
+            if layout_interner.safe_to_memcpy(layout) {
+                store_at(
+                    root,
+                    ident_ids,
+                    layout_interner,
+                    block,
+                    buffer,
+                    offset,
+                    value,
+                    layout,
+                );
+                return extra_offset;
+            }
+
+            let new_extra = root.create_symbol(ident_ids, "extra_offset");
+            let arguments = arena.alloc([value, buffer, offset, extra_offset]);
+            let call = root
+                .call_specialized_op(ident_ids, ctx, layout_interner, layout, arguments)
+                .unwrap();
+            block.push(new_extra, call, layout_isize)
+        }
+    }
+}
+
+/// The body of a `CloneTo` helper proc.
+/// Arguments are (value, buffer, offset, extra_offset) and it returns the new extra_offset.
+pub fn clone_generic<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    layout: InLayout<'a>,
+) -> Stmt<'a> {
+    match layout_interner.get_repr(layout) {
+        LayoutRepr::Builtin(Builtin::List(elem_layout)) => {
+            clone_list(root, ident_ids, ctx, layout_interner, elem_layout)
+        }
+        LayoutRepr::Struct(field_layouts) => {
+            clone_struct(root, ident_ids, ctx, layout_interner, field_layouts)
+        }
+        LayoutRepr::Union(union_layout) => {
+            clone_tag_union(root, ident_ids, ctx, layout_interner, union_layout)
+        }
+        _ => internal_error!(
+            "No generated proc for cloning {:?}. Use direct code gen.",
+            layout
+        ),
+    }
+}
+
+fn clone_struct<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    field_layouts: &'a [InLayout<'a>],
+) -> Stmt<'a> {
+    let arena = root.arena;
+    let mut block = Block::new(arena);
+
+    let mut offset = ARG_3;
+    let mut extra_offset = ARG_4;
+
+    for (i, field_layout) in field_layouts.iter().copied().enumerate() {
+        let field = root.create_symbol(ident_ids, "field");
+        let field_expr = Expr::StructAtIndex {
+            index: i as u64,
+            field_layouts,
+            structure: ARG_1,
+        };
+        block.push(field, field_expr, field_layout);
+
+        extra_offset = clone_value(
+            root,
+            ident_ids,
+            ctx,
+            layout_interner,
+            &mut block,
+            field,
+            field_layout,
+            ARG_2,
+            offset,
+            extra_offset,
+        );
+
+        let field_width = layout_interner.stack_size(field_layout);
+        offset = add_offset(root, ident_ids, &mut block, offset, field_width);
+    }
+
+    block.finish(arena, arena.alloc(Stmt::Ret(extra_offset)))
+}
+
+fn clone_list<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    elem_layout: InLayout<'a>,
+) -> Stmt<'a> {
+    use LowLevel::*;
+    let arena = root.arena;
+    let layout_isize = root.layout_isize;
+    let ptr_size = root.target.ptr_size() as u32;
+
+    let ptr_layout = layout_interner.insert_direct_no_semantic(LayoutRepr::Ptr(elem_layout));
+
+    let mut block = Block::new(arena);
+
+    let len = root.create_symbol(ident_ids, "len");
+    block.push(len, lowlevel(arena, ListLenUsize, &[ARG_1]), layout_isize);
+
+    // we only copy the elements we actually have (and skip extra capacity)
+    let mut offset = ARG_3;
+    for value in [ARG_4, len, len] {
+        store_at(
+            root,
+            ident_ids,
+            layout_interner,
+            &mut block,
+            ARG_2,
+            offset,
+            value,
+            layout_isize,
+        );
+        offset = add_offset(root, ident_ids, &mut block, offset, ptr_size);
+    }
+
+    let elements = root.create_symbol(ident_ids, "elements");
+    let elements_expr = Expr::StructAtIndex {
+        index: 0,
+        field_layouts: arena.alloc([ptr_layout, layout_isize]),
+        structure: ARG_1,
+    };
+    block.push(elements, elements_expr, ptr_layout);
+
+    let start = root.create_symbol(ident_ids, "start");
+    block.push(start, lowlevel(arena, PtrCast, &[elements]), layout_isize);
+
+    let elem_size = layout_interner.stack_size(elem_layout) as i128;
+    let size = int_literal(root, ident_ids, &mut block, layout_isize, elem_size);
+
+    let list_size = root.create_symbol(ident_ids, "list_size");
+    let list_size_expr = lowlevel(arena, NumMul, &[len, size]);
+    block.push(list_size, list_size_expr, layout_isize);
+
+    let end = root.create_symbol(ident_ids, "end");
+    block.push(
+        end,
+        lowlevel(arena, NumAdd, &[start, list_size]),
+        layout_isize,
+    );
+
+    // The elements are cloned to extra_offset. If they have any heap data, it goes after them.
+    let rest_start = root.create_symbol(ident_ids, "rest_start");
+    let rest_start_expr = lowlevel(arena, NumAdd, &[ARG_4, list_size]);
+    block.push(rest_start, rest_start_expr, layout_isize);
+
+    //
+    // Loop over the elements
+    //
+
+    let elems_loop = JoinPointId(root.create_symbol(ident_ids, "elems_loop"));
+    let addr = root.create_symbol(ident_ids, "addr");
+    let elem_offset = root.create_symbol(ident_ids, "elem_offset");
+    let rest = root.create_symbol(ident_ids, "rest");
+    let params = [addr, elem_offset, rest].map(|symbol| Param {
+        symbol,
+        layout: layout_isize,
+    });
+
+    let mut loop_body = Block::new(arena);
+
+    let ptr = root.create_symbol(ident_ids, "ptr");
+    loop_body.push(ptr, lowlevel(arena, PtrCast, &[addr]), ptr_layout);
+
+    let elem = root.create_symbol(ident_ids, "elem");
+    loop_body.push(elem, Expr::ptr_load(arena.alloc(ptr)), elem_layout);
+
+    let new_rest = clone_value(
+        root,
+        ident_ids,
+        ctx,
+        layout_interner,
+        &mut loop_body,
+        elem,
+        elem_layout,
+        ARG_2,
+        elem_offset,
+        rest,
+    );
+
+    let next_addr = root.create_symbol(ident_ids, "next_addr");
+    loop_body.push(
+        next_addr,
+        lowlevel(arena, NumAdd, &[addr, size]),
+        layout_isize,
+    );
+
+    let next_offset = root.create_symbol(ident_ids, "next_offset");
+    let next_offset_expr = lowlevel(arena, NumAdd, &[elem_offset, size]);
+    loop_body.push(next_offset, next_offset_expr, layout_isize);
+
+    let jump_back = Stmt::Jump(elems_loop, arena.alloc([next_addr, next_offset, new_rest]));
+    let loop_body = loop_body.finish(arena, arena.alloc(jump_back));
+
+    let is_end = root.create_symbol(ident_ids, "is_end");
+    let is_end_expr = lowlevel(arena, NumGte, &[addr, end]);
+
+    let joinpoint_loop = Stmt::Join {
+        id: elems_loop,
+        parameters: arena.alloc(params),
+        body: arena.alloc(Stmt::Let(
+            is_end,
+            is_end_expr,
+            LAYOUT_BOOL,
+            arena.alloc(Stmt::if_then_else(
+                arena,
+                is_end,
+                layout_isize,
+                Stmt::Ret(rest),
+                arena.alloc(loop_body),
+            )),
+        )),
+        remainder: arena.alloc(Stmt::Jump(
+            elems_loop,
+            arena.alloc([start, ARG_4, rest_start]),
+        )),
+    };
+
+    block.finish(arena, arena.alloc(joinpoint_loop))
+}
+
+fn clone_tag_union<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    union_layout: UnionLayout<'a>,
+) -> Stmt<'a> {
+    use UnionLayout::*;
+
+    let parent_rec_ptr_layout = ctx.recursive_union;
+    if !matches!(union_layout, NonRecursive(_)) {
+        ctx.recursive_union = Some(union_layout);
+    }
+
+    let mut branches = Vec::new_in(root.arena);
+
+    match union_layout {
+        NonRecursive(tags) => {
+            for (tag_id, field_layouts) in tags.iter().enumerate() {
+                let tag_id = tag_id as TagIdIntType;
+                let stmt = clone_non_recursive_tag(
+                    root,
+                    ident_ids,
+                    ctx,
+                    layout_interner,
+                    union_layout,
+                    tag_id,
+                    field_layouts,
+                );
+                branches.push((tag_id as u64, BranchInfo::None, stmt));
+            }
+        }
+        Recursive(tags) => {
+            for (tag_id, field_layouts) in tags.iter().enumerate() {
+                let tag_id = tag_id as TagIdIntType;
+                let stmt = clone_recursive_tag(
+                    root,
+                    ident_ids,
+                    ctx,
+                    layout_interner,
+                    union_layout,
+                    tag_id,
+                    field_layouts,
+                );
+                branches.push((tag_id as u64, BranchInfo::None, stmt));
+            }
+        }
+        NonNullableUnwrapped(field_layouts) => {
+            let stmt = clone_recursive_tag(
+                root,
+                ident_ids,
+                ctx,
+                layout_interner,
+                union_layout,
+                0,
+                field_layouts,
+            );
+            branches.push((0, BranchInfo::None, stmt));
+        }
+        NullableWrapped {
+            nullable_id,
+            other_tags,
+        } => {
+            let null_stmt = clone_null_tag(root, ident_ids, layout_interner);
+            branches.push((nullable_id as u64, BranchInfo::None, null_stmt));
+
+            for tag_id in 0..other_tags.len() as TagIdIntType + 1 {
+                if tag_id == nullable_id {
+                    continue;
+                }
+
+                let field_layouts = if tag_id > nullable_id {
+                    other_tags[tag_id as usize - 1]
+                } else {
+                    other_tags[tag_id as usize]
+                };
+
+                let stmt = clone_recursive_tag(
+                    root,
+                    ident_ids,
+                    ctx,
+                    layout_interner,
+                    union_layout,
+                    tag_id,
+                    field_layouts,
+                );
+                branches.push((tag_id as u64, BranchInfo::None, stmt));
+            }
+        }
+        NullableUnwrapped {
+            nullable_id,
+            other_fields,
+        } => {
+            let null_stmt = clone_null_tag(root, ident_ids, layout_interner);
+            branches.push((nullable_id as u64, BranchInfo::None, null_stmt));
+
+            let other_id = !nullable_id as TagIdIntType;
+            let stmt = clone_recursive_tag(
+                root,
+                ident_ids,
+                ctx,
+                layout_interner,
+                union_layout,
+                other_id,
+                other_fields,
+            );
+            branches.push((other_id as u64, BranchInfo::None, stmt));
+        }
+    }
+
+    ctx.recursive_union = parent_rec_ptr_layout;
+
+    match branches.pop() {
+        None => {
+            // cannot be reached at runtime, but we need to generate valid code
+            Stmt::Ret(ARG_4)
+        }
+        Some((_, _, only_tag)) if branches.is_empty() => only_tag,
+        Some((_, _, default)) => {
+            let tag_id = root.create_symbol(ident_ids, "tag_id");
+            let tag_id_layout = union_layout.tag_id_layout();
+
+            let switch = Stmt::Switch {
+                cond_symbol: tag_id,
+                cond_layout: tag_id_layout,
+                branches: branches.into_bump_slice(),
+                default_branch: (BranchInfo::None, root.arena.alloc(default)),
+                ret_layout: root.layout_isize,
+            };
+
+            Stmt::Let(
+                tag_id,
+                Expr::GetTagId {
+                    structure: ARG_1,
+                    union_layout,
+                },
+                tag_id_layout,
+                root.arena.alloc(switch),
+            )
+        }
+    }
+}
+
+/// Build a struct from the fields of one tag, so that they can be cloned together
+#[allow(clippy::too_many_arguments)]
+fn tag_payload<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    layout_interner: &mut STLayoutInterner<'a>,
+    block: &mut Block<'a>,
+    union_layout: UnionLayout<'a>,
+    tag_id: TagIdIntType,
+    field_layouts: &'a [InLayout<'a>],
+    with_tag_id: bool,
+) -> (Symbol, InLayout<'a>) {
+    let arena = root.arena;
+    let mut fields = Vec::with_capacity_in(field_layouts.len() + 1, arena);
+    let mut layouts = Vec::with_capacity_in(field_layouts.len() + 1, arena);
+
+    for (i, field_layout) in field_layouts.iter().copied().enumerate() {
+        let field = root.create_symbol(ident_ids, "field");
+        let field_expr = Expr::UnionAtIndex {
+            structure: ARG_1,
+            tag_id,
+            union_layout,
+            index: i as u64,
+        };
+        fields.push(block.push(field, field_expr, field_layout));
+        layouts.push(field_layout);
+    }
+
+    if with_tag_id {
+        // [...fields, tag ID]
+        let tag_id_layout = union_layout.tag_id_layout();
+        fields.push(int_literal(
+            root,
+            ident_ids,
+            block,
+            tag_id_layout,
+            tag_id as i128,
+        ));
+        layouts.push(tag_id_layout);
+    }
+
+    let payload_layout =
+        layout_interner.insert_direct_no_semantic(LayoutRepr::struct_(layouts.into_bump_slice()));
+
+    let payload = root.create_symbol(ident_ids, "payload");
+    let payload_expr = Expr::Struct(fields.into_bump_slice());
+    block.push(payload, payload_expr, payload_layout);
+
+    (payload, payload_layout)
+}
+
+fn clone_non_recursive_tag<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    union_layout: UnionLayout<'a>,
+    tag_id: TagIdIntType,
+    field_layouts: &'a [InLayout<'a>],
+) -> Stmt<'a> {
+    let arena = root.arena;
+    let mut block = Block::new(arena);
+
+    let (payload, payload_layout) = tag_payload(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        union_layout,
+        tag_id,
+        field_layouts,
+        false,
+    );
+
+    let answer = clone_value(
+        root,
+        ident_ids,
+        ctx,
+        layout_interner,
+        &mut block,
+        payload,
+        payload_layout,
+        ARG_2,
+        ARG_3,
+        ARG_4,
+    );
+
+    // write the tag id, after the data and its padding
+    let tag_id_offset = union_layout.tag_id_offset(layout_interner).unwrap();
+    let tag_id_at = add_offset(root, ident_ids, &mut block, ARG_3, tag_id_offset);
+    let tag_id = int_literal(root, ident_ids, &mut block, Layout::U8, tag_id as i128);
+    store_at(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        ARG_2,
+        tag_id_at,
+        tag_id,
+        Layout::U8,
+    );
+
+    block.finish(arena, arena.alloc(Stmt::Ret(answer)))
+}
+
+/// Clone a heap-allocated tag. We write an offset where the pointer was, and clone the data
+/// to that offset.
+fn clone_recursive_tag<'a>(
+    root: &mut CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    ctx: &mut Context<'a>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    union_layout: UnionLayout<'a>,
+    tag_id: TagIdIntType,
+    field_layouts: &'a [InLayout<'a>],
+) -> Stmt<'a> {
+    let arena = root.arena;
+    let layout_isize = root.layout_isize;
+    let mut block = Block::new(arena);
+
+    // write the "pointer" at the current offset
+    let has_tag_id = matches!(
+        union_layout,
+        UnionLayout::Recursive(_) | UnionLayout::NullableWrapped { .. }
+    );
+    if has_tag_id && union_layout.stores_tag_id_in_pointer(root.target) {
+        let tag_id = int_literal(root, ident_ids, &mut block, Layout::U32, tag_id as i128);
+        store_at(
+            root,
+            ident_ids,
+            layout_interner,
+            &mut block,
+            ARG_2,
+            ARG_3,
+            tag_id,
+            Layout::U32,
+        );
+
+        let offset = add_offset(root, ident_ids, &mut block, ARG_3, 4);
+        let extra_offset = root.create_symbol(ident_ids, "extra_offset_u32");
+        let cast_expr = lowlevel(arena, LowLevel::NumIntCast, &[ARG_4]);
+        block.push(extra_offset, cast_expr, Layout::U32);
+        store_at(
+            root,
+            ident_ids,
+            layout_interner,
+            &mut block,
+            ARG_2,
+            offset,
+            extra_offset,
+            Layout::U32,
+        );
+    } else {
+        store_at(
+            root,
+            ident_ids,
+            layout_interner,
+            &mut block,
+            ARG_2,
+            ARG_3,
+            ARG_4,
+            layout_isize,
+        );
+    }
+
+    let with_tag_id = matches!(union_layout, UnionLayout::Recursive(_))
+        && !union_layout.stores_tag_id_in_pointer(root.target);
+
+    let (payload, payload_layout) = tag_payload(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        union_layout,
+        tag_id,
+        field_layouts,
+        with_tag_id,
+    );
+
+    let width = match union_layout {
+        UnionLayout::NullableUnwrapped { .. } => layout_interner.stack_size(payload_layout),
+        _ => union_layout.data_size_and_alignment(layout_interner).0,
+    };
+    let extra_offset = add_offset(root, ident_ids, &mut block, ARG_4, width);
+
+    let answer = clone_value(
+        root,
+        ident_ids,
+        ctx,
+        layout_interner,
+        &mut block,
+        payload,
+        payload_layout,
+        ARG_2,
+        ARG_4,
+        extra_offset,
+    );
+
+    block.finish(arena, arena.alloc(Stmt::Ret(answer)))
+}
+
+fn clone_null_tag<'a>(
+    root: &CodeGenHelp<'a>,
+    ident_ids: &mut IdentIds,
+    layout_interner: &mut STLayoutInterner<'a>,
+) -> Stmt<'a> {
+    let arena = root.arena;
+    let mut block = Block::new(arena);
+
+    let null = int_literal(root, ident_ids, &mut block, root.layout_isize, 0);
+    store_at(
+        root,
+        ident_ids,
+        layout_interner,
+        &mut block,
+        ARG_2,
+        ARG_3,
+        null,
+        root.layout_isize,
+    );
+
+    block.finish(arena, arena.alloc(Stmt::Ret(ARG_4)))
+}
@@ -3,11 +3,12 @@ use bumpalo::collections::CollectIn;
 use bumpalo::Bump;
 use roc_module::low_level::LowLevel;
 use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+use roc_region::all::Region;
 use roc_target::Target;
 
 use crate::ir::{
-    BranchInfo, Call, CallSpecId, CallType, Expr, JoinPointId, Literal, ModifyRc, PassedFunction,
-    Proc, ProcLayout, SelfRecursive, Stmt, UpdateModeId,
+    BranchInfo, Call, CallSpecId, CallType, Expr, JoinPointId, Literal, LookupType, ModifyRc,
+    PassedFunction, Proc, ProcLayout, SelfRecursive, Stmt, UpdateModeId,
 };
 use crate::layout::{
     Builtin, InLayout, LambdaName, Layout, LayoutInterner, LayoutRepr, LayoutWrapper, Niche,
@@ -16,6 +17,7 @@ use crate::layout::{
 
 mod copy;
 mod equality;
+mod expect;
 mod refcount;
 
 const LAYOUT_BOOL: InLayout = Layout::BOOL;
@@ -45,6 +47,7 @@ pub enum HelperOp {
     ResetRef,
     Eq,
     IndirectCopy,
+    CloneTo,
 }
 
 impl HelperOp {
@@ -277,6 +280,64 @@ impl<'a> CodeGenHelp<'a> {
         (expr, ctx.new_linker_data)
     }
 
+    /// Expand a `Dbg` node to a call to `roc_dbg`, followed by the remainder
+    pub fn expand_dbg_stmt(
+        &mut self,
+        ident_ids: &mut IdentIds,
+        layout_interner: &mut STLayoutInterner<'a>,
+        source_location: &'a str,
+        source: &'a str,
+        symbol: Symbol,
+        remainder: &'a Stmt<'a>,
+    ) -> &'a Stmt<'a> {
+        let stmt = expect::dbg_stmt(
+            self,
+            ident_ids,
+            layout_interner,
+            source_location,
+            source,
+            symbol,
+            remainder,
+        );
+        self.arena.alloc(stmt)
+    }
+
+    /// Expand an `Expect` or `ExpectFx` node. If the condition is false, the looked-up values
+    /// are cloned to shared memory, using specialized helper procs for any data structures.
+    #[allow(clippy::too_many_arguments)]
+    pub fn expand_expect_stmt(
+        &mut self,
+        ident_ids: &mut IdentIds,
+        layout_interner: &mut STLayoutInterner<'a>,
+        condition: Symbol,
+        region: Region,
+        lookups: &[(Symbol, InLayout<'a>)],
+        variables: &[LookupType],
+        ret_layout: InLayout<'a>,
+        remainder: &'a Stmt<'a>,
+    ) -> (&'a Stmt<'a>, Vec<'a, (Symbol, ProcLayout<'a>)>) {
+        let mut ctx = Context {
+            new_linker_data: Vec::new_in(self.arena),
+            recursive_union: None,
+            op: HelperOp::CloneTo,
+        };
+
+        let stmt = expect::expect_stmt(
+            self,
+            ident_ids,
+            &mut ctx,
+            layout_interner,
+            condition,
+            region,
+            lookups,
+            variables,
+            ret_layout,
+            remainder,
+        );
+
+        (self.arena.alloc(stmt), ctx.new_linker_data)
+    }
+
     /// Generate a copy procedure, *without* a Call expression.
     /// *This method should be rarely used* - only when the proc is to be called from Zig.
     pub fn gen_copy_proc(
@@ -343,6 +404,10 @@ impl<'a> CodeGenHelp<'a> {
                     IndirectInc => (LAYOUT_UNIT, arena.alloc([ptr_arg])),
                     Eq => (LAYOUT_BOOL, self.arena.alloc([arg, arg])),
                     IndirectCopy => (LAYOUT_UNIT, self.arena.alloc([ptr_arg, ptr_arg])),
+                    CloneTo => {
+                        let isize = self.layout_isize;
+                        (isize, arena.alloc([arg, isize, isize, isize]))
+                    }
                 }
             };
 
@@ -456,6 +521,10 @@ impl<'a> CodeGenHelp<'a> {
                 LAYOUT_UNIT,
                 copy::copy_indirect(self, ident_ids, ctx, layout_interner, layout),
             ),
+            CloneTo => (
+                self.layout_isize,
+                expect::clone_generic(self, ident_ids, ctx, layout_interner, layout),
+            ),
         };
 
         let args: &'a [(InLayout<'a>, Symbol)] = {
@@ -483,6 +552,11 @@ impl<'a> CodeGenHelp<'a> {
                         layout_interner.insert_direct_no_semantic(LayoutRepr::Ptr(layout));
                     self.arena.alloc([(ptr_layout, ARG_1), (ptr_layout, ARG_2)])
                 }
+                CloneTo => {
+                    let isize = self.layout_isize;
+                    self.arena
+                        .alloc([roc_value, (isize, ARG_2), (isize, ARG_3), (isize, ARG_4)])
+                }
             }
         };
 
@@ -564,6 +638,15 @@ impl<'a> CodeGenHelp<'a> {
                     niche: Niche::NONE,
                 }
             }
+            HelperOp::CloneTo => {
+                let isize = self.layout_isize;
+
+                ProcLayout {
+                    arguments: self.arena.alloc([layout, isize, isize, isize]),
+                    result: isize,
+                    niche: Niche::NONE,
+                }
+            }
         };
 
         (proc_symbol, proc_layout)
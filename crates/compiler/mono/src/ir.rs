@@ -140,7 +140,10 @@ pub struct SingleEntryPoint<'a> {
 #[derive(Debug, Clone, Copy)]
 pub enum EntryPoint<'a> {
     Program(&'a [SingleEntryPoint<'a>]),
-    Expects { symbols: &'a [Symbol] },
+    /// The argument layouts of each expect. They are empty, except for property expects.
+    Expects {
+        symbols: &'a [(Symbol, &'a [InLayout<'a>])],
+    },
 }
 
 #[derive(Clone, Copy, Debug)]
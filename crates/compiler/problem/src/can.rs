@@ -54,6 +54,9 @@ pub enum Problem {
         existing_symbol_region: Region,
     },
     DeprecatedBackpassing(Region),
+    /// A code example in a doc comment has an `expect`, but doesn't end in one,
+    /// so `roc test --doc` can't run it
+    SkippedDocTest(Region),
     /// First symbol is the name of the closure with that argument
     /// Bool is whether the closure is anonymous
     /// Second symbol is the name of the argument that is unused
@@ -276,6 +279,7 @@ impl Problem {
             Problem::ExplicitBuiltinTypeImport(_, _) => Warning,
             Problem::ImportShadowsSymbol { .. } => RuntimeError,
             Problem::DeprecatedBackpassing(_) => Warning,
+            Problem::SkippedDocTest(_) => Warning,
             Problem::ExposedButNotDefined(_) => RuntimeError,
             Problem::UnusedArgument(_, _, _, _) => Warning,
             Problem::UnusedBranchDef(_, _) => Warning,
@@ -368,6 +372,7 @@ impl Problem {
             | Problem::ExplicitBuiltinTypeImport(_, region)
             | Problem::ImportShadowsSymbol { region, .. }
             | Problem::DeprecatedBackpassing(region)
+            | Problem::SkippedDocTest(region)
             | Problem::UnusedArgument(_, _, _, region)
             | Problem::UnusedBranchDef(_, region)
             | Problem::PrecedenceProblem(PrecedenceProblem::BothNonAssociative(region, _, _))
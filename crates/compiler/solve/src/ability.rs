@@ -516,6 +516,11 @@ fn is_builtin_bool_alias(symbol: Symbol) -> bool {
     matches!(symbol, Symbol::BOOL_BOOL)
 }
 
+#[inline(always)]
+fn is_builtin_set_or_dict_alias(symbol: Symbol) -> bool {
+    matches!(symbol, Symbol::SET_SET | Symbol::DICT_DICT)
+}
+
 struct NotDerivable {
     var: Variable,
     context: NotDerivableContext,
@@ -532,6 +537,13 @@ trait DerivableVisitor {
         false
     }
 
+    /// Whether the derived implementation for this builtin opaque is generic over the opaque's
+    /// type arguments, in which case the type arguments must be derivable as well.
+    #[inline(always)]
+    fn derives_through_builtin_opaque(_symbol: Symbol) -> bool {
+        false
+    }
+
     #[inline(always)]
     fn visit_recursion(var: Variable) -> Result<Descend, NotDerivable> {
         Err(NotDerivable {
@@ -804,7 +816,7 @@ trait DerivableVisitor {
                         stack.push(real_var)
                     }
                 }
-                Alias(opaque, _alias_variables, _real_var, AliasKind::Opaque) => {
+                Alias(opaque, alias_variables, _real_var, AliasKind::Opaque) => {
                     if obligation_cache
                         .check_opaque_and_read(abilities_store, opaque, Self::ABILITY)
                         .is_err()
@@ -815,6 +827,12 @@ trait DerivableVisitor {
                             context: NotDerivableContext::Opaque(opaque),
                         });
                     }
+
+                    if Self::derives_through_builtin_opaque(opaque) {
+                        for i in alias_variables.named_type_arguments() {
+                            stack.push(subs[i]);
+                        }
+                    }
                 }
                 Alias(symbol, _alias_variables, real_var, AliasKind::Structural) => {
                     let descend = Self::visit_alias(var, symbol)?;
@@ -1042,7 +1060,14 @@ impl DerivableVisitor for DeriveDecoding {
 
     #[inline(always)]
     fn is_derivable_builtin_opaque(symbol: Symbol) -> bool {
-        is_builtin_number_alias(symbol) || is_builtin_bool_alias(symbol)
+        is_builtin_number_alias(symbol)
+            || is_builtin_bool_alias(symbol)
+            || is_builtin_set_or_dict_alias(symbol)
+    }
+
+    #[inline(always)]
+    fn derives_through_builtin_opaque(symbol: Symbol) -> bool {
+        is_builtin_set_or_dict_alias(symbol)
     }
 
     #[inline(always)]
@@ -1467,6 +1492,20 @@ pub(crate) fn builtin_module_with_unlisted_ability_impl(module_id: ModuleId) ->
     matches!(module_id, ModuleId::NUM | ModuleId::BOOL)
 }
 
+/// Whether the implementation of `ability_member` for this opaque type should be resolved via
+/// derive_key, rather than by looking up the implementation the opaque lists.
+///
+/// `Dict` and `Set` list their own implementations of most abilities, but like `List`, their
+/// decoders are derived from the decoders of their type arguments.
+#[inline]
+pub(crate) fn builtin_opaque_with_unlisted_ability_impl(
+    opaque: Symbol,
+    ability_member: Symbol,
+) -> bool {
+    builtin_module_with_unlisted_ability_impl(opaque.module_id())
+        || (is_builtin_set_or_dict_alias(opaque) && ability_member == Symbol::DECODE_DECODER)
+}
+
 #[derive(Debug)]
 pub enum ResolveError {
     NonDerivableAbility(Symbol),
@@ -1520,7 +1559,7 @@ pub fn resolve_ability_specialization<R: AbilityResolver>(
 
     let resolved = match obligated {
         Obligated::Opaque(symbol) => {
-            if builtin_module_with_unlisted_ability_impl(symbol.module_id()) {
+            if builtin_opaque_with_unlisted_ability_impl(symbol, ability_member) {
                 let derive_key = roc_derive_key::Derived::builtin_with_builtin_symbol(
                     ability_member.try_into().map_err(NonDerivableAbility)?,
                     symbol,
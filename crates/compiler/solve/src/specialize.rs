@@ -21,7 +21,7 @@ use roc_types::{
 use roc_unify::unify::{unify, MustImplementConstraints};
 
 use crate::{
-    ability::builtin_module_with_unlisted_ability_impl,
+    ability::builtin_opaque_with_unlisted_ability_impl,
     deep_copy::deep_copy_var_in,
     env::{DerivedEnv, SolveEnv},
 };
@@ -618,7 +618,7 @@ fn make_specialization_decision<P: Phase>(
     use SpecializationTypeKey::*;
     match subs.get_content_without_compacting(var) {
         Alias(opaque, _, _, AliasKind::Opaque)
-            if !builtin_module_with_unlisted_ability_impl(opaque.module_id()) =>
+            if !builtin_opaque_with_unlisted_ability_impl(*opaque, ability_member) =>
         {
             if P::IS_LATE {
                 SpecializeDecision::Specialize(Opaque(*opaque))
@@ -33,8 +33,32 @@ test_key_eq! {
     same_tuple_fields_diff_types:
         v!((v!(U8), v!(U16),)), v!((v!(U32), v!(U64),))
 
+    same_tag_union:
+        v!([ A v!(U8) v!(STR), B v!(STR) ]), v!([ A v!(U8) v!(STR), B v!(STR) ])
+    same_tag_union_tags_diff_types:
+        v!([ A v!(U8) v!(U8), B v!(U8) ]), v!([ A v!(STR) v!(STR), B v!(STR) ])
+    same_tag_union_tags_any_order:
+        v!([ A v!(U8) v!(U8), B v!(U8), C ]), v!([ C, B v!(STR), A v!(STR) v!(STR) ])
+    explicit_empty_tag_union_and_implicit_empty_tag_union:
+        v!(EMPTY_TAG_UNION), v!([])
+
+    same_recursive_tag_union:
+        v!([ Nil, Cons v!(^lst)] as lst), v!([ Nil, Cons v!(^lst)] as lst)
+    same_tag_union_and_recursive_tag_union_fields:
+        v!([ Nil, Cons v!(STR)]), v!([ Nil, Cons v!(^lst)] as lst)
+
     list_list_diff_types:
         v!(Symbol::LIST_LIST v!(STR)), v!(Symbol::LIST_LIST v!(U8))
+    set_set_diff_types:
+        v!(Symbol::SET_SET v!(STR)), v!(Symbol::SET_SET v!(U8))
+    dict_dict_diff_types:
+        v!(Symbol::DICT_DICT v!(STR) v!(STR)), v!(Symbol::DICT_DICT v!(U8) v!(U8))
+    opaque_set_set_diff_types:
+        v!(@Symbol::SET_SET v!(STR) => v!(EMPTY_RECORD)),
+        v!(@Symbol::SET_SET v!(U8) => v!(EMPTY_RECORD))
+    opaque_dict_dict_diff_types:
+        v!(@Symbol::DICT_DICT v!(STR) v!(STR) => v!(EMPTY_RECORD)),
+        v!(@Symbol::DICT_DICT v!(U8) v!(U8) => v!(EMPTY_RECORD))
     str_str:
         v!(Symbol::STR_STR), v!(Symbol::STR_STR)
 }
@@ -49,6 +73,20 @@ test_key_neq! {
 
     different_tuple_arities:
         v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16), v!(U32),))
+
+    different_tag_union_tags:
+        v!([ A v!(U8) ]), v!([ B v!(U8) ])
+    different_tag_union_payload_sizes:
+        v!([ A v!(U8) ]), v!([ A v!(U8) v!(U8) ])
+    tag_union_empty_vs_nonempty:
+        v!(EMPTY_TAG_UNION), v!([ B v!(U8) ])
+    different_recursive_tag_union_tags:
+        v!([ Nil, Cons v!(^lst) ] as lst), v!([ Nil, Next v!(^lst) ] as lst)
+
+    list_vs_set:
+        v!(Symbol::LIST_LIST v!(STR)), v!(Symbol::SET_SET v!(STR))
+    set_vs_dict:
+        v!(Symbol::SET_SET v!(STR)), v!(Symbol::DICT_DICT v!(STR) v!(STR))
 }
 
 #[test]
@@ -101,6 +139,48 @@ fn derivable_record_with_record_ext() {
     );
 }
 
+#[test]
+fn derivable_tag_ext_flex_var() {
+    check_derivable(
+        Decoder,
+        v!([ A v!(STR) ]* ),
+        DeriveKey::Decoder(FlatDecodableKey::TagUnion(vec![("A".into(), 1)])),
+    );
+}
+
+#[test]
+fn derivable_tag_ext_flex_able_var() {
+    check_derivable(
+        Decoder,
+        v!([ A v!(STR) ]a implements Symbol::DECODE_DECODER),
+        DeriveKey::Decoder(FlatDecodableKey::TagUnion(vec![("A".into(), 1)])),
+    );
+}
+
+#[test]
+fn derivable_tag_with_tag_ext() {
+    check_derivable(
+        Decoder,
+        v!([ B v!(STR) v!(U8) ][ A v!(STR) ]),
+        DeriveKey::Decoder(FlatDecodableKey::TagUnion(vec![
+            ("A".into(), 1),
+            ("B".into(), 2),
+        ])),
+    );
+}
+
+#[test]
+fn derivable_recursive_tag_union() {
+    check_derivable(
+        Decoder,
+        v!([ Nil, Cons v!(^lst) ] as lst),
+        DeriveKey::Decoder(FlatDecodableKey::TagUnion(vec![
+            ("Cons".into(), 1),
+            ("Nil".into(), 0),
+        ])),
+    );
+}
+
 #[test]
 fn list() {
     derive_test(Decoder, v!(Symbol::LIST_LIST v!(STR)), |golden| {
@@ -119,6 +199,55 @@ fn list() {
     })
 }
 
+#[test]
+fn set() {
+    derive_test(
+        Decoder,
+        v!(@Symbol::SET_SET v!(STR) => v!(EMPTY_RECORD)),
+        |golden| {
+            assert_snapshot!(golden, @r###"
+        # derived for Set Str
+        # Decoder (Set k) fmt where fmt implements DecoderFormatting, k implements Hash & Decoding & Eq
+        # List U8, fmt -[[custom(3)]]-> { rest : List U8, result : [Err [TooShort], Ok (({ buckets : List { dataIndex : U32, distAndFingerprint : U32 }, data : List ( k, {} )a, maxBucketCapacity : U64, maxLoadFactor : F32, shifts : U8 }))] } where fmt implements DecoderFormatting, k implements Hash & Decoding & Eq
+        # Specialization lambda sets:
+        #   @<1>: [[custom(3)]]
+        #Derived.decoder_set =
+          custom
+            \#Derived.bytes, #Derived.fmt ->
+              mapResult
+                (decodeWith #Derived.bytes (list decoder) #Derived.fmt)
+                Set.fromList
+        "###
+            )
+        },
+    )
+}
+
+#[test]
+fn dict() {
+    derive_test(
+        Decoder,
+        v!(@Symbol::DICT_DICT v!(STR) v!(U8) => v!(EMPTY_RECORD)),
+        |golden| {
+            assert_snapshot!(golden, @r###"
+        # derived for Dict Str U8
+        # Decoder (Dict val val1) fmt where fmt implements DecoderFormatting, val implements Hash & Decoding & Eq, val1 implements Decoding
+        # List U8, fmt -[[custom(3)]]-> { rest : List U8, result : [Err [TooShort], Ok ({ buckets : List { dataIndex : U32, distAndFingerprint : U32 }, data : List ( val, val1 )a, maxBucketCapacity : U64, maxLoadFactor : F32, shifts : U8 })] } where fmt implements DecoderFormatting, val implements Hash & Decoding & Eq, val1 implements Decoding
+        # Specialization lambda sets:
+        #   @<1>: [[custom(3)]]
+        #Derived.decoder_dict =
+          custom
+            \#Derived.bytes, #Derived.fmt ->
+              decodeWith
+                #Derived.bytes
+                (dict (Dict.empty {}) Dict.insert decoder decoder)
+                #Derived.fmt
+        "###
+            )
+        },
+    )
+}
+
 #[test]
 fn record_2_fields() {
     derive_test(Decoder, v!({first: v!(STR), second: v!(STR),}), |golden| {
@@ -239,3 +368,81 @@ fn tuple_2_fields() {
         )
     })
 }
+
+#[test]
+fn tag_two_labels() {
+    derive_test(Decoder, v!([A v!(U8) v!(STR), B]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A U8 Str, B]
+        # Decoder [A val val1, B] fmt where fmt implements DecoderFormatting, val implements Decoding, val1 implements Decoding
+        # List U8, fmt -[[custom(29)]]-> { rest : List U8, result : [Err [TooShort], Ok [A val val1, B]] } where fmt implements DecoderFormatting, val implements Decoding, val1 implements Decoding
+        # Specialization lambda sets:
+        #   @<1>: [[custom(29)]]
+        #Derived.decoder_[A 2,B 0] =
+          custom
+            \#Derived.bytes3, #Derived.fmt3 ->
+              decodeWith
+                #Derived.bytes3
+                (tag
+                  \#Derived.tag ->
+                    when #Derived.tag is
+                      "A" ->
+                        Keep (tuple
+                          { e1: Err NoElem, e0: Err NoElem }
+                          \#Derived.stateRecord2, #Derived.index ->
+                            when #Derived.index is
+                              0 ->
+                                Next (custom
+                                  \#Derived.bytes, #Derived.fmt ->
+                                    when decodeWith
+                                        #Derived.bytes
+                                        decoder
+                                        #Derived.fmt is
+                                      #Derived.rec ->
+                                        {
+                                          result: when #Derived.rec.result is
+                                              Ok #Derived.val ->
+                                                Ok {
+                                                stateRecord2 & e0: Ok #Derived.val
+                                                }
+                                              Err #Derived.err -> Err #Derived.err,
+                                          rest: #Derived.rec.rest
+                                        })
+                              1 ->
+                                Next (custom
+                                  \#Derived.bytes2, #Derived.fmt2 ->
+                                    when decodeWith
+                                        #Derived.bytes2
+                                        decoder
+                                        #Derived.fmt2 is
+                                      #Derived.rec2 ->
+                                        {
+                                          result: when #Derived.rec2.result is
+                                              Ok #Derived.val2 ->
+                                                Ok {
+                                                stateRecord2 & e1: Ok #Derived.val2
+                                                }
+                                              Err #Derived.err2 -> Err #Derived.err2,
+                                          rest: #Derived.rec2.rest
+                                        })
+                              _ -> TooLong
+                          \#Derived.stateRecord ->
+                            when #Derived.stateRecord.e0 is
+                              Ok #Derived.0 ->
+                                when #Derived.stateRecord.e1 is
+                                  Ok #Derived.1 -> Ok (A #Derived.0 #Derived.1)
+                                  _ -> Err TooShort
+                              _ -> Err TooShort)
+                      "B" ->
+                        Keep (tuple
+                          { }
+                          \#Derived.stateRecord4, #Derived.index2 ->
+                            when #Derived.index2 is
+                              _ -> TooLong
+                          \#Derived.stateRecord3 -> Ok B)
+                      _ -> Skip)
+                #Derived.fmt3
+        "###
+        )
+    })
+}
@@ -37,14 +37,19 @@ use roc_types::{
 
 const DERIVED_MODULE: ModuleId = ModuleId::DERIVED_SYNTH;
 
-fn module_source_and_path(builtin: DeriveBuiltin) -> (ModuleId, &'static str, PathBuf) {
-    use roc_builtins::roc::module_source;
-
+fn builtins_path() -> PathBuf {
     let repo_root = std::env::var("ROC_WORKSPACE_DIR").expect("are you running with `cargo test`?");
-    let builtins_path = PathBuf::from(repo_root)
+
+    PathBuf::from(repo_root)
         .join("compiler")
         .join("builtins")
-        .join("roc");
+        .join("roc")
+}
+
+fn module_source_and_path(builtin: DeriveBuiltin) -> (ModuleId, &'static str, PathBuf) {
+    use roc_builtins::roc::module_source;
+
+    let builtins_path = builtins_path();
 
     match builtin {
         DeriveBuiltin::ToEncoder => (
@@ -75,6 +80,36 @@ fn module_source_and_path(builtin: DeriveBuiltin) -> (ModuleId, &'static str, Pa
     }
 }
 
+/// Builtin modules, other than the one defining the ability, whose values the derived
+/// implementations of the ability refer to.
+fn supporting_modules(builtin: DeriveBuiltin) -> &'static [(ModuleId, &'static str)] {
+    match builtin {
+        DeriveBuiltin::Decoder => &[(ModuleId::DICT, "Dict.roc"), (ModuleId::SET, "Set.roc")],
+        DeriveBuiltin::ToEncoder
+        | DeriveBuiltin::Hash
+        | DeriveBuiltin::IsEq
+        | DeriveBuiltin::ToInspector => &[],
+    }
+}
+
+fn load_builtin_module(arena: &Bump, source: &'static str, path: PathBuf) -> LoadedModule {
+    roc_load_internal::file::load_and_typecheck_str(
+        arena,
+        path.file_name().unwrap().into(),
+        source,
+        path.parent().unwrap().to_path_buf(),
+        None,
+        Default::default(),
+        roc_target::Target::LinuxX64,
+        FunctionKind::LambdaSet,
+        roc_reporting::report::RenderTarget::ColorTerminal,
+        roc_reporting::report::DEFAULT_PALETTE,
+        RocCacheDir::Disallowed,
+        Threading::AllAvailable,
+    )
+    .unwrap()
+}
+
 /// DSL for creating [`Content`][roc_types::subs::Content].
 #[macro_export]
 macro_rules! v {
@@ -170,7 +205,7 @@ macro_rules! v {
          use roc_types::types::AliasKind;
          use roc_module::symbol::Symbol;
          |subs: &mut Subs| {
-             let args = vec![$( $arg(subs) )*];
+             let args = vec![$( $arg(subs) ,)*];
              let alias_variables = AliasVariables::insert_into_subs::<Vec<_>, Vec<_>, _>(subs, args, vec![], vec![]);
              let real_var = $real_var(subs);
              roc_derive::synth_var(subs, Content::Alias(Symbol::$alias, alias_variables, real_var, AliasKind::Structural))
@@ -181,7 +216,7 @@ macro_rules! v {
          use roc_types::types::AliasKind;
          use roc_module::symbol::Symbol;
          |subs: &mut Subs| {
-             let args = vec![$( $arg(subs) )*];
+             let args = vec![$( $arg(subs) ,)*];
              let alias_variables = AliasVariables::insert_into_subs::<Vec<_>, Vec<_>, _>(subs, args, vec![], vec![]);
              let real_var = $real_var(subs);
              roc_derive::synth_var(subs, Content::Alias(Symbol::$alias, alias_variables, real_var, AliasKind::Opaque))
@@ -355,6 +390,8 @@ struct DeriveBuiltinEnv {
     module_id: ModuleId,
     exposed_types: ExposedTypesStorageSubs,
     abilities_store: AbilitiesStore,
+    /// Exposed types of the [supporting_modules] of the ability.
+    supporting_exposed_types: Vec<(ModuleId, ExposedTypesStorageSubs)>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -386,6 +423,11 @@ fn check_derived_typechecks_and_golden(
         .keys()
         .copied()
         .collect::<VecSet<_>>();
+    let values_to_import_from_supporting_modules = derive_builtin_env
+        .supporting_exposed_types
+        .iter()
+        .flat_map(|(_, exposed_types)| exposed_types.stored_vars_by_symbol.keys().copied())
+        .collect::<VecSet<_>>();
     let pending_abilities = derive_builtin_env
         .abilities_store
         .closure_from_imported(&values_to_import_from_builtin_module);
@@ -397,8 +439,19 @@ fn check_derived_typechecks_and_golden(
             resolved_implementations: ResolvedImplementations::default(),
         },
     );
+    for (module_id, exposed_types) in derive_builtin_env.supporting_exposed_types {
+        exposed_by_module.insert(
+            module_id,
+            ExposedModuleTypes {
+                exposed_types_storage_subs: exposed_types,
+                resolved_implementations: ResolvedImplementations::default(),
+            },
+        );
+    }
     let exposed_for_module = ExposedForModule::new(
-        values_to_import_from_builtin_module.iter(),
+        values_to_import_from_builtin_module
+            .iter()
+            .chain(values_to_import_from_supporting_modules.iter()),
         exposed_by_module,
     );
     let mut def_types = Default::default();
@@ -518,7 +571,6 @@ where
 {
     let arena = Bump::new();
     let (builtin_module, source, path) = module_source_and_path(builtin);
-    let target = roc_target::Target::LinuxX64;
 
     let LoadedModule {
         mut interns,
@@ -526,21 +578,17 @@ where
         abilities_store,
         resolved_implementations,
         ..
-    } = roc_load_internal::file::load_and_typecheck_str(
-        &arena,
-        path.file_name().unwrap().into(),
-        source,
-        path.parent().unwrap().to_path_buf(),
-        None,
-        Default::default(),
-        target,
-        FunctionKind::LambdaSet,
-        roc_reporting::report::RenderTarget::ColorTerminal,
-        roc_reporting::report::DEFAULT_PALETTE,
-        RocCacheDir::Disallowed,
-        Threading::AllAvailable,
-    )
-    .unwrap();
+    } = load_builtin_module(&arena, source, path);
+
+    let supporting_exposed_types = supporting_modules(builtin)
+        .iter()
+        .map(|&(module_id, file_name)| {
+            let source = roc_builtins::roc::module_source(module_id);
+            let loaded = load_builtin_module(&arena, source, builtins_path().join(file_name));
+
+            (module_id, loaded.exposed_types_storage)
+        })
+        .collect::<Vec<_>>();
 
     let mut subs = Subs::new();
     let ident_ids = IdentIds::default();
@@ -557,6 +605,15 @@ where
             resolved_implementations,
         },
     );
+    for (module_id, exposed_types) in supporting_exposed_types.iter() {
+        exposed_by_module.insert(
+            *module_id,
+            ExposedModuleTypes {
+                exposed_types_storage_subs: exposed_types.clone(),
+                resolved_implementations: ResolvedImplementations::default(),
+            },
+        );
+    }
 
     let (_derived_symbol, derived_def, specialization_lsets) =
         derived_module.get_or_insert(&exposed_by_module, key);
@@ -584,6 +641,7 @@ where
             module_id: builtin_module,
             exposed_types: exposed_types_storage,
             abilities_store,
+            supporting_exposed_types,
         },
         source_var,
         &derived_program,
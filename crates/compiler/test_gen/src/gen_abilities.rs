@@ -1485,6 +1485,190 @@ fn decode_tuple_of_tuples() {
     )
 }
 
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_tag_union() {
+    assert_evals_to!(
+        &formatdoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            {TAG_LEN_ENCODER_FMT}
+
+            main =
+                when Str.toUtf8 "l3 s4 Rect n2 n3 " |> Decode.fromBytes tagLenFmt is
+                    Ok (Rect 2u8 3u8) -> "rect"
+                    Ok (Circle _) -> "circle"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("rect"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_tag_union_unknown_tag() {
+    assert_evals_to!(
+        &formatdoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            {TAG_LEN_ENCODER_FMT}
+
+            main =
+                when Str.toUtf8 "l2 s8 Triangle n2 " |> Decode.fromBytes tagLenFmt is
+                    Ok (Rect 2u8 3u8) -> "rect"
+                    Ok (Circle _) -> "circle"
+                    Err TooShort -> "unknown tag"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("unknown tag"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn encode_then_decode_tag_union() {
+    assert_evals_to!(
+        &formatdoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            {TAG_LEN_ENCODER_FMT}
+
+            Shape : [Circle F64, Rect {{ w : F64, h : F64 }}]
+
+            main =
+                shapes : List Shape
+                shapes = [Circle 1.5, Rect {{ w: 2, h: 3 }}]
+
+                when Encode.toBytes shapes tagLenFmt |> Decode.fromBytes tagLenFmt is
+                    Ok decoded if decoded == shapes -> "roundtrip"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("roundtrip"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn encode_then_decode_recursive_tag_union() {
+    assert_evals_to!(
+        &formatdoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            {TAG_LEN_ENCODER_FMT}
+
+            Tree : [Leaf U8, Node Tree Tree]
+
+            main =
+                tree : Tree
+                tree = Node (Leaf 1) (Node (Leaf 2) (Leaf 3))
+
+                when Encode.toBytes tree tagLenFmt |> Decode.fromBytes tagLenFmt is
+                    Ok decoded if decoded == tree -> "roundtrip"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("roundtrip"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_set() {
+    assert_evals_to!(
+        &formatdoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            {TAG_LEN_ENCODER_FMT}
+
+            main =
+                when Str.toUtf8 "l3 n1 n2 n1 " |> Decode.fromBytes tagLenFmt is
+                    Ok set if set == Set.fromList [1u8, 2u8] -> "set"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("set"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_dict() {
+    assert_evals_to!(
+        &formatdoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            {TAG_LEN_ENCODER_FMT}
+
+            main =
+                when Str.toUtf8 "m2 s1 a n1 s1 b n2 " |> Decode.fromBytes tagLenFmt is
+                    Ok dict if dict == Dict.fromList [("a", 1u8), ("b", 2u8)] -> "dict"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("dict"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_record_with_dict_field() {
+    assert_evals_to!(
+        &formatdoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            {TAG_LEN_ENCODER_FMT}
+
+            main =
+                decoded : Result {{ counts : Dict Str U8, tags : Set Str }} _
+                decoded = Str.toUtf8 "r2 s6 counts m1 s1 x n3 s4 tags l1 s1 y " |> Decode.fromBytes tagLenFmt
+
+                when decoded is
+                    Ok {{ counts, tags }} -> (Dict.get counts "x" |> Result.withDefault 0) + Num.toU8 (Set.len tags)
+                    _ -> 0
+            "#
+        ),
+        4,
+        u8
+    )
+}
+
 #[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
 mod hash {
     #[cfg(feature = "gen-llvm")]
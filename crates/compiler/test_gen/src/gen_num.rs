@@ -567,16 +567,12 @@ fn various_sized_abs() {
     assert_evals_to!("Num.abs -6i16", 6, i16);
     assert_evals_to!("Num.abs -6i32", 6, i32);
     assert_evals_to!("Num.abs -6i64", 6, i64);
-    if !cfg!(feature = "gen-wasm") {
-        assert_evals_to!("Num.abs -6i128", I128::from(6), I128);
-    }
+    assert_evals_to!("Num.abs -6i128", I128::from(6), I128);
     assert_evals_to!("Num.abs 6u8", 6, u8);
     assert_evals_to!("Num.abs 6u16", 6, u16);
     assert_evals_to!("Num.abs 6u32", 6, u32);
     assert_evals_to!("Num.abs 6u64", 6, u64);
-    if !cfg!(feature = "gen-wasm") {
-        assert_evals_to!("Num.abs 6u128", U128::from(6), U128);
-    }
+    assert_evals_to!("Num.abs 6u128", U128::from(6), U128);
 }
 
 #[test]
@@ -2191,6 +2187,135 @@ fn shift_right_cast_i8() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn bitwise_i128() {
+    assert_evals_to!(
+        "Num.bitwiseAnd 0xffff_0000_0000_0000_0000_0000_0000_ff00u128 0x0ff0_0000_0000_0000_0000_0000_0000_0ff0",
+        U128::from(0x0ff0_0000_0000_0000_0000_0000_0000_0f00u128),
+        U128
+    );
+    assert_evals_to!(
+        "Num.bitwiseOr 0xf000_0000_0000_0000_0000_0000_0000_0000u128 0x0f",
+        U128::from(0xf000_0000_0000_0000_0000_0000_0000_000fu128),
+        U128
+    );
+    assert_evals_to!("Num.bitwiseXor -1i128 5", I128::from(-6), I128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn shift_by_i128() {
+    assert_evals_to!("Num.shiftLeftBy 1i128 100", I128::from(1 << 100), I128);
+    assert_evals_to!("Num.shiftLeftBy 1u128 127", U128::from(1 << 127), U128);
+    assert_evals_to!("Num.shiftLeftBy 1i128 200", I128::from(0), I128);
+    assert_evals_to!(
+        "Num.shiftRightBy (Num.neg (Num.shiftLeftBy 1i128 100)) 98",
+        I128::from(-4),
+        I128
+    );
+    assert_evals_to!("Num.shiftRightBy Num.maxI128 126", I128::from(1), I128);
+    assert_evals_to!("Num.shiftRightBy -12i128 200", I128::from(-1), I128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn int_negate_i128() {
+    assert_evals_to!("Num.neg 123i128", I128::from(-123), I128);
+    assert_evals_to!("Num.neg Num.maxI128", I128::from(-i128::MAX), I128);
+    assert_evals_to!("Num.neg (Num.minI128 + 1)", I128::from(i128::MAX), I128);
+    assert_evals_to!(
+        "Num.neg 0x1_0000_0000_0000_0000i128",
+        I128::from(-0x1_0000_0000_0000_0000i128),
+        I128
+    );
+    assert_evals_to!("Num.abs (Num.minI128 + 1)", I128::from(i128::MAX), I128);
+    assert_evals_to!(
+        "Num.abs -0x1_0000_0000_0000_0001i128",
+        I128::from(0x1_0000_0000_0000_0001),
+        I128
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[should_panic(
+    expected = r#"Roc failed with message: "Integer negation overflowed because its argument is the minimum value"#
+)]
+fn int_negate_i128_min_overflow() {
+    assert_evals_to!("Num.neg Num.minI128", I128::from(0), I128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn div_trunc_rem_i128() {
+    assert_evals_to!("Num.divTrunc -7i128 2", I128::from(-3), I128);
+    assert_evals_to!(
+        "Num.divTrunc Num.maxU128 3",
+        U128::from(u128::MAX / 3),
+        U128
+    );
+    assert_evals_to!("Num.rem -7i128 2", I128::from(-1), I128);
+    assert_evals_to!("Num.rem Num.maxU128 10", U128::from(u128::MAX % 10), U128);
+    assert_evals_to!(
+        "Num.isMultipleOf 1_000_000_000_000_000_000_000i128 -1000",
+        true,
+        bool
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn round_to_i128() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            n : I128
+            n = Num.floor -124.5f64
+            n
+            "
+        ),
+        I128::from(-125),
+        I128
+    );
+    assert_evals_to!(
+        indoc!(
+            r"
+            n : U128
+            n = Num.ceiling 124.5f32
+            n
+            "
+        ),
+        U128::from(125),
+        U128
+    );
+    assert_evals_to!(
+        indoc!(
+            r"
+            n : I128
+            n = Num.round -3.5f64
+            n
+            "
+        ),
+        I128::from(-4),
+        I128
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn pow_int_i128() {
+    assert_evals_to!("Num.powInt 3i128 70", I128::from(3i128.pow(70)), I128);
+    assert_evals_to!("Num.powInt 2u128 127", U128::from(1 << 127), U128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn compare_u128() {
+    assert_evals_to!("Num.compare Num.maxU128 1u128", RocOrder::Gt, RocOrder);
+    assert_evals_to!("Num.compare -1i128 1i128", RocOrder::Lt, RocOrder);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn min_i128() {
@@ -2361,12 +2486,12 @@ num_conversion_tests! {
         to_i64_sign_extend_i8, "-15i8", -15, ["gen-wasm", "gen-dev"]
         to_i64_sign_extend_i16, "-15i16", -15, ["gen-wasm", "gen-dev"]
         to_i64_sign_extend_i32, "-15i32", -15, ["gen-wasm", "gen-dev"]
-        to_i64_truncate, "115i128", 115
-        to_i64_truncate_wraps, "10_000_000_000_000_000_000i128", -8446744073709551616
+        to_i64_truncate, "115i128", 115, ["gen-wasm"]
+        to_i64_truncate_wraps, "10_000_000_000_000_000_000i128", -8446744073709551616, ["gen-wasm"]
     )
     "Num.toI128", i128, (
-        to_i128_same_width, "15u128", 15, ["gen-dev"]
-        to_i128_extend, "15i8", 15
+        to_i128_same_width, "15u128", 15, ["gen-wasm", "gen-dev"]
+        to_i128_extend, "15i8", 15, ["gen-wasm"]
     )
     "Num.toU8", u8, (
         to_u8_same_width, "15i8", 15, ["gen-wasm", "gen-dev"]
@@ -2388,25 +2513,25 @@ num_conversion_tests! {
     "Num.toU64", u64, (
         to_u64_same_width, "15i64", 15, ["gen-wasm", "gen-dev"]
         to_u64_extend, "15i8", 15, ["gen-wasm", "gen-dev"]
-        to_u64_truncate, "115i128", 115
-        to_u64_truncate_wraps, "10_000_000_000_000_000_000_000i128", 1864712049423024128
+        to_u64_truncate, "115i128", 115, ["gen-wasm"]
+        to_u64_truncate_wraps, "10_000_000_000_000_000_000_000i128", 1864712049423024128, ["gen-wasm"]
     )
     "Num.toU128", u128, (
-        to_u128_same_width, "15i128", 15, ["gen-dev"]
-        to_u128_extend, "15i8", 15
-        to_u128_big, "11562537357600483583u64", 11562537357600483583, ["gen-dev"]
+        to_u128_same_width, "15i128", 15, ["gen-wasm", "gen-dev"]
+        to_u128_extend, "15i8", 15, ["gen-wasm"]
+        to_u128_big, "11562537357600483583u64", 11562537357600483583, ["gen-wasm", "gen-dev"]
     )
     "Num.toF32", f32, (
         to_f32_from_i8, "15i8", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_i16, "15i16", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_i32, "15i32", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_i64, "15i64", 15.0, ["gen-wasm", "gen-dev"]
-        to_f32_from_i128, "15i128", 15.0, ["gen-dev"]
+        to_f32_from_i128, "15i128", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_u8, "15u8", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_u16, "15u16", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_u32, "15u32", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_u64, "15u64", 15.0, ["gen-wasm", "gen-dev"]
-        to_f32_from_u128, "15u128", 15.0, ["gen-dev"]
+        to_f32_from_u128, "15u128", 15.0, ["gen-wasm", "gen-dev"]
         to_f32_from_f32, "1.5f32", 1.5, ["gen-wasm", "gen-dev"]
         to_f32_from_f64, "1.5f64", 1.5, ["gen-wasm", "gen-dev"]
     )
@@ -2415,13 +2540,13 @@ num_conversion_tests! {
         to_f64_from_i16, "15i16", 15.0, ["gen-wasm", "gen-dev"]
         to_f64_from_i32, "15i32", 15.0, ["gen-wasm", "gen-dev"]
         to_f64_from_i64, "15i64", 15.0, ["gen-wasm", "gen-dev"]
-        to_f64_from_i128, "15i128", 15.0, ["gen-dev"]
+        to_f64_from_i128, "15i128", 15.0, ["gen-wasm", "gen-dev"]
         to_f64_from_u8, "15u8", 15.0, ["gen-wasm", "gen-dev"]
         to_f64_from_u16, "15u16", 15.0, ["gen-wasm", "gen-dev"]
         to_f64_from_u32, "15u32", 15.0, ["gen-wasm", "gen-dev"]
         to_f64_from_u64, "15u64", 15.0, ["gen-wasm", "gen-dev"]
-        to_f64_from_u128, "15u128", 15.0, ["gen-dev"]
-        to_f64_from_f32, "1.5f32", 1.5, ["gen-dev"]
+        to_f64_from_u128, "15u128", 15.0, ["gen-wasm", "gen-dev"]
+        to_f64_from_f32, "1.5f32", 1.5, ["gen-wasm", "gen-dev"]
         to_f64_from_f64, "1.5f64", 1.5, ["gen-wasm", "gen-dev"]
     )
 }
@@ -2620,6 +2745,31 @@ fn to_u128_checked_same_width_signed_oob() {
     assert_evals_to!(&test_roc_code, true, bool)
 }
 
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn to_float_checked_in_bounds() {
+    assert_evals_to!("Num.toF32Checked 15i8", RocResult::ok(15.0), RocResult<f32, ()>);
+    assert_evals_to!("Num.toF32Checked 15u128", RocResult::ok(15.0), RocResult<f32, ()>);
+    assert_evals_to!("Num.toF32Checked 1.5f64", RocResult::ok(1.5), RocResult<f32, ()>);
+    assert_evals_to!("Num.toF64Checked -15i128", RocResult::ok(-15.0), RocResult<f64, ()>);
+    assert_evals_to!("Num.toF64Checked 1.5f32", RocResult::ok(1.5), RocResult<f64, ()>);
+    assert_evals_to!("Num.toF64Checked 1.5dec", RocResult::ok(1.5), RocResult<f64, ()>);
+    assert_evals_to!("Num.toF32Checked -2.25dec", RocResult::ok(-2.25), RocResult<f32, ()>);
+    assert_evals_to!(
+        "Num.toF32Checked (Num.toF64 Num.infinityF32)",
+        RocResult::ok(f32::INFINITY),
+        RocResult<f32, ()>
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn to_float_checked_out_of_bounds() {
+    assert_evals_to!("Result.isErr (Num.toF32Checked Num.maxF64)", true, bool);
+    assert_evals_to!("Result.isErr (Num.toF32Checked Num.maxU128)", true, bool);
+    assert_evals_to!("Result.isErr (Num.toF64Checked Num.maxU128)", false, bool);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn is_multiple_of_signed() {
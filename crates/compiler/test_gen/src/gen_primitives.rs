@@ -4695,3 +4695,54 @@ fn issue_6139_prefixes() {
         RocList<RocStr>
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn inline_dbg_and_expect() {
+    // Test builds leave out dbg and inline expects, so even a failing one doesn't stop main
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main : I64
+            main =
+                x = 41
+                dbg x
+
+                expect x == 40
+
+                x + 1
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn inline_dbg_and_expect_in_dev_build() {
+    let (value, dbg_output, expect_failures) =
+        crate::helpers::wasm::run_with_expects::<i64>(indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main : I64
+            main =
+                x = 41
+                dbg x
+
+                expect x == 40
+                expect x == 41
+
+                x + 1
+            "#
+        ))
+        .unwrap();
+
+    assert_eq!(value, 42);
+    assert_eq!(dbg_output.len(), 1, "{dbg_output:?}");
+    assert!(dbg_output[0].ends_with("] x = 41"), "{dbg_output:?}");
+    assert_eq!(expect_failures.len(), 1, "{expect_failures:?}");
+}
@@ -9,6 +9,7 @@ use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::DEFAULT_PALETTE_HTML;
 use roc_solve::FunctionKind;
 use roc_std::RocStr;
+use roc_wasm_interp::roc_host::{decode_roc_str, format_dbg};
 use roc_wasm_interp::{wasi, ImportDispatcher, Instance, WasiDispatcher};
 use roc_wasm_module::{Export, ExportType, Value, WasmModule};
 use std::marker::PhantomData;
@@ -56,8 +57,14 @@ pub fn compile_to_wasm_bytes<'a, T: Wasm32Result>(
     let platform_bytes = include_bytes!(host_bytes_path!());
     println!("Loading test host {}", host_bytes_path!());
 
-    let compiled_bytes =
-        compile_roc_to_wasm_bytes(arena, platform_bytes, src, test_wrapper_type_info);
+    let runs_expects = false;
+    let compiled_bytes = compile_roc_to_wasm_bytes(
+        arena,
+        platform_bytes,
+        src,
+        test_wrapper_type_info,
+        runs_expects,
+    );
 
     if write_final_wasm() {
         let build_dir_hash = crate::helpers::src_hash(src);
@@ -72,6 +79,7 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
     host_bytes: &[u8],
     src: &str,
     _test_wrapper_type_info: PhantomData<T>,
+    runs_expects: bool,
 ) -> Vec<u8> {
     let filename = PathBuf::from("Test.roc");
     let src_dir = PathBuf::from("fake/test/path");
@@ -130,6 +138,7 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         module_id,
         exposed_to_host,
         stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+        runs_expects,
     };
 
     let host_module = roc_gen_wasm::parse_host(env.arena, host_bytes).unwrap_or_else(|e| {
@@ -187,8 +196,44 @@ where
     run_wasm_test_bytes::<T>(TEST_WRAPPER_NAME, wasm_bytes)
 }
 
+/// Run an app built like `roc dev` builds it, with `dbg` and inline `expect`s.
+/// Returns the value of `main`, the `dbg` output, and the `expect` failures.
+#[allow(dead_code)]
+pub fn run_with_expects<T>(src: &str) -> Result<(T, Vec<String>, Vec<String>), String>
+where
+    T: FromWasm32Memory + Wasm32Result,
+{
+    let arena = Bump::new();
+    let platform_bytes = include_bytes!(host_bytes_path!());
+    let runs_expects = true;
+    let wasm_bytes =
+        compile_roc_to_wasm_bytes(&arena, platform_bytes, src, PhantomData::<T>, runs_expects);
+
+    let require_relocatable = false;
+    let module = WasmModule::preload(&arena, &wasm_bytes, require_relocatable)
+        .map_err(|e| format!("{:?}", e))?;
+
+    let dispatcher = TestDispatcher::default();
+    let is_debug_mode = roc_debug_flags::dbg_set!(roc_debug_flags::ROC_LOG_WASM_INTERP);
+    let mut inst = Instance::for_module(&arena, &module, dispatcher, is_debug_mode)?;
+    let opt_value = inst.call_export(TEST_WRAPPER_NAME, [])?;
+    let addr_value = opt_value.ok_or("No return address from Wasm test")?;
+    let addr = addr_value.expect_i32().map_err(|e| format!("{:?}", e))?;
+    let output = <T as FromWasm32Memory>::decode(&inst.memory, addr as u32);
+
+    let TestDispatcher {
+        dbg_output,
+        expect_failures,
+        ..
+    } = inst.import_dispatcher;
+    Ok((output, dbg_output, expect_failures))
+}
+
+#[derive(Default)]
 struct TestDispatcher<'a> {
     wasi: WasiDispatcher<'a>,
+    dbg_output: Vec<String>,
+    expect_failures: Vec<String>,
 }
 
 impl<'a> ImportDispatcher for TestDispatcher<'a> {
@@ -211,6 +256,20 @@ impl<'a> ImportDispatcher for TestDispatcher<'a> {
                 _ => format!(r#"Got an invald panic tag: "{panic_tag}""#),
             };
             panic!("{}", msg)
+        } else if module_name == "env" && function_name == "send_dbg_to_rust" {
+            let [loc, msg, src] = [0, 1, 2]
+                .map(|i| decode_roc_str(memory, arguments[i].expect_i32().unwrap() as u32));
+            self.dbg_output.push(format_dbg(&loc, &msg, &src));
+            None
+        } else if module_name == "env" && function_name == "send_expect_failed_to_rust" {
+            let module_ptr = arguments[0].expect_i32().unwrap();
+            let region_start = arguments[1].expect_i32().unwrap();
+            let region_end = arguments[2].expect_i32().unwrap();
+            let module_name = RocStr::decode(memory, module_ptr as _);
+            self.expect_failures.push(format!(
+                "Expect failed in {module_name} at bytes {region_start}..{region_end}"
+            ));
+            None
         } else {
             panic!(
                 "TestDispatcher does not implement {}.{}",
@@ -242,9 +301,7 @@ pub(crate) fn run_wasm_test_module<'a, T>(
 where
     T: FromWasm32Memory + Wasm32Result,
 {
-    let dispatcher = TestDispatcher {
-        wasi: wasi::WasiDispatcher::default(),
-    };
+    let dispatcher = TestDispatcher::default();
     let is_debug_mode = roc_debug_flags::dbg_set!(roc_debug_flags::ROC_LOG_WASM_INTERP);
     let mut inst = Instance::for_module(&arena, &module, dispatcher, is_debug_mode)?;
     let opt_value = inst.call_export(test_wrapper_name, [])?;
@@ -271,9 +328,7 @@ where
     let module = WasmModule::preload(&arena, &wasm_bytes, require_relocatable)
         .map_err(|e| format!("{:?}", e))?;
 
-    let dispatcher = TestDispatcher {
-        wasi: wasi::WasiDispatcher::default(),
-    };
+    let dispatcher = TestDispatcher::default();
     let is_debug_mode = roc_debug_flags::dbg_set!(roc_debug_flags::ROC_LOG_WASM_INTERP);
     let mut inst = Instance::for_module(&arena, &module, dispatcher, is_debug_mode)?;
 
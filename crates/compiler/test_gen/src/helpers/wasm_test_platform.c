@@ -132,8 +132,23 @@ void roc_panic(void* msg, unsigned int panic_tag)
     exit(101);
 }
 
-// TODO: add a way to send dbg to rust.
-void roc_debug(void* loc, void* msg) {}
+//--------------------------
+
+extern void send_dbg_to_rust(void* loc, void* msg, void* src);
+
+void roc_dbg(void* loc, void* msg, void* src)
+{
+    send_dbg_to_rust(loc, msg, src);
+}
+
+//--------------------------
+
+extern void send_expect_failed_to_rust(void* module_name, uint32_t region_start, uint32_t region_end);
+
+void roc_expect_failed(void* module_name, uint32_t region_start, uint32_t region_end)
+{
+    send_expect_failed_to_rust(module_name, region_start, region_end);
+}
 
 //--------------------------
 
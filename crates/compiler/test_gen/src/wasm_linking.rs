@@ -159,6 +159,7 @@ impl<'a> BackendInputs<'a> {
             module_id,
             exposed_to_host,
             stack_bytes: Env::DEFAULT_STACK_BYTES,
+            runs_expects: false,
         };
 
         // Identifier stuff for the backend
@@ -51,6 +51,8 @@ ErrDecoder := {} implements [
             list: decodeList,
             record: decodeRecord,
             tuple: decodeTuple,
+            tag: decodeTag,
+            dict: decodeDict,
         },
     ]
 decodeU8 = Decode.custom \rest, @ErrDecoder {} -> { result: Err TooShort, rest }
@@ -74,6 +76,10 @@ decodeRecord : state, (state, Str -> [Keep (Decoder state ErrDecoder), Skip]), (
 decodeRecord = \_, _, _ -> Decode.custom \rest, @ErrDecoder {} -> { result: Err TooShort, rest }
 decodeTuple : state, (state, U64 -> [Next (Decoder state ErrDecoder), TooLong]), (state -> Result val DecodeError) -> Decoder val ErrDecoder
 decodeTuple = \_, _, _ -> Decode.custom \rest, @ErrDecoder {} -> { result: Err TooShort, rest }
+decodeTag : (Str -> [Keep (Decoder val ErrDecoder), Skip]) -> Decoder val ErrDecoder
+decodeTag = \_ -> Decode.custom \rest, @ErrDecoder {} -> { result: Err TooShort, rest }
+decodeDict : state, (state, k, v -> state), Decoder k ErrDecoder, Decoder v ErrDecoder -> Decoder state ErrDecoder
+decodeDict = \_, _, _, _ -> Decode.custom \rest, @ErrDecoder {} -> { result: Err TooShort, rest }
 "#;
 
 /// Without this, some tests pass in `cargo test --release` but fail without
@@ -3695,3 +3701,28 @@ fn dec_refcount_for_usage_after_early_return_in_if() {
         "#
     )
 }
+
+#[mono_test(mode = "test")]
+fn property_expect() {
+    indoc!(
+        r"
+        interface Test exposes [] imports []
+
+        double : I64 -> I64
+        double = \n -> n * 2
+
+        expect \n -> double n == n + n
+        "
+    )
+}
+
+#[mono_test(mode = "test")]
+fn property_expect_defaults_unbound_arguments() {
+    indoc!(
+        r"
+        interface Test exposes [] imports []
+
+        expect \xs -> List.reverse (List.reverse xs) == xs
+        "
+    )
+}
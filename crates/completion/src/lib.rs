@@ -0,0 +1,184 @@
+//! Finds what could complete the word being typed: the symbols in scope, and the fields of
+//! records. The language server turns these into completion items, and the REPL into
+//! tab completions.
+use log::{debug, warn};
+
+use roc_can::{expr::Declarations, traverse::Visitor};
+use roc_module::symbol::{Interns, Symbol};
+use roc_region::all::Position;
+use roc_types::subs::{Subs, Variable};
+
+use self::visitor::CompletionVisitor;
+
+mod visitor;
+
+/// What the word being typed is asking for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// A module, or something a module exposes, like `Str` or `Str.con`
+    Module,
+    /// A field of a record, like `rec.fie` or `rec.inner.fie`
+    RecordField,
+    /// A symbol in scope, like `myVa`
+    Variable,
+}
+
+pub fn completion_kind(symbol_prefix: &str) -> CompletionKind {
+    let is_capitalized = |str: &str| str.starts_with(|c: char| c.is_uppercase());
+
+    match symbol_prefix.rsplit_once('.') {
+        // If the second to last section is capitalised we know we are completing a
+        // module inside an import of a module, e.g.: My.Module.function
+        Some((before_last, _)) if is_capitalized(before_last.rsplit('.').next().unwrap_or("")) => {
+            CompletionKind::Module
+        }
+        Some(_) => CompletionKind::RecordField,
+        None if is_capitalized(symbol_prefix) => CompletionKind::Module,
+        None => CompletionKind::Variable,
+    }
+}
+
+/// The symbols that are in scope at the given position and start with the prefix
+pub fn get_completions(
+    position: Position,
+    decls: &Declarations,
+    prefix: String,
+    interns: &Interns,
+) -> Vec<(Symbol, Variable)> {
+    let mut visitor = CompletionVisitor {
+        position,
+        found_declarations: Vec::new(),
+        interns,
+        prefix,
+    };
+    visitor.visit_decls(decls);
+    visitor.found_declarations
+}
+
+/// E.g. a.b.c.d->{variable_name:"a", field:"d", middle_fields:["b","c"]}
+struct RecFieldCompletion {
+    /// name of variable that is a record
+    variable_name: String,
+    field: String,
+    middle_fields: Vec<String>,
+}
+
+/// Finds the types of and names of all the fields of a record.
+/// `var` should be a `Variable` that you know is of type record or else it will return an empty list.
+pub fn find_record_fields(var: Variable, subs: &mut Subs) -> Vec<(String, Variable)> {
+    let content = subs.get(var);
+    match content.content {
+        roc_types::subs::Content::Structure(typ) => match typ {
+            roc_types::subs::FlatType::Record(fields, ext) => {
+                let field_types = fields.unsorted_iterator(subs, ext);
+
+                match field_types {
+                    Ok(field) => field
+                        .map(|a| (a.0.clone().into(), a.1.into_inner()))
+                        .collect::<Vec<_>>(),
+                    Err(err) => {
+                        warn!("Error getting record field types for completion: {:?}", err);
+                        vec![]
+                    }
+                }
+            }
+            roc_types::subs::FlatType::Tuple(elems, ext) => {
+                let elems = elems.unsorted_iterator(subs, ext);
+
+                match elems {
+                    Ok(elem) => elem.map(|(num, var)| (num.to_string(), var)).collect(),
+                    Err(err) => {
+                        warn!("Error getting tuple elems for completion: {:?}", err);
+                        vec![]
+                    }
+                }
+            }
+
+            _ => {
+                warn!(
+                    "Trying to get field completion for a type that is not a record: {:?}",
+                    typ
+                );
+                vec![]
+            }
+        },
+        roc_types::subs::Content::Alias(_, _, real_var, _) => find_record_fields(real_var, subs),
+        roc_types::subs::Content::Error => {
+            //This is caused by typechecking our partially typed variable name causing the typechecking to be confused as the type of the parent variable
+            //TODO! ideally i could recover using some previous typecheck result that isn't broken
+            warn!("Variable type of record was of type 'error', cannot access field",);
+            vec![]
+        }
+        _ => {
+            warn!(
+                "Variable before field was unsupported type: {:?}",
+                subs.dbg(var)
+            );
+            vec![]
+        }
+    }
+}
+
+/// Splits a completion prefix for a field into its components.
+/// E.g. a.b.c.d->{variable_name:"a",middle_fields:["b","c"],field:"d"}
+fn get_field_completion_parts(symbol_prefix: &str) -> Option<RecFieldCompletion> {
+    let mut parts = symbol_prefix.split('.').collect::<Vec<_>>();
+    let field = parts.pop().unwrap_or("").to_string();
+    let variable_name = parts.remove(0).to_string();
+    // Now that we have the head and tail removed this is all the intermediate fields.
+    let middle_fields = parts.into_iter().map(ToString::to_string).collect();
+
+    Some(RecFieldCompletion {
+        variable_name,
+        field,
+        middle_fields,
+    })
+}
+
+/// The names and types of the fields that could complete a prefix like `rec.inner.fie`
+pub fn field_completions(
+    position: Position,
+    symbol_prefix: &str,
+    declarations: &Declarations,
+    interns: &Interns,
+    subs: &mut Subs,
+) -> Option<Vec<(String, Variable)>> {
+    let RecFieldCompletion {
+        variable_name,
+        field,
+        middle_fields,
+    } = get_field_completion_parts(symbol_prefix)?;
+
+    debug!(
+        "Getting record field completions: variable: {:?} field: {:?} middle: {:?} ",
+        variable_name, field, middle_fields
+    );
+
+    // We get completions here, but all we really want is the info about the variable that
+    // is the first part of our record completion.
+    // We are completing the full name of the variable, so we want the one that matches exactly.
+    let completion = get_completions(position, declarations, variable_name.clone(), interns)
+        .into_iter()
+        .map(|(symbol, var)| (symbol.as_str(interns).to_string(), var))
+        .find(|(name, _)| name == &variable_name)?;
+
+    // If we have a type that has nested records we could have a completion prefix like: "var.field1.field2.fi".
+    // If the document isn't fully typechecked we won't know what the type of field2 is for us to offer
+    // completions based on it's fields. Instead we get the type of "var" and then the type of "field1" within
+    // var's type and then "field2" within field1's type etc etc, until we have the type of the record we are
+    // actually looking for field completions for.
+    let completion_record = middle_fields.iter().fold(completion, |state, chain_field| {
+        let fields_vars = find_record_fields(state.1, subs);
+        fields_vars
+            .into_iter()
+            .find(|type_field| chain_field == &type_field.0)
+            .unwrap_or(state)
+    });
+
+    let field_completions: Vec<_> = find_record_fields(completion_record.1, subs)
+        .into_iter()
+        .filter(|(str, _)| str.starts_with(&field.to_string()))
+        .collect();
+
+    Some(field_completions)
+}
@@ -0,0 +1,235 @@
+//! Renders a package's docs as JSON, for tools like editor plugins that want the
+//! documentation as data rather than as HTML.
+use crate::{get_exposed_module_docs, load_module_for_docs};
+use roc_load::docs::{AbilityMember, DocDef, DocEntry, ModuleDocumentation};
+use roc_load::docs::{ImplementsClause, RecordField, Tag, TypeAnnotation};
+use roc_parse::ast::FunctionArrow;
+use roc_region::all::{LineColumn, LineInfo, Region};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn generate_docs_json(root_file: PathBuf, build_dir: &Path) {
+    let mut loaded_module = load_module_for_docs(root_file);
+    let exposed_module_docs = get_exposed_module_docs(&mut loaded_module);
+
+    let modules: Vec<Value> = exposed_module_docs
+        .iter()
+        .map(|(module_id, module_docs)| {
+            let (path, src) = &loaded_module.sources[module_id];
+
+            module_to_json(module_docs, path, &LineInfo::new(src))
+        })
+        .collect();
+
+    let mut json = serde_json::to_string_pretty(&json!({ "modules": modules })).unwrap();
+    json.push('\n');
+
+    fs::create_dir_all(build_dir).expect("TODO gracefully handle being unable to create build dir");
+    fs::write(build_dir.join("docs.json"), json)
+        .expect("TODO gracefully handle failing to write docs.json");
+
+    println!("🎉 Docs generated in {}", build_dir.display());
+}
+
+fn module_to_json(module_docs: &ModuleDocumentation, path: &Path, lines: &LineInfo) -> Value {
+    let entries: Vec<Value> = module_docs
+        .entries
+        .iter()
+        .map(|entry| match entry {
+            DocEntry::DocDef(doc_def) => doc_def_to_json(doc_def, lines),
+            DocEntry::DetachedDoc(docs) => json!({ "kind": "detached_doc", "docs": docs }),
+            DocEntry::ModuleDoc(docs) => json!({ "kind": "module_doc", "docs": docs }),
+        })
+        .collect();
+
+    json!({
+        "name": module_docs.name,
+        "path": path.to_string_lossy(),
+        "entries": entries,
+    })
+}
+
+fn doc_def_to_json(doc_def: &DocDef, lines: &LineInfo) -> Value {
+    json!({
+        "kind": "def",
+        "name": doc_def.name,
+        "type_vars": doc_def.type_vars,
+        "type_annotation": type_annotation_to_json(&doc_def.type_annotation),
+        "docs": doc_def.docs,
+        "region": region_to_json(doc_def.region, lines),
+        "implements": doc_def.implements,
+    })
+}
+
+fn region_to_json(region: Region, lines: &LineInfo) -> Value {
+    // 1-based, like editors and compiler reports show them
+    let position = |lc: LineColumn| json!({ "line": lc.line + 1, "column": lc.column + 1 });
+    let lc_region = lines.convert_region(region);
+
+    json!({
+        "start": position(lc_region.start),
+        "end": position(lc_region.end),
+        "start_offset": region.start().offset,
+        "end_offset": region.end().offset,
+    })
+}
+
+/// The annotation as a tree of objects with a `kind`. Missing annotations, including the
+/// extension of a closed record or tag union, are `null`.
+fn type_annotation_to_json(type_ann: &TypeAnnotation) -> Value {
+    let many = |anns: &[TypeAnnotation]| -> Vec<Value> {
+        anns.iter().map(type_annotation_to_json).collect()
+    };
+
+    match type_ann {
+        TypeAnnotation::TagUnion { tags, extension } => json!({
+            "kind": "tag_union",
+            "tags": tags
+                .iter()
+                .map(|Tag { name, values }| json!({ "name": name, "values": many(values) }))
+                .collect::<Vec<_>>(),
+            "extension": type_annotation_to_json(extension),
+        }),
+        TypeAnnotation::Function {
+            args,
+            arrow,
+            output,
+        } => json!({
+            "kind": "function",
+            "args": many(args),
+            "effectful": matches!(arrow, FunctionArrow::Effectful),
+            "output": type_annotation_to_json(output),
+        }),
+        TypeAnnotation::ObscuredTagUnion => json!({ "kind": "obscured_tag_union" }),
+        TypeAnnotation::ObscuredRecord => json!({ "kind": "obscured_record" }),
+        TypeAnnotation::BoundVariable(name) => json!({ "kind": "bound_variable", "name": name }),
+        TypeAnnotation::Apply { name, parts } => json!({
+            "kind": "apply",
+            "name": name,
+            "args": many(parts),
+        }),
+        TypeAnnotation::Record { fields, extension } => json!({
+            "kind": "record",
+            "fields": fields.iter().map(record_field_to_json).collect::<Vec<_>>(),
+            "extension": type_annotation_to_json(extension),
+        }),
+        TypeAnnotation::Tuple { elems, extension } => json!({
+            "kind": "tuple",
+            "elems": many(elems),
+            "extension": type_annotation_to_json(extension),
+        }),
+        TypeAnnotation::Ability { members } => json!({
+            "kind": "ability",
+            "members": members.iter().map(ability_member_to_json).collect::<Vec<_>>(),
+        }),
+        TypeAnnotation::Wildcard => json!({ "kind": "wildcard" }),
+        TypeAnnotation::NoTypeAnn => Value::Null,
+        TypeAnnotation::Where { ann, implements } => json!({
+            "kind": "where",
+            "type_annotation": type_annotation_to_json(ann),
+            "implements": implements
+                .iter()
+                .map(|ImplementsClause { name, abilities }| {
+                    json!({ "name": name, "abilities": many(abilities) })
+                })
+                .collect::<Vec<_>>(),
+        }),
+        TypeAnnotation::As { ann, name, vars } => json!({
+            "kind": "as",
+            "type_annotation": type_annotation_to_json(ann),
+            "name": name,
+            "vars": vars,
+        }),
+    }
+}
+
+fn record_field_to_json(field: &RecordField) -> Value {
+    match field {
+        RecordField::RecordField {
+            name,
+            type_annotation,
+        } => json!({
+            "kind": "required",
+            "name": name,
+            "type_annotation": type_annotation_to_json(type_annotation),
+        }),
+        RecordField::OptionalField {
+            name,
+            type_annotation,
+        } => json!({
+            "kind": "optional",
+            "name": name,
+            "type_annotation": type_annotation_to_json(type_annotation),
+        }),
+        RecordField::LabelOnly { name } => json!({ "kind": "label_only", "name": name }),
+    }
+}
+
+fn ability_member_to_json(member: &AbilityMember) -> Value {
+    json!({
+        "name": member.name,
+        "type_annotation": type_annotation_to_json(&member.type_annotation),
+        "able_variables": member
+            .able_variables
+            .iter()
+            .map(|(name, abilities)| json!({
+                "name": name,
+                "abilities": abilities.iter().map(type_annotation_to_json).collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+        "docs": member.docs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::type_annotation_to_json;
+    use roc_load::docs::{RecordField, TypeAnnotation};
+    use roc_parse::ast::FunctionArrow;
+    use serde_json::json;
+
+    #[test]
+    fn function_with_record_arg() {
+        // { name : Str }* -> List a
+        let ann = TypeAnnotation::Function {
+            args: vec![TypeAnnotation::Record {
+                fields: vec![RecordField::RecordField {
+                    name: "name".to_string(),
+                    type_annotation: TypeAnnotation::Apply {
+                        name: "Str".to_string(),
+                        parts: Vec::new(),
+                    },
+                }],
+                extension: Box::new(TypeAnnotation::Wildcard),
+            }],
+            arrow: FunctionArrow::Pure,
+            output: Box::new(TypeAnnotation::Apply {
+                name: "List".to_string(),
+                parts: vec![TypeAnnotation::BoundVariable("a".to_string())],
+            }),
+        };
+
+        assert_eq!(
+            type_annotation_to_json(&ann),
+            json!({
+                "kind": "function",
+                "args": [{
+                    "kind": "record",
+                    "fields": [{
+                        "kind": "required",
+                        "name": "name",
+                        "type_annotation": { "kind": "apply", "name": "Str", "args": [] },
+                    }],
+                    "extension": { "kind": "wildcard" },
+                }],
+                "effectful": false,
+                "output": {
+                    "kind": "apply",
+                    "name": "List",
+                    "args": [{ "kind": "bound_variable", "name": "a" }],
+                },
+            })
+        );
+    }
+}
@@ -19,6 +19,10 @@ use roc_region::all::Region;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+mod json;
+
+pub use json::generate_docs_json;
+
 const LINK_SVG: &str = include_str!("./static/link.svg");
 
 pub fn generate_docs_html(root_file: PathBuf, build_dir: &Path) {
@@ -10,6 +10,7 @@ use parking_lot::Mutex;
 use roc_can::{abilities::AbilitiesStore, expr::Declarations};
 use roc_collections::{MutMap, MutSet, VecMap};
 use roc_load::{docs::ModuleDocumentation, CheckedModule, LoadedModule};
+use roc_load::{ExecutionMode, LoadResult, LoadStart, LoadingProblem, SolvedModuleCache};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_packaging::cache::{self, RocCacheDir};
 use roc_region::all::LineInfo;
@@ -17,19 +18,26 @@ use roc_reporting::report::RocDocAllocator;
 use roc_solve_problem::TypeError;
 use roc_types::subs::{Subs, Variable};
 
-use tower_lsp::lsp_types::{Diagnostic, SemanticTokenType, Url};
+use tower_lsp::lsp_types::{CodeAction, Diagnostic, SemanticTokenType, Url};
 
 mod analysed_doc;
+mod code_actions;
 mod completion;
+mod document_symbols;
+mod inlay_hints;
 mod parse_ast;
+mod references;
 mod semantic_tokens;
+mod signature_help;
 mod tokens;
 mod utils;
 
 use crate::convert::diag::{IntoLspDiagnostic, ProblemFmt};
 
-pub(crate) use self::analysed_doc::{AnalyzedDocument, DocInfo};
-use self::{analysed_doc::ModuleIdToUrl, tokens::Token};
+pub(crate) use self::analysed_doc::{AnalyzedDocument, DocInfo, GlobalSymbol};
+pub(crate) use self::inlay_hints::InlayHintsConfig;
+pub(crate) use self::references::is_valid_value_name;
+use self::{analysed_doc::ModuleIdToUrl, code_actions::QuickFixEnv, parse_ast::Ast, tokens::Token};
 
 pub const HIGHLIGHT_TOKENS_LEGEND: &[SemanticTokenType] = Token::LEGEND;
 
@@ -53,6 +61,29 @@ impl ModulesInfo {
         self.docs_by_module.get(module_id)
     }
 
+    /// The types of the values a module exposes, which is all that the modules importing it can
+    /// depend on. `subs` must be the module's own.
+    fn exposed_types(
+        &self,
+        module_id: ModuleId,
+        subs: &mut Subs,
+        interns: &Interns,
+    ) -> Vec<String> {
+        let Some(exposed) = self.exposed_by_module.get(&module_id) else {
+            return vec![];
+        };
+
+        let mut types: Vec<_> = exposed
+            .iter()
+            .map(|(symbol, var)| {
+                let type_str = utils::format_var_type(*var, subs, &module_id, interns);
+                format!("{} : {}", symbol.as_str(interns), type_str)
+            })
+            .collect();
+        types.sort();
+        types
+    }
+
     fn from_loaded_module(
         exposes: MutMap<ModuleId, Vec<(Symbol, Variable)>>,
         typechecked: &MutMap<ModuleId, CheckedModule>,
@@ -93,30 +124,31 @@ pub(super) struct AnalyzedModule {
     // ModuleIds are not stable between compilations, so a ModuleId visible to
     // one module may not be true global to the language server.
     module_id_to_url: ModuleIdToUrl,
+    /// See [ModulesInfo::exposed_types].
+    exposed_types: Vec<String>,
+    /// The exposed types of the non-builtin modules this module imports, as of when it was
+    /// checked.
+    imported_exposed_types: HashMap<Url, Arc<Vec<String>>>,
 }
 #[derive(Debug, Clone)]
 pub struct AnalysisResult {
     module: Option<AnalyzedModule>,
     diagnostics: Vec<Diagnostic>,
+    /// Code actions that fix some of the `diagnostics`.
+    quick_fixes: Vec<CodeAction>,
 }
 
-pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
+/// Checks the module in `doc_info` and everything it imports. The imported modules that didn't
+/// change since they were last checked are reused from `solved_cache` rather than solved again.
+pub(crate) fn global_analysis(
+    doc_info: DocInfo,
+    solved_cache: SolvedModuleCache,
+) -> Vec<AnalyzedDocument> {
     let fi = doc_info.url.to_file_path().unwrap();
     let src_dir = find_src_dir(&fi).to_path_buf();
 
     let arena = Bump::new();
-    let loaded = roc_load::load_and_typecheck_str(
-        &arena,
-        fi,
-        &doc_info.source,
-        src_dir,
-        None,
-        roc_target::Target::LinuxX64,
-        roc_load::FunctionKind::LambdaSet,
-        roc_reporting::report::RenderTarget::LanguageServer,
-        RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
-        roc_reporting::report::DEFAULT_PALETTE,
-    );
+    let loaded = load_and_typecheck_str(&arena, fi, &doc_info.source, src_dir, solved_cache);
 
     let module = match loaded {
         Ok(module) => module,
@@ -131,6 +163,7 @@ pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
                 analysis_result: AnalysisResult {
                     module: None,
                     diagnostics: all_problems,
+                    quick_fixes: vec![],
                 },
             };
 
@@ -180,6 +213,7 @@ pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
         exposed_imports,
         imports: &mut imports,
         modules_info,
+        exposed_types_by_module: HashMap::new(),
     };
 
     for (module_id, (path, source)) in sources {
@@ -190,6 +224,33 @@ pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
     documents
 }
 
+fn load_and_typecheck_str<'a>(
+    arena: &'a Bump,
+    filename: PathBuf,
+    source: &'a str,
+    src_dir: PathBuf,
+    solved_cache: SolvedModuleCache,
+) -> Result<LoadedModule, LoadingProblem<'a>> {
+    let cache_dir = cache::roc_cache_packages_dir();
+    let roc_cache_dir = RocCacheDir::Persistent(cache_dir.as_path());
+    let load_start = LoadStart::from_str(arena, filename, None, source, roc_cache_dir, src_dir)?
+        .with_solved_cache(solved_cache);
+
+    match roc_load::load_single_threaded(
+        arena,
+        load_start,
+        roc_target::Target::LinuxX64,
+        roc_load::FunctionKind::LambdaSet,
+        roc_reporting::report::RenderTarget::LanguageServer,
+        roc_reporting::report::DEFAULT_PALETTE,
+        roc_cache_dir,
+        ExecutionMode::Check,
+    )? {
+        LoadResult::Monomorphized(_) => unreachable!("we only type-check"),
+        LoadResult::TypeChecked(module) => Ok(module),
+    }
+}
+
 /// Take the exposed imports from each module, lookup the symbol within that module's list of
 /// exposed symbols and then get the type info for that import.
 /// example: `import {Task.{await}}`. `await` is an exposed_import, so we need to lookup its type info.
@@ -270,6 +331,7 @@ struct AnalyzedDocumentBuilder<'a> {
     imports: &'a mut MutMap<ModuleId, MutSet<ModuleId>>,
     exposed_imports: HashMap<ModuleId, Vec<(Symbol, Variable)>>,
     modules_info: Arc<ModulesInfo>,
+    exposed_types_by_module: HashMap<ModuleId, Arc<Vec<String>>>,
 }
 
 impl<'a> AnalyzedDocumentBuilder<'a> {
@@ -280,12 +342,13 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
         module_id: ModuleId,
         version: i32,
     ) -> AnalyzedDocument {
-        let subs;
+        let mut subs;
         let abilities;
         let declarations;
 
         //lookup the type info for each import from the module where it was exposed
         let this_imports = self.imports.remove(&module_id).unwrap_or_default();
+        let imported_exposed_types = self.get_exposed_types_for_imports(&this_imports);
         let imports = self.get_symbols_for_imports(this_imports);
 
         let exposed_imports = self.exposed_imports.remove(&module_id).unwrap_or_default();
@@ -301,6 +364,10 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
             declarations = self.declarations_by_id.remove(&module_id).unwrap();
         }
 
+        let exposed_types = self
+            .modules_info
+            .exposed_types(module_id, &mut subs, self.interns);
+
         let analyzed_module = AnalyzedModule {
             exposed_imports,
             imports_by_module: imports,
@@ -311,21 +378,25 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
             modules_info: self.modules_info.clone(),
             interns: self.interns.clone(),
             module_id_to_url: self.module_id_to_url.clone(),
+            exposed_types,
+            imported_exposed_types,
         };
 
-        let line_info = LineInfo::new(&source);
-        let diagnostics = self.build_diagnostics(&path, &source, &line_info, module_id);
+        let doc_info = DocInfo {
+            url: path_to_url(&path),
+            line_info: LineInfo::new(&source),
+            source: source.into(),
+            version,
+        };
+        let (diagnostics, quick_fixes) =
+            self.build_diagnostics(&path, &doc_info, &analyzed_module.declarations, module_id);
 
         AnalyzedDocument {
-            doc_info: DocInfo {
-                url: path_to_url(&path),
-                line_info,
-                source: source.into(),
-                version,
-            },
+            doc_info,
             analysis_result: AnalysisResult {
                 module: Some(analyzed_module),
                 diagnostics,
+                quick_fixes,
             },
         }
     }
@@ -350,13 +421,50 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
             .collect::<HashMap<_, _>>()
     }
 
+    /// Gets the exposed types of each imported module that isn't a builtin, by its url
+    fn get_exposed_types_for_imports(
+        &mut self,
+        imports: &MutSet<ModuleId>,
+    ) -> HashMap<Url, Arc<Vec<String>>> {
+        let modules_info = &self.modules_info;
+        let interns = self.interns;
+
+        imports
+            .iter()
+            .filter(|id| !id.is_builtin())
+            .filter_map(|id| {
+                let url = self.module_id_to_url.get(id)?.clone();
+                let exposed_types = match self.exposed_types_by_module.get(id) {
+                    Some(exposed_types) => exposed_types.clone(),
+                    None => {
+                        let exposed_types = modules_info.with_subs(id, |subs| {
+                            Arc::new(modules_info.exposed_types(*id, subs, interns))
+                        })?;
+                        self.exposed_types_by_module
+                            .insert(*id, exposed_types.clone());
+                        exposed_types
+                    }
+                };
+
+                Some((url, exposed_types))
+            })
+            .collect()
+    }
+
+    /// Builds the diagnostics of a module, and the quick fixes for them.
     fn build_diagnostics(
         &mut self,
         source_path: &Path,
-        source: &str,
-        line_info: &LineInfo,
+        doc_info: &DocInfo,
+        declarations: &Declarations,
         module_id: ModuleId,
-    ) -> Vec<Diagnostic> {
+    ) -> (Vec<Diagnostic>, Vec<CodeAction>) {
+        let DocInfo {
+            url,
+            source,
+            line_info,
+            ..
+        } = doc_info;
         let lines: Vec<_> = source.lines().collect();
 
         let alloc = RocDocAllocator::new(&lines, module_id, self.interns);
@@ -372,18 +480,38 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
 
         let type_problems = self.type_problems.remove(&module_id).unwrap_or_default();
 
+        let arena = Bump::new();
+        let quick_fix_env = QuickFixEnv {
+            source,
+            line_info,
+            ast: Ast::parse(&arena, source).ok(),
+            declarations,
+            interns: self.interns,
+        };
+        let mut quick_fixes = Vec::new();
+
         for can_problem in can_problems {
+            let quick_fix = quick_fix_env.can_problem_fix(&can_problem);
+
             if let Some(diag) = can_problem.into_lsp_diagnostic(&fmt) {
+                if let Some(quick_fix) = quick_fix {
+                    quick_fixes.push(quick_fix.into_code_action(url, diag.clone()));
+                }
                 all_problems.push(diag);
             }
         }
 
         for type_problem in type_problems {
+            let quick_fix = quick_fix_env.type_problem_fix(&type_problem);
+
             if let Some(diag) = type_problem.into_lsp_diagnostic(&fmt) {
+                if let Some(quick_fix) = quick_fix {
+                    quick_fixes.push(quick_fix.into_code_action(url, diag.clone()));
+                }
                 all_problems.push(diag);
             }
         }
 
-        all_problems
+        (all_problems, quick_fixes)
     }
 }
@@ -3,29 +3,47 @@ use std::collections::HashMap;
 
 use bumpalo::Bump;
 
+use roc_completion::{completion_kind, CompletionKind};
 use roc_module::symbol::{ModuleId, Symbol};
 
 use roc_region::all::LineInfo;
 
 use tower_lsp::lsp_types::{
-    CompletionItem, Diagnostic, GotoDefinitionResponse, Hover, HoverContents, LanguageString,
-    Location, MarkedString, Position, Range, SemanticTokens, SemanticTokensResult, TextEdit, Url,
+    CodeActionOrCommand, CompletionItem, Diagnostic, DocumentSymbol, GotoDefinitionResponse, Hover,
+    HoverContents, InlayHint, LanguageString, Location, MarkedString, Position,
+    PrepareRenameResponse, Range, SemanticTokens, SemanticTokensResult, SignatureHelp,
+    SymbolInformation, TextDocumentContentChangeEvent, TextEdit, Url,
 };
 
 use crate::{
     analysis::completion::{field_completion, get_completion_items, get_module_completion_items},
-    convert::{ToRange, ToRocPosition},
+    convert::{ToRange, ToRegion, ToRocPosition},
 };
 
 use super::{
+    code_actions::annotation_action,
+    inlay_hints::{find_inlay_hints, InlayHintsConfig},
     parse_ast::Ast,
+    references::{find_occurrences, Occurrence, OccurrenceKind},
     semantic_tokens::arrange_semantic_tokens,
+    signature_help::signature_help,
     utils::{format_var_type, is_roc_identifier_char},
     AnalysisResult, AnalyzedModule,
 };
 
 pub(super) type ModuleIdToUrl = HashMap<ModuleId, Url>;
 
+/// A symbol that can be looked up in documents analyzed by a different load than the one it
+/// came from. [Symbol]s can't be used for this, since [ModuleId]s are not stable between loads.
+#[derive(Debug, Clone)]
+pub(crate) struct GlobalSymbol {
+    pub module_url: Url,
+    pub module_name: String,
+    pub name: String,
+    /// Only exposed symbols can be referenced from other modules.
+    pub exposed: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct AnalyzedDocument {
     pub doc_info: DocInfo,
@@ -49,6 +67,25 @@ impl DocInfo {
         }
     }
 
+    /// Applies the changes of an incremental sync, in order. A change without a range replaces
+    /// the whole document.
+    pub fn with_changes(&self, changes: Vec<TextDocumentContentChangeEvent>, version: i32) -> Self {
+        let mut source = self.source.clone();
+
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let start = offset_of(&source, range.start);
+                    let end = offset_of(&source, range.end).max(start);
+                    source.replace_range(start..end, &change.text);
+                }
+                None => source = change.text,
+            }
+        }
+
+        Self::new(self.url.clone(), source, version)
+    }
+
     #[cfg(debug_assertions)]
     #[allow(unused)]
     fn debug_log_prefix(&self, offset: usize) {
@@ -114,6 +151,87 @@ impl DocInfo {
             data,
         }))
     }
+
+    pub fn document_symbols(&self) -> Option<Vec<DocumentSymbol>> {
+        let arena = &Bump::new();
+
+        let ast = Ast::parse(arena, &self.source).ok()?;
+        Some(ast.document_symbols(&self.line_info))
+    }
+
+    /// The document's symbols whose names match `query`, with the members of abilities
+    /// flattened out.
+    pub fn workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        let mut matching = Vec::new();
+        let mut symbols: Vec<_> = self
+            .document_symbols()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|symbol| (symbol, None))
+            .collect();
+
+        while let Some((mut symbol, container_name)) = symbols.pop() {
+            let children = symbol.children.take().unwrap_or_default();
+            symbols.extend(
+                children
+                    .into_iter()
+                    .map(|child| (child, Some(symbol.name.clone()))),
+            );
+
+            if matches_query(&symbol.name, query) {
+                #[allow(deprecated)]
+                matching.push(SymbolInformation {
+                    name: symbol.name,
+                    kind: symbol.kind,
+                    tags: None,
+                    deprecated: None,
+                    location: Location {
+                        uri: self.url.clone(),
+                        range: symbol.range,
+                    },
+                    container_name,
+                });
+            }
+        }
+
+        matching.sort_by_key(|symbol| symbol.location.range.start);
+        matching
+    }
+}
+
+/// Whether the characters of `query` appear in order in `name`, ignoring case.
+fn matches_query(name: &str, query: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| name.any(|c| c == q))
+}
+
+/// The byte offset of `position` in `source`. Clients count columns in UTF-16 code units, and
+/// positions past the end of a line or of the document are clamped to it.
+fn offset_of(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match source[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return source.len(),
+        }
+    }
+
+    let line = &source[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + offset;
+        }
+        units += c.len_utf16();
+    }
+
+    line_start + line.len()
 }
 
 impl AnalyzedDocument {
@@ -136,6 +254,24 @@ impl AnalyzedDocument {
         }
     }
 
+    /// Whether this is one of the builtin modules, which are analyzed along with every module.
+    pub fn is_builtin(&self) -> bool {
+        self.module()
+            .map_or(false, |module| module.module_id.is_builtin())
+    }
+
+    /// The types of the values this module exposes, if it type checked.
+    pub fn exposed_types(&self) -> Option<&[String]> {
+        Some(&self.module()?.exposed_types)
+    }
+
+    /// The exposed types of the module at `url` this module was checked against, if it imports
+    /// that module.
+    pub fn imported_exposed_types(&self, url: &Url) -> Option<&[String]> {
+        let exposed_types = self.module()?.imported_exposed_types.get(url)?;
+        Some(exposed_types)
+    }
+
     pub fn type_checked(&self) -> bool {
         self.analysis_result.module.is_some()
     }
@@ -144,6 +280,47 @@ impl AnalyzedDocument {
         self.analysis_result.diagnostics.clone()
     }
 
+    /// The quick fixes for diagnostics overlapping `range`, and the refactorings available at
+    /// its start.
+    pub fn code_actions(&self, range: Range) -> Vec<CodeActionOrCommand> {
+        let quick_fixes = self
+            .analysis_result
+            .quick_fixes
+            .iter()
+            .filter(|action| {
+                action.diagnostics.iter().flatten().any(|diagnostic| {
+                    diagnostic.range.start <= range.end && range.start <= diagnostic.range.end
+                })
+            })
+            .cloned();
+
+        let annotation = self.module().and_then(|module| {
+            let position = range.start.to_roc_position(self.line_info());
+            annotation_action(&self.doc_info, module, position)
+        });
+
+        quick_fixes
+            .chain(annotation)
+            .map(CodeActionOrCommand::CodeAction)
+            .collect()
+    }
+
+    pub fn inlay_hints(&self, range: Range, config: InlayHintsConfig) -> Option<Vec<InlayHint>> {
+        let region = range.to_region(self.line_info());
+
+        Some(find_inlay_hints(
+            &self.doc_info,
+            self.module()?,
+            region,
+            config,
+        ))
+    }
+
+    pub fn signature_help(&self, position: Position) -> Option<SignatureHelp> {
+        let position = position.to_roc_position(self.line_info());
+        signature_help(&self.doc_info.source, self.module()?, position)
+    }
+
     pub fn symbol_at(&self, position: Position) -> Option<Symbol> {
         let line_info = self.line_info();
 
@@ -218,6 +395,138 @@ impl AnalyzedDocument {
         self.module()?.module_id_to_url.get(&module_id).cloned()
     }
 
+    pub(crate) fn global_symbol(&self, symbol: Symbol) -> Option<GlobalSymbol> {
+        let AnalyzedModule {
+            interns,
+            modules_info,
+            ..
+        } = self.module()?;
+
+        if symbol.module_id().is_builtin() {
+            return None;
+        }
+
+        let exposed = modules_info
+            .exposed_by_module
+            .get(&symbol.module_id())
+            .map_or(false, |exposed| {
+                exposed.iter().any(|(exposed, _)| *exposed == symbol)
+            });
+
+        Some(GlobalSymbol {
+            module_url: self.module_url(symbol.module_id())?,
+            module_name: symbol.module_id().to_ident_str(interns).to_string(),
+            name: symbol.as_str(interns).to_string(),
+            exposed,
+        })
+    }
+
+    /// Finds the symbol `global` refers to in this document's load, if it is exposed.
+    pub(crate) fn resolve_global_symbol(&self, global: &GlobalSymbol) -> Option<Symbol> {
+        let AnalyzedModule {
+            interns,
+            modules_info,
+            module_id_to_url,
+            ..
+        } = self.module()?;
+
+        let (module_id, _) = module_id_to_url
+            .iter()
+            .find(|(_, url)| **url == global.module_url)?;
+
+        modules_info
+            .exposed_by_module
+            .get(module_id)?
+            .iter()
+            .map(|(symbol, _)| *symbol)
+            .find(|symbol| symbol.as_str(interns) == global.name)
+    }
+
+    fn occurrences(&self, symbol: Symbol) -> Vec<Occurrence> {
+        let Some(AnalyzedModule {
+            declarations,
+            interns,
+            ..
+        }) = self.module()
+        else {
+            return vec![];
+        };
+
+        let name = symbol.as_str(interns);
+
+        find_occurrences(symbol, name, &self.doc_info.source, declarations)
+    }
+
+    pub fn references(&self, symbol: Symbol, include_declaration: bool) -> Vec<Location> {
+        self.occurrences(symbol)
+            .into_iter()
+            .filter(|occurrence| {
+                include_declaration
+                    || !matches!(
+                        occurrence.kind,
+                        OccurrenceKind::Definition | OccurrenceKind::Annotation
+                    )
+            })
+            .map(|occurrence| self.location(occurrence.region.to_range(self.line_info())))
+            .collect()
+    }
+
+    pub fn prepare_rename(&self, position: Position) -> Option<PrepareRenameResponse> {
+        let symbol = self.symbol_at(position)?;
+        let global = self.global_symbol(symbol)?;
+
+        let pos = position.to_roc_position(self.line_info());
+        let occurrence = self
+            .occurrences(symbol)
+            .into_iter()
+            .find(|occurrence| occurrence.region.contains_pos(pos))?;
+
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: occurrence.region.to_range(self.line_info()),
+            placeholder: global.name,
+        })
+    }
+
+    /// The edits renaming `symbol` to `new_name` in this document, including in its header and
+    /// in the `exposing` lists of its imports.
+    pub(crate) fn rename_edits(
+        &self,
+        symbol: Symbol,
+        global: &GlobalSymbol,
+        new_name: &str,
+    ) -> Vec<TextEdit> {
+        let mut edits: Vec<_> = self
+            .occurrences(symbol)
+            .into_iter()
+            .map(|Occurrence { region, kind }| {
+                let new_text = match kind {
+                    OccurrenceKind::PunnedField(label) => format!("{}: {}", label, new_name),
+                    _ => new_name.to_string(),
+                };
+
+                TextEdit::new(region.to_range(self.line_info()), new_text)
+            })
+            .collect();
+
+        if global.exposed {
+            let arena = &Bump::new();
+
+            if let Ok(ast) = Ast::parse(arena, &self.doc_info.source) {
+                let header_regions = if self.url() == &global.module_url {
+                    ast.exposed_name_regions(&global.name)
+                } else {
+                    ast.imported_name_regions(&global.module_name, &global.name)
+                };
+
+                edits.extend(header_regions.into_iter().map(|region| {
+                    TextEdit::new(region.to_range(self.line_info()), new_name.to_string())
+                }));
+            }
+        }
+
+        edits
+    }
+
     pub fn completion_items(
         &self,
         position: Position,
@@ -247,19 +556,9 @@ impl AnalyzedDocument {
             ..
         } = self.module()?;
 
-        let is_field_or_module_completion = symbol_prefix.contains('.');
-
-        if is_field_or_module_completion {
-            // If the second to last section is capitalised we know we are completing a
-            // module inside an import of a module, e.g.: My.Module.function
-            let is_module_completion = symbol_prefix
-                .split('.')
-                .nth_back(1) // second to last
-                .map(|str| str.starts_with(|c: char| c.is_uppercase()))
-                .unwrap_or(false);
-
-            if is_module_completion {
-                info!("Getting module dot completion...");
+        match completion_kind(&symbol_prefix) {
+            CompletionKind::Module => {
+                info!("Getting module completion...");
                 Some(get_module_completion_items(
                     symbol_prefix,
                     interns,
@@ -267,7 +566,8 @@ impl AnalyzedDocument {
                     modules_info,
                     true,
                 ))
-            } else {
+            }
+            CompletionKind::RecordField => {
                 info!("Getting record dot completion...");
                 field_completion(
                     position,
@@ -278,21 +578,7 @@ impl AnalyzedDocument {
                     module_id,
                 )
             }
-        } else {
-            let is_module_or_type_completion =
-                symbol_prefix.starts_with(|c: char| c.is_uppercase());
-
-            if is_module_or_type_completion {
-                info!("Getting module completion...");
-                let completions = get_module_completion_items(
-                    symbol_prefix,
-                    interns,
-                    imports,
-                    modules_info,
-                    true,
-                );
-                Some(completions)
-            } else {
+            CompletionKind::Variable => {
                 info!("Getting variable completion...");
                 let completions = get_completion_items(
                     position,
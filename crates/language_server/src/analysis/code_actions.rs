@@ -0,0 +1,365 @@
+use std::{collections::HashMap, fmt::Write};
+
+use roc_can::{
+    exhaustive::{GUARD_CTOR, NONEXHAUSIVE_CTOR},
+    expr::{DeclarationTag, Declarations},
+    traverse::find_declaration,
+};
+use roc_exhaustive::{Context, CtorName, ListArity, Pattern, RenderAs};
+use roc_module::{ident::TagName, symbol::Interns};
+use roc_problem::can::{Problem, RuntimeError};
+use roc_region::all::{LineInfo, Position, Region};
+use roc_solve_problem::TypeError;
+use tower_lsp::lsp_types::{CodeAction, CodeActionKind, Diagnostic, TextEdit, Url, WorkspaceEdit};
+
+use crate::convert::ToRange;
+
+use super::{
+    parse_ast::{Ast, NewImportLocation},
+    references::{find_occurrences, OccurrenceKind},
+    utils::format_var_type,
+    AnalyzedModule, DocInfo,
+};
+
+/// What quick fixes are computed from: the problems of a module, and the module itself.
+pub(super) struct QuickFixEnv<'a> {
+    pub source: &'a str,
+    pub line_info: &'a LineInfo,
+    /// Only some fixes need the parsed module, so this is missing if it doesn't parse.
+    pub ast: Option<Ast<'a>>,
+    pub declarations: &'a Declarations,
+    pub interns: &'a Interns,
+}
+
+/// A title and the edits that fix a problem, before the problem is turned into a [Diagnostic].
+pub(super) struct QuickFix {
+    title: String,
+    edits: Vec<TextEdit>,
+}
+
+impl QuickFix {
+    fn new(title: String, edits: Vec<TextEdit>) -> Option<Self> {
+        (!edits.is_empty()).then_some(QuickFix { title, edits })
+    }
+
+    pub fn into_code_action(self, url: &Url, diagnostic: Diagnostic) -> CodeAction {
+        CodeAction {
+            title: self.title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic]),
+            edit: Some(workspace_edit(url, self.edits)),
+            is_preferred: Some(true),
+            ..Default::default()
+        }
+    }
+}
+
+fn workspace_edit(url: &Url, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEdit::new(HashMap::from([(url.clone(), edits)]))
+}
+
+impl QuickFixEnv<'_> {
+    pub fn can_problem_fix(&self, problem: &Problem) -> Option<QuickFix> {
+        match problem {
+            Problem::RuntimeError(RuntimeError::ModuleNotImported {
+                module_name,
+                module_exists: true,
+                ..
+            }) => {
+                let edit = self.add_import(module_name.as_str())?;
+                QuickFix::new(format!("Import `{}`", module_name), vec![edit])
+            }
+            Problem::UnusedModuleImport(module_id, region) => {
+                if !self.ast.as_ref()?.is_import_statement(*region) {
+                    return None;
+                }
+
+                let name = module_id.to_ident_str(self.interns);
+                QuickFix::new(
+                    format!("Remove unused import of `{}`", name),
+                    vec![self.delete_lines(*region)],
+                )
+            }
+            Problem::UnusedImport(symbol, region) => {
+                let (import_region, new_import) =
+                    self.ast.as_ref()?.import_without_exposed(*region)?;
+                let edit = TextEdit::new(import_region.to_range(self.line_info), new_import);
+
+                QuickFix::new(
+                    format!("Remove unused import `{}`", symbol.as_str(self.interns)),
+                    vec![edit],
+                )
+            }
+            Problem::UnusedDef(symbol, _) => {
+                let name = symbol.as_str(self.interns);
+                let mut region = find_declaration(*symbol, self.declarations)?.region();
+
+                // The annotation isn't part of the declaration's region, but it should go too.
+                for occurrence in find_occurrences(*symbol, name, self.source, self.declarations) {
+                    if occurrence.kind == OccurrenceKind::Annotation {
+                        region = Region::span_across(&occurrence.region, &region);
+                    }
+                }
+
+                QuickFix::new(
+                    format!("Remove unused definition `{}`", name),
+                    vec![self.delete_lines(region)],
+                )
+            }
+            _ => None,
+        }
+    }
+
+    pub fn type_problem_fix(&self, problem: &TypeError) -> Option<QuickFix> {
+        match problem {
+            TypeError::Exhaustive(roc_exhaustive::Error::Incomplete(
+                region,
+                Context::BadCase,
+                missing,
+            )) => {
+                let indent = self.branch_indent(*region)?;
+                let end = region.end();
+
+                let mut new_text = String::new();
+                for pattern in missing {
+                    let pattern = pattern_to_string(pattern, self.interns, false);
+                    write!(new_text, "\n{}{} -> crash \"TODO\"", indent, pattern).unwrap();
+                }
+
+                let title = if missing.len() == 1 {
+                    "Add missing branch".to_string()
+                } else {
+                    "Add missing branches".to_string()
+                };
+
+                QuickFix::new(title, vec![self.insert(end, new_text)])
+            }
+            _ => None,
+        }
+    }
+
+    fn add_import(&self, module_name: &str) -> Option<TextEdit> {
+        let import = format!("import {}", module_name);
+
+        let edit = match self.ast.as_ref()?.new_import_location() {
+            NewImportLocation::AfterImport(region) => {
+                self.insert(region.end(), format!("\n{}", import))
+            }
+            NewImportLocation::BeforeDef(region) => {
+                let start = line_start(self.source, region.start().offset as usize);
+                self.insert(Position::new(start as u32), format!("{}\n\n", import))
+            }
+            NewImportLocation::EndOfFile => {
+                let separator = if self.source.ends_with('\n') {
+                    ""
+                } else {
+                    "\n"
+                };
+                let end = Position::new(self.source.len() as u32);
+                self.insert(end, format!("{}\n{}\n", separator, import))
+            }
+        };
+
+        Some(edit)
+    }
+
+    /// The indentation of the first branch of the `when` at `region`.
+    fn branch_indent(&self, region: Region) -> Option<&str> {
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+        let when = self.source.get(start..end)?;
+
+        when.lines()
+            .skip(1)
+            .find(|line| !line.trim().is_empty())
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+    }
+
+    fn insert(&self, position: Position, new_text: String) -> TextEdit {
+        insert(self.line_info, position, new_text)
+    }
+
+    /// Deletes the lines `region` spans. If those are preceded by a blank line, the blank lines
+    /// after them go too, or the ones before them at the end of the file.
+    fn delete_lines(&self, region: Region) -> TextEdit {
+        let source = self.source;
+        let mut start = line_start(source, region.start().offset as usize);
+        let mut end = line_end(source, region.end().offset as usize);
+
+        let preceded_by_blank_line = start == 0 || source[..start - 1].ends_with('\n');
+
+        if preceded_by_blank_line {
+            while source[end..].starts_with('\n') {
+                end += 1;
+            }
+
+            if end == source.len() {
+                while source[..start].ends_with("\n\n") {
+                    start -= 1;
+                }
+            }
+        }
+
+        let region = Region::new(Position::new(start as u32), Position::new(end as u32));
+        TextEdit::new(region.to_range(self.line_info), String::new())
+    }
+}
+
+fn insert(line_info: &LineInfo, position: Position, new_text: String) -> TextEdit {
+    let region = Region::new(position, position);
+    TextEdit::new(region.to_range(line_info), new_text)
+}
+
+/// The offset of the start of the line containing `offset`.
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1)
+}
+
+/// The offset of the start of the line after the one containing `offset`.
+fn line_end(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find('\n')
+        .map_or(source.len(), |newline| offset + newline + 1)
+}
+
+/// Offers to annotate the top-level definition whose name is at `position` with its inferred type.
+pub(super) fn annotation_action(
+    doc_info: &DocInfo,
+    module: &AnalyzedModule,
+    position: Position,
+) -> Option<CodeAction> {
+    let DocInfo {
+        url,
+        source,
+        line_info,
+        ..
+    } = doc_info;
+    let AnalyzedModule {
+        declarations,
+        subs,
+        module_id,
+        interns,
+        ..
+    } = module;
+
+    let index = (0..declarations.len()).find(|index| {
+        let is_definition = matches!(
+            declarations.declarations[*index],
+            DeclarationTag::Value
+                | DeclarationTag::Function(_)
+                | DeclarationTag::Recursive(_)
+                | DeclarationTag::TailRecursive(_)
+        );
+
+        is_definition
+            && declarations.annotations[*index].is_none()
+            && declarations.symbols[*index].region.contains_pos(position)
+    })?;
+
+    let loc_symbol = declarations.symbols[index];
+    let name = loc_symbol.value.as_str(interns);
+    let type_str = format_var_type(
+        declarations.variables[index],
+        &mut subs.clone(),
+        module_id,
+        interns,
+    );
+
+    let start = line_start(source, loc_symbol.region.start().offset as usize);
+    let indent = &source[start..loc_symbol.region.start().offset as usize];
+    let edit = insert(
+        line_info,
+        Position::new(start as u32),
+        format!("{}{} : {}\n", indent, name, type_str),
+    );
+
+    Some(CodeAction {
+        title: format!("Add type annotation to `{}`", name),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(workspace_edit(url, vec![edit])),
+        ..Default::default()
+    })
+}
+
+/// Renders a pattern missing from a `when` as source code.
+fn pattern_to_string(pattern: &Pattern, interns: &Interns, in_type_param: bool) -> String {
+    use roc_exhaustive::Literal::*;
+
+    match pattern {
+        Pattern::Anything => "_".to_string(),
+        Pattern::Literal(literal) => match literal {
+            Int(i) => i128::from_ne_bytes(*i).to_string(),
+            U128(i) => u128::from_ne_bytes(*i).to_string(),
+            Bit(true) => "Bool.true".to_string(),
+            Bit(false) => "Bool.false".to_string(),
+            Byte(b) => b.to_string(),
+            Float(f) => f.to_string(),
+            // Decimals are never exhaustively matched, so any decimal will do.
+            Decimal(_) => "_".to_string(),
+            Str(s) => format!("{:?}", s),
+        },
+        Pattern::List(arity, patterns) => {
+            let mut items: Vec<_> = patterns
+                .iter()
+                .map(|pattern| pattern_to_string(pattern, interns, false))
+                .collect();
+
+            if let ListArity::Slice(num_before, _) = arity {
+                items.insert(*num_before, "..".to_string());
+            }
+
+            format!("[{}]", items.join(", "))
+        }
+        Pattern::Ctor(union, tag_id, args) => match &union.render_as {
+            // #Guard <fake-condition-tag> <unexhausted-pattern>
+            RenderAs::Guard => {
+                debug_assert!(union.alternatives[tag_id.0 as usize]
+                    .name
+                    .is_tag(&TagName(GUARD_CTOR.into())));
+                pattern_to_string(&args[1], interns, in_type_param)
+            }
+            RenderAs::Record(field_names) => {
+                let fields: Vec<_> = field_names
+                    .iter()
+                    .zip(args)
+                    .map(|(label, arg)| match arg {
+                        Pattern::Anything => label.to_string(),
+                        _ => format!("{}: {}", label, pattern_to_string(arg, interns, false)),
+                    })
+                    .collect();
+
+                format!("{{ {} }}", fields.join(", "))
+            }
+            RenderAs::Tuple => {
+                let elems: Vec<_> = args
+                    .iter()
+                    .map(|arg| pattern_to_string(arg, interns, false))
+                    .collect();
+
+                format!("({})", elems.join(", "))
+            }
+            RenderAs::Tag | RenderAs::Opaque => {
+                let name = match &union.alternatives[tag_id.0 as usize].name {
+                    CtorName::Tag(TagName(name)) if name.as_str() == NONEXHAUSIVE_CTOR => {
+                        return "_".to_string();
+                    }
+                    CtorName::Tag(TagName(name)) => name.to_string(),
+                    CtorName::Opaque(opaque) => format!("@{}", opaque.as_str(interns)),
+                };
+
+                let ctor = std::iter::once(name)
+                    .chain(args.iter().map(|arg| pattern_to_string(arg, interns, true)))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                if in_type_param && !args.is_empty() {
+                    format!("({})", ctor)
+                } else {
+                    ctor
+                }
+            }
+        },
+    }
+}
@@ -0,0 +1,125 @@
+use roc_parse::ast::{Defs, Expr, Pattern, TypeDef, ValueDef};
+use roc_region::all::{LineInfo, Loc, Region};
+use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+
+use crate::convert::ToRange;
+
+/// The top-level values, type aliases, opaque types and abilities defined in `defs`.
+pub(super) fn document_symbols(
+    defs: &Defs,
+    source: &str,
+    line_info: &LineInfo,
+) -> Vec<DocumentSymbol> {
+    let symbols = DocumentSymbols { source, line_info };
+
+    defs.loc_defs()
+        .filter_map(|def| match def {
+            Ok(Loc { region, value }) => symbols.type_def(region, &value),
+            Err(Loc { region, value }) => symbols.value_def(region, &value),
+        })
+        .collect()
+}
+
+struct DocumentSymbols<'a> {
+    source: &'a str,
+    line_info: &'a LineInfo,
+}
+
+impl DocumentSymbols<'_> {
+    fn type_def(&self, region: Region, type_def: &TypeDef) -> Option<DocumentSymbol> {
+        match type_def {
+            TypeDef::Alias { header, .. } => {
+                Some(self.symbol(header.name, SymbolKind::STRUCT, None, region))
+            }
+            TypeDef::Opaque { header, .. } => {
+                Some(self.symbol(header.name, SymbolKind::CLASS, None, region))
+            }
+            TypeDef::Ability {
+                header, members, ..
+            } => {
+                let children = members
+                    .iter()
+                    .map(|member| {
+                        let name = Loc::at(member.name.region, *member.name.value.item());
+                        let detail = self.text(member.typ.region);
+                        self.symbol(name, SymbolKind::METHOD, detail, member.region())
+                    })
+                    .collect();
+
+                let mut symbol = self.symbol(header.name, SymbolKind::INTERFACE, None, region);
+                symbol.children = Some(children);
+                Some(symbol)
+            }
+        }
+    }
+
+    fn value_def(&self, region: Region, value_def: &ValueDef) -> Option<DocumentSymbol> {
+        let (pattern, annotation, body) = match value_def {
+            ValueDef::Annotation(pattern, annotation) => (pattern, Some(annotation), None),
+            ValueDef::Body(pattern, body) => (*pattern, None, Some(*body)),
+            ValueDef::AnnotatedBody {
+                ann_pattern,
+                ann_type,
+                body_expr,
+                ..
+            } => (*ann_pattern, Some(*ann_type), Some(*body_expr)),
+            _ => return None,
+        };
+
+        let name = match without_spaces_pattern(&pattern.value) {
+            Pattern::Identifier { ident } => Loc::at(pattern.region, *ident),
+            _ => return None,
+        };
+
+        let kind = match body.map(|body| without_spaces_expr(&body.value)) {
+            Some(Expr::Closure(..)) => SymbolKind::FUNCTION,
+            _ => SymbolKind::CONSTANT,
+        };
+
+        let detail = annotation.and_then(|annotation| self.text(annotation.region));
+
+        Some(self.symbol(name, kind, detail, region))
+    }
+
+    #[allow(deprecated)]
+    fn symbol(
+        &self,
+        name: Loc<&str>,
+        kind: SymbolKind,
+        detail: Option<String>,
+        region: Region,
+    ) -> DocumentSymbol {
+        DocumentSymbol {
+            name: name.value.to_string(),
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: region.to_range(self.line_info),
+            selection_range: name.region.to_range(self.line_info),
+            children: None,
+        }
+    }
+
+    fn text(&self, region: Region) -> Option<String> {
+        let text = self
+            .source
+            .get(region.start().offset as usize..region.end().offset as usize)?;
+
+        Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
+
+fn without_spaces_pattern<'a>(mut pattern: &'a Pattern<'a>) -> &'a Pattern<'a> {
+    while let Pattern::SpaceBefore(inner, _) | Pattern::SpaceAfter(inner, _) = pattern {
+        pattern = *inner;
+    }
+    pattern
+}
+
+fn without_spaces_expr<'a>(mut expr: &'a Expr<'a>) -> &'a Expr<'a> {
+    while let Expr::SpaceBefore(inner, _) | Expr::SpaceAfter(inner, _) = expr {
+        expr = *inner;
+    }
+    expr
+}
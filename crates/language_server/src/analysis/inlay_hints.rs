@@ -0,0 +1,208 @@
+use roc_can::{
+    def::Def,
+    expr::{AnnotatedMark, Declarations, Expr},
+    pattern::Pattern,
+    traverse::{walk_decl, walk_def, walk_expr, DeclarationInfo, Visitor},
+};
+use roc_module::symbol::Symbol;
+use roc_region::all::{Loc, Region};
+use roc_types::subs::Variable;
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel};
+
+use crate::convert::ToRange;
+
+use super::{utils::format_var_type, AnalyzedModule, DocInfo};
+
+/// Which kinds of inlay hints to show.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct InlayHintsConfig {
+    /// Show the types of closure parameters.
+    pub parameters: bool,
+    /// Show the types of unannotated defs, and of values bound by backpassing or `!`.
+    pub bindings: bool,
+}
+
+impl Default for InlayHintsConfig {
+    fn default() -> Self {
+        Self {
+            parameters: true,
+            bindings: true,
+        }
+    }
+}
+
+/// Finds the inferred types to show after bindings and closure parameters within `range`.
+pub(super) fn find_inlay_hints(
+    doc_info: &DocInfo,
+    module: &AnalyzedModule,
+    range: Region,
+    config: InlayHintsConfig,
+) -> Vec<InlayHint> {
+    let AnalyzedModule {
+        declarations,
+        subs,
+        module_id,
+        interns,
+        ..
+    } = module;
+
+    let mut visitor = InlayHintVisitor {
+        range,
+        config,
+        source: &doc_info.source,
+        decls: declarations,
+        annotated_closure: None,
+        hints: Vec::new(),
+    };
+    visitor.visit_decls(declarations);
+
+    let mut subs = subs.clone();
+    let mut hints = visitor.hints;
+    hints.sort_by_key(|(region, _)| region.start());
+
+    hints
+        .into_iter()
+        .map(|(region, var)| InlayHint {
+            position: region.to_range(&doc_info.line_info).end,
+            label: InlayHintLabel::String(format!(
+                ": {}",
+                format_var_type(var, &mut subs, module_id, interns)
+            )),
+            kind: Some(InlayHintKind::TYPE),
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        })
+        .collect()
+}
+
+struct InlayHintVisitor<'a> {
+    range: Region,
+    config: InlayHintsConfig,
+    source: &'a str,
+    decls: &'a Declarations,
+    /// The closure bound by the annotated def being visited. Its parameters are already
+    /// annotated, so they don't get hints.
+    annotated_closure: Option<Region>,
+    hints: Vec<(Region, Variable)>,
+}
+
+impl InlayHintVisitor<'_> {
+    /// Adds a hint after `pattern`, if it is a name written in the source.
+    fn push(&mut self, pattern: &Loc<Pattern>, var: Variable) {
+        let region = pattern.region;
+        let is_written_name = match pattern.value {
+            Pattern::Identifier(_) => self
+                .source
+                .get(region.start().offset as usize..region.end().offset as usize)
+                .map_or(false, |name| {
+                    name.starts_with(|c: char| c.is_ascii_lowercase())
+                }),
+            _ => false,
+        };
+
+        if is_written_name && self.range.contains(&region) {
+            self.hints.push((region, var));
+        }
+    }
+
+    fn push_binding(&mut self, pattern: &Loc<Pattern>, var: Variable) {
+        if self.config.bindings {
+            self.push(pattern, var);
+        }
+    }
+
+    fn push_parameters(&mut self, arguments: &[(Variable, AnnotatedMark, Loc<Pattern>)]) {
+        if self.config.parameters {
+            for (var, _, pattern) in arguments {
+                self.push(pattern, *var);
+            }
+        }
+    }
+
+    fn is_annotated(&self, symbol: Symbol) -> bool {
+        self.decls
+            .symbols
+            .iter()
+            .zip(self.decls.annotations.iter())
+            .any(|(loc_symbol, annotation)| loc_symbol.value == symbol && annotation.is_some())
+    }
+}
+
+impl Visitor for InlayHintVisitor<'_> {
+    fn should_visit(&mut self, region: Region) -> bool {
+        region.start() <= self.range.end() && self.range.start() <= region.end()
+    }
+
+    fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+        if !self.should_visit(decl.region()) {
+            return;
+        }
+
+        match &decl {
+            DeclarationInfo::Value {
+                loc_symbol,
+                expr_var,
+                annotation: None,
+                ..
+            } => {
+                let pattern = Loc::at(loc_symbol.region, Pattern::Identifier(loc_symbol.value));
+                self.push_binding(&pattern, *expr_var);
+            }
+            DeclarationInfo::Function {
+                loc_symbol,
+                expr_var,
+                function,
+                ..
+            } if !self.is_annotated(loc_symbol.value) => {
+                let pattern = Loc::at(loc_symbol.region, Pattern::Identifier(loc_symbol.value));
+                self.push_binding(&pattern, *expr_var);
+                self.push_parameters(&function.value.arguments);
+            }
+            _ => {}
+        }
+
+        walk_decl(self, decl);
+    }
+
+    fn visit_def(&mut self, def: &Def) {
+        if !self.should_visit(def.region()) {
+            return;
+        }
+
+        match (&def.annotation, &def.loc_expr.value) {
+            (None, _) => self.push_binding(&def.loc_pattern, def.expr_var),
+            (Some(_), Expr::Closure(_)) => self.annotated_closure = Some(def.loc_expr.region),
+            (Some(_), _) => {}
+        }
+
+        walk_def(self, def);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if !self.should_visit(region) {
+            return;
+        }
+
+        if let Expr::Closure(closure) = expr {
+            let is_lambda = self
+                .source
+                .get(region.start().offset as usize..)
+                .map_or(false, |source| source.starts_with(['\\', '|']));
+
+            if !is_lambda {
+                // Backpassing and `!` bind values with a closure that has the rest of the
+                // block as its body.
+                for (var, _, pattern) in &closure.arguments {
+                    self.push_binding(pattern, *var);
+                }
+            } else if self.annotated_closure != Some(region) {
+                self.push_parameters(&closure.arguments);
+            }
+        }
+
+        walk_expr(self, expr, var);
+    }
+}
@@ -1,22 +1,37 @@
 use bumpalo::Bump;
-use roc_fmt::Buf;
+use roc_fmt::{annotation::Formattable, Buf};
 use roc_parse::{
-    ast::{Defs, Header, SpacesBefore},
-    header::parse_module_defs,
+    ast::{Collection, Defs, Header, ModuleImport, Spaced, SpacesBefore, ValueDef},
+    header::{parse_module_defs, ExposedName, KeywordItem},
     parser::SyntaxError,
 };
-use roc_region::all::Loc;
+use roc_region::all::{LineInfo, Loc, Region};
+use tower_lsp::lsp_types::DocumentSymbol;
 
 use self::format::FormattedAst;
 
-use super::tokens::{IterTokens, Token};
+use super::{
+    document_symbols::document_symbols,
+    tokens::{IterTokens, Token},
+};
 
 mod format;
 
 pub struct Ast<'a> {
     arena: &'a Bump,
+    src: &'a str,
     module: SpacesBefore<'a, Header<'a>>,
     defs: Defs<'a>,
+    /// The number of leading `defs` that are imports upgraded from an old-style header.
+    /// These still point into the header, so they can't be edited as statements.
+    header_imports: usize,
+}
+
+/// Where a new `import` statement should be inserted.
+pub enum NewImportLocation {
+    AfterImport(Region),
+    BeforeDef(Region),
+    EndOfFile,
 }
 
 impl<'a> Ast<'a> {
@@ -27,6 +42,7 @@ impl<'a> Ast<'a> {
             .map_err(|e| SyntaxError::Header(e.problem))?;
 
         let (header, defs) = module.item.upgrade_header_imports(arena);
+        let header_imports = defs.len();
 
         let defs = parse_module_defs(arena, state, defs)?;
 
@@ -37,6 +53,8 @@ impl<'a> Ast<'a> {
             },
             defs,
             arena,
+            src,
+            header_imports,
         })
     }
 
@@ -58,4 +76,123 @@ impl<'a> Ast<'a> {
 
         header_tokens.into_iter().chain(body_tokens)
     }
+
+    pub fn document_symbols(&self, line_info: &LineInfo) -> Vec<DocumentSymbol> {
+        document_symbols(&self.defs, self.src, line_info)
+    }
+
+    /// The regions of `name` in the list of values this module exposes (or provides).
+    pub fn exposed_name_regions(&self, name: &str) -> Vec<Region> {
+        let exposes = match &self.module.item {
+            Header::Module(header) => header.exposes,
+            Header::App(header) => header.provides,
+            Header::Hosted(header) => header.exposes.item,
+            Header::Package(_) | Header::Platform(_) => Collection::empty(),
+        };
+
+        exposed_name_regions(exposes, name)
+    }
+
+    fn import_statements(&self) -> impl Iterator<Item = (Region, &ModuleImport<'a>)> {
+        self.defs
+            .list_value_defs()
+            .skip_while(|(index, _)| *index < self.header_imports)
+            .filter_map(|(index, value_def)| match value_def {
+                ValueDef::ModuleImport(import) => Some((self.defs.regions[index], import)),
+                _ => None,
+            })
+    }
+
+    /// Whether there is an `import` statement at `region`.
+    pub fn is_import_statement(&self, region: Region) -> bool {
+        self.import_statements()
+            .any(|(import_region, _)| import_region == region)
+    }
+
+    pub fn new_import_location(&self) -> NewImportLocation {
+        if let Some((region, _)) = self.import_statements().last() {
+            return NewImportLocation::AfterImport(region);
+        }
+
+        let first_def = self
+            .defs
+            .regions
+            .iter()
+            .skip(self.header_imports)
+            .min_by_key(|region| region.start());
+
+        match first_def {
+            Some(region) => NewImportLocation::BeforeDef(*region),
+            None => NewImportLocation::EndOfFile,
+        }
+    }
+
+    /// Formats the `import` statement that exposes the name at `exposed_region`, without that
+    /// name. Returns the region of the statement and its new source.
+    pub fn import_without_exposed(&self, exposed_region: Region) -> Option<(Region, String)> {
+        let (region, import) = self.import_statements().find(|(_, import)| {
+            import.exposed.map_or(false, |exposed| {
+                exposed
+                    .item
+                    .iter()
+                    .any(|name| name.region == exposed_region)
+            })
+        })?;
+        let exposed = import.exposed?;
+
+        let remaining: Vec<_> = exposed
+            .item
+            .iter()
+            .filter(|name| name.region != exposed_region)
+            .copied()
+            .collect();
+
+        let new_import = ModuleImport {
+            exposed: if remaining.is_empty() {
+                None
+            } else {
+                Some(KeywordItem {
+                    item: exposed
+                        .item
+                        .replace_items(self.arena.alloc_slice_copy(&remaining)),
+                    ..exposed
+                })
+            },
+            ..*import
+        };
+
+        let mut buf = Buf::new_in(self.arena);
+        new_import.format(&mut buf, 0);
+
+        Some((region, buf.as_str().to_string()))
+    }
+
+    /// The regions of `name` in the `exposing` lists of imports of `module_name`.
+    /// Imports in old-style headers are included.
+    pub fn imported_name_regions(&self, module_name: &str, name: &str) -> Vec<Region> {
+        self.defs
+            .value_defs
+            .iter()
+            .filter_map(|value_def| match value_def {
+                ValueDef::ModuleImport(ModuleImport {
+                    name: imported,
+                    exposed: Some(exposed),
+                    ..
+                }) if imported.value.name.as_str() == module_name => Some(exposed.item),
+                _ => None,
+            })
+            .flat_map(|exposed| exposed_name_regions(exposed, name))
+            .collect()
+    }
+}
+
+fn exposed_name_regions(
+    exposed: Collection<'_, Loc<Spaced<'_, ExposedName<'_>>>>,
+    name: &str,
+) -> Vec<Region> {
+    exposed
+        .iter()
+        .filter(|loc_name| loc_name.value.item().as_str() == name)
+        .map(|loc_name| loc_name.region)
+        .collect()
 }
@@ -0,0 +1,250 @@
+use roc_can::{
+    def::{Annotation, Def},
+    expr::{Declarations, Expr, Field},
+    pattern::{DestructType, Pattern, RecordDestruct},
+    traverse::{walk_decls, walk_def, walk_expr, walk_pattern, Visitor},
+};
+use roc_module::{ident::Lowercase, symbol::Symbol};
+use roc_region::all::{Position, Region};
+use roc_types::subs::Variable;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum OccurrenceKind {
+    /// The pattern that binds the symbol.
+    Definition,
+    /// The name in front of the symbol's type annotation, e.g. `foo` in `foo : Str`.
+    Annotation,
+    /// Any use of the symbol.
+    Reference,
+    /// A record field that binds or uses the symbol under the field's own name, e.g. `{ foo }`.
+    /// Renaming the symbol must keep the field name, so this becomes `{ foo: newName }`.
+    PunnedField(Lowercase),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Occurrence {
+    /// The region of just the symbol's name, without any module qualifier.
+    pub region: Region,
+    pub kind: OccurrenceKind,
+}
+
+/// Finds every place `symbol` is defined, annotated or referenced in `decls`.
+///
+/// The canonical AST doesn't always have a region for just the symbol's name (e.g. for
+/// `Module.name` or `pattern as name`), so `source` is used to narrow regions down to the name.
+pub(crate) fn find_occurrences(
+    symbol: Symbol,
+    name: &str,
+    source: &str,
+    decls: &Declarations,
+) -> Vec<Occurrence> {
+    let mut visitor = OccurrenceVisitor {
+        symbol,
+        name,
+        source,
+        occurrences: Vec::new(),
+    };
+
+    for (index, loc_symbol) in decls.symbols.iter().enumerate() {
+        if loc_symbol.value == symbol {
+            if let Some(annotation) = &decls.annotations[index] {
+                visitor.push_annotation(annotation);
+            }
+        }
+    }
+
+    walk_decls(&mut visitor, decls);
+
+    let mut occurrences = visitor.occurrences;
+    occurrences.sort_by_key(|occurrence| occurrence.region.start());
+    occurrences.dedup();
+    occurrences
+}
+
+struct OccurrenceVisitor<'a> {
+    symbol: Symbol,
+    name: &'a str,
+    source: &'a str,
+    occurrences: Vec<Occurrence>,
+}
+
+impl OccurrenceVisitor<'_> {
+    fn push(&mut self, region: Option<Region>, kind: OccurrenceKind) {
+        if let Some(region) = region {
+            self.occurrences.push(Occurrence { region, kind });
+        }
+    }
+
+    fn push_annotation(&mut self, annotation: &Annotation) {
+        let region = annotation_name_region(self.source, annotation.region, self.name);
+        self.push(region, OccurrenceKind::Annotation);
+    }
+
+    fn last_name_in(&self, region: Region) -> Option<Region> {
+        find_ident(self.source, region, self.name).last()
+    }
+
+    fn first_name_in(&self, region: Region) -> Option<Region> {
+        find_ident(self.source, region, self.name).next()
+    }
+
+    fn visit_field(&mut self, label: &Lowercase, field: &Field) {
+        let Field {
+            var,
+            region,
+            loc_expr,
+        } = field;
+
+        match loc_expr.value {
+            Expr::Var(symbol, _) if symbol == self.symbol && *region == loc_expr.region => {
+                let region = self.first_name_in(*region);
+                self.push(region, OccurrenceKind::PunnedField(label.clone()));
+            }
+            _ => self.visit_expr(&loc_expr.value, loc_expr.region, *var),
+        }
+    }
+}
+
+impl Visitor for OccurrenceVisitor<'_> {
+    fn visit_def(&mut self, def: &Def) {
+        if let (Pattern::Identifier(symbol), Some(annotation)) =
+            (&def.loc_pattern.value, &def.annotation)
+        {
+            if *symbol == self.symbol {
+                self.push_annotation(annotation);
+            }
+        }
+
+        walk_def(self, def);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        match expr {
+            Expr::Var(symbol, _)
+            | Expr::ParamsVar { symbol, .. }
+            | Expr::AbilityMember(symbol, _, _)
+                if *symbol == self.symbol =>
+            {
+                let region = self.last_name_in(region);
+                self.push(region, OccurrenceKind::Reference);
+            }
+            Expr::Record { fields, .. } => {
+                for (label, field) in fields.iter() {
+                    self.visit_field(label, field);
+                }
+            }
+            Expr::RecordUpdate {
+                symbol, updates, ..
+            } => {
+                if *symbol == self.symbol {
+                    // `{ record & field: value }` only has a region for the whole expression,
+                    // and the updated record is the first thing in it.
+                    let region = self.first_name_in(region);
+                    self.push(region, OccurrenceKind::Reference);
+                }
+
+                for (label, field) in updates.iter() {
+                    self.visit_field(label, field);
+                }
+            }
+            _ => walk_expr(self, expr, var),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+        match pattern {
+            Pattern::Identifier(symbol)
+            | Pattern::AbilityMemberSpecialization { ident: symbol, .. }
+                if *symbol == self.symbol =>
+            {
+                let region = self.last_name_in(region);
+                self.push(region, OccurrenceKind::Definition);
+            }
+            Pattern::As(_, symbol) if *symbol == self.symbol => {
+                let region = self.last_name_in(region);
+                self.push(region, OccurrenceKind::Definition);
+
+                walk_pattern(self, pattern);
+            }
+            _ => walk_pattern(self, pattern),
+        }
+    }
+
+    fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+        if destruct.symbol == self.symbol {
+            match &destruct.typ {
+                DestructType::Required | DestructType::Optional(..) => {
+                    let region = self.first_name_in(region);
+                    self.push(region, OccurrenceKind::PunnedField(destruct.label.clone()));
+                }
+                DestructType::Guard(..) => {}
+            }
+        }
+
+        match &destruct.typ {
+            DestructType::Required => {}
+            DestructType::Optional(var, expr) => self.visit_expr(&expr.value, expr.region, *var),
+            DestructType::Guard(var, pat) => self.visit_pattern(&pat.value, pat.region, Some(*var)),
+        }
+    }
+}
+
+/// Finds the name an annotation is attached to, given the region of its signature.
+/// That is, `foo` in `foo : Str`.
+fn annotation_name_region(source: &str, signature: Region, name: &str) -> Option<Region> {
+    let before_signature = source.get(..signature.start().offset as usize)?;
+    let before_colon = before_signature.trim_end().strip_suffix(':')?.trim_end();
+
+    let name_start = before_colon.strip_suffix(name).map(str::len)?;
+    let is_whole_ident = before_colon[..name_start]
+        .chars()
+        .next_back()
+        .map_or(true, |c| !is_ident_char(c));
+
+    is_whole_ident.then(|| {
+        Region::new(
+            Position::new(name_start as u32),
+            Position::new((name_start + name.len()) as u32),
+        )
+    })
+}
+
+/// Finds the regions of `name` within `region` of `source`, where it appears as a whole identifier.
+pub(crate) fn find_ident<'a>(
+    source: &'a str,
+    region: Region,
+    name: &'a str,
+) -> impl Iterator<Item = Region> + 'a {
+    let start = region.start().offset as usize;
+    let end = region.end().offset as usize;
+    let text = source.get(start..end).unwrap_or_default();
+
+    text.match_indices(name)
+        .filter(move |(offset, _)| {
+            let before = text[..*offset].chars().next_back();
+            let after = text[offset + name.len()..].chars().next();
+
+            before.map_or(true, |c| !is_ident_char(c))
+                && after.map_or(true, |c| !is_ident_char(c) && c != '!')
+        })
+        .map(move |(offset, _)| {
+            let name_start = (start + offset) as u32;
+            Region::new(
+                Position::new(name_start),
+                Position::new(name_start + name.len() as u32),
+            )
+        })
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether `name` can be used as the new name of a value.
+pub(crate) fn is_valid_value_name(name: &str) -> bool {
+    let name = name.strip_suffix('!').unwrap_or(name);
+    let mut chars = name.chars();
+
+    chars.next().map_or(false, |c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
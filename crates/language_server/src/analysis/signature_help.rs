@@ -0,0 +1,201 @@
+use roc_can::{
+    expr::Expr,
+    traverse::{walk_expr, Visitor},
+};
+use roc_module::{called_via::CalledVia, symbol::Symbol};
+use roc_region::all::{Position, Region};
+use roc_types::subs::{Content, FlatType, Variable};
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureInformation,
+};
+
+use super::{utils::format_var_type, AnalyzedModule};
+
+/// A call of a function, or a function that is about to be called.
+struct FoundCall {
+    fn_var: Variable,
+    callee: Option<Symbol>,
+    args: Vec<Region>,
+}
+
+/// Finds the signature of the function being called at `position`.
+pub(super) fn signature_help(
+    source: &str,
+    module: &AnalyzedModule,
+    position: Position,
+) -> Option<SignatureHelp> {
+    let AnalyzedModule {
+        declarations,
+        subs,
+        module_id,
+        interns,
+        modules_info,
+        ..
+    } = module;
+
+    let mut finder = CallFinder {
+        source,
+        position,
+        call: None,
+        function: None,
+    };
+    finder.visit_decls(declarations);
+
+    let found = finder.call.or(finder.function)?;
+
+    let mut subs = subs.clone();
+    let is_function = matches!(
+        subs.get_content_without_compacting(found.fn_var),
+        Content::Structure(FlatType::Func(..))
+    );
+    if !is_function {
+        return None;
+    }
+
+    let type_str = format_var_type(found.fn_var, &mut subs, module_id, interns);
+    let name = match found.callee {
+        Some(symbol) => symbol.as_str(interns),
+        None => "\\",
+    };
+    let label = format!("{} : {}", name, type_str);
+    let parameters = parameter_offsets(&label, name.len() + " : ".len());
+
+    let active_parameter = found
+        .args
+        .iter()
+        .position(|arg| arg.start() <= position && position <= arg.end())
+        .unwrap_or_else(|| found.args.iter().filter(|arg| arg.end() < position).count())
+        as u32;
+
+    let documentation = found.callee.and_then(|symbol| {
+        let docs = modules_info
+            .get_docs(&symbol.module_id())?
+            .get_doc_for_symbol(&symbol)?;
+
+        Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: docs,
+        }))
+    });
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation,
+            parameters: Some(
+                parameters
+                    .into_iter()
+                    .map(|offsets| ParameterInformation {
+                        label: ParameterLabel::LabelOffsets(offsets),
+                        documentation: None,
+                    })
+                    .collect(),
+            ),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    })
+}
+
+/// Finds the offsets (in UTF-16 code units) of the arguments in a printed function type,
+/// which starts at `start` in `label`. E.g. `List a, (a -> b) -> List b` has two arguments.
+fn parameter_offsets(label: &str, start: usize) -> Vec<[u32; 2]> {
+    let utf16_offset = |offset: usize| label[..offset].encode_utf16().count() as u32;
+
+    let mut offsets = Vec::new();
+    let mut depth = 0;
+    let mut arg_start = start;
+
+    for (offset, c) in label
+        .char_indices()
+        .skip_while(|(offset, _)| *offset < start)
+    {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                offsets.push([utf16_offset(arg_start), utf16_offset(offset)]);
+                arg_start = offset + ", ".len();
+            }
+            '-' | '=' if depth == 0 && label[offset + 1..].starts_with('>') => {
+                // The arrow has a space on each side.
+                offsets.push([utf16_offset(arg_start), utf16_offset(offset - 1)]);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    offsets
+}
+
+struct CallFinder<'a> {
+    source: &'a str,
+    position: Position,
+    /// The innermost call that `position` is in, or right after.
+    call: Option<FoundCall>,
+    /// A function that `position` is right after, which hasn't been given arguments yet.
+    function: Option<FoundCall>,
+}
+
+impl CallFinder<'_> {
+    /// Whether `position` is within `region`, or after it with only spaces in between.
+    fn reaches_position(&self, region: Region) -> bool {
+        if region.start() > self.position {
+            return false;
+        }
+
+        let end = region.end().offset as usize;
+        let position = self.position.offset as usize;
+
+        end >= position
+            || self
+                .source
+                .get(end..position)
+                .map_or(false, |between| between.chars().all(|c| c == ' '))
+    }
+}
+
+impl Visitor for CallFinder<'_> {
+    fn should_visit(&mut self, region: Region) -> bool {
+        region.start() <= self.position
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if !self.should_visit(region) {
+            return;
+        }
+
+        match expr {
+            Expr::Call(function, args, CalledVia::Space) if self.reaches_position(region) => {
+                let (fn_var, loc_fn, ..) = &**function;
+                let callee = match loc_fn.value {
+                    Expr::Var(symbol, _)
+                    | Expr::ParamsVar { symbol, .. }
+                    | Expr::AbilityMember(symbol, _, _) => Some(symbol),
+                    _ => None,
+                };
+
+                self.call = Some(FoundCall {
+                    fn_var: *fn_var,
+                    callee,
+                    args: args.iter().map(|(_, arg)| arg.region).collect(),
+                });
+            }
+            Expr::Var(symbol, fn_var)
+                if region.end() < self.position && self.reaches_position(region) =>
+            {
+                self.function = Some(FoundCall {
+                    fn_var: *fn_var,
+                    callee: Some(*symbol),
+                    args: vec![],
+                });
+            }
+            _ => {}
+        }
+
+        walk_expr(self, expr, var);
+    }
+}
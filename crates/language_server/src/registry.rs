@@ -8,12 +8,15 @@ use std::{
 
 use tokio::sync::{Mutex, MutexGuard};
 
+use roc_module::symbol::Symbol;
 use tower_lsp::lsp_types::{
-    CompletionResponse, Diagnostic, GotoDefinitionResponse, Hover, Position, SemanticTokensResult,
-    TextEdit, Url,
+    CodeActionResponse, CompletionResponse, Diagnostic, DocumentSymbolResponse,
+    GotoDefinitionResponse, Hover, InlayHint, Location, Position, PrepareRenameResponse, Range,
+    SemanticTokensResult, SignatureHelp, SymbolInformation, TextDocumentContentChangeEvent,
+    TextEdit, Url, WorkspaceEdit,
 };
 
-use crate::analysis::{AnalyzedDocument, DocInfo};
+use crate::analysis::{AnalyzedDocument, DocInfo, GlobalSymbol, InlayHintsConfig};
 
 #[derive(Debug)]
 pub(crate) struct DocumentPair {
@@ -115,11 +118,72 @@ impl Registry {
         );
 
         for document in analysed_docs {
+            // The other documents were only loaded as dependencies. If one hasn't changed, the
+            // analysis we already have for it is just as good, and keeps its own version.
+            let unchanged = document.url() != &updating_url
+                && documents
+                    .get(document.url())
+                    .map_or(false, |pair| pair.info.source == document.doc_info.source);
+            if unchanged {
+                continue;
+            }
+
             let document = Arc::new(document);
             Registry::update_document(&mut documents, document, &updating_url);
         }
     }
 
+    /// Applies the changes of an incremental sync to the document at `url`, returning the
+    /// resulting document.
+    pub async fn apply_content_changes(
+        &self,
+        url: &Url,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: i32,
+    ) -> Option<DocInfo> {
+        let mut documents = self.documents.lock().await;
+        let pair = documents.get_mut(url)?;
+
+        let info = pair.info.with_changes(changes, version);
+        debug!(
+            "Set the docInfo for {:?} to version:{:?}",
+            url.as_str(),
+            info.version
+        );
+        *pair = DocumentPair {
+            info: info.clone(),
+            last_good_document: pair.last_good_document.clone(),
+            latest_document: OnceLock::new(),
+        };
+
+        Some(info)
+    }
+
+    /// Finds the documents that import the module at `url` and were checked against different
+    /// exposed types than it has now, so need checking again.
+    pub async fn stale_dependents(&self, url: &Url) -> Vec<Url> {
+        let Some(document) = self.latest_document_by_url(url).await else {
+            return vec![];
+        };
+        let Some(exposed_types) = document.exposed_types() else {
+            return vec![];
+        };
+
+        let documents = self.documents.lock().await;
+        let mut dependents: Vec<_> = documents
+            .iter()
+            .filter(|(_, pair)| {
+                pair.last_good_document
+                    .imported_exposed_types(url)
+                    .map_or(false, |imported| imported != exposed_types)
+            })
+            .map(|(url, _)| url.clone())
+            .collect();
+
+        dependents.sort();
+        dependents
+    }
+
     pub async fn apply_doc_info_changes(&self, url: Url, info: DocInfo) {
         let mut documents_lock = self.documents.lock().await;
         let doc = documents_lock.get_mut(&url);
@@ -140,7 +204,7 @@ impl Registry {
         }
     }
 
-    async fn document_info_by_url(&self, url: &Url) -> Option<DocInfo> {
+    pub async fn document_info_by_url(&self, url: &Url) -> Option<DocInfo> {
         self.documents.lock().await.get(url).map(|a| a.info.clone())
     }
 
@@ -187,6 +251,53 @@ impl Registry {
         def_document.definition(symbol)
     }
 
+    pub async fn code_actions(&self, url: &Url, range: Range) -> Option<CodeActionResponse> {
+        let document = self.latest_document_by_url(url).await?;
+        Some(document.code_actions(range))
+    }
+
+    pub async fn inlay_hints(
+        &self,
+        url: &Url,
+        range: Range,
+        config: InlayHintsConfig,
+    ) -> Option<Vec<InlayHint>> {
+        self.latest_document_by_url(url)
+            .await?
+            .inlay_hints(range, config)
+    }
+
+    pub async fn signature_help(&self, url: &Url, position: Position) -> Option<SignatureHelp> {
+        self.latest_document_by_url(url)
+            .await?
+            .signature_help(position)
+    }
+
+    pub async fn document_symbols(&self, url: &Url) -> Option<DocumentSymbolResponse> {
+        let document = self.document_info_by_url(url).await?;
+        document
+            .document_symbols()
+            .map(DocumentSymbolResponse::Nested)
+    }
+
+    /// Finds the symbols matching `query` in every module the registry knows about, other than
+    /// the builtins.
+    pub async fn workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        let documents = self.documents.lock().await;
+
+        let mut symbols: Vec<_> = documents
+            .values()
+            .filter(|pair| !pair.last_good_document.is_builtin())
+            .flat_map(|pair| pair.info.workspace_symbols(query))
+            .collect();
+
+        symbols.sort_by(|a, b| {
+            (a.location.uri.as_str(), a.location.range.start)
+                .cmp(&(b.location.uri.as_str(), b.location.range.start))
+        });
+        symbols
+    }
+
     pub async fn formatting(&self, url: &Url) -> Option<Vec<TextEdit>> {
         let document = self.document_info_by_url(url).await?;
         document.format()
@@ -217,4 +328,83 @@ impl Registry {
 
         Some(CompletionResponse::Array(completions))
     }
+
+    /// Finds every document `symbol` can be used in, along with what it is called in each of
+    /// them. Only exposed symbols can be used outside of the document that defines them.
+    async fn documents_using_symbol(
+        &self,
+        document: Arc<AnalyzedDocument>,
+        symbol: Symbol,
+    ) -> Option<(GlobalSymbol, Vec<(Arc<AnalyzedDocument>, Symbol)>)> {
+        let global = document.global_symbol(symbol)?;
+
+        if !global.exposed {
+            return Some((global, vec![(document, symbol)]));
+        }
+
+        let urls: Vec<Url> = self.documents.lock().await.keys().cloned().collect();
+        let mut documents = Vec::with_capacity(urls.len());
+
+        for url in urls {
+            let Some(document) = self.latest_document_by_url(&url).await else {
+                continue;
+            };
+
+            if let Some(symbol) = document.resolve_global_symbol(&global) {
+                documents.push((document, symbol));
+            }
+        }
+
+        Some((global, documents))
+    }
+
+    pub async fn references(
+        &self,
+        url: &Url,
+        position: Position,
+        include_declaration: bool,
+    ) -> Option<Vec<Location>> {
+        let document = self.latest_document_by_url(url).await?;
+        let symbol = document.symbol_at(position)?;
+        let (_, documents) = self.documents_using_symbol(document, symbol).await?;
+
+        let references = documents
+            .iter()
+            .flat_map(|(document, symbol)| document.references(*symbol, include_declaration))
+            .collect();
+
+        Some(references)
+    }
+
+    pub async fn prepare_rename(
+        &self,
+        url: &Url,
+        position: Position,
+    ) -> Option<PrepareRenameResponse> {
+        self.latest_document_by_url(url)
+            .await?
+            .prepare_rename(position)
+    }
+
+    pub async fn rename(
+        &self,
+        url: &Url,
+        position: Position,
+        new_name: &str,
+    ) -> Option<WorkspaceEdit> {
+        let document = self.latest_document_by_url(url).await?;
+        let symbol = document.symbol_at(position)?;
+        let (global, documents) = self.documents_using_symbol(document, symbol).await?;
+
+        let changes = documents
+            .iter()
+            .map(|(document, symbol)| {
+                let edits = document.rename_edits(*symbol, &global, new_name);
+                (document.url().clone(), edits)
+            })
+            .filter(|(_, edits)| !edits.is_empty())
+            .collect();
+
+        Some(WorkspaceEdit::new(changes))
+    }
 }
@@ -2,6 +2,7 @@ use analysis::HIGHLIGHT_TOKENS_LEGEND;
 
 use log::{debug, trace};
 use registry::{Registry, RegistryConfig};
+use roc_load::SolvedModuleCache;
 use std::future::Future;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::time::Duration;
@@ -10,7 +11,7 @@ use tower_lsp::jsonrpc::{self, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
-use crate::analysis::{global_analysis, DocInfo};
+use crate::analysis::{global_analysis, is_valid_value_name, DocInfo, InlayHintsConfig};
 
 mod analysis;
 mod convert;
@@ -23,12 +24,14 @@ struct RocServer {
 
 struct RocServerConfig {
     pub debounce_ms: Duration,
+    pub inlay_hints: InlayHintsConfig,
 }
 
 impl Default for RocServerConfig {
     fn default() -> Self {
         Self {
             debounce_ms: Duration::from_millis(100),
+            inlay_hints: InlayHintsConfig::default(),
         }
     }
 }
@@ -37,6 +40,8 @@ impl Default for RocServerConfig {
 struct RocServerState {
     registry: Registry,
     config: RocServerConfig,
+    /// The solved types of the modules analyzed so far, so unchanged ones aren't solved again
+    solved_cache: SolvedModuleCache,
 }
 
 impl std::panic::RefUnwindSafe for RocServer {}
@@ -47,6 +52,12 @@ fn read_env_num(name: &str) -> Option<u64> {
         .and_then(|a| str::parse::<u64>(&a).ok())
 }
 
+fn read_env_bool(name: &str) -> Option<bool> {
+    std::env::var(name)
+        .ok()
+        .and_then(|a| str::parse::<bool>(&a).ok())
+}
+
 impl RocServer {
     pub fn new(client: Client) -> Self {
         let registry_config = RegistryConfig {
@@ -56,6 +67,10 @@ impl RocServer {
         };
         let config = RocServerConfig {
             debounce_ms: Duration::from_millis(read_env_num("ROCLS_DEBOUNCE_MS").unwrap_or(100)),
+            inlay_hints: InlayHintsConfig {
+                parameters: read_env_bool("ROCLS_PARAMETER_HINTS").unwrap_or(true),
+                bindings: read_env_bool("ROCLS_BINDING_HINTS").unwrap_or(true),
+            },
         };
         Self {
             state: RocServerState::new(config, Registry::new(registry_config)),
@@ -64,14 +79,12 @@ impl RocServer {
     }
 
     pub fn capabilities() -> ServerCapabilities {
-        let text_document_sync = TextDocumentSyncCapability::Options(
-            // TODO: later on make this incremental
-            TextDocumentSyncOptions {
-                open_close: Some(true),
-                change: Some(TextDocumentSyncKind::FULL),
-                ..TextDocumentSyncOptions::default()
-            },
-        );
+        let text_document_sync = TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::INCREMENTAL),
+            save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+            ..TextDocumentSyncOptions::default()
+        });
         let hover_provider = HoverProviderCapability::Simple(true);
         let definition_provider = DefinitionOptions {
             work_done_progress_options: WorkDoneProgressOptions {
@@ -103,6 +116,34 @@ impl RocServer {
                 work_done_progress: None,
             },
         };
+        let references_provider = ReferencesOptions {
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
+        let rename_provider = RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
+        let code_action_provider = CodeActionOptions {
+            code_action_kinds: Some(vec![
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::REFACTOR_REWRITE,
+            ]),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+            resolve_provider: None,
+        };
+        let signature_help_provider = SignatureHelpOptions {
+            trigger_characters: Some(vec![" ".to_string()]),
+            retrigger_characters: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
         ServerCapabilities {
             text_document_sync: Some(text_document_sync),
             hover_provider: Some(hover_provider),
@@ -110,6 +151,13 @@ impl RocServer {
             document_formatting_provider: Some(OneOf::Right(document_formatting_provider)),
             semantic_tokens_provider: Some(semantic_tokens_provider),
             completion_provider: Some(completion_provider),
+            references_provider: Some(OneOf::Right(references_provider)),
+            rename_provider: Some(OneOf::Right(rename_provider)),
+            code_action_provider: Some(CodeActionProviderCapability::Options(code_action_provider)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            signature_help_provider: Some(signature_help_provider),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
             ..ServerCapabilities::default()
         }
     }
@@ -117,7 +165,46 @@ impl RocServer {
     /// Records a document content change.
     async fn change(&self, fi: Url, text: String, version: i32) {
         let updating_result = self.state.change(&fi, text, version).await;
+        self.publish_diagnostics(fi, version, updating_result).await;
+    }
 
+    /// Records the changes of an incremental sync.
+    async fn edit(&self, fi: Url, changes: Vec<TextDocumentContentChangeEvent>, version: i32) {
+        let updating_result = self.state.edit(&fi, changes, version).await;
+        self.publish_diagnostics(fi, version, updating_result).await;
+    }
+
+    /// Checks the documents that import `fi` again if the types it exposes changed, and then
+    /// their dependents in turn.
+    async fn recheck_dependents(&self, fi: Url) {
+        let mut stale = self.state.registry.stale_dependents(&fi).await;
+
+        while let Some(dependent) = stale.pop() {
+            let Some(version) = self.state.registry.get_latest_version(&dependent).await else {
+                continue;
+            };
+
+            let updating_result = self.state.recheck(&dependent).await;
+            let rechecked = updating_result.is_ok();
+            self.publish_diagnostics(dependent.clone(), version, updating_result)
+                .await;
+
+            if rechecked {
+                for url in self.state.registry.stale_dependents(&dependent).await {
+                    if !stale.contains(&url) {
+                        stale.push(url);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn publish_diagnostics(
+        &self,
+        fi: Url,
+        version: i32,
+        updating_result: std::result::Result<(), String>,
+    ) {
         //The analysis task can be cancelled by another change coming in which will update the watched variable
         if let Err(e) = updating_result {
             debug!("Cancelled change. Reason:{:?}", e);
@@ -136,7 +223,11 @@ impl RocServer {
 
 impl RocServerState {
     pub fn new(config: RocServerConfig, registry: Registry) -> RocServerState {
-        Self { config, registry }
+        Self {
+            config,
+            registry,
+            solved_cache: SolvedModuleCache::default(),
+        }
     }
 
     async fn close(&self, _fi: Url) {}
@@ -154,6 +245,38 @@ impl RocServerState {
             .apply_doc_info_changes(fi.clone(), doc_info.clone())
             .await;
 
+        self.analyze(fi, doc_info).await
+    }
+
+    pub async fn edit(
+        &self,
+        fi: &Url,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: i32,
+    ) -> std::result::Result<(), String> {
+        debug!("V{:?}:starting edit", version);
+        let doc_info = self
+            .registry
+            .apply_content_changes(fi, changes, version)
+            .await
+            .ok_or_else(|| format!("Got changes for unknown document {:?}", fi.as_str()))?;
+
+        self.analyze(fi, doc_info).await
+    }
+
+    /// Analyzes the latest version of a document again, without it having changed.
+    pub async fn recheck(&self, fi: &Url) -> std::result::Result<(), String> {
+        let doc_info = self
+            .registry
+            .document_info_by_url(fi)
+            .await
+            .ok_or_else(|| format!("Can't recheck unknown document {:?}", fi.as_str()))?;
+
+        self.analyze(fi, doc_info).await
+    }
+
+    async fn analyze(&self, fi: &Url, doc_info: DocInfo) -> std::result::Result<(), String> {
+        let version = doc_info.version;
         debug!(
             "V{:?}:finished updating docinfo, starting analysis ",
             version
@@ -173,9 +296,12 @@ impl RocServerState {
                 return Err("Not latest version skipping analysis".to_string());
             }
 
+            let solved_cache = inner_ref.solved_cache.clone();
             let results = match tokio::time::timeout(
                 Duration::from_secs(60),
-                tokio::task::spawn_blocking(|| catch_unwind(|| global_analysis(doc_info))),
+                tokio::task::spawn_blocking(move || {
+                    catch_unwind(AssertUnwindSafe(|| global_analysis(doc_info, solved_cache)))
+                }),
             )
             .await
             {
@@ -243,15 +369,15 @@ impl LanguageServer for RocServer {
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let VersionedTextDocumentIdentifier { uri, version, .. } = params.text_document;
 
-        // NOTE: We specify that we expect full-content syncs in the server capabilities,
-        // so here we assume the only change passed is a change of the entire document's content.
-        let TextDocumentContentChangeEvent { text, .. } = params
-            .content_changes
-            .into_iter()
-            .last()
-            .expect("textDocument change event had no changes ");
+        self.edit(uri, params.content_changes, version).await;
+    }
 
-        self.change(uri, text, version).await;
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        let TextDocumentIdentifier { uri } = params.text_document;
+
+        // Imported modules are loaded from disk, so the modules importing this one only see its
+        // changes once it's saved.
+        self.recheck_dependents(uri).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
@@ -308,6 +434,79 @@ impl LanguageServer for RocServer {
         unwind_async(self.state.registry.formatting(&text_document.uri)).await
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let CodeActionParams {
+            text_document,
+            range,
+            context: _,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.code_actions(&text_document.uri, range)).await
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let InlayHintParams {
+            text_document,
+            range,
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.inlay_hints(
+            &text_document.uri,
+            range,
+            self.state.config.inlay_hints,
+        ))
+        .await
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let SignatureHelpParams {
+            text_document_position_params:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            context: _,
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .signature_help(&text_document.uri, position),
+        )
+        .await
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let DocumentSymbolParams {
+            text_document,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.document_symbols(&text_document.uri)).await
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let WorkspaceSymbolParams {
+            query,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        let symbols = unwind_async(self.state.registry.workspace_symbols(&query)).await?;
+        Ok(Some(symbols))
+    }
+
     async fn semantic_tokens_full(
         &self,
         params: SemanticTokensParams,
@@ -332,6 +531,69 @@ impl LanguageServer for RocServer {
         )
         .await
     }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let ReferenceParams {
+            text_document_position:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            context,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.references(
+            &text_document.uri,
+            position,
+            context.include_declaration,
+        ))
+        .await
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let TextDocumentPositionParams {
+            text_document,
+            position,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .prepare_rename(&text_document.uri, position),
+        )
+        .await
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let RenameParams {
+            text_document_position:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            new_name,
+            work_done_progress_params: _,
+        } = params;
+
+        if !is_valid_value_name(&new_name) {
+            return Err(jsonrpc::Error::invalid_params(format!(
+                "`{}` is not a valid name for a value",
+                new_name
+            )));
+        }
+
+        unwind_async(
+            self.state
+                .registry
+                .rename(&text_document.uri, position, &new_name),
+        )
+        .await
+    }
 }
 
 async fn unwind_async<Fut, T>(future: Fut) -> tower_lsp::jsonrpc::Result<T>
@@ -612,4 +874,851 @@ mod tests {
         "#]]
         .assert_debug_eq(&actual);
     }
+
+    /// Applies the edits of a rename to `source`, so tests can show the renamed document.
+    fn apply_edits(source: &str, mut edits: Vec<TextEdit>) -> String {
+        let line_info = roc_region::all::LineInfo::new(source);
+        let offset = |position: Position| {
+            line_info
+                .convert_line_column(roc_region::all::LineColumn {
+                    line: position.line,
+                    column: position.character,
+                })
+                .offset as usize
+        };
+
+        edits.sort_by_key(|edit| std::cmp::Reverse(offset(edit.range.start)));
+
+        let mut source = source.to_string();
+        for edit in edits {
+            source.replace_range(
+                offset(edit.range.start)..offset(edit.range.end),
+                &edit.new_text,
+            );
+        }
+        source
+    }
+
+    async fn rename_test(doc: &str, position: Position, new_name: &str) -> String {
+        let (inner, url) = test_setup(doc.to_string()).await;
+
+        let edit = inner
+            .registry
+            .rename(&url, position, new_name)
+            .await
+            .expect("rename failed");
+        let mut changes = edit.changes.unwrap();
+
+        apply_edits(doc, changes.remove(&url).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_references() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r"
+            helper = \num -> num + 1
+
+            main =
+              answer = helper 1
+              helper answer
+            "};
+
+        let (inner, url) = test_setup(doc).await;
+        let references = |include_declaration| {
+            inner
+                .registry
+                .references(&url, Position::new(3, 0), include_declaration)
+        };
+
+        let ranges = |locations: Option<Vec<Location>>| {
+            locations
+                .unwrap()
+                .into_iter()
+                .map(|location| {
+                    let Range { start, end } = location.range;
+                    format!(
+                        "{}:{}-{}:{}",
+                        start.line, start.character, end.line, end.character
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let actual = [
+            ranges(references(true).await),
+            ranges(references(false).await),
+        ];
+
+        expect![[r#"
+            [
+                [
+                    "3:0-3:6",
+                    "6:11-6:17",
+                    "7:2-7:8",
+                ],
+                [
+                    "6:11-6:17",
+                    "7:2-7:8",
+                ],
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_local() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r"
+            main =
+              count : U64
+              count = 1
+              { count, total: count + 1 }
+            "};
+
+        let actual = rename_test(&doc, Position::new(5, 2), "amount").await;
+
+        expect![[r#"
+            interface Test
+              exposes []
+              imports []
+            main =
+              amount : U64
+              amount = 1
+              { count: amount, total: amount + 1 }
+        "#]]
+        .assert_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_destructured_field() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r"
+            area = \{ width, height } -> width * height
+            "};
+
+        let actual = rename_test(&doc, Position::new(3, 30), "w").await;
+
+        expect![[r#"
+            interface Test
+              exposes []
+              imports []
+            area = \{ width: w, height } -> w * height
+        "#]]
+        .assert_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_rename() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r#"
+            main =
+              value = Str.concat "a" "b"
+              value
+            "#};
+
+        let (inner, url) = test_setup(doc).await;
+        let registry = &inner.registry;
+
+        let local = registry.prepare_rename(&url, Position::new(5, 3)).await;
+        let builtin = registry.prepare_rename(&url, Position::new(4, 14)).await;
+
+        expect![[r#"
+            [
+                Some(
+                    RangeWithPlaceholder {
+                        range: Range {
+                            start: Position {
+                                line: 5,
+                                character: 2,
+                            },
+                            end: Position {
+                                line: 5,
+                                character: 7,
+                            },
+                        },
+                        placeholder: "value",
+                    },
+                ),
+                None,
+            ]
+        "#]]
+        .assert_debug_eq(&[local, builtin]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_across_modules() {
+        let dir = std::env::temp_dir().join(format!("roc_ls_rename_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let other = indoc! {r#"
+            module [greeting]
+
+            greeting : Str
+            greeting = "hello"
+            "#};
+        std::fs::write(dir.join("Other.roc"), other).unwrap();
+
+        let main = indoc! {r"
+            module [main]
+
+            import Other exposing [greeting]
+
+            main = Str.concat greeting Other.greeting
+            "};
+        let main_url = Url::from_file_path(dir.join("Main.roc")).unwrap();
+        let other_url = Url::from_file_path(dir.join("Other.roc")).unwrap();
+
+        let inner = RocServerState::new(RocServerConfig::default(), Registry::default());
+        inner.change(&main_url, main.to_string(), 0).await.unwrap();
+
+        let edit = inner
+            .registry
+            .rename(&main_url, Position::new(4, 19), "salutation")
+            .await
+            .expect("rename failed");
+        let mut changes = edit.changes.unwrap();
+
+        let actual = [
+            apply_edits(main, changes.remove(&main_url).unwrap_or_default()),
+            apply_edits(other, changes.remove(&other_url).unwrap_or_default()),
+        ];
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        expect![[r#"
+            [
+                "module [main]\n\nimport Other exposing [salutation]\n\nmain = Str.concat salutation Other.salutation\n",
+                "module [salutation]\n\nsalutation : Str\nsalutation = \"hello\"\n",
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    /// Applies each code action to `doc` on its own, so tests can show what they do.
+    fn apply_code_actions(
+        doc: &str,
+        url: &Url,
+        actions: CodeActionResponse,
+    ) -> Vec<(String, String)> {
+        actions
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title,
+                    edit: Some(edit),
+                    ..
+                }) => {
+                    let mut changes = edit.changes.unwrap();
+                    (title, apply_edits(doc, changes.remove(url).unwrap()))
+                }
+                other => panic!("unexpected code action {:?}", other),
+            })
+            .collect()
+    }
+
+    async fn code_action_test(doc: &str, range: Range) -> Vec<(String, String)> {
+        let (inner, url) = test_setup(doc.to_string()).await;
+
+        let actions = inner
+            .registry
+            .code_actions(&url, range)
+            .await
+            .expect("code actions failed");
+
+        apply_code_actions(doc, &url, actions)
+    }
+
+    fn whole_document(doc: &str) -> Range {
+        Range::new(
+            Position::new(0, 0),
+            Position::new(doc.lines().count() as u32, 0),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_code_action_missing_branches() {
+        let doc = indoc! {r"
+            module [main]
+
+            Color : [Red, Green, Blue]
+
+            main : Color -> U64
+            main = \color ->
+                when color is
+                    Red -> 1
+            "};
+
+        let actual = code_action_test(doc, whole_document(doc)).await;
+
+        expect![[r#"
+            [
+                (
+                    "Add missing branches",
+                    "module [main]\n\nColor : [Red, Green, Blue]\n\nmain : Color -> U64\nmain = \\color ->\n    when color is\n        Red -> 1\n        Blue -> crash \"TODO\"\n        Green -> crash \"TODO\"\n",
+                ),
+            ]
+        "#]].assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_code_action_unused_def() {
+        let doc = indoc! {r"
+            module [main]
+
+            main = 1
+
+            helper : U64 -> U64
+            helper = \x -> x + 1
+
+            other = 2
+            "};
+
+        let actual = code_action_test(doc, whole_document(doc)).await;
+
+        expect![[r#"
+            [
+                (
+                    "Remove unused definition `helper`",
+                    "module [main]\n\nmain = 1\n\nother = 2\n",
+                ),
+                (
+                    "Remove unused definition `other`",
+                    "module [main]\n\nmain = 1\n\nhelper : U64 -> U64\nhelper = \\x -> x + 1\n",
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_code_action_annotation() {
+        let doc = indoc! {r#"
+            module [main]
+
+            main = \name -> Str.concat "Hello, " name
+            "#};
+
+        let actual =
+            code_action_test(doc, Range::new(Position::new(2, 1), Position::new(2, 1))).await;
+
+        expect![[r#"
+            [
+                (
+                    "Add type annotation to `main`",
+                    "module [main]\n\nmain : Str -> Str\nmain = \\name -> Str.concat \"Hello, \" name\n",
+                ),
+            ]
+        "#]].assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_code_action_imports() {
+        let dir = std::env::temp_dir().join(format!("roc_ls_imports_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(
+            dir.join("Other.roc"),
+            "module [greeting, farewell]\n\ngreeting = \"hello\"\nfarewell = \"bye\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("Unused.roc"),
+            "module [value]\n\nimport Third\n\nvalue = Third.value\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("Third.roc"), "module [value]\n\nvalue = \"3\"\n").unwrap();
+
+        let main = indoc! {r"
+            module [main]
+
+            import Other exposing [greeting, farewell]
+            import Unused
+
+            main = Str.concat greeting Third.value
+            "};
+        let url = Url::from_file_path(dir.join("Main.roc")).unwrap();
+
+        let inner = RocServerState::new(RocServerConfig::default(), Registry::default());
+        inner.change(&url, main.to_string(), 0).await.unwrap();
+
+        let actions = inner
+            .registry
+            .code_actions(&url, whole_document(main))
+            .await
+            .expect("code actions failed");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let actual = apply_code_actions(main, &url, actions);
+
+        expect![[r#"
+            [
+                (
+                    "Import `Third`",
+                    "module [main]\n\nimport Other exposing [greeting, farewell]\nimport Unused\nimport Third\n\nmain = Str.concat greeting Third.value\n",
+                ),
+                (
+                    "Remove unused import `farewell`",
+                    "module [main]\n\nimport Other exposing [greeting]\nimport Unused\n\nmain = Str.concat greeting Third.value\n",
+                ),
+                (
+                    "Remove unused import of `Unused`",
+                    "module [main]\n\nimport Other exposing [greeting, farewell]\n\nmain = Str.concat greeting Third.value\n",
+                ),
+            ]
+        "#]].assert_debug_eq(&actual);
+    }
+
+    async fn inlay_hints_test(doc: &str, config: InlayHintsConfig) -> Vec<String> {
+        let (inner, url) = test_setup(doc.to_string()).await;
+
+        let hints = inner
+            .registry
+            .inlay_hints(&url, whole_document(doc), config)
+            .await
+            .expect("inlay hints failed");
+
+        hints
+            .into_iter()
+            .map(|hint| {
+                let InlayHintLabel::String(label) = hint.label else {
+                    panic!("unexpected label {:?}", hint.label);
+                };
+                format!(
+                    "{}:{} {}",
+                    hint.position.line, hint.position.character, label
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hints() {
+        let doc = indoc! {r#"
+            module [main, greet]
+
+            greet = \name -> Str.concat "Hello, " name
+
+            main =
+                double = \n -> n * 2
+                total : U64
+                total = double 21
+                word <- List.map [total]
+                Num.toStr word
+            "#};
+
+        let all = inlay_hints_test(doc, InlayHintsConfig::default()).await;
+        let parameters = inlay_hints_test(
+            doc,
+            InlayHintsConfig {
+                parameters: true,
+                bindings: false,
+            },
+        )
+        .await;
+        let bindings = inlay_hints_test(
+            doc,
+            InlayHintsConfig {
+                parameters: false,
+                bindings: true,
+            },
+        )
+        .await;
+
+        expect![[r#"
+            [
+                [
+                    "2:5 : Str -> Str",
+                    "2:13 : Str",
+                    "4:4 : List Str",
+                    "5:10 : Num a -> Num a",
+                    "5:15 : Num *",
+                    "8:8 : U64",
+                ],
+                [
+                    "2:13 : Str",
+                    "5:15 : Num *",
+                ],
+                [
+                    "2:5 : Str -> Str",
+                    "4:4 : List Str",
+                    "5:10 : Num a -> Num a",
+                    "8:8 : U64",
+                ],
+            ]
+        "#]]
+        .assert_debug_eq(&[all, parameters, bindings]);
+    }
+
+    async fn signature_help_test(doc: &str, position: Position) -> Option<String> {
+        let (inner, url) = test_setup(doc.to_string()).await;
+
+        let help = inner.registry.signature_help(&url, position).await?;
+        let signature = &help.signatures[0];
+
+        let parameters: Vec<_> = signature
+            .parameters
+            .iter()
+            .flatten()
+            .map(|parameter| match parameter.label {
+                ParameterLabel::LabelOffsets([start, end]) => {
+                    signature.label[start as usize..end as usize].to_string()
+                }
+                ParameterLabel::Simple(ref label) => label.clone(),
+            })
+            .collect();
+        let docs = match &signature.documentation {
+            Some(Documentation::MarkupContent(content)) => content.value.trim(),
+            _ => "",
+        };
+
+        Some(format!(
+            "{} {:?} active: {:?} docs: {:?}",
+            signature.label,
+            parameters,
+            help.active_parameter.unwrap(),
+            docs
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_signature_help() {
+        let doc = indoc! {r#"
+            module [main]
+
+            ## Joins a name and a greeting.
+            greet : Str, Str -> Str
+            greet = \greeting, name -> "$(greeting), $(name)"
+
+            main =
+                a = greet "Hello" "World"
+                b = List.map [1, 2] Num.toStr
+                Str.concat a (Str.joinWith b ", ")
+            "#};
+
+        let positions = [
+            // In the first argument.
+            Position::new(7, 15),
+            // Right after the first argument.
+            Position::new(7, 22),
+            // In the second argument.
+            Position::new(7, 25),
+            // At the start of the first argument.
+            Position::new(8, 17),
+            // In a call nested in an argument.
+            Position::new(9, 32),
+            // Not in a call.
+            Position::new(6, 6),
+        ];
+
+        let mut actual = Vec::new();
+        for position in positions {
+            actual.push(signature_help_test(doc, position).await);
+        }
+
+        expect![[r#"
+            [
+                Some(
+                    "greet : Str, Str -> Str [\"Str\", \"Str\"] active: 0 docs: \"Joins a name and a greeting.\"",
+                ),
+                Some(
+                    "greet : Str, Str -> Str [\"Str\", \"Str\"] active: 1 docs: \"Joins a name and a greeting.\"",
+                ),
+                Some(
+                    "greet : Str, Str -> Str [\"Str\", \"Str\"] active: 1 docs: \"Joins a name and a greeting.\"",
+                ),
+                Some(
+                    "map : List (Num a), (Num a -> Str) -> List Str [\"List (Num a)\", \"(Num a -> Str)\"] active: 0 docs: \"Convert each element in the list to something new, by calling a conversion\\nfunction on each of them. Then return a new list of the converted values.\\n```roc\\nexpect List.map [1, 2, 3] (\\\\num -> num + 1) == [2, 3, 4]\\n\\nexpect List.map [\\\"\\\", \\\"a\\\", \\\"bc\\\"] Str.isEmpty == [Bool.true, Bool.false, Bool.false]\\n```\"",
+                ),
+                Some(
+                    "joinWith : List Str, Str -> Str [\"List Str\", \"Str\"] active: 0 docs: \"Combines a [List] of strings into a single string, with a separator\\nstring in between each.\\n```roc\\nexpect Str.joinWith [\\\"one\\\", \\\"two\\\", \\\"three\\\"] \\\", \\\" == \\\"one, two, three\\\"\\nexpect Str.joinWith [\\\"1\\\", \\\"2\\\", \\\"3\\\", \\\"4\\\"] \\\".\\\" == \\\"1.2.3.4\\\"\\n```\"",
+                ),
+                None,
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_document_symbols() {
+        let doc = indoc! {r#"
+            module [main, Person, Shape, Describe]
+
+            Person : { name : Str, age : U64 }
+
+            Shape := [Circle F64, Square F64]
+
+            Describe implements
+                describe : a -> Str where a implements Describe
+
+            version = "1.0"
+
+            main : Str -> Str
+            main = \name -> Str.concat version name
+            "#};
+        let (inner, url) = test_setup(doc.to_string()).await;
+
+        let Some(DocumentSymbolResponse::Nested(symbols)) =
+            inner.registry.document_symbols(&url).await
+        else {
+            panic!("expected nested document symbols");
+        };
+
+        fn describe(symbol: &DocumentSymbol, depth: usize, lines: &mut Vec<String>) {
+            lines.push(format!(
+                "{}{} {:?} {:?} {}:{}-{}:{}",
+                "  ".repeat(depth),
+                symbol.name,
+                symbol.kind,
+                symbol.detail,
+                symbol.range.start.line,
+                symbol.range.start.character,
+                symbol.range.end.line,
+                symbol.range.end.character,
+            ));
+            for child in symbol.children.iter().flatten() {
+                describe(child, depth + 1, lines);
+            }
+        }
+
+        let mut actual = Vec::new();
+        for symbol in &symbols {
+            describe(symbol, 0, &mut actual);
+        }
+
+        expect![[r#"
+            [
+                "Person Struct None 2:0-2:34",
+                "Shape Class None 4:0-4:33",
+                "Describe Interface None 6:0-7:51",
+                "  describe Method Some(\"a -> Str where a implements Describe\") 7:4-7:51",
+                "version Constant None 9:0-9:15",
+                "main Function Some(\"Str -> Str\") 11:0-12:39",
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_workspace_symbols() {
+        let dir = std::env::temp_dir().join(format!("roc_ls_symbols_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let other = indoc! {r#"
+            module [greeting, Greeting]
+
+            Greeting := Str
+
+            greeting : Str
+            greeting = "hello"
+            "#};
+        std::fs::write(dir.join("Other.roc"), other).unwrap();
+
+        let main = indoc! {r#"
+            module [main]
+
+            import Other exposing [greeting]
+
+            main = greetAll [greeting]
+
+            greetAll = \names -> Str.joinWith names ", "
+
+            Greeter implements
+                greetWith : a -> Str where a implements Greeter
+            "#};
+        let main_url = Url::from_file_path(dir.join("Main.roc")).unwrap();
+
+        let inner = RocServerState::new(RocServerConfig::default(), Registry::default());
+        inner.change(&main_url, main.to_string(), 0).await.unwrap();
+
+        let mut actual = Vec::new();
+        for query in ["greet", "GRT", "main", "nothing"] {
+            let symbols = inner.registry.workspace_symbols(query).await;
+            let names: Vec<_> = symbols
+                .iter()
+                .map(|symbol| {
+                    let file = symbol.location.uri.path_segments().unwrap().last().unwrap();
+                    format!(
+                        "{} {:?} {:?} {}:{}",
+                        symbol.name,
+                        symbol.kind,
+                        symbol.container_name,
+                        file,
+                        symbol.location.range.start.line
+                    )
+                })
+                .collect();
+            actual.push((query, names));
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        expect![[r#"
+            [
+                (
+                    "greet",
+                    [
+                        "greetAll Function None Main.roc:6",
+                        "Greeter Interface None Main.roc:8",
+                        "greetWith Method Some(\"Greeter\") Main.roc:9",
+                        "Greeting Class None Other.roc:2",
+                        "greeting Constant None Other.roc:4",
+                    ],
+                ),
+                (
+                    "GRT",
+                    [
+                        "greetAll Function None Main.roc:6",
+                        "Greeter Interface None Main.roc:8",
+                        "greetWith Method Some(\"Greeter\") Main.roc:9",
+                        "Greeting Class None Other.roc:2",
+                        "greeting Constant None Other.roc:4",
+                    ],
+                ),
+                (
+                    "main",
+                    [
+                        "main Constant None Main.roc:4",
+                    ],
+                ),
+                (
+                    "nothing",
+                    [],
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_sync() {
+        let doc = indoc! {r#"
+            module [main]
+
+            main = "héllo"
+            "#};
+        let (inner, url) = test_setup(doc.to_string()).await;
+
+        let change = |range: Option<Range>, text: &str| TextDocumentContentChangeEvent {
+            range,
+            range_length: None,
+            text: text.to_string(),
+        };
+        let changes = vec![
+            // Columns count UTF-16 code units, so `é` is one column.
+            change(
+                Some(Range::new(Position::new(2, 9), Position::new(2, 13))),
+                "ey",
+            ),
+            change(
+                Some(Range::new(Position::new(2, 0), Position::new(2, 0))),
+                "## Greets\n",
+            ),
+            // Positions past the end of the document are clamped to it.
+            change(
+                Some(Range::new(Position::new(9, 0), Position::new(9, 5))),
+                "\nother = 1\n",
+            ),
+        ];
+        inner.edit(&url, changes, 1).await.unwrap();
+        let edited = inner.registry.document_info_by_url(&url).await.unwrap();
+
+        inner
+            .edit(&url, vec![change(None, "module []\n")], 2)
+            .await
+            .unwrap();
+        let replaced = inner.registry.document_info_by_url(&url).await.unwrap();
+
+        expect![[r#"
+            [
+                (
+                    1,
+                    "module [main]\n\n## Greets\nmain = \"hey\"\n\nother = 1\n",
+                ),
+                (
+                    2,
+                    "module []\n",
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&[
+            (edited.version, edited.source),
+            (replaced.version, replaced.source),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_recheck_dependents() {
+        let dir = std::env::temp_dir().join(format!("roc_ls_recheck_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let other = indoc! {r#"
+            module [greeting]
+
+            greeting = "hello"
+            "#};
+        std::fs::write(dir.join("Other.roc"), other).unwrap();
+
+        let main = indoc! {r#"
+            module [main]
+
+            import Other exposing [greeting]
+
+            main = Str.concat greeting "!"
+            "#};
+        let main_url = Url::from_file_path(dir.join("Main.roc")).unwrap();
+        let other_url = Url::from_file_path(dir.join("Other.roc")).unwrap();
+
+        let inner = RocServerState::new(RocServerConfig::default(), Registry::default());
+        inner.change(&main_url, main.to_string(), 0).await.unwrap();
+        inner
+            .change(&other_url, other.to_string(), 0)
+            .await
+            .unwrap();
+
+        // Changing a definition without changing its type doesn't affect `Main`.
+        let other = other.replace("hello", "hi");
+        std::fs::write(dir.join("Other.roc"), &other).unwrap();
+        inner.change(&other_url, other.clone(), 1).await.unwrap();
+        let unchanged_types = inner.registry.stale_dependents(&other_url).await;
+
+        let other = other.replace("\"hi\"", "42");
+        std::fs::write(dir.join("Other.roc"), &other).unwrap();
+        inner.change(&other_url, other.clone(), 2).await.unwrap();
+        let changed_types = inner.registry.stale_dependents(&other_url).await;
+
+        inner.recheck(&main_url).await.unwrap();
+        let diagnostics: Vec<_> = inner
+            .registry
+            .diagnostics(&main_url)
+            .await
+            .into_iter()
+            .map(|diagnostic| diagnostic.message.lines().next().unwrap_or("").to_string())
+            .collect();
+        let after_recheck = inner.registry.stale_dependents(&other_url).await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let file_name = |url: &Url| url.path_segments().unwrap().last().unwrap().to_string();
+        let names = |urls: Vec<Url>| urls.iter().map(file_name).collect::<Vec<_>>();
+
+        expect![[r#"
+            (
+                [],
+                [
+                    "Main.roc",
+                ],
+                [
+                    "TYPE MISMATCH",
+                ],
+                [],
+            )
+        "#]]
+        .assert_debug_eq(&(
+            names(unchanged_types),
+            names(changed_types),
+            diagnostics,
+            names(after_recheck),
+        ));
+    }
 }
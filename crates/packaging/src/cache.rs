@@ -1,6 +1,8 @@
 #[cfg(not(target_family = "wasm"))]
 use {
     crate::https::{self, PackageMetadata, Problem},
+    crate::inventory,
+    crate::manifest::{self, manifest_path, tarball_path, PackageManifest, Verification},
     roc_error_macros::internal_error,
     std::fs,
 };
@@ -13,6 +15,12 @@ use std::path::{Path, PathBuf};
 pub enum RocCacheDir<'a> {
     /// Normal scenario: reading from the user's cache dir on disk
     Persistent(&'a Path),
+    /// Reading from a project's vendor dir (see `roc vendor`), or else from the user's cache dir.
+    /// Nothing gets downloaded; a package that's in neither is an error.
+    Offline {
+        vendor_dir: &'a Path,
+        cache_dir: &'a Path,
+    },
     /// For build.rs and tests where we never want to be downloading anything - yell loudly if we try!
     Disallowed,
     /// For tests only; we don't want to write to the real cache during a test!
@@ -24,11 +32,26 @@ impl RocCacheDir<'_> {
     pub fn as_persistent_path(&self) -> Option<&Path> {
         match self {
             RocCacheDir::Persistent(path) => Some(path),
+            RocCacheDir::Offline { cache_dir, .. } => Some(cache_dir),
             RocCacheDir::Disallowed => None,
             #[cfg(test)]
             RocCacheDir::Temp(_) => None,
         }
     }
+
+    /// Only set when offline; packages in here take precedence over the cache.
+    pub fn vendor_dir(&self) -> Option<&Path> {
+        match self {
+            RocCacheDir::Offline { vendor_dir, .. } => Some(vendor_dir),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a package name in a module header is a URL (https://, or file:// for a local tarball),
+/// as opposed to a path to the package's root module on disk.
+pub fn is_package_url(package_name: &str) -> bool {
+    package_name.starts_with("https://") || package_name.starts_with("file://")
 }
 
 // Errors in case NixOS users try to use a dynamically linked platform
@@ -95,10 +118,21 @@ pub fn install_package<'a>(
             // e.g. ~/.cache/roc/example.com/roc-packages/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE
             let dest_dir = parent_dir.join(content_hash);
 
+            if dest_dir.exists() && manifest::verify_on_use() {
+                if let Verification::Corrupted(problem) = manifest::verify_package(&dest_dir) {
+                    eprintln!(
+                        "The cached copy of {url} doesn't match its hash ({problem}), so I'll download it again.\n"
+                    );
+                    inventory::remove_package(&dest_dir).map_err(Problem::IoErr)?;
+                }
+            }
+
             if dest_dir.exists() {
                 // If the cache dir exists already, we assume it has the correct contents
                 // (it's a cache, after all!) and return without downloading anything.
-                //
+                // ROC_VERIFY_PACKAGES checks that assumption, above.
+                manifest::mark_used(&dest_dir);
+
                 #[cfg(target_os = "linux")]
                 {
                     nixos_error_if_dynamic(url, &dest_dir);
@@ -113,21 +147,29 @@ pub fn install_package<'a>(
                 );
                 let tempdir = tempfile::tempdir().map_err(Problem::IoErr)?;
                 let tempdir_path = tempdir.path();
-                let downloaded_hash =
-                    https::download_and_hash(url, tempdir_path, MAX_DOWNLOAD_BYTES)?;
+
+                // Create the destination dir's parent dir, since it may not exist yet.
+                // The tarball is kept there, so `roc cache verify` can rehash it later.
+                fs::create_dir_all(&parent_dir).or_else(|err| match err.kind() {
+                    // It's fine if the destination dir's parent already exists
+                    ErrorKind::AlreadyExists => Ok(()),
+                    _ => Err(Problem::IoErr(err)),
+                })?;
+
+                let mut tarball =
+                    tempfile::NamedTempFile::new_in(&parent_dir).map_err(Problem::IoErr)?;
+                let (downloaded_hash, encoding) = https::download_and_hash(
+                    url,
+                    tempdir_path,
+                    tarball.as_file_mut(),
+                    MAX_DOWNLOAD_BYTES,
+                )?;
 
                 // Download the tarball into memory and verify it.
                 // The tarball name is the hash of its contents.
                 if downloaded_hash == content_hash {
                     // Now that we've verified the hash, rename the tempdir to the real dir.
 
-                    // Create the destination dir's parent dir, since it may not exist yet.
-                    fs::create_dir_all(parent_dir).or_else(|err| match err.kind() {
-                        // It's fine if the destination dir's parent already exists
-                        ErrorKind::AlreadyExists => Ok(()),
-                        _ => Err(Problem::IoErr(err)),
-                    })?;
-
                     // This rename should be super cheap if it succeeds - just an inode change.
                     let rename_err_kind = fs::rename(tempdir_path, &dest_dir)
                         .err()
@@ -170,6 +212,18 @@ pub fn install_package<'a>(
                         })?;
                     }
 
+                    tarball
+                        .persist(tarball_path(&dest_dir))
+                        .map_err(|err| Problem::IoErr(err.error))?;
+
+                    PackageManifest {
+                        content_hash: content_hash.to_string(),
+                        url: url.to_string(),
+                        encoding: encoding.directive().to_string(),
+                    }
+                    .write(&manifest_path(&dest_dir))
+                    .map_err(Problem::IoErr)?;
+
                     #[cfg(target_os = "linux")]
                     {
                         nixos_error_if_dynamic(url, &dest_dir);
@@ -185,6 +239,47 @@ pub fn install_package<'a>(
                 }
             }
         }
+        RocCacheDir::Offline {
+            vendor_dir,
+            cache_dir,
+        } => {
+            // e.g. ./vendor/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE
+            let vendored_dir = vendor_dir.join(content_hash);
+            // e.g. ~/.cache/roc/example.com/roc-packages/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE
+            let cached_dir = cache_dir.join(cache_subdir).join(content_hash);
+
+            match [vendored_dir, cached_dir]
+                .into_iter()
+                .find(|package_dir| package_dir.exists())
+            {
+                Some(package_dir) => {
+                    // vendored packages have no manifest, so this only affects cached ones
+                    if manifest::verify_on_use() {
+                        if let Verification::Corrupted(problem) =
+                            manifest::verify_package(&package_dir)
+                        {
+                            return Err(Problem::CorruptedPackage {
+                                dir: package_dir,
+                                problem,
+                            });
+                        }
+                    }
+
+                    manifest::mark_used(&package_dir);
+
+                    #[cfg(target_os = "linux")]
+                    {
+                        nixos_error_if_dynamic(url, &package_dir);
+                    }
+
+                    Ok((package_dir, root_module_filename))
+                }
+                None => Err(Problem::NotAvailableOffline {
+                    vendor_dir: vendor_dir.to_path_buf(),
+                    cache_dir: cache_dir.to_path_buf(),
+                }),
+            }
+        }
         RocCacheDir::Disallowed => {
             internal_error!(
                 "Tried to download a package ({:?}) via RocCacheDir::Disallowed - which was explicitly used in order to disallow downloading packages in the current context!",
@@ -196,6 +291,50 @@ pub fn install_package<'a>(
     }
 }
 
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn install_package_from_file_url() {
+    use crate::tarball::{self, Compression};
+
+    let package_dir = tempfile::tempdir().unwrap();
+    let main_path = package_dir.path().join("main.roc");
+    fs::write(&main_path, "package [Foo] {}\n").unwrap();
+    fs::write(
+        package_dir.path().join("Foo.roc"),
+        "module [foo]\n\nfoo = 1\n",
+    )
+    .unwrap();
+
+    let tarball_name = tarball::build(&main_path, Compression::Brotli).unwrap();
+    let tarball_path = package_dir.path().join(tarball_name);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let roc_cache_dir = RocCacheDir::Persistent(cache_dir.path());
+
+    let url = format!("file://{}#main.roc", tarball_path.display());
+    let (dest_dir, root_module) = install_package(roc_cache_dir, &url).unwrap();
+
+    assert!(dest_dir.starts_with(cache_dir.path().join("file")));
+    assert!(dest_dir.join("Foo.roc").is_file());
+    assert_eq!(root_module, Some("main.roc"));
+    assert_eq!(manifest::verify_package(&dest_dir), Verification::Ok);
+
+    // The tarball is kept as it was downloaded, still compressed
+    assert_eq!(
+        fs::read(manifest::tarball_path(&dest_dir)).unwrap(),
+        fs::read(&tarball_path).unwrap()
+    );
+
+    // The hash still gets checked, even though the tarball is on this machine
+    let renamed_path = package_dir.path().join("notTheHash.tar.br");
+    fs::rename(&tarball_path, &renamed_path).unwrap();
+
+    let url = format!("file://{}", renamed_path.display());
+    assert!(matches!(
+        install_package(roc_cache_dir, &url),
+        Err(Problem::InvalidContentHash { .. })
+    ));
+}
+
 #[cfg(windows)]
 // e.g. the "Roc" in %APPDATA%\\Roc
 const ROC_CACHE_DIR_NAME: &str = "Roc";
@@ -1,6 +1,6 @@
 use std::{
     io::{self, Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use crate::tarball::Compression;
@@ -13,11 +13,22 @@ use crate::tarball::Compression;
 // let's try to avoid doing that.
 const BROTLI_BUFFER_BYTES: usize = 8 * 1_000_000; // MB
 
+/// Whitespace-separated `<prefix>=<replacement>` rules, e.g.
+/// `https://github.com/=https://artifacts.example.com/github/`. Before downloading
+/// a package from a URL that starts with <prefix>, we try the same URL with <prefix>
+/// replaced by <replacement> (which may also be a file:// URL).
+pub const MIRRORS_ENV_VAR: &str = "ROC_PACKAGE_MIRRORS";
+
+/// Local tarballs (like the ones `roc build --bundle` makes) don't come from a server,
+/// so they all share one subfolder in the cache dir.
+const FILE_URL_CACHE_SUBDIR: &str = "file";
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct PackageMetadata<'a> {
     /// The BLAKE3 hash of the tarball's contents. Also the .tar filename on disk.
     pub content_hash: &'a str,
-    /// On disk, this will be the subfolder inside the cache dir where the package lives
+    /// On disk, this will be the subfolder inside the cache dir where the package lives.
+    /// For file:// URLs, it's always "file".
     pub cache_subdir: &'a str,
     /// Other code will default this to main.roc, but this module isn't concerned with that default.
     pub root_module_filename: Option<&'a str>,
@@ -65,12 +76,15 @@ impl<'a> TryFrom<&'a str> for PackageMetadata<'a> {
 
 impl<'a> PackageMetadata<'a> {
     fn new(url: &'a str) -> Result<Self, UrlProblem> {
-        // First, verify that the URL starts with https://
-        let without_protocol = match url.split_once("https://") {
-            Some((_, without_protocol)) => without_protocol,
-            None => {
-                return Err(UrlProblem::MissingHttps);
-            }
+        // First, verify that the URL starts with https:// (or file://, for a local tarball)
+        let (without_protocol, is_file_url) = match url.split_once("https://") {
+            Some((_, without_protocol)) => (without_protocol, false),
+            None => match url.strip_prefix("file://") {
+                Some(without_protocol) => (without_protocol, true),
+                None => {
+                    return Err(UrlProblem::MissingHttps);
+                }
+            },
         };
 
         // Next, check if there are misleading characters in the URL
@@ -120,7 +134,11 @@ impl<'a> PackageMetadata<'a> {
         };
 
         Ok(PackageMetadata {
-            cache_subdir: path,
+            cache_subdir: if is_file_url {
+                FILE_URL_CACHE_SUBDIR
+            } else {
+                path
+            },
             content_hash: tarball_name,
             root_module_filename: fragment,
         })
@@ -226,6 +244,19 @@ fn url_with_fragment() {
     );
 }
 
+#[test]
+fn file_url() {
+    let expected = Ok(PackageMetadata {
+        cache_subdir: "file",
+        content_hash: "hash",
+        root_module_filename: Some("main.roc"),
+    });
+    assert_eq!(
+        PackageMetadata::try_from("file:///home/user/platform/hash.tar.br#main.roc"),
+        expected
+    );
+}
+
 #[derive(Debug)]
 pub enum Problem {
     UnsupportedEncoding(String),
@@ -241,13 +272,95 @@ pub enum Problem {
     /// The Content-Length header of the response exceeded max_download_bytes
     DownloadTooBig(u64),
     NotFound,
+    /// We may not download anything, and the package is neither vendored nor in the cache
+    NotAvailableOffline {
+        vendor_dir: PathBuf,
+        cache_dir: PathBuf,
+    },
+    /// The cached package's tarball doesn't match its hash, or its files don't match its tarball
+    CorruptedPackage {
+        dir: PathBuf,
+        problem: String,
+    },
 }
 
+/// Download the tarball at the given URL, unpack it into dest_dir, and write a copy of it, exactly
+/// as it was downloaded, to the given writer. Returns the base64url-encoded BLAKE3 hash of the
+/// decompressed tarball, and how that copy is compressed.
 pub fn download_and_hash(
     url: &str,
     dest_dir: &Path,
+    tarball: &mut impl Write,
     max_download_bytes: u64,
-) -> Result<String, Problem> {
+) -> Result<(String, Encoding), Problem> {
+    let mirror_rules = mirror_rules_from_env();
+    let mut mirror_urls = mirror_urls(url, &mirror_rules);
+
+    // Try the mirrors first, and only fall back on the original URL if none of them has the package.
+    // Falling back is only safe before anything was unpacked into dest_dir.
+    let download = loop {
+        match mirror_urls.next() {
+            Some(mirror_url) => match fetch(&mirror_url, max_download_bytes) {
+                Ok(download) => break download,
+                Err(problem) => {
+                    let reason = match problem {
+                        Problem::NotFound => "it was not found".to_string(),
+                        Problem::HttpErr(err) => err.to_string(),
+                        Problem::IoErr(err) => err.to_string(),
+                        other => format!("{other:?}"),
+                    };
+
+                    eprintln!("Could not download from the mirror {mirror_url} ({reason}), trying the next source.");
+                }
+            },
+            None => break fetch(url, max_download_bytes)?,
+        }
+    };
+
+    // Use .take to prevent a malicious server from sending back bytes
+    // until system resources are exhausted!
+    let mut reader = TeeReader {
+        reader: ProgressReporter::new(
+            download.reader.take(max_download_bytes),
+            download.content_length,
+        ),
+        copy: tarball,
+    };
+    let hash = decompress_into(dest_dir, download.encoding, &mut reader)?;
+
+    // The decompressor may not have needed everything after the end of the compressed data,
+    // but the copy should have it all.
+    io::copy(&mut reader, &mut io::sink()).map_err(Problem::IoErr)?;
+
+    Ok((hash, download.encoding))
+}
+
+/// A package tarball we're about to read, from a server or from disk
+struct Download {
+    reader: Box<dyn Read>,
+    content_length: Option<usize>,
+    encoding: Encoding,
+}
+
+/// Start reading the tarball at the given https:// or file:// URL, without reading its contents yet.
+fn fetch(url: &str, max_download_bytes: u64) -> Result<Download, Problem> {
+    if let Some(path) = url.strip_prefix("file://") {
+        // Drop the URL fragment (e.g. #main.roc); it's not part of the file's path
+        let path = path.rsplit_once('#').map_or(path, |(path, _)| path);
+        let file = std::fs::File::open(path).map_err(Problem::IoErr)?;
+        let content_len = file.metadata().map_err(Problem::IoErr)?.len();
+
+        if content_len > max_download_bytes {
+            return Err(Problem::DownloadTooBig(content_len));
+        }
+
+        return Ok(Download {
+            reader: Box::new(file),
+            content_length: Some(content_len as usize),
+            encoding: Encoding::new("", url)?,
+        });
+    }
+
     // TODO apparently it really improves performance to construct a Client once and then reuse it,
     // instead of making a new Client for every request.
     // Per https://github.com/seanmonstar/reqwest/issues/1454#issuecomment-1026076701
@@ -280,17 +393,75 @@ pub fn download_and_hash(
         Encoding::new(content_encoding, url)?
     };
 
-    let content_length = resp.content_length().map(|n| n as usize);
+    Ok(Download {
+        content_length: resp.content_length().map(|n| n as usize),
+        reader: Box::new(resp),
+        encoding,
+    })
+}
 
-    // Use .take to prevent a malicious server from sending back bytes
-    // until system resources are exhausted!
-    let resp = ProgressReporter::new(resp.take(max_download_bytes), content_length);
-    decompress_into(dest_dir, encoding, resp)
+/// One of the rules in ROC_PACKAGE_MIRRORS
+#[derive(Debug, PartialEq, Eq)]
+pub struct MirrorRule {
+    pub prefix: String,
+    pub replacement: String,
+}
+
+pub fn mirror_rules_from_env() -> Vec<MirrorRule> {
+    match std::env::var(MIRRORS_ENV_VAR) {
+        Ok(rules) => parse_mirror_rules(&rules),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn parse_mirror_rules(rules: &str) -> Vec<MirrorRule> {
+    rules
+        .split_whitespace()
+        .filter_map(|rule| match rule.split_once('=') {
+            Some((prefix, replacement)) if !prefix.is_empty() => Some(MirrorRule {
+                prefix: prefix.to_string(),
+                replacement: replacement.to_string(),
+            }),
+            _ => {
+                eprintln!("Ignoring `{rule}` in {MIRRORS_ENV_VAR}, because it isn't of the form <prefix>=<replacement>.");
+
+                None
+            }
+        })
+        .collect()
+}
+
+/// The mirror URLs to try for the given URL, in the order their rules were given
+fn mirror_urls<'r>(url: &'r str, rules: &'r [MirrorRule]) -> impl Iterator<Item = String> + 'r {
+    rules.iter().filter_map(move |rule| {
+        url.strip_prefix(rule.prefix.as_str())
+            .map(|rest| format!("{}{rest}", rule.replacement))
+    })
+}
+
+#[test]
+fn mirror_rules() {
+    let rules = parse_mirror_rules(
+        "https://github.com/=https://mirror.example.com/github/\n  https://github.com/roc-lang/=file:///mnt/roc/",
+    );
+    let url = "https://github.com/roc-lang/basic-cli/releases/download/0.1.0/hash.tar.br";
+
+    assert_eq!(
+        mirror_urls(url, &rules).collect::<Vec<_>>(),
+        [
+            "https://mirror.example.com/github/roc-lang/basic-cli/releases/download/0.1.0/hash.tar.br",
+            "file:///mnt/roc/basic-cli/releases/download/0.1.0/hash.tar.br",
+        ]
+    );
+    assert_eq!(
+        mirror_urls("https://example.com/hash.tar.br", &rules).count(),
+        0
+    );
 }
 
 /// The content encodings we support
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum Encoding {
+pub enum Encoding {
     Gzip,
     Brotli,
     Deflate,
@@ -306,6 +477,7 @@ impl Encoding {
             "br" => Ok(Brotli),
             "gzip" => Ok(Gzip),
             "deflate" => Ok(Deflate),
+            "identity" => Ok(Uncompressed),
             "" => {
                 // There was no Content-Encoding header, but we can infer the encoding
                 // from the file extension in the URL.
@@ -333,6 +505,16 @@ impl Encoding {
             }
         }
     }
+
+    /// The Content-Encoding directive for this encoding, which `new` turns back into it
+    pub fn directive(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Brotli => "br",
+            Encoding::Deflate => "deflate",
+            Encoding::Uncompressed => "identity",
+        }
+    }
 }
 
 #[test]
@@ -363,10 +545,10 @@ fn hash_and_unpack(dest_dir: &Path, reader: impl Read) -> Result<String, Problem
 }
 
 /// Read from the given reader, decompress the bytes using the given Content-Encoding string,
-/// write them to the given writer, and return the base64url-encoded BLAKE3 hash of what was written.
-/// This both writes and hashes incrementally as it reads, so the only extra work that's done
-/// at the end is base64url-encoding the final hash.
-fn decompress_into(
+/// unpack them into dest_dir, and return the base64url-encoded BLAKE3 hash of the decompressed
+/// bytes. This both unpacks and hashes incrementally as it reads, so the only extra work that's
+/// done at the end is base64url-encoding the final hash.
+pub(crate) fn decompress_into(
     dest_dir: &Path,
     encoding: Encoding,
     reader: impl Read,
@@ -385,6 +567,22 @@ fn decompress_into(
     }
 }
 
+/// Read something while writing a copy of what was read
+struct TeeReader<R: Read, W: Write> {
+    reader: R,
+    copy: W,
+}
+
+impl<R: Read, W: Write> Read for TeeReader<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes_read = self.reader.read(buf)?;
+
+        self.copy.write_all(&buf[0..bytes_read])?;
+
+        Ok(bytes_read)
+    }
+}
+
 /// Read something while calculating its BLAKE3 hash
 struct HashReader<R: Read> {
     reader: R,
@@ -0,0 +1,204 @@
+//! What's in the package cache, for `roc cache list` and `roc cache prune`.
+use crate::manifest::{manifest_path, tarball_path, PackageManifest, MANIFEST_EXT, TARBALL_EXT};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// BLAKE3 hashes are 32 bytes, which is 43 characters in base64url without padding
+const CONTENT_HASH_LEN: usize = 43;
+
+#[derive(Debug)]
+pub struct CachedPackage {
+    /// e.g. ~/.cache/roc/packages/example.com/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE
+    pub dir: PathBuf,
+    /// Only known from the manifest; packages cached before roc kept manifests don't have one
+    pub url: Option<String>,
+    pub size_bytes: u64,
+    /// When a build last used the package (or when it was cached, if it predates manifests)
+    pub last_used: Option<SystemTime>,
+}
+
+/// When `roc cache prune` removes packages. A package goes if any of these says so.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PrunePolicy {
+    /// Remove the packages that haven't been used for this long
+    pub unused_for: Option<Duration>,
+    /// Remove the least recently used packages until the rest add up to at most this many bytes
+    pub max_total_bytes: Option<u64>,
+}
+
+/// Every package in the cache, most recently used first
+pub fn cached_packages(cache_dir: &Path) -> io::Result<Vec<CachedPackage>> {
+    let mut packages = Vec::new();
+
+    if cache_dir.is_dir() {
+        find_packages(cache_dir, &mut packages)?;
+    }
+
+    packages.sort_by(|a, b| b.last_used.cmp(&a.last_used).then(a.dir.cmp(&b.dir)));
+
+    Ok(packages)
+}
+
+/// A package dir is one with a manifest next to it, or else one named after a content hash.
+/// Everything else is part of a URL's path, like example.com/roc-packages/
+fn find_packages(dir: &Path, packages: &mut Vec<CachedPackage>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if !path.is_dir() {
+            continue;
+        }
+
+        let manifest = manifest_path(&path);
+
+        if manifest.is_file() || looks_like_content_hash(&path) {
+            let last_used = fs::metadata(&manifest)
+                .or_else(|_| fs::metadata(&path))
+                .and_then(|metadata| metadata.modified())
+                .ok();
+
+            packages.push(CachedPackage {
+                url: PackageManifest::read(&manifest)
+                    .ok()
+                    .map(|manifest| manifest.url),
+                size_bytes: dir_size(&path)
+                    + fs::metadata(tarball_path(&path)).map_or(0, |metadata| metadata.len()),
+                last_used,
+                dir: path,
+            });
+        } else {
+            find_packages(&path, packages)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn looks_like_content_hash(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    name.len() == CONTENT_HASH_LEN
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// The packages the policy removes, given every cached package, most recently used first
+pub fn packages_to_prune(
+    packages: Vec<CachedPackage>,
+    policy: PrunePolicy,
+    now: SystemTime,
+) -> Vec<CachedPackage> {
+    let mut total_bytes = 0;
+
+    packages
+        .into_iter()
+        .filter(|package| {
+            let unused_too_long = match (policy.unused_for, package.last_used) {
+                (Some(unused_for), Some(last_used)) => now
+                    .duration_since(last_used)
+                    .is_ok_and(|unused| unused > unused_for),
+                _ => false,
+            };
+
+            if unused_too_long {
+                return true;
+            }
+
+            // the packages are in order, so the most recently used ones get to stay
+            total_bytes += package.size_bytes;
+
+            policy
+                .max_total_bytes
+                .is_some_and(|max_total_bytes| total_bytes > max_total_bytes)
+        })
+        .collect()
+}
+
+/// Remove a package and its manifest and tarball from the cache
+pub fn remove_package(package_dir: &Path) -> io::Result<()> {
+    fs::remove_dir_all(package_dir)?;
+
+    for path in [manifest_path(package_dir), tarball_path(package_dir)] {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Manifests and tarballs whose package dir is gone, e.g. because someone deleted it by hand
+pub fn orphaned_files(cache_dir: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(cache_dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == MANIFEST_EXT || ext == TARBALL_EXT)
+                && !path.with_extension("").is_dir()
+        })
+        .collect()
+}
+
+#[test]
+fn prune_by_age_and_size() {
+    let now = SystemTime::now();
+    let days_ago = |days: u64| Some(now - Duration::from_secs(days * 24 * 60 * 60));
+    let package = |name: &str, size_bytes, last_used| CachedPackage {
+        dir: PathBuf::from(name),
+        url: None,
+        size_bytes,
+        last_used,
+    };
+    let pruned_names = |policy| {
+        let packages = vec![
+            package("a", 10, days_ago(1)),
+            package("b", 20, days_ago(5)),
+            package("c", 30, days_ago(40)),
+            package("d", 40, None),
+        ];
+
+        packages_to_prune(packages, policy, now)
+            .into_iter()
+            .map(|package| package.dir.display().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        pruned_names(PrunePolicy {
+            unused_for: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            max_total_bytes: None,
+        }),
+        ["c"]
+    );
+    assert_eq!(
+        pruned_names(PrunePolicy {
+            unused_for: None,
+            max_total_bytes: Some(35),
+        }),
+        ["c", "d"]
+    );
+    assert_eq!(
+        pruned_names(PrunePolicy {
+            unused_for: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            max_total_bytes: Some(65),
+        }),
+        ["c", "d"]
+    );
+    assert!(pruned_names(PrunePolicy::default()).is_empty());
+}
@@ -1,4 +1,10 @@
 pub mod cache;
 #[cfg(not(target_family = "wasm"))]
 pub mod https;
+#[cfg(not(target_family = "wasm"))]
+pub mod inventory;
+#[cfg(not(target_family = "wasm"))]
+pub mod manifest;
 pub mod tarball;
+#[cfg(not(target_family = "wasm"))]
+pub mod vendor;
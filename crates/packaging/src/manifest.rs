@@ -0,0 +1,383 @@
+//! Next to each package dir in the cache, we keep the tarball it came in, exactly as it was
+//! downloaded, and a manifest saying where it came from and how the tarball is compressed,
+//! e.g. `<hash>.tarball` and `<hash>.manifest` next to `<hash>/`. The decompressed tarball's
+//! BLAKE3 hash is the dir's name, so `roc cache verify` can rehash it while decompressing it,
+//! and then compare what's in it to the dir's files.
+//!
+//! The manifest's modification time doubles as the time the package was last used.
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::https::{self, Encoding, Problem};
+
+pub const MANIFEST_EXT: &str = "manifest";
+pub const TARBALL_EXT: &str = "tarball";
+
+/// The first line of every manifest, so we can change the format later
+const MANIFEST_HEADER: &str = "roc package manifest v1";
+
+/// Setting this to anything but "" or "0" makes `install_package` verify cached packages
+/// against their manifests before using them.
+pub const VERIFY_PACKAGES_ENV_VAR: &str = "ROC_VERIFY_PACKAGES";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PackageManifest {
+    /// The hash from the package's URL, which is also the name of its dir
+    pub content_hash: String,
+    pub url: String,
+    /// How the tarball is compressed, as a Content-Encoding directive like `br`
+    pub encoding: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct FileEntry {
+    kind: EntryKind,
+    /// base64url-encoded BLAKE3 hash of the file's contents, or of a symlink's target
+    hash: String,
+    /// Relative to the package dir, always with / as the separator
+    path: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    File,
+    Symlink,
+}
+
+/// What `verify_package` found
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verification {
+    Ok,
+    /// The package was cached before roc kept tarballs, so there's nothing to rehash
+    NoTarball,
+    /// The package's tarball doesn't match its hash, or its files don't match its tarball; says how
+    Corrupted(String),
+}
+
+/// e.g. ~/.cache/roc/packages/example.com/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.manifest
+pub fn manifest_path(package_dir: &Path) -> PathBuf {
+    sibling_path(package_dir, MANIFEST_EXT)
+}
+
+/// e.g. ~/.cache/roc/packages/example.com/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tarball
+pub fn tarball_path(package_dir: &Path) -> PathBuf {
+    sibling_path(package_dir, TARBALL_EXT)
+}
+
+fn sibling_path(package_dir: &Path, ext: &str) -> PathBuf {
+    let mut file_name = package_dir.file_name().unwrap_or_default().to_os_string();
+
+    file_name.push(".");
+    file_name.push(ext);
+
+    package_dir.with_file_name(file_name)
+}
+
+impl PackageManifest {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} has an unexpected line: {line:?}", path.display()),
+            )
+        };
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+
+        match lines.next() {
+            Some(MANIFEST_HEADER) => {}
+            other => return Err(invalid(other.unwrap_or_default())),
+        }
+
+        let mut field = |name: &str| match lines.next() {
+            Some(line) => line
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(' '))
+                .map(str::to_string)
+                .ok_or_else(|| invalid(line)),
+            None => Err(invalid("")),
+        };
+
+        let content_hash = field("hash")?;
+        let url = field("url")?;
+        let encoding = field("encoding")?;
+
+        if let Some(line) = lines.next() {
+            return Err(invalid(line));
+        }
+
+        Ok(Self {
+            content_hash,
+            url,
+            encoding,
+        })
+    }
+
+    /// Write to a temporary file first, so a manifest is never half-written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let contents = format!(
+            "{MANIFEST_HEADER}\nhash {}\nurl {}\nencoding {}\n",
+            self.content_hash, self.url, self.encoding
+        );
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+
+        io::Write::write_all(&mut temp_file, contents.as_bytes())?;
+        temp_file.persist(path).map_err(|err| err.error)?;
+
+        Ok(())
+    }
+}
+
+/// Decompress and rehash a cached package's tarball, check that against the name of the
+/// package's dir, and then compare the dir's files to what's in the tarball.
+pub fn verify_package(package_dir: &Path) -> Verification {
+    let tarball = tarball_path(package_dir);
+
+    if !tarball.is_file() {
+        return Verification::NoTarball;
+    }
+
+    let encoding = match PackageManifest::read(&manifest_path(package_dir)) {
+        Ok(manifest) => match Encoding::new(&manifest.encoding, "") {
+            Ok(encoding) => encoding,
+            Err(_) => {
+                return Verification::Corrupted(format!(
+                    "its manifest has an unknown encoding: {}",
+                    manifest.encoding
+                ));
+            }
+        },
+        Err(err) => return Verification::Corrupted(format!("its manifest is unreadable: {err}")),
+    };
+
+    let (hash, unpacked) = match unpack_tarball(&tarball, encoding) {
+        Ok(unpacked) => unpacked,
+        Err(err) => {
+            return Verification::Corrupted(format!("its tarball can't be unpacked: {err}"));
+        }
+    };
+
+    let dir_name = package_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    if hash != dir_name {
+        return Verification::Corrupted(format!("its tarball's BLAKE3 hash is {hash}"));
+    }
+
+    let (expected, actual) = match (list_files(unpacked.path()), list_files(package_dir)) {
+        (Ok(expected), Ok(actual)) => (expected, actual),
+        (Err(err), _) | (_, Err(err)) => {
+            return Verification::Corrupted(format!("its files are unreadable: {err}"));
+        }
+    };
+
+    let mut differences = Vec::new();
+    let mut expected_entries = expected.iter().peekable();
+    let mut actual_entries = actual.iter().peekable();
+
+    // both are sorted by path, so walk through them side by side
+    loop {
+        match (expected_entries.peek(), actual_entries.peek()) {
+            (Some(expected), Some(actual)) if expected.path == actual.path => {
+                if expected != actual {
+                    differences.push(format!("{} was changed", expected.path));
+                }
+
+                expected_entries.next();
+                actual_entries.next();
+            }
+            (Some(expected), Some(actual)) if expected.path < actual.path => {
+                differences.push(format!("{} is missing", expected.path));
+                expected_entries.next();
+            }
+            (Some(expected), None) => {
+                differences.push(format!("{} is missing", expected.path));
+                expected_entries.next();
+            }
+            (_, Some(actual)) => {
+                differences.push(format!("{} was added", actual.path));
+                actual_entries.next();
+            }
+            (None, None) => break,
+        }
+    }
+
+    if differences.is_empty() {
+        Verification::Ok
+    } else {
+        Verification::Corrupted(differences.join(", "))
+    }
+}
+
+/// Whether the ROC_VERIFY_PACKAGES env var asks for cached packages to be verified before use
+pub fn verify_on_use() -> bool {
+    std::env::var_os(VERIFY_PACKAGES_ENV_VAR).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Note that a cached package was just used, for `roc cache prune`.
+/// This is best-effort; a read-only cache just won't have accurate times.
+pub fn mark_used(package_dir: &Path) {
+    if let Ok(file) = File::options().write(true).open(manifest_path(package_dir)) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Decompress and unpack a tarball into a temp dir, returning the decompressed tarball's hash too
+fn unpack_tarball(tarball: &Path, encoding: Encoding) -> io::Result<(String, tempfile::TempDir)> {
+    let dir = tempfile::tempdir()?;
+    let hash = match https::decompress_into(dir.path(), encoding, File::open(tarball)?) {
+        Ok(hash) => hash,
+        Err(Problem::IoErr(err)) => return Err(err),
+        Err(other) => return Err(io::Error::new(io::ErrorKind::Other, format!("{other:?}"))),
+    };
+
+    Ok((hash, dir))
+}
+
+/// Every file and symlink in a dir, sorted by path
+fn list_files(dir: &Path) -> io::Result<Vec<FileEntry>> {
+    let mut entries = Vec::new();
+
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry.map_err(io::Error::from)?;
+        let file_type = entry.file_type();
+        let (kind, hash) = if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            let hash = blake3::hash(target.to_string_lossy().as_bytes());
+
+            (EntryKind::Symlink, base64_url::encode(hash.as_bytes()))
+        } else if file_type.is_file() {
+            (EntryKind::File, hash_file(entry.path())?)
+        } else {
+            continue;
+        };
+
+        entries.push(FileEntry {
+            kind,
+            hash,
+            path: relative_path(dir, entry.path()),
+        });
+    }
+
+    // walkdir's order puts "a/b" before "a-b", so sort by the whole path
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(entries)
+}
+
+/// The base64url-encoded BLAKE3 hash of a file's contents, like the one in a package's URL
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    let mut file = File::open(path)?;
+    let mut buf = vec![0; 64 * 1024];
+
+    loop {
+        match file.read(&mut buf)? {
+            0 => break,
+            bytes_read => {
+                hasher.update(&buf[..bytes_read]);
+            }
+        }
+    }
+
+    Ok(base64_url::encode(hasher.finalize().as_bytes()))
+}
+
+fn relative_path(package_dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(package_dir).unwrap_or(path);
+    let components: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+
+    components.join("/")
+}
+
+#[test]
+fn verify_package_against_tarball() {
+    use crate::tarball::{self, Compression};
+
+    let src_dir = tempfile::tempdir().unwrap();
+    let main_path = src_dir.path().join("main.roc");
+    fs::write(&main_path, "package [Foo] {}\n").unwrap();
+    fs::write(src_dir.path().join("Foo.roc"), "module [foo]\n\nfoo = 1\n").unwrap();
+
+    let tarball_name = tarball::build(&main_path, Compression::Gzip).unwrap();
+    let built_tarball = src_dir.path().join(&tarball_name);
+    let content_hash = tarball_name.strip_suffix(".tar.gz").unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let package_dir = cache_dir.path().join(content_hash);
+
+    tar::Archive::new(flate2::read::GzDecoder::new(
+        File::open(&built_tarball).unwrap(),
+    ))
+    .unpack(&package_dir)
+    .unwrap();
+
+    assert_eq!(verify_package(&package_dir), Verification::NoTarball);
+
+    fs::copy(&built_tarball, tarball_path(&package_dir)).unwrap();
+    PackageManifest {
+        content_hash: content_hash.to_string(),
+        url: format!("file://{}", built_tarball.display()),
+        encoding: "gzip".to_string(),
+    }
+    .write(&manifest_path(&package_dir))
+    .unwrap();
+
+    assert_eq!(verify_package(&package_dir), Verification::Ok);
+
+    fs::write(package_dir.join("Foo.roc"), "module [foo]\n\nfoo = 2\n").unwrap();
+    fs::remove_file(package_dir.join("main.roc")).unwrap();
+    fs::write(package_dir.join("extra.roc"), "").unwrap();
+
+    assert_eq!(
+        verify_package(&package_dir),
+        Verification::Corrupted(
+            "Foo.roc was changed, extra.roc was added, main.roc is missing".to_string()
+        )
+    );
+
+    // the tarball itself gets checked against the package's hash, too
+    let mut tar_bytes = Vec::new();
+    flate2::read::GzDecoder::new(File::open(&built_tarball).unwrap())
+        .read_to_end(&mut tar_bytes)
+        .unwrap();
+    tar_bytes.extend_from_slice(&[0; 512]);
+
+    let mut encoder = flate2::write::GzEncoder::new(
+        File::create(tarball_path(&package_dir)).unwrap(),
+        flate2::Compression::default(),
+    );
+    io::Write::write_all(&mut encoder, &tar_bytes).unwrap();
+    encoder.finish().unwrap();
+
+    assert!(matches!(
+        verify_package(&package_dir),
+        Verification::Corrupted(problem) if problem.starts_with("its tarball's BLAKE3 hash is ")
+    ));
+}
+
+#[test]
+fn manifest_round_trip() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let package_dir = cache_dir.path().join("hash");
+    let manifest = PackageManifest {
+        content_hash: "hash".to_string(),
+        url: "https://example.com/hash.tar.br".to_string(),
+        encoding: "br".to_string(),
+    };
+
+    manifest.write(&manifest_path(&package_dir)).unwrap();
+
+    assert_eq!(
+        PackageManifest::read(&manifest_path(&package_dir)).unwrap(),
+        manifest
+    );
+}
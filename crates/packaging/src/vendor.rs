@@ -0,0 +1,253 @@
+//! `roc vendor` copies every URL package a project depends on into the project itself,
+//! so that it can be built with `--offline` on machines without network access.
+use crate::cache::{self, RocCacheDir};
+use crate::https::{PackageMetadata, Problem};
+use bumpalo::Bump;
+use roc_parse::ast::{ExtractSpaces, Header};
+use roc_parse::header::{parse_header, AppHeader, PackageHeader, PlatformHeader};
+use roc_parse::state::State;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The vendor dir sits next to the project's root module
+pub const VENDOR_DIR_NAME: &str = "vendor";
+
+/// Packages without a URL fragment start at this module (the same default `roc_load` uses)
+const DEFAULT_MAIN_NAME: &str = "main.roc";
+
+#[derive(Debug)]
+pub struct VendoredPackage {
+    pub url: String,
+    /// Where the package is now, e.g. vendor/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE
+    pub dir: PathBuf,
+    /// False if an earlier `roc vendor` already copied it
+    pub newly_vendored: bool,
+}
+
+#[derive(Debug)]
+pub enum VendorProblem {
+    /// We couldn't download the package, or find it in the cache
+    Package {
+        url: String,
+        /// The module whose header refers to the package
+        module_path: PathBuf,
+        problem: Problem,
+    },
+    /// A module's header couldn't be read or parsed
+    Header {
+        module_path: PathBuf,
+        problem: String,
+    },
+    /// Copying a package into the vendor dir failed
+    Copy { dir: PathBuf, problem: String },
+}
+
+/// The vendor dir of the project whose root module (or directory) is at the given path
+pub fn vendor_dir(root_path: &Path) -> PathBuf {
+    if root_path.is_dir() {
+        root_path.join(VENDOR_DIR_NAME)
+    } else {
+        match root_path.parent() {
+            Some(parent) => parent.join(VENDOR_DIR_NAME),
+            None => PathBuf::from(VENDOR_DIR_NAME),
+        }
+    }
+}
+
+/// Starting from the given root module, follow the `packages` in module headers, and copy
+/// every URL package into `vendor_dir`, named after its content hash. That's where
+/// `RocCacheDir::Offline` looks for them. Packages that aren't in `roc_cache_dir` yet
+/// get downloaded first.
+///
+/// Returns the packages in the order they were found.
+pub fn vendor_packages(
+    roc_cache_dir: RocCacheDir<'_>,
+    root_module: &Path,
+    vendor_dir: &Path,
+) -> Result<Vec<VendoredPackage>, VendorProblem> {
+    let mut vendored = Vec::new();
+    let mut visited_modules = HashSet::new();
+    let mut vendored_hashes = HashSet::new();
+    let mut stack = vec![root_module.to_path_buf()];
+
+    while let Some(module_path) = stack.pop() {
+        if !visited_modules.insert(module_path.clone()) {
+            continue;
+        }
+
+        for package_name in header_package_names(&module_path)? {
+            if !cache::is_package_url(&package_name) {
+                // a package on disk, like "../platform/main.roc"; its own packages need vendoring too
+                let module_dir = module_path.parent().unwrap_or(Path::new(""));
+                stack.push(module_dir.join(package_name));
+
+                continue;
+            }
+
+            let to_problem = |problem| VendorProblem::Package {
+                url: package_name.clone(),
+                module_path: module_path.clone(),
+                problem,
+            };
+
+            let (package_dir, opt_root_module) =
+                cache::install_package(roc_cache_dir, &package_name).map_err(to_problem)?;
+            let content_hash = PackageMetadata::try_from(package_name.as_str())
+                .map_err(|problem| to_problem(Problem::InvalidUrl(problem)))?
+                .content_hash;
+
+            let dest_dir = vendor_dir.join(content_hash);
+            let newly_vendored = !dest_dir.exists();
+
+            if newly_vendored {
+                copy_package(&package_dir, vendor_dir, &dest_dir)?;
+            }
+
+            if vendored_hashes.insert(content_hash.to_string()) {
+                stack.push(dest_dir.join(opt_root_module.unwrap_or(DEFAULT_MAIN_NAME)));
+
+                vendored.push(VendoredPackage {
+                    url: package_name.clone(),
+                    dir: dest_dir,
+                    newly_vendored,
+                });
+            }
+        }
+    }
+
+    Ok(vendored)
+}
+
+/// The package names in a module's header, like "https://example.com/jDRl….tar.br"
+/// or "../platform/main.roc". Only app, package, and platform modules have any.
+fn header_package_names(module_path: &Path) -> Result<Vec<String>, VendorProblem> {
+    let header_problem = |problem: String| VendorProblem::Header {
+        module_path: module_path.to_path_buf(),
+        problem,
+    };
+
+    let arena = Bump::new();
+    let src = fs::read(module_path).map_err(|err| header_problem(err.to_string()))?;
+    let (header, _) = parse_header(&arena, State::new(arena.alloc_slice_copy(&src)))
+        .map_err(|fail| header_problem(format!("{:?}", fail.problem)))?;
+
+    let packages = match header.item {
+        Header::App(AppHeader { packages, .. })
+        | Header::Package(PackageHeader { packages, .. }) => packages.value.items,
+        Header::Platform(PlatformHeader { packages, .. }) => packages.item.items,
+        Header::Module(_) | Header::Hosted(_) => &[],
+    };
+
+    Ok(packages
+        .iter()
+        .map(|entry| {
+            let entry = entry.value.extract_spaces().item;
+            entry.package_name.value.to_str().to_string()
+        })
+        .collect())
+}
+
+/// Copy into a temporary dir first, so an interrupted copy never looks like a vendored package.
+fn copy_package(
+    package_dir: &Path,
+    vendor_dir: &Path,
+    dest_dir: &Path,
+) -> Result<(), VendorProblem> {
+    let copy_problem = |err: &dyn std::fmt::Display| VendorProblem::Copy {
+        dir: dest_dir.to_path_buf(),
+        problem: err.to_string(),
+    };
+
+    fs::create_dir_all(vendor_dir).map_err(|err| copy_problem(&err))?;
+
+    let tempdir = tempfile::tempdir_in(vendor_dir).map_err(|err| copy_problem(&err))?;
+
+    fs_extra::dir::copy(
+        package_dir,
+        tempdir.path(),
+        &fs_extra::dir::CopyOptions {
+            content_only: true,
+            ..Default::default()
+        },
+    )
+    .map_err(|err| copy_problem(&err))?;
+
+    match fs::rename(tempdir.path(), dest_dir) {
+        Ok(()) => Ok(()),
+        // another `roc vendor` got there first; the contents are the same, since the name is their hash
+        Err(_) if dest_dir.exists() => Ok(()),
+        Err(err) => Err(copy_problem(&err)),
+    }
+}
+
+#[test]
+fn offline_uses_vendored_packages_before_the_cache() {
+    let url = "https://example.com/path/hash.tar.br";
+    let project_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let vendor_dir = project_dir.path().join(VENDOR_DIR_NAME);
+    let offline = RocCacheDir::Offline {
+        vendor_dir: &vendor_dir,
+        cache_dir: cache_dir.path(),
+    };
+
+    assert!(matches!(
+        cache::install_package(offline, url),
+        Err(Problem::NotAvailableOffline { .. })
+    ));
+
+    let cached_dir = cache_dir.path().join("example.com/path/hash");
+    fs::create_dir_all(&cached_dir).unwrap();
+    assert_eq!(cache::install_package(offline, url).unwrap().0, cached_dir);
+
+    let vendored_dir = vendor_dir.join("hash");
+    fs::create_dir_all(&vendored_dir).unwrap();
+    assert_eq!(
+        cache::install_package(offline, url).unwrap().0,
+        vendored_dir
+    );
+}
+
+#[test]
+fn vendor_cached_packages_of_an_app() {
+    let project_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let root_module = project_dir.path().join("main.roc");
+    let vendor_dir = vendor_dir(&root_module);
+
+    fs::write(
+        &root_module,
+        "app [main] { pf: platform \"https://example.com/path/hash.tar.br#platform.roc\" }\n",
+    )
+    .unwrap();
+
+    let cached_dir = cache_dir.path().join("example.com/path/hash");
+    fs::create_dir_all(&cached_dir).unwrap();
+    fs::write(
+        cached_dir.join("platform.roc"),
+        "platform \"pf\" requires {} { main : Str } exposes [] packages {} imports [] provides [mainForHost]\n",
+    )
+    .unwrap();
+
+    let vendored = vendor_packages(
+        RocCacheDir::Persistent(cache_dir.path()),
+        &root_module,
+        &vendor_dir,
+    )
+    .unwrap();
+
+    assert_eq!(vendored.len(), 1);
+    assert_eq!(vendored[0].dir, vendor_dir.join("hash"));
+    assert!(vendored[0].newly_vendored);
+    assert!(vendor_dir.join("hash/platform.roc").is_file());
+
+    let vendored_again = vendor_packages(
+        RocCacheDir::Persistent(cache_dir.path()),
+        &root_module,
+        &vendor_dir,
+    )
+    .unwrap();
+
+    assert!(!vendored_again[0].newly_vendored);
+}
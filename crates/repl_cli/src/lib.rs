@@ -8,16 +8,21 @@ use roc_mono::ir::OptLevel;
 use roc_repl_eval::gen::Problems;
 use roc_repl_ui::colors::{CYAN, END_COL};
 use roc_repl_ui::repl_state::{ReplAction, ReplState};
-use roc_repl_ui::{format_output, is_incomplete, CONT_PROMPT, PROMPT, SHORT_INSTRUCTIONS, TIPS};
+use roc_repl_ui::{
+    format_loaded, format_output, format_packages_added, is_incomplete, CONT_PROMPT, PROMPT,
+    SHORT_INSTRUCTIONS, TIPS,
+};
 use roc_reporting::report::{
     strip_colors, to_file_problem_report_string, ANSI_STYLE_CODES, DEFAULT_PALETTE,
 };
 use roc_target::Target;
+use rustyline::completion::Completer;
 use rustyline::highlight::{Highlighter, PromptInfo};
 use rustyline::validate::{self, ValidationContext, ValidationResult, Validator};
-use rustyline::Config;
-use rustyline_derive::{Completer, Helper, Hinter};
+use rustyline::{Config, Context};
+use rustyline_derive::{Helper, Hinter};
 use std::borrow::Cow;
+use std::path::PathBuf;
 use target_lexicon::Triple;
 
 use crate::cli_gen::eval_llvm;
@@ -31,7 +36,7 @@ pub const WELCOME_MESSAGE: &str = concatcp!(
     "\n\n"
 );
 
-#[derive(Completer, Helper, Hinter, Default)]
+#[derive(Helper, Hinter, Default)]
 pub struct ReplHelper {
     validator: InputValidator,
     state: ReplState,
@@ -69,6 +74,14 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
         Editor::<ReplHelper>::with_config(Config::builder().color_mode(editor_color_mode).build());
     let repl_helper = ReplHelper::default();
     editor.set_helper(Some(repl_helper));
+
+    let opt_history_path = history_path();
+
+    if let Some(history_path) = &opt_history_path {
+        // There's no history yet the first time the repl runs
+        let _ = editor.load_history(history_path);
+    }
+
     let target = Triple::host().into();
     let mut arena = Bump::new();
 
@@ -79,6 +92,11 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
 
                 editor.add_history_entry(line);
 
+                if let Some(history_path) = &opt_history_path {
+                    // Losing the history isn't worth interrupting the session over
+                    let _ = editor.save_history(history_path);
+                }
+
                 let repl_state = &mut editor
                     .helper_mut()
                     .expect("Editor helper was not set")
@@ -103,6 +121,41 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
                             to_file_problem_report_string(filename, error, has_color)
                         );
                     }
+                    ReplAction::Type {
+                        opt_output,
+                        problems,
+                    } => {
+                        let output = format_output(ANSI_STYLE_CODES, opt_output, problems);
+
+                        println!("{}", strip_colors_if_necessary(&output));
+                    }
+                    ReplAction::Defs(defs) => {
+                        for def in defs {
+                            println!("{def}\n");
+                        }
+                    }
+                    ReplAction::PackagesAdded {
+                        shorthands,
+                        skipped_platform,
+                    } => {
+                        let message =
+                            format_packages_added(&shorthands, skipped_platform.as_deref());
+
+                        println!("{}", strip_colors_if_necessary(&message));
+                    }
+                    ReplAction::Loaded {
+                        filename,
+                        def_count,
+                        problems,
+                    } => {
+                        let output =
+                            format_loaded(ANSI_STYLE_CODES, &filename, def_count, problems);
+
+                        println!("{}", strip_colors_if_necessary(&output));
+                    }
+                    ReplAction::Problem(message) => {
+                        println!("{}", strip_colors_if_necessary(&message));
+                    }
                     ReplAction::Help => {
                         println!("{TIPS}");
                     }
@@ -129,6 +182,15 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
     }
 }
 
+/// Where the history of past sessions is kept, e.g. ~/.cache/roc/repl_history.txt
+fn history_path() -> Option<PathBuf> {
+    let cache_dir = roc_packaging::cache::roc_cache_dir();
+
+    std::fs::create_dir_all(&cache_dir).ok()?;
+
+    Some(cache_dir.join("repl_history.txt"))
+}
+
 pub fn evaluate(
     opt_mono: Option<MonomorphizedModule<'_>>,
     problems: Problems,
@@ -169,6 +231,32 @@ impl Highlighter for ReplHelper {
     }
 }
 
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // The word before the cursor, which can have dots in it, like `Str.con` or `rec.fie`
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .take_while(|(_, ch)| ch.is_alphanumeric() || matches!(ch, '_' | '.' | ':'))
+            .last()
+            .map_or(pos, |(index, _)| index);
+        let word = &line[start..pos];
+
+        if word.is_empty() {
+            return Ok((pos, Vec::new()));
+        }
+
+        Ok((start, self.state.completions(word, Triple::host().into())))
+    }
+}
+
 impl Validator for ReplHelper {
     fn validate(
         &self,
@@ -2,17 +2,26 @@ use bumpalo::Bump;
 use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
 use roc_problem::Severity;
-use roc_reporting::report::Palette;
-use std::path::PathBuf;
+use roc_reporting::report::{Palette, DEFAULT_PALETTE};
+use std::path::{Path, PathBuf};
 
+use roc_collections::MutMap;
 use roc_fmt::annotation::Formattable;
 use roc_fmt::annotation::{Newlines, Parens};
-use roc_load::{LoadingProblem, MonomorphizedModule};
+use roc_load::{LoadedModule, LoadingProblem, MonomorphizedModule};
+use roc_module::symbol::{Interns, ModuleId};
 use roc_parse::ast::Expr;
 use roc_region::all::LineInfo;
-use roc_reporting::report::{can_problem, type_problem, RocDocAllocator};
+use roc_reporting::report::{
+    can_problem, to_file_problem_report_string, type_problem, RocDocAllocator,
+};
 use roc_solve::FunctionKind;
+use roc_solve_problem::TypeError;
 use roc_target::Target;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+
+/// The def the REPL's expr goes in, after all the past defs
+const REPL_MODULE_MAIN_DEF: &str = "replOutput =\n";
 
 #[derive(Debug)]
 pub struct ReplOutput {
@@ -45,21 +54,28 @@ impl Problems {
     }
 }
 
-pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
+/// `packages` are the (shorthand, URL or path) pairs that go in the app header, so that
+/// the defs can import modules from them. Other imported modules are found in `src_dir`.
+pub fn compile_to_mono<'a, 'i, P, I>(
     arena: &'a Bump,
+    packages: P,
     defs: I,
+    src_dir: &Path,
     expr: &str,
     target: Target,
     palette: Palette,
-) -> (Option<MonomorphizedModule<'a>>, Problems) {
+) -> (Option<MonomorphizedModule<'a>>, Problems)
+where
+    P: Iterator<Item = (&'i str, &'i str)>,
+    I: Iterator<Item = &'i str>,
+{
     let filename = PathBuf::from("replfile.roc");
-    let src_dir = PathBuf::from(".");
-    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, defs, expr);
+    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, packages, defs, expr);
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
         filename,
         module_src,
-        src_dir,
+        src_dir.to_path_buf(),
         None,
         RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
         LoadConfig {
@@ -102,6 +118,195 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
         ..
     } = &mut loaded;
 
+    let problems = collect_problems(
+        module_src,
+        bytes_before_expr,
+        sources,
+        can_problems,
+        type_problems,
+        interns,
+        palette,
+    );
+
+    (Some(loaded), problems)
+}
+
+/// Infer the type of the expr in the context of the defs, without evaluating it (for `:type`).
+pub fn check_expr_type<'a, 'i, P, I>(
+    arena: &'a Bump,
+    packages: P,
+    defs: I,
+    src_dir: &Path,
+    expr: &str,
+    target: Target,
+    palette: Palette,
+) -> (Option<String>, Problems)
+where
+    P: Iterator<Item = (&'i str, &'i str)>,
+    I: Iterator<Item = &'i str>,
+{
+    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, packages, defs, expr);
+
+    let mut loaded = match load_and_typecheck(arena, module_src, src_dir, target, palette) {
+        Ok(loaded) => loaded,
+        Err(problems) => return (None, problems),
+    };
+
+    let LoadedModule {
+        module_id,
+        interns,
+        solved,
+        sources,
+        can_problems,
+        type_problems,
+        exposed_to_host,
+        ..
+    } = &mut loaded;
+
+    let problems = collect_problems(
+        module_src,
+        bytes_before_expr,
+        sources,
+        can_problems,
+        type_problems,
+        interns,
+        palette,
+    );
+
+    // replOutput is the only thing the REPL's app module provides
+    let opt_expr_type = exposed_to_host.values().next().map(|var| {
+        name_and_print_var(
+            *var,
+            solved.inner_mut(),
+            *module_id,
+            interns,
+            DebugPrint::NOTHING,
+        )
+    });
+
+    (opt_expr_type, problems)
+}
+
+/// Type-check just the defs, e.g. to find out what's in scope for tab completion.
+/// Returns None if they couldn't be loaded at all.
+pub fn check_defs<'a, 'i, P, I>(
+    arena: &'a Bump,
+    packages: P,
+    defs: I,
+    src_dir: &Path,
+    target: Target,
+) -> Option<LoadedModule>
+where
+    P: Iterator<Item = (&'i str, &'i str)>,
+    I: Iterator<Item = &'i str>,
+{
+    let (_, module_src) = promote_expr_to_module(arena, packages, defs, "{}");
+
+    load_and_typecheck(arena, module_src, src_dir, target, DEFAULT_PALETTE).ok()
+}
+
+/// Type-check the defs, reporting only the problems in the ones after the first `old_defs_count`,
+/// like the defs `:load` just added. Problems in past defs were reported when they were entered.
+pub fn check_new_defs<'a, 'i, P, I>(
+    arena: &'a Bump,
+    packages: P,
+    defs: I,
+    src_dir: &Path,
+    old_defs_count: usize,
+    target: Target,
+    palette: Palette,
+) -> Problems
+where
+    P: Iterator<Item = (&'i str, &'i str)>,
+    I: Iterator<Item = &'i str>,
+{
+    let defs: Vec<&str> = defs.collect();
+    let (bytes_before_expr, module_src) =
+        promote_expr_to_module(arena, packages, defs.iter().copied(), "{}");
+
+    // promote_expr_to_module puts a blank line after each def
+    let new_defs_len: usize = defs
+        .iter()
+        .skip(old_defs_count)
+        .map(|def| def.len() + "\n\n".len())
+        .sum();
+    let bytes_before_new_defs = bytes_before_expr - REPL_MODULE_MAIN_DEF.len() - new_defs_len;
+
+    let mut loaded = match load_and_typecheck(arena, module_src, src_dir, target, palette) {
+        Ok(loaded) => loaded,
+        Err(problems) => return problems,
+    };
+
+    let LoadedModule {
+        interns,
+        sources,
+        can_problems,
+        type_problems,
+        ..
+    } = &mut loaded;
+
+    // Nothing uses the new defs yet, but that's what they're there for
+    for problems in can_problems.values_mut() {
+        problems.retain(|problem| !matches!(problem, roc_problem::can::Problem::UnusedDef(..)));
+    }
+
+    collect_problems(
+        module_src,
+        bytes_before_new_defs,
+        sources,
+        can_problems,
+        type_problems,
+        interns,
+        palette,
+    )
+}
+
+fn load_and_typecheck<'a>(
+    arena: &'a Bump,
+    module_src: &'a str,
+    src_dir: &Path,
+    target: Target,
+    palette: Palette,
+) -> Result<LoadedModule, Problems> {
+    let loaded = roc_load::load_and_typecheck_str(
+        arena,
+        PathBuf::from("replfile.roc"),
+        module_src,
+        src_dir.to_path_buf(),
+        None,
+        target,
+        FunctionKind::LambdaSet,
+        roc_reporting::report::RenderTarget::ColorTerminal,
+        RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+        palette,
+    );
+
+    let report = match loaded {
+        Ok(loaded) => return Ok(loaded),
+        Err(LoadingProblem::FormattedReport(report)) => report,
+        Err(LoadingProblem::FileProblem { filename, error }) => {
+            to_file_problem_report_string(filename, error, true)
+        }
+        // Load should always end up with a formatted report, but this is reachable
+        // from tab completion, which must never crash the REPL.
+        Err(problem) => format!("Failed with error: {problem:?}"),
+    };
+
+    Err(Problems {
+        errors: vec![report],
+        warnings: Vec::new(),
+    })
+}
+
+fn collect_problems(
+    module_src: &str,
+    bytes_before_expr: usize,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
+    type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    interns: &Interns,
+    palette: Palette,
+) -> Problems {
     let mut problems = Problems::default();
 
     let errors = &mut problems.errors;
@@ -165,19 +370,44 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
         }
     }
 
-    (Some(loaded), problems)
+    problems
 }
 
-fn promote_expr_to_module<'a, 'i, I: Iterator<Item = &'i str>>(
+fn promote_expr_to_module<'a, 'i, P, I>(
     arena: &'a Bump,
+    packages: P,
     defs: I,
     expr: &str,
-) -> (usize, &'a str) {
-    const REPL_MODULE_HEADER: &str = "app \"app\" provides [replOutput] to \"./platform\"\n\n";
-    const REPL_MODULE_MAIN_DEF: &str = "replOutput =\n";
+) -> (usize, &'a str)
+where
+    P: Iterator<Item = (&'i str, &'i str)>,
+    I: Iterator<Item = &'i str>,
+{
+    const REPL_MODULE_PROVIDES: &str = "provides [replOutput] to \"./platform\"\n\n";
     const INDENT: &str = "    ";
 
-    let mut buffer = bumpalo::collections::string::String::from_str_in(REPL_MODULE_HEADER, arena);
+    let mut buffer = bumpalo::collections::string::String::from_str_in("app \"app\" ", arena);
+    let mut packages = packages.peekable();
+
+    if packages.peek().is_some() {
+        buffer.push_str("packages {");
+
+        for (index, (shorthand, package_name)) in packages.enumerate() {
+            if index > 0 {
+                buffer.push(',');
+            }
+
+            buffer.push(' ');
+            buffer.push_str(shorthand);
+            buffer.push_str(": \"");
+            buffer.push_str(package_name);
+            buffer.push('"');
+        }
+
+        buffer.push_str(" } ");
+    }
+
+    buffer.push_str(REPL_MODULE_PROVIDES);
 
     for line in defs {
         // don't indent the defs
@@ -15,6 +15,8 @@ use {
 #[cfg(not(windows))]
 mod app;
 #[cfg(not(windows))]
+mod property;
+#[cfg(not(windows))]
 pub mod run;
 
 #[cfg(not(windows))]
@@ -94,15 +96,19 @@ mod test {
     use indoc::indoc;
     use pretty_assertions::assert_eq;
     use roc_error_macros::internal_error;
-    use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult, run_roc_dylib};
+    use roc_gen_llvm::llvm::build::LlvmBackendMode;
     use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError, Threading};
     use roc_packaging::cache::RocCacheDir;
     use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
     use target_lexicon::Triple;
 
-    use crate::run::expect_mono_module_to_dylib;
+    use crate::run::{expect_mono_module_to_dylib, ExpectRunner};
 
     fn run_expect_test(source: &str, expected: &str) {
+        run_expect_test_in_mode(source, expected, ExecutionMode::Test)
+    }
+
+    fn run_expect_test_in_mode(source: &str, expected: &str, exec_mode: ExecutionMode) {
         let arena = bumpalo::Bump::new();
         let arena = &arena;
 
@@ -123,7 +129,7 @@ mod test {
             render: RenderTarget::ColorTerminal,
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
-            exec_mode: ExecutionMode::Test,
+            exec_mode,
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -147,37 +153,30 @@ mod test {
 
         let interns = loaded.interns.clone();
 
-        let (dy_lib, expects_by_module, layout_interner) =
-            expect_mono_module_to_dylib(arena, target, loaded, opt_level, LlvmBackendMode::CliTest)
-                .unwrap();
+        let (dylib, expects_by_module, layout_interner) =
+            expect_mono_module_to_dylib(arena, target, loaded, opt_level, LlvmBackendMode::CliTest);
 
         let arena = &bumpalo::Bump::new();
         let interns = arena.alloc(interns);
 
-        const BUFFER_SIZE: usize = 1024;
-
-        let mut shared_buffer = [0u8; BUFFER_SIZE];
-        let mut memory = crate::run::ExpectMemory::from_slice(&mut shared_buffer);
-
-        // communicate the mmapped name to zig/roc
-        let set_shared_buffer = run_roc_dylib!(dy_lib, "set_shared_buffer", (*mut u8, usize), ());
-        let mut result = RocCallResult::default();
-        unsafe { set_shared_buffer((shared_buffer.as_mut_ptr(), BUFFER_SIZE), &mut result) };
+        let mut runner = ExpectRunner::start().unwrap();
+        runner.load(&dylib).unwrap();
 
         let mut writer = Vec::with_capacity(1024);
 
         let global_layout_interner = layout_interner.into_global();
         for (_, expect_funcs) in expects_by_module {
-            let (_failed, _passed) = crate::run::run_expects_with_memory(
+            let (_failed, _passed) = crate::run::run_toplevel_expects(
                 &mut writer,
                 RenderTarget::ColorTerminal,
                 arena,
                 interns,
                 &global_layout_interner,
-                &dy_lib,
+                &mut runner,
                 &mut expectations,
                 expect_funcs,
-                &mut memory,
+                crate::run::ExpectRunOptions::default(),
+                &mut Vec::new(),
             )
             .unwrap();
         }
@@ -207,6 +206,20 @@ mod test {
         }
     }
 
+    #[test]
+    fn builtin_doc_tests() {
+        // The examples in the docs of the builtins, like Str.roc and Box.roc, all pass
+        run_expect_test_in_mode(
+            indoc!(
+                r#"
+                module []
+                "#
+            ),
+            "",
+            ExecutionMode::TestBuiltinDocs,
+        );
+    }
+
     #[test]
     fn equals_pass() {
         run_expect_test(
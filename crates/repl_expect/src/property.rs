@@ -0,0 +1,846 @@
+//! Property expects take arguments, like `expect \xs -> List.reverse (List.reverse xs) == xs`.
+//! `roc test` calls them with values that it generates from the types of those arguments, and
+//! when one of them fails, shrinks its arguments down to a minimal counterexample.
+
+use bumpalo::Bump;
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::layout::{cmp_fields, Builtin, InLayout, LayoutCache, LayoutInterner, LayoutRepr};
+use roc_types::subs::{Content, FlatType, GetSubsSlice, Subs, Variable};
+use roc_types::types::AliasKind;
+
+/// How many generated inputs a property has to pass
+pub(crate) const RUNS: usize = 100;
+
+/// Generated lists and strings get longer over the course of the runs, up to this length
+const MAX_SIZE: usize = 20;
+
+/// Stop shrinking after this many steps, even if the arguments could get smaller still
+const MAX_SHRINKS: usize = 1000;
+
+/// Dec values are stored as integers, scaled by this factor
+const DEC_SCALE: i128 = 1_000_000_000_000_000_000;
+
+/// A small and fast random number generator (SplitMix64). It does not need to be
+/// cryptographically secure, only reproducible from the seed.
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, or 0 if `n` is 0
+    fn below(&mut self, n: u64) -> u64 {
+        match n {
+            0 => 0,
+            _ => self.next_u64() % n,
+        }
+    }
+
+    fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+}
+
+/// What the values of an argument look like in memory
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Shape {
+    Bool,
+    Int(IntWidth),
+    Float(FloatWidth),
+    Dec,
+    Str,
+    /// A tag union whose tags have no payloads, stored as the tag id
+    Enum {
+        tag_count: usize,
+        width: IntWidth,
+    },
+    List {
+        element: Box<Shape>,
+        stride: usize,
+        alignment: usize,
+    },
+    /// A record or tuple, or all arguments of a property together. Each field has its offset.
+    Struct {
+        fields: Vec<(usize, Shape)>,
+    },
+}
+
+/// A generated value. Enums and decimals are stored as their integer representation.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    List(Vec<Value>),
+    Struct(Vec<Value>),
+}
+
+/// Works out the [Shape] of a type, walking its type variable and its layout side by side.
+/// The layout says where things are in memory, and the type variable says what they mean,
+/// for example how many tags a tag union has.
+pub(crate) struct ShapeBuilder<'a, 'r> {
+    pub(crate) arena: &'a Bump,
+    pub(crate) subs: &'r Subs,
+    pub(crate) interns: &'r Interns,
+    pub(crate) layout_cache: &'r mut LayoutCache<'a>,
+}
+
+impl<'a, 'r> ShapeBuilder<'a, 'r> {
+    /// The shape of the arguments struct that a property expect is called with.
+    /// Its fields are in argument order, laid out like a C struct.
+    ///
+    /// Also returns the size of that struct. Errors describe the types that can't be generated.
+    pub(crate) fn arguments(&mut self, arguments: &[Variable]) -> Result<(Shape, usize), String> {
+        let mut fields = Vec::with_capacity(arguments.len());
+        let mut offset = 0;
+
+        for var in arguments {
+            let layout = self.layout(*var)?;
+            let (size, alignment) = self.layout_cache.interner.stack_size_and_alignment(layout);
+
+            offset = align_up(offset, alignment as usize);
+            fields.push((offset, self.shape(*var, layout)?));
+            offset += size as usize;
+        }
+
+        Ok((Shape::Struct { fields }, offset))
+    }
+
+    fn layout(&mut self, var: Variable) -> Result<InLayout<'a>, String> {
+        self.layout_cache
+            .from_var(self.arena, var, self.subs)
+            .map_err(|_| "values of this type".to_string())
+    }
+
+    fn shape(&mut self, var: Variable, layout: InLayout<'a>) -> Result<Shape, String> {
+        let var = self.unwrap_aliases(var)?;
+
+        match self.layout_cache.get_repr(layout) {
+            LayoutRepr::Builtin(builtin) => match builtin {
+                Builtin::Bool => Ok(Shape::Bool),
+                Builtin::Int(width) => match self.tag_count(var)? {
+                    Some(tag_count) => Ok(Shape::Enum { tag_count, width }),
+                    None => Ok(Shape::Int(width)),
+                },
+                Builtin::Float(width) => Ok(Shape::Float(width)),
+                Builtin::Decimal => Ok(Shape::Dec),
+                Builtin::Str => Ok(Shape::Str),
+                Builtin::List(element_layout) => {
+                    let element_var = match self.subs.get_content_without_compacting(var) {
+                        Content::Structure(FlatType::Apply(Symbol::LIST_LIST, arguments)) => {
+                            self.subs.get_subs_slice(*arguments)[0]
+                        }
+                        _ => return Err("values of this type".to_string()),
+                    };
+
+                    let (size, alignment) = self
+                        .layout_cache
+                        .interner
+                        .stack_size_and_alignment(element_layout);
+
+                    Ok(Shape::List {
+                        element: Box::new(self.shape(element_var, element_layout)?),
+                        stride: align_up(size as usize, alignment as usize),
+                        alignment: alignment as usize,
+                    })
+                }
+            },
+            LayoutRepr::Struct(_) => match self.subs.get_content_without_compacting(var) {
+                Content::Structure(FlatType::Record(fields, ext)) => {
+                    let fields = fields
+                        .sorted_iterator(self.subs, *ext)
+                        .map(|(label, field)| (label, field.into_inner()))
+                        .collect();
+
+                    self.struct_shape(fields)
+                }
+                Content::Structure(FlatType::Tuple(elems, ext)) => {
+                    let elems = elems.sorted_iterator(self.subs, *ext).collect();
+
+                    self.struct_shape(elems)
+                }
+                Content::Structure(FlatType::EmptyRecord) => {
+                    Ok(Shape::Struct { fields: Vec::new() })
+                }
+                Content::Structure(FlatType::TagUnion(..) | FlatType::FunctionOrTagUnion(..)) => {
+                    match self.tag_count(var)? {
+                        // a single tag without a payload takes up no space
+                        Some(_) => Ok(Shape::Struct { fields: Vec::new() }),
+                        None => Err("values of this type".to_string()),
+                    }
+                }
+                _ => Err("values of this type".to_string()),
+            },
+            LayoutRepr::LambdaSet(_) | LayoutRepr::FunctionPointer(_) => {
+                Err("functions".to_string())
+            }
+            _ => {
+                // reports tags with payloads, which is the most likely reason to end up here
+                self.tag_count(var)?;
+
+                Err("values of this type".to_string())
+            }
+        }
+    }
+
+    /// Sorts the fields into memory order, and works out their offsets
+    fn struct_shape<L: Ord>(&mut self, fields: Vec<(L, Variable)>) -> Result<Shape, String> {
+        let mut in_memory_fields = Vec::with_capacity(fields.len());
+        for (label, var) in fields {
+            in_memory_fields.push((label, var, self.layout(var)?));
+        }
+
+        in_memory_fields.sort_by(|(label1, _, layout1), (label2, _, layout2)| {
+            cmp_fields(
+                &self.layout_cache.interner,
+                label1,
+                *layout1,
+                label2,
+                *layout2,
+            )
+        });
+
+        let mut shapes = Vec::with_capacity(in_memory_fields.len());
+        let mut offset = 0;
+
+        for (_, var, layout) in in_memory_fields {
+            let (size, alignment) = self.layout_cache.interner.stack_size_and_alignment(layout);
+
+            offset = align_up(offset, alignment as usize);
+            shapes.push((offset, self.shape(var, layout)?));
+            offset += size as usize;
+        }
+
+        Ok(Shape::Struct { fields: shapes })
+    }
+
+    /// Looks through type aliases. Opaque types usually have invariants that random values would
+    /// break, so only the builtin numbers and `Bool` are allowed.
+    fn unwrap_aliases(&self, mut var: Variable) -> Result<Variable, String> {
+        while let Content::Alias(symbol, _, real_var, kind) =
+            self.subs.get_content_without_compacting(var)
+        {
+            let is_builtin_opaque = matches!(symbol.module_id(), ModuleId::NUM | ModuleId::BOOL);
+
+            if let AliasKind::Opaque = kind {
+                if !is_builtin_opaque {
+                    return Err(format!(
+                        "values of the opaque type `{}`",
+                        symbol.as_str(self.interns)
+                    ));
+                }
+            }
+
+            var = *real_var;
+        }
+
+        Ok(var)
+    }
+
+    /// The number of tags, if this is a tag union whose tags have no payloads
+    fn tag_count(&self, var: Variable) -> Result<Option<usize>, String> {
+        match self.subs.get_content_without_compacting(var) {
+            Content::Structure(FlatType::TagUnion(tags, ext)) => {
+                let mut tag_count = 0;
+
+                for (_, payload) in tags.unsorted_iterator(self.subs, *ext) {
+                    if !payload.is_empty() {
+                        return Err("tags with payloads".to_string());
+                    }
+
+                    tag_count += 1;
+                }
+
+                Ok(Some(tag_count))
+            }
+            // a tag that is only ever used as a value, like `Red` in `\color -> color == Red`
+            Content::Structure(FlatType::FunctionOrTagUnion(tag_names, _, _)) => {
+                Ok(Some(tag_names.len()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Shape {
+    /// Generates a random value. Lists and strings are at most `size` long.
+    pub(crate) fn generate(&self, rng: &mut Rng, size: usize) -> Value {
+        match self {
+            Shape::Bool => Value::Bool(rng.one_in(2)),
+            Shape::Int(width) => Value::Int(generate_int(rng, *width, size)),
+            Shape::Float(_) => Value::Float(generate_float(rng, size)),
+            Shape::Dec => Value::Int(generate_dec(rng, size)),
+            Shape::Str => {
+                let length = rng.below(size as u64 + 1);
+
+                Value::Str((0..length).map(|_| generate_char(rng)).collect())
+            }
+            Shape::Enum { tag_count, .. } => Value::Int(rng.below(*tag_count as u64) as i128),
+            Shape::List { element, .. } => {
+                let length = rng.below(size as u64 + 1);
+
+                // otherwise nested lists get very big
+                let element_size = size / 2;
+
+                Value::List(
+                    (0..length)
+                        .map(|_| element.generate(rng, element_size))
+                        .collect(),
+                )
+            }
+            Shape::Struct { fields } => Value::Struct(
+                fields
+                    .iter()
+                    .map(|(_, field)| field.generate(rng, size))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Values that are a bit simpler than this one, simplest first
+    pub(crate) fn shrink(&self, value: &Value) -> Vec<Value> {
+        let mut candidates = match (self, value) {
+            (Shape::Bool, Value::Bool(true)) => vec![Value::Bool(false)],
+            (Shape::Int(_) | Shape::Enum { .. }, Value::Int(n)) => {
+                vec![Value::Int(0), Value::Int(n / 2), Value::Int(n - n.signum())]
+            }
+            (Shape::Dec, Value::Int(n)) => vec![
+                Value::Int(0),
+                Value::Int(n - n % DEC_SCALE),
+                Value::Int(n / 2 - (n / 2) % DEC_SCALE),
+                Value::Int(n - n.signum() * DEC_SCALE),
+            ],
+            (Shape::Float(_), Value::Float(f)) => vec![
+                Value::Float(0.0),
+                Value::Float(f.trunc()),
+                Value::Float((f / 2.0).trunc()),
+            ],
+            (Shape::Str, Value::Str(string)) => {
+                let chars: Vec<char> = string.chars().collect();
+                let to_string = |chars: &[char]| Value::Str(chars.iter().collect());
+
+                let mut candidates = shrink_sequence(&chars, to_string);
+
+                // then make the characters themselves simpler
+                for (index, char) in chars.iter().enumerate() {
+                    if *char != 'a' {
+                        let mut simpler = chars.clone();
+                        simpler[index] = 'a';
+                        candidates.push(to_string(&simpler));
+                    }
+                }
+
+                candidates
+            }
+            (Shape::List { element, .. }, Value::List(elements)) => {
+                let mut candidates =
+                    shrink_sequence(elements, |elements| Value::List(elements.to_vec()));
+
+                for (index, value) in elements.iter().enumerate() {
+                    for simpler_value in element.shrink(value) {
+                        let mut simpler = elements.clone();
+                        simpler[index] = simpler_value;
+                        candidates.push(Value::List(simpler));
+                    }
+                }
+
+                candidates
+            }
+            (Shape::Struct { fields }, Value::Struct(values)) => {
+                let mut candidates = Vec::new();
+
+                for (index, ((_, field), value)) in fields.iter().zip(values).enumerate() {
+                    for simpler_value in field.shrink(value) {
+                        let mut simpler = values.clone();
+                        simpler[index] = simpler_value;
+                        candidates.push(Value::Struct(simpler));
+                    }
+                }
+
+                candidates
+            }
+            _ => Vec::new(),
+        };
+
+        candidates.retain(|candidate| candidate != value);
+        candidates.dedup();
+
+        candidates
+    }
+
+    /// Shrinks a failing value for as long as `fails` keeps failing on a simpler one.
+    /// Returns the simplest failing value found, and how many times it was shrunk.
+    pub(crate) fn shrink_failure(
+        &self,
+        mut value: Value,
+        mut fails: impl FnMut(&Value) -> bool,
+    ) -> (Value, usize) {
+        let mut shrinks = 0;
+
+        'shrinking: while shrinks < MAX_SHRINKS {
+            for candidate in self.shrink(&value) {
+                if fails(&candidate) {
+                    value = candidate;
+                    shrinks += 1;
+
+                    continue 'shrinking;
+                }
+            }
+
+            break;
+        }
+
+        (value, shrinks)
+    }
+
+    /// An upper bound on the number of bytes that values on the heap need
+    fn heap_size(&self, value: &Value) -> usize {
+        match (self, value) {
+            (Shape::Str, Value::Str(string)) if string.len() >= SMALL_STR_BYTES => {
+                ALLOCATION_OVERHEAD + string.len()
+            }
+            (
+                Shape::List {
+                    element, stride, ..
+                },
+                Value::List(elements),
+            ) => {
+                let nested: usize = elements.iter().map(|e| element.heap_size(e)).sum();
+
+                ALLOCATION_OVERHEAD + elements.len() * stride + nested
+            }
+            (Shape::Struct { fields }, Value::Struct(values)) => fields
+                .iter()
+                .zip(values)
+                .map(|((_, field), value)| field.heap_size(value))
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
+/// The number of generated inputs that lists and strings can be as long as, for a run
+pub(crate) fn size_for_run(run: usize) -> usize {
+    run * MAX_SIZE / RUNS
+}
+
+fn generate_int(rng: &mut Rng, width: IntWidth, size: usize) -> i128 {
+    let bits = width.stack_size() * 8;
+    let (min, max) = if width.is_signed() {
+        (i128::MIN >> (128 - bits), i128::MAX >> (128 - bits))
+    } else {
+        // for U128 that is still big enough, and it keeps everything fitting into an i128
+        (
+            0,
+            (u128::MAX >> (128 - bits)).min(i128::MAX as u128) as i128,
+        )
+    };
+
+    match rng.below(10) {
+        0 => [min, max, 0, 1, -1][rng.below(5) as usize].clamp(min, max),
+        1 | 2 => {
+            let random = ((rng.next_u64() as u128) << 64) | rng.next_u64() as u128;
+
+            if width.is_signed() {
+                (random << (128 - bits)) as i128 >> (128 - bits)
+            } else {
+                (random >> (128 - bits)) as i128 & max
+            }
+        }
+        _ => {
+            let size = size as i128 + 1;
+
+            (rng.below(2 * size as u64 + 1) as i128 - size).clamp(min, max)
+        }
+    }
+}
+
+fn generate_float(rng: &mut Rng, size: usize) -> f64 {
+    if rng.one_in(10) {
+        return [0.0, 1.0, -1.0, 0.5][rng.below(4) as usize];
+    }
+
+    let unit = rng.next_u64() as f64 / u64::MAX as f64;
+
+    (unit * 2.0 - 1.0) * (size + 1) as f64
+}
+
+fn generate_dec(rng: &mut Rng, size: usize) -> i128 {
+    let whole = generate_int(rng, IntWidth::I32, size);
+    // keep to two decimal places, so failures are easy to read
+    let hundredths = rng.below(100) as i128 * (DEC_SCALE / 100);
+
+    whole * DEC_SCALE + whole.signum() * hundredths
+}
+
+fn generate_char(rng: &mut Rng) -> char {
+    if rng.one_in(10) {
+        ['é', 'ß', '中', '🐦', '\n', '\t'][rng.below(6) as usize]
+    } else {
+        // printable ASCII
+        (b' ' + rng.below(95) as u8) as char
+    }
+}
+
+/// Shorter versions of a list or string: empty, then each half, then with one element removed
+fn shrink_sequence<T: Clone>(elements: &[T], rebuild: impl Fn(&[T]) -> Value) -> Vec<Value> {
+    if elements.is_empty() {
+        return Vec::new();
+    }
+
+    let half = elements.len() / 2;
+    let mut candidates = vec![
+        rebuild(&[]),
+        rebuild(&elements[..half]),
+        rebuild(&elements[half..]),
+    ];
+
+    for index in 0..elements.len() {
+        let mut shorter = elements.to_vec();
+        shorter.remove(index);
+        candidates.push(rebuild(&shorter));
+    }
+
+    candidates
+}
+
+fn align_up(offset: usize, alignment: usize) -> usize {
+    offset.next_multiple_of(alignment.max(1))
+}
+
+const USIZE_BYTES: usize = std::mem::size_of::<usize>();
+
+/// Strings shorter than this are stored inline in the `RocStr` itself
+const SMALL_STR_BYTES: usize = 3 * USIZE_BYTES;
+
+/// The refcount (and the element count, for lists) before the data, plus padding
+const ALLOCATION_OVERHEAD: usize = 2 * 16;
+
+/// Generated values, laid out in memory the way compiled Roc code expects them
+pub(crate) struct ValueBuffer {
+    words: Vec<u128>,
+}
+
+impl ValueBuffer {
+    /// Writes `value` at `offset`, with anything it points to after it.
+    ///
+    /// Pointers are `base` plus their offset in the buffer. To call Roc code with the value,
+    /// `base` is where the buffer will be copied to. A `base` of 0 gives offsets from the start
+    /// of the buffer, like in the memory shared with an `expect`. That is what
+    /// [crate::get_values] reads.
+    pub(crate) fn new(
+        shape: &Shape,
+        value: &Value,
+        offset: usize,
+        stack_size: usize,
+        base: usize,
+    ) -> Self {
+        let length = offset + stack_size + shape.heap_size(value);
+        let mut words = vec![0u128; length.div_ceil(16)];
+
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(words.as_mut_ptr().cast::<u8>(), words.len() * 16)
+        };
+
+        let mut writer = Writer {
+            bytes,
+            heap: offset + stack_size,
+            base,
+        };
+        writer.write(shape, value, offset);
+
+        Self { words }
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.words.as_mut_ptr().cast()
+    }
+
+    pub(crate) fn bytes_mut(&mut self) -> &mut [u8] {
+        let length = self.words.len() * 16;
+
+        unsafe { std::slice::from_raw_parts_mut(self.as_mut_ptr(), length) }
+    }
+}
+
+/// Lays out arguments like the memory shared with an `expect`, so that [crate::get_values] can
+/// read them: an offset and a type variable for each of the `lookups`, then the values.
+/// Each lookup is the index of an argument, and its type.
+pub(crate) fn arguments_as_lookups(
+    shape: &Shape,
+    value: &Value,
+    stack_size: usize,
+    lookups: &[(usize, Variable)],
+) -> ValueBuffer {
+    const LOOKUP_HEADER_SIZE: usize = USIZE_BYTES + 4;
+
+    let Shape::Struct { fields } = shape else {
+        roc_error_macros::internal_error!("the arguments of a property are always a struct")
+    };
+
+    let offset = align_up(lookups.len() * LOOKUP_HEADER_SIZE, 16);
+    let mut buffer = ValueBuffer::new(shape, value, offset, stack_size, 0);
+    let bytes = buffer.bytes_mut();
+
+    for (index, (argument, var)) in lookups.iter().enumerate() {
+        let at = index * LOOKUP_HEADER_SIZE;
+        let (argument_offset, _) = fields[*argument];
+
+        bytes[at..][..USIZE_BYTES].copy_from_slice(&(offset + argument_offset).to_le_bytes());
+        bytes[at + USIZE_BYTES..][..4].copy_from_slice(&var.index().to_le_bytes());
+    }
+
+    buffer
+}
+
+struct Writer<'b> {
+    bytes: &'b mut [u8],
+    /// Where the next heap allocation goes
+    heap: usize,
+    /// What gets added to offsets in the buffer to make pointers
+    base: usize,
+}
+
+impl<'b> Writer<'b> {
+    fn write(&mut self, shape: &Shape, value: &Value, at: usize) {
+        match (shape, value) {
+            (Shape::Bool, Value::Bool(b)) => self.bytes[at] = *b as u8,
+            (Shape::Int(width) | Shape::Enum { width, .. }, Value::Int(n)) => {
+                let size = width.stack_size() as usize;
+                self.bytes[at..][..size].copy_from_slice(&n.to_le_bytes()[..size]);
+            }
+            (Shape::Dec, Value::Int(n)) => {
+                self.bytes[at..][..16].copy_from_slice(&n.to_le_bytes());
+            }
+            (Shape::Float(FloatWidth::F32), Value::Float(f)) => {
+                self.bytes[at..][..4].copy_from_slice(&(*f as f32).to_le_bytes());
+            }
+            (Shape::Float(FloatWidth::F64), Value::Float(f)) => {
+                self.bytes[at..][..8].copy_from_slice(&f.to_le_bytes());
+            }
+            (Shape::Str, Value::Str(string)) => {
+                let length = string.len();
+
+                if length < SMALL_STR_BYTES {
+                    self.bytes[at..][..length].copy_from_slice(string.as_bytes());
+                    self.bytes[at + SMALL_STR_BYTES - 1] = length as u8 | 0x80;
+                } else {
+                    let data = self.allocate(length, 1, 0);
+                    self.bytes[data..][..length].copy_from_slice(string.as_bytes());
+
+                    self.write_usize(at, self.base + data);
+                    self.write_usize(at + USIZE_BYTES, length);
+                    self.write_usize(at + 2 * USIZE_BYTES, length);
+                }
+            }
+            (
+                Shape::List {
+                    element,
+                    stride,
+                    alignment,
+                },
+                Value::List(elements),
+            ) => {
+                // an empty list is all zeroes, which the buffer already is
+                if !elements.is_empty() {
+                    let length = elements.len();
+                    let data = self.allocate(length * stride, *alignment, length);
+
+                    for (index, value) in elements.iter().enumerate() {
+                        self.write(element, value, data + index * stride);
+                    }
+
+                    self.write_usize(at, self.base + data);
+                    self.write_usize(at + USIZE_BYTES, length);
+                    self.write_usize(at + 2 * USIZE_BYTES, length);
+                }
+            }
+            (Shape::Struct { fields }, Value::Struct(values)) => {
+                for ((offset, field), value) in fields.iter().zip(values) {
+                    self.write(field, value, at + offset);
+                }
+            }
+            _ => roc_error_macros::internal_error!("{value:?} does not have shape {shape:?}"),
+        }
+    }
+
+    /// Reserves room on the heap, and returns the offset of the data. The refcount before the data
+    /// is 0, which marks the allocation as static, so Roc code never writes to it or frees it.
+    fn allocate(&mut self, size: usize, alignment: usize, element_count: usize) -> usize {
+        let data = align_up(self.heap + 2 * USIZE_BYTES, alignment.max(16));
+
+        // lists of refcounted elements keep their element count before the refcount
+        self.write_usize(data - 2 * USIZE_BYTES, element_count);
+        self.write_usize(data - USIZE_BYTES, 0);
+
+        self.heap = data + size;
+
+        data
+    }
+
+    fn write_usize(&mut self, at: usize, value: usize) {
+        self.bytes[at..][..USIZE_BYTES].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn list_of_i64() -> Shape {
+        Shape::List {
+            element: Box::new(Shape::Int(IntWidth::I64)),
+            stride: 8,
+            alignment: 8,
+        }
+    }
+
+    #[test]
+    fn generation_is_reproducible() {
+        let shape = list_of_i64();
+
+        let first: Vec<_> = {
+            let mut rng = Rng::new(42);
+            (0..10).map(|_| shape.generate(&mut rng, 10)).collect()
+        };
+        let second: Vec<_> = {
+            let mut rng = Rng::new(42);
+            (0..10).map(|_| shape.generate(&mut rng, 10)).collect()
+        };
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn generated_ints_fit_their_width() {
+        let mut rng = Rng::new(0);
+
+        for _ in 0..1000 {
+            let n = generate_int(&mut rng, IntWidth::U8, 300);
+            assert!((0..=255).contains(&n), "{n} is not a U8");
+
+            let n = generate_int(&mut rng, IntWidth::I8, 300);
+            assert!((-128..=127).contains(&n), "{n} is not an I8");
+        }
+    }
+
+    #[test]
+    fn shrink_int_to_boundary() {
+        let shape = Shape::Int(IntWidth::I64);
+        let fails = |value: &Value| matches!(value, Value::Int(n) if *n >= 10);
+
+        let (value, _) = shape.shrink_failure(Value::Int(1 << 40), fails);
+
+        assert_eq!(value, Value::Int(10));
+    }
+
+    #[test]
+    fn shrink_list_to_minimal_counterexample() {
+        // fails when the list is not sorted
+        let shape = list_of_i64();
+        let fails = |value: &Value| match value {
+            Value::List(elements) => elements.windows(2).any(|pair| match pair {
+                [Value::Int(a), Value::Int(b)] => a > b,
+                _ => false,
+            }),
+            _ => false,
+        };
+
+        let failing = Value::List([5, -3, 17, 2, 8, 100, -40].map(Value::Int).to_vec());
+        let (value, shrinks) = shape.shrink_failure(failing, fails);
+
+        assert_eq!(value, Value::List(vec![Value::Int(0), Value::Int(-1)]));
+        assert!(shrinks > 0);
+    }
+
+    #[test]
+    fn shrink_string() {
+        let fails = |value: &Value| matches!(value, Value::Str(s) if s.contains('!'));
+
+        let failing = Value::Str("Hello, World!".to_string());
+        let (value, _) = Shape::Str.shrink_failure(failing, fails);
+
+        assert_eq!(value, Value::Str("!".to_string()));
+    }
+
+    #[test]
+    fn write_small_and_big_strings() {
+        let shape = Shape::Struct {
+            fields: vec![(0, Shape::Str), (24, Shape::Str)],
+        };
+        let big = "a string that does not fit inline";
+        let value = Value::Struct(vec![
+            Value::Str("small".to_string()),
+            Value::Str(big.to_string()),
+        ]);
+
+        let mut buffer = ValueBuffer::new(&shape, &value, 0, 48, 0);
+        let bytes = buffer.bytes_mut();
+
+        assert_eq!(&bytes[..5], b"small");
+        assert_eq!(bytes[23], 5 | 0x80);
+
+        let read_usize = |at: usize| usize::from_le_bytes(bytes[at..][..8].try_into().unwrap());
+        let (data, length, capacity) = (read_usize(24), read_usize(32), read_usize(40));
+
+        assert_eq!((length, capacity), (big.len(), big.len()));
+        assert_eq!(&bytes[data..][..length], big.as_bytes());
+        assert_eq!(data % 16, 0);
+        // a static refcount
+        assert_eq!(read_usize(data - 8), 0);
+    }
+
+    #[test]
+    fn arguments_as_lookups_point_at_the_values() {
+        let shape = Shape::Struct {
+            fields: vec![(0, Shape::Bool), (8, Shape::Int(IntWidth::I64))],
+        };
+        let value = Value::Struct(vec![Value::Bool(true), Value::Int(7)]);
+        let var = Variable::I64;
+
+        let mut buffer = arguments_as_lookups(&shape, &value, 16, &[(1, var)]);
+        let bytes = buffer.bytes_mut();
+
+        let offset = usize::from_le_bytes(bytes[..8].try_into().unwrap());
+        assert_eq!(
+            u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+            var.index()
+        );
+        assert_eq!(bytes[offset], 7);
+        assert_eq!(bytes[offset - 8], 1);
+    }
+
+    #[test]
+    fn write_list_with_absolute_pointers() {
+        let shape = list_of_i64();
+        let value = Value::List(vec![Value::Int(1), Value::Int(-2)]);
+
+        let base = 0x1000;
+        let mut buffer = ValueBuffer::new(&shape, &value, 0, 24, base);
+        let bytes = buffer.bytes_mut();
+
+        let read_usize = |at: usize| usize::from_le_bytes(bytes[at..][..8].try_into().unwrap());
+        let data = read_usize(0) - base;
+
+        assert_eq!((read_usize(8), read_usize(16)), (2, 2));
+        assert_eq!(read_usize(data), 1);
+        assert_eq!(read_usize(data + 8) as i64, -2);
+        // the element count, then a static refcount
+        assert_eq!((read_usize(data - 16), read_usize(data - 8)), (2, 0));
+    }
+}
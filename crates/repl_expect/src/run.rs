@@ -1,15 +1,17 @@
 use std::{
-    os::unix::process::parent_id,
+    io::Write,
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, AtomicU32},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 use bumpalo::collections::Vec as BumpVec;
 use bumpalo::Bump;
 use inkwell::context::Context;
-use roc_build::link::llvm_module_to_dylib;
+use roc_build::link::llvm_module_to_dylib_file;
 use roc_can::expr::ExpectLookup;
 use roc_collections::{MutMap, MutSet, VecMap};
 use roc_error_macros::internal_error;
@@ -22,12 +24,12 @@ use roc_load::{Expectations, MonomorphizedModule};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::{
     ir::OptLevel,
-    layout::{GlobalLayoutInterner, STLayoutInterner},
+    layout::{GlobalLayoutInterner, LayoutCache, STLayoutInterner},
 };
 use roc_region::all::Region;
 use roc_reporting::{error::expect::Renderer, report::RenderTarget};
 use roc_target::Target;
-use roc_types::subs::Subs;
+use roc_types::subs::{Subs, Variable};
 
 pub struct ExpectMemory<'a> {
     ptr: *mut u8,
@@ -39,26 +41,11 @@ pub struct ExpectMemory<'a> {
 impl<'a> ExpectMemory<'a> {
     const SHM_SIZE: usize = 1024;
 
-    #[cfg(test)]
-    pub(crate) fn from_slice(slice: &mut [u8]) -> Self {
-        Self {
-            ptr: slice.as_mut_ptr(),
-            length: slice.len(),
-            shm_name: None,
-            _marker: std::marker::PhantomData,
-        }
-    }
-
     pub fn create_or_reuse_mmap(shm_name: &str) -> Self {
         let cstring = std::ffi::CString::new(shm_name).unwrap();
         Self::mmap_help(cstring, libc::O_RDWR | libc::O_CREAT)
     }
 
-    fn reuse_mmap(&mut self) -> Option<Self> {
-        let shm_name = self.shm_name.as_ref()?.clone();
-        Some(Self::mmap_help(shm_name, libc::O_RDWR))
-    }
-
     fn mmap_help(cstring: std::ffi::CString, shm_flags: i32) -> Self {
         let ptr = unsafe {
             let shared_fd = libc::shm_open(cstring.as_ptr().cast(), shm_flags, 0o666);
@@ -90,19 +77,44 @@ impl<'a> ExpectMemory<'a> {
                 roc_error_macros::internal_error!("failed to mmap shared pointer")
             }
 
-            // fill the buffer with a fill pattern
-            libc::memset(ptr, 0xAA, Self::SHM_SIZE);
-
             ptr
         };
 
+        Self::from_mapping(ptr, Some(cstring))
+    }
+
+    /// Memory that only forked children share, so it can't clash with the memory of
+    /// expects that run on other threads of this process.
+    pub fn create_anonymous_mmap() -> Self {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                Self::SHM_SIZE,
+                libc::PROT_WRITE | libc::PROT_READ,
+                libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+
+        if ptr == libc::MAP_FAILED {
+            internal_error!("failed to mmap anonymous shared memory")
+        }
+
+        Self::from_mapping(ptr, None)
+    }
+
+    fn from_mapping(ptr: *mut libc::c_void, shm_name: Option<std::ffi::CString>) -> Self {
+        // fill the buffer with a fill pattern
+        unsafe { libc::memset(ptr, 0xAA, Self::SHM_SIZE) };
+
         // puts in the initial header
         let _ = ExpectSequence::new(ptr as *mut u8);
 
         Self {
             ptr: ptr.cast(),
             length: Self::SHM_SIZE,
-            shm_name: Some(cstring),
+            shm_name,
             _marker: std::marker::PhantomData,
         }
     }
@@ -124,275 +136,945 @@ impl<'a> ExpectMemory<'a> {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn run_inline_expects<'a, W: std::io::Write>(
-    writer: &mut W,
-    render_target: RenderTarget,
-    arena: &'a Bump,
-    interns: &'a Interns,
-    layout_interner: &GlobalLayoutInterner<'a>,
-    lib: &libloading::Library,
-    expectations: &mut VecMap<ModuleId, Expectations>,
-    expects: ExpectFunctions<'_>,
-) -> std::io::Result<(usize, usize)> {
-    let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
-    let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
+/// The expects of a program, compiled into a dylib that an [ExpectRunner] can load
+pub struct ExpectDylib {
+    path: PathBuf,
+    /// The dylib is deleted along with this
+    _dir: tempfile::TempDir,
+}
 
-    run_expects_with_memory(
-        writer,
-        render_target,
-        arena,
-        interns,
-        layout_interner,
-        lib,
-        expectations,
-        expects,
-        &mut memory,
-    )
+/// Runs expects in processes forked from a server process. Forking while another thread is in
+/// the middle of something (like holding the allocator's lock) isn't safe, so `roc test` starts
+/// its runners before it starts any threads. Then its other threads can keep compiling while
+/// the expects run.
+pub struct ExpectRunner {
+    pid: libc::pid_t,
+    requests: std::fs::File,
+    responses: std::fs::File,
+    /// Shared with the server and its children, at the same address
+    memory: ExpectMemory<'static>,
+    /// Where the arguments of property expects go, shared like `memory`
+    arguments: *mut u8,
+    loaded: bool,
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn run_toplevel_expects<'a, W: std::io::Write>(
-    writer: &mut W,
-    render_target: RenderTarget,
-    arena: &'a Bump,
-    interns: &'a Interns,
-    layout_interner: &GlobalLayoutInterner<'a>,
-    lib: &libloading::Library,
-    expectations: &mut VecMap<ModuleId, Expectations>,
-    expects: ExpectFunctions<'_>,
-) -> std::io::Result<(usize, usize)> {
-    let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
-    let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
+// The shared memory is only used by the thread that owns the runner, and its server.
+unsafe impl Send for ExpectRunner {}
 
-    run_expects_with_memory(
-        writer,
-        render_target,
-        arena,
-        interns,
-        layout_interner,
-        lib,
-        expectations,
-        expects,
-        &mut memory,
-    )
+impl ExpectRunner {
+    const ARGUMENTS_SIZE: usize = 16 * 1024 * 1024;
+
+    const LOAD: u8 = 0;
+    const RUN: u8 = 1;
+
+    /// Forks the server process. This process should have no other threads yet.
+    pub fn start() -> std::io::Result<Self> {
+        let mut memory = ExpectMemory::create_anonymous_mmap();
+
+        let arguments = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                Self::ARGUMENTS_SIZE,
+                libc::PROT_WRITE | libc::PROT_READ,
+                libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+
+        if arguments == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let (request_reader, requests) = cloexec_pipe()?;
+        let (responses, response_writer) = cloexec_pipe()?;
+
+        // otherwise anything still buffered would be written by both processes
+        std::io::stdout().flush()?;
+
+        match unsafe { libc::fork() } {
+            0 => {
+                // we are the server
+                drop(requests);
+                drop(responses);
+
+                serve(
+                    request_reader,
+                    response_writer,
+                    &mut memory,
+                    arguments.cast(),
+                )
+            }
+            -1 => Err(std::io::Error::last_os_error()),
+            pid => Ok(Self {
+                pid,
+                requests,
+                responses,
+                memory,
+                arguments: arguments.cast(),
+                loaded: false,
+            }),
+        }
+    }
+
+    /// Loads the dylib in the server, so its children can run the expects in it.
+    pub fn load(&mut self, dylib: &ExpectDylib) -> std::io::Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut request = vec![Self::LOAD];
+        request.extend_from_slice(dylib.path.as_os_str().as_bytes());
+        write_frame(&mut self.requests, &request)?;
+
+        match read_frame(&mut self.responses)?.as_slice() {
+            [] => {
+                self.loaded = true;
+
+                Ok(())
+            }
+            error => Err(std::io::Error::other(
+                String::from_utf8_lossy(error).into_owned(),
+            )),
+        }
+    }
+
+    fn run(&mut self, name: &str, deadline: Option<Instant>) -> std::io::Result<ExpectRun> {
+        self.request_run(name, false, deadline)
+    }
+
+    fn run_with_arguments(
+        &mut self,
+        name: &str,
+        shape: &crate::property::Shape,
+        value: &crate::property::Value,
+        stack_size: usize,
+        deadline: Option<Instant>,
+    ) -> std::io::Result<ExpectRun> {
+        use crate::property::ValueBuffer;
+
+        let mut buffer = ValueBuffer::new(shape, value, 0, stack_size, self.arguments as usize);
+        let bytes = buffer.bytes_mut();
+
+        if bytes.len() > Self::ARGUMENTS_SIZE {
+            internal_error!("{} bytes of generated arguments don't fit", bytes.len());
+        }
+
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), self.arguments, bytes.len()) };
+
+        self.request_run(name, true, deadline)
+    }
+
+    fn request_run(
+        &mut self,
+        name: &str,
+        with_arguments: bool,
+        deadline: Option<Instant>,
+    ) -> std::io::Result<ExpectRun> {
+        if !self.loaded {
+            internal_error!("expects can only run once their dylib is loaded");
+        }
+
+        let timeout_ms = match deadline {
+            None => u64::MAX,
+            Some(deadline) => match deadline.saturating_duration_since(Instant::now()) {
+                remaining if remaining.is_zero() => return Ok(ExpectRun::TimedOut),
+                remaining => remaining.as_millis().max(1) as u64,
+            },
+        };
+
+        let mut request = vec![Self::RUN, with_arguments as u8];
+        request.extend_from_slice(&timeout_ms.to_le_bytes());
+        request.extend_from_slice(name.as_bytes());
+        write_frame(&mut self.requests, &request)?;
+
+        let response = read_frame(&mut self.responses)?;
+        let Some((exit, message)) = ChildExit::decode(&response) else {
+            return Err(std::io::Error::other(
+                "the expect runner sent a malformed response",
+            ));
+        };
+
+        let run = match exit {
+            ChildExit::Exited(0) => match message.split_first() {
+                Some((&ExpectRun::RETURNED, [])) => ExpectRun::Returned,
+                Some((&ExpectRun::PANICKED, panic_message)) => {
+                    ExpectRun::Panicked(String::from_utf8_lossy(panic_message).into_owned())
+                }
+                _ => ExpectRun::Crashed("It exited without reporting a result.".to_string()),
+            },
+            ChildExit::Exited(code) => {
+                ExpectRun::Crashed(format!("The process running it exited with code {code}."))
+            }
+            ChildExit::Signaled(signal) => ExpectRun::Crashed(describe_signal(signal)),
+            ChildExit::TimedOut => ExpectRun::TimedOut,
+        };
+
+        Ok(run)
+    }
 }
 
+impl Drop for ExpectRunner {
+    fn drop(&mut self) {
+        // Servers started after this one inherited our end of its request pipe, so it won't
+        // see the pipe close until they're gone. It has nothing to clean up anyway.
+        unsafe {
+            libc::kill(self.pid, libc::SIGKILL);
+            libc::waitpid(self.pid, std::ptr::null_mut(), 0);
+            libc::munmap(self.arguments.cast(), Self::ARGUMENTS_SIZE);
+        }
+    }
+}
+
+/// How running an expect in a child of the server went
+enum ExpectRun {
+    /// It returned, and how many of its expectations failed is in the shared memory
+    Returned,
+    Panicked(String),
+    /// The process running it died, for this reason
+    Crashed(String),
+    TimedOut,
+}
+
+impl ExpectRun {
+    const RETURNED: u8 = 0;
+    const PANICKED: u8 = 1;
+}
+
+/// Runs every expect in its own process, forked by the runner, so that a crash or an infinite
+/// loop in one of them is reported as a failure of that expect, and the rest still run.
+/// The runner must have loaded the dylib with the expects in it.
 #[allow(clippy::too_many_arguments)]
-pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
+pub fn run_toplevel_expects<'a, W: std::io::Write>(
     writer: &mut W,
     render_target: RenderTarget,
     arena: &'a Bump,
     interns: &'a Interns,
     layout_interner: &GlobalLayoutInterner<'a>,
-    lib: &libloading::Library,
+    runner: &mut ExpectRunner,
     expectations: &mut VecMap<ModuleId, Expectations>,
     expects: ExpectFunctions<'_>,
-    memory: &mut ExpectMemory,
+    options: ExpectRunOptions,
+    outcomes: &mut Vec<ExpectOutcome>,
 ) -> std::io::Result<(usize, usize)> {
     let mut failed = 0;
     let mut passed = 0;
 
-    for expect in expects.fx {
-        let result = run_expect_fx(
+    for expect in expects.fx.into_iter().chain(expects.pure) {
+        if options.fail_fast && failed > 0 {
+            break;
+        }
+
+        let start_time = Instant::now();
+
+        let failure = run_expect(
             writer,
             render_target,
             arena,
             interns,
             layout_interner,
-            lib,
+            runner,
             expectations,
-            memory,
             expect,
+            options,
         )?;
 
-        match result {
-            true => passed += 1,
-            false => failed += 1,
+        match failure {
+            None => passed += 1,
+            Some(_) => failed += 1,
         }
+
+        outcomes.push(ExpectOutcome::new(expect, start_time.elapsed(), failure));
     }
 
-    memory.set_shared_buffer(lib);
+    Ok((failed, passed))
+}
 
-    for expect in expects.pure {
-        let result = run_expect_pure(
+/// Runs an expect. If it fails, the failure is rendered to `writer`,
+/// and also returned rendered without colors for test reports.
+#[allow(clippy::too_many_arguments)]
+fn run_expect<'a, W: std::io::Write>(
+    writer: &mut W,
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    layout_interner: &GlobalLayoutInterner<'a>,
+    runner: &mut ExpectRunner,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+    expect: ToplevelExpect<'_>,
+    options: ExpectRunOptions,
+) -> std::io::Result<Option<ExpectFailure>> {
+    if !expect.arguments.is_empty() {
+        return run_property_expect(
             writer,
             render_target,
             arena,
             interns,
             layout_interner,
-            lib,
+            runner,
             expectations,
-            memory,
             expect,
-        )?;
+            options,
+        );
+    }
+
+    let sequence = ExpectSequence::new(runner.memory.ptr);
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+
+    let run = runner.run(expect.name, deadline)?;
+
+    let kind = match run {
+        ExpectRun::Returned if sequence.count_failures() == 0 => return Ok(None),
+        ExpectRun::Returned => ExpectFailureKind::Failed,
+        ExpectRun::Panicked(_) | ExpectRun::Crashed(_) => ExpectFailureKind::Crashed,
+        ExpectRun::TimedOut => ExpectFailureKind::TimedOut,
+    };
+
+    let shared_memory_ptr: *const u8 = runner.memory.ptr;
+
+    let module_id = expect.symbol.module_id();
+    let data = expectations.get(&module_id).unwrap();
+
+    let filename = data.path.to_owned();
+    let source = std::fs::read_to_string(&data.path).unwrap();
 
-        match result {
-            true => passed += 1,
-            false => failed += 1,
+    let mut report: Vec<u8> = Vec::new();
+
+    for (target, mut out) in failure_outputs(&mut *writer, render_target, &mut report, options) {
+        let renderer = Renderer::new(arena, interns, target, module_id, filename.clone(), &source);
+
+        match &run {
+            ExpectRun::Returned => {
+                let mut offset = ExpectSequence::START_OFFSET;
+
+                for _ in 0..sequence.count_failures() {
+                    offset = render_expect_failure(
+                        &mut out,
+                        &renderer,
+                        arena,
+                        Some(expect),
+                        expectations,
+                        interns,
+                        layout_interner,
+                        shared_memory_ptr,
+                        offset,
+                    )?;
+                }
+            }
+            ExpectRun::Panicked(roc_panic_message) => {
+                renderer.render_panic(&mut out, roc_panic_message, expect.region)?
+            }
+            ExpectRun::Crashed(description) => {
+                renderer.render_crash(&mut out, description, expect.region)?
+            }
+            ExpectRun::TimedOut => {
+                // only an expect with a timeout can run out of time
+                let timeout = options.timeout.unwrap_or_default();
+                renderer.render_timeout(&mut out, timeout, expect.region)?
+            }
         }
+
+        writeln!(out)?;
     }
 
-    Ok((failed, passed))
+    Ok(Some(ExpectFailure {
+        kind,
+        report: String::from_utf8_lossy(&report).into_owned(),
+    }))
+}
+
+enum PropertyRun {
+    Passed,
+    /// The number of inline expects that failed
+    Failed(usize),
+    Panicked(String),
+    /// The process running it died, for this reason
+    Crashed(String),
+    TimedOut,
 }
 
+/// Runs a property expect, like `expect \\xs -> ...`, with generated arguments. If it fails for
+/// any of them, those arguments are shrunk, and the failure is rendered for the simplest
+/// arguments that still fail. The timeout is for all of the runs together.
 #[allow(clippy::too_many_arguments)]
-fn run_expect_pure<'a, W: std::io::Write>(
+fn run_property_expect<'a, W: std::io::Write>(
     writer: &mut W,
     render_target: RenderTarget,
     arena: &'a Bump,
     interns: &'a Interns,
     layout_interner: &GlobalLayoutInterner<'a>,
-    lib: &libloading::Library,
+    runner: &mut ExpectRunner,
     expectations: &mut VecMap<ModuleId, Expectations>,
-    shared_memory: &mut ExpectMemory,
     expect: ToplevelExpect<'_>,
-) -> std::io::Result<bool> {
-    use roc_gen_llvm::try_run_jit_function;
+    options: ExpectRunOptions,
+) -> std::io::Result<Option<ExpectFailure>> {
+    use crate::property::{self, Rng, ShapeBuilder};
 
-    let sequence = ExpectSequence::new(shared_memory.ptr.cast());
+    let seed = options.seed;
 
-    let result: Result<(), (String, _)> = try_run_jit_function!(lib, expect.name, (), |v: ()| v);
+    // we always run programs as the host
+    let target = target_lexicon::Triple::host().into();
 
-    let shared_memory_ptr: *const u8 = shared_memory.ptr.cast();
+    let module_id = expect.symbol.module_id();
+    let data = expectations.get(&module_id).unwrap();
+    let source = std::fs::read_to_string(&data.path).unwrap();
+    let filename = data.path.clone();
 
-    if result.is_err() || sequence.count_failures() > 0 {
-        let module_id = expect.symbol.module_id();
-        let data = expectations.get_mut(&module_id).unwrap();
+    let argument_vars: Vec<_> = expect.arguments.iter().map(|(_, var)| *var).collect();
+    let mut layout_cache = LayoutCache::new(layout_interner.fork(), target);
+    let mut shape_builder = ShapeBuilder {
+        arena,
+        subs: &data.subs,
+        interns,
+        layout_cache: &mut layout_cache,
+    };
+    let shape = shape_builder.arguments(&argument_vars);
 
-        let path = &data.path;
-        let filename = data.path.to_owned();
-        let source = std::fs::read_to_string(path).unwrap();
+    let mut report: Vec<u8> = Vec::new();
 
-        let renderer = Renderer::new(arena, interns, render_target, module_id, filename, &source);
+    let (shape, stack_size) = match shape {
+        Ok(shape) => shape,
+        Err(description) => {
+            for (target, mut out) in
+                failure_outputs(&mut *writer, render_target, &mut report, options)
+            {
+                let renderer =
+                    Renderer::new(arena, interns, target, module_id, filename.clone(), &source);
+                renderer.render_unsupported_property(&mut out, &description, expect.region)?;
 
-        if let Err((roc_panic_message, _roc_panic_tag)) = result {
-            renderer.render_panic(writer, &roc_panic_message, expect.region)?;
-        } else {
-            let mut offset = ExpectSequence::START_OFFSET;
+                writeln!(out)?;
+            }
 
-            for _ in 0..sequence.count_failures() {
-                offset = render_expect_failure(
-                    writer,
-                    &renderer,
-                    arena,
-                    Some(expect),
-                    expectations,
-                    interns,
-                    layout_interner,
-                    shared_memory_ptr,
-                    offset,
-                )?;
+            return Ok(Some(ExpectFailure {
+                kind: ExpectFailureKind::Failed,
+                report: String::from_utf8_lossy(&report).into_owned(),
+            }));
+        }
+    };
+
+    let shared_memory_ptr = runner.memory.ptr;
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+
+    let mut run = |value: &property::Value| -> std::io::Result<PropertyRun> {
+        let sequence = ExpectSequence::new(shared_memory_ptr);
+
+        let run = runner.run_with_arguments(expect.name, &shape, value, stack_size, deadline)?;
+
+        Ok(match run {
+            ExpectRun::Returned => match sequence.count_failures() {
+                0 => PropertyRun::Passed,
+                failure_count => PropertyRun::Failed(failure_count),
+            },
+            ExpectRun::Panicked(roc_panic_message) => PropertyRun::Panicked(roc_panic_message),
+            ExpectRun::Crashed(description) => PropertyRun::Crashed(description),
+            ExpectRun::TimedOut => PropertyRun::TimedOut,
+        })
+    };
+
+    // every expect gets its own inputs, but they are the same in every run with this seed
+    let mut rng = Rng::new(seed ^ u64::from(expect.region.start().offset));
+    let mut failing = None;
+
+    for index in 0..property::RUNS {
+        let value = shape.generate(&mut rng, property::size_for_run(index));
+
+        match run(&value)? {
+            PropertyRun::Passed => {}
+            outcome => {
+                failing = Some((index + 1, value, outcome));
+                break;
             }
         }
+    }
 
-        writeln!(writer)?;
+    let Some((runs, value, first_outcome)) = failing else {
+        return Ok(None);
+    };
 
-        Ok(false)
-    } else {
-        Ok(true)
+    let (value, shrinks) = match first_outcome {
+        // there's no time left to run it with simpler arguments
+        PropertyRun::TimedOut => (value, 0),
+        _ => {
+            // the runner only fails if its process is gone, so every later run would fail too
+            let mut runner_error = None;
+
+            let shrunk = shape.shrink_failure(value, |value| match run(value) {
+                Ok(outcome) => !matches!(outcome, PropertyRun::Passed | PropertyRun::TimedOut),
+                Err(error) => {
+                    runner_error.get_or_insert(error);
+                    false
+                }
+            });
+
+            if let Some(error) = runner_error {
+                return Err(error);
+            }
+
+            shrunk
+        }
+    };
+
+    // run it once more, so the shared memory holds the values for the simplest arguments
+    let outcome = run(&value)?;
+
+    let data = expectations.get(&module_id).unwrap();
+
+    let panic_lookups = match &outcome {
+        PropertyRun::Panicked(_) => {
+            let named_arguments = expect
+                .arguments
+                .iter()
+                .enumerate()
+                .filter_map(|(index, (name, var))| name.map(|name| (name, (index, *var))));
+            let (symbols, lookups): (Vec<_>, Vec<_>) = named_arguments.unzip();
+
+            let mut buffer = property::arguments_as_lookups(&shape, &value, stack_size, &lookups);
+            let (_, expressions, variables) = crate::get_values(
+                target,
+                arena,
+                &data.subs,
+                interns,
+                layout_interner,
+                buffer.as_mut_ptr(),
+                0,
+                lookups.len(),
+            );
+
+            Some((symbols, variables, expressions))
+        }
+        _ => None,
+    };
+
+    let plural = |count: usize, word: &str| match count {
+        1 => format!("{count} {word}"),
+        _ => format!("{count} {word}s"),
+    };
+    let note = format!(
+        "I found these arguments after {}, and simplified them in {}. Run `roc test --seed {seed}` to get the same arguments again.",
+        plural(runs, "run"),
+        plural(shrinks, "step"),
+    );
+
+    for (target, mut out) in failure_outputs(&mut *writer, render_target, &mut report, options) {
+        let renderer = Renderer::new(arena, interns, target, module_id, filename.clone(), &source);
+
+        match (&outcome, &panic_lookups) {
+            (PropertyRun::Panicked(message), Some((symbols, variables, expressions))) => {
+                renderer.render_property_panic(
+                    &mut out,
+                    &mut expectations.get_mut(&module_id).unwrap().subs,
+                    message,
+                    symbols,
+                    variables,
+                    expressions,
+                    expect.region,
+                )?;
+            }
+            (PropertyRun::Failed(failure_count), _) => {
+                let mut offset = ExpectSequence::START_OFFSET;
+
+                for _ in 0..*failure_count {
+                    offset = render_expect_failure(
+                        &mut out,
+                        &renderer,
+                        arena,
+                        Some(expect),
+                        expectations,
+                        interns,
+                        layout_interner,
+                        shared_memory_ptr,
+                        offset,
+                    )?;
+                }
+            }
+            (PropertyRun::Crashed(description), _) => {
+                renderer.render_crash(&mut out, description, expect.region)?;
+            }
+            (PropertyRun::TimedOut, _) => {
+                // only an expect with a timeout can run out of time
+                let timeout = options.timeout.unwrap_or_default();
+                renderer.render_timeout(&mut out, timeout, expect.region)?;
+            }
+            _ => {
+                // only flaky properties get here, since these arguments failed before
+                renderer.render_crash(
+                    &mut out,
+                    "It failed for these arguments before, but passed when I ran it again.",
+                    expect.region,
+                )?;
+            }
+        }
+
+        if !matches!(outcome, PropertyRun::TimedOut) {
+            writeln!(out, "{note}")?;
+        }
+        writeln!(out)?;
     }
+
+    let kind = match outcome {
+        PropertyRun::Panicked(_) | PropertyRun::Crashed(_) => ExpectFailureKind::Crashed,
+        PropertyRun::TimedOut => ExpectFailureKind::TimedOut,
+        _ => ExpectFailureKind::Failed,
+    };
+
+    Ok(Some(ExpectFailure {
+        kind,
+        report: String::from_utf8_lossy(&report).into_owned(),
+    }))
 }
 
-#[allow(clippy::too_many_arguments)]
-fn run_expect_fx<'a, W: std::io::Write>(
-    writer: &mut W,
+/// Failures are always rendered to the writer, and also to the plain-text report if
+/// it will be used.
+fn failure_outputs<'w>(
+    writer: &'w mut dyn std::io::Write,
     render_target: RenderTarget,
-    arena: &'a Bump,
-    interns: &'a Interns,
-    layout_interner: &GlobalLayoutInterner<'a>,
-    lib: &libloading::Library,
-    expectations: &mut VecMap<ModuleId, Expectations>,
-    parent_memory: &mut ExpectMemory,
-    expect: ToplevelExpect<'_>,
-) -> std::io::Result<bool> {
-    use signal_hook::{consts::signal::SIGCHLD, consts::signal::SIGUSR1, iterator::Signals};
+    report: &'w mut Vec<u8>,
+    options: ExpectRunOptions,
+) -> Vec<(RenderTarget, &'w mut dyn std::io::Write)> {
+    let mut outputs = vec![(render_target, writer)];
 
-    let mut signals = Signals::new([SIGCHLD, SIGUSR1]).unwrap();
+    if options.collect_reports {
+        outputs.push((RenderTarget::Generic, report as &mut dyn std::io::Write));
+    }
 
-    match unsafe { libc::fork() } {
-        0 => unsafe {
-            // we are the child
+    outputs
+}
+
+enum ChildExit {
+    Exited(i32),
+    Signaled(i32),
+    TimedOut,
+}
 
-            use roc_gen_llvm::try_run_jit_function;
+impl ChildExit {
+    const EXITED: u8 = 0;
+    const SIGNALED: u8 = 1;
+    const TIMED_OUT: u8 = 2;
+
+    /// How the server tells the runner how a child exited, followed by what the child sent
+    fn encode(&self, message: &[u8]) -> Vec<u8> {
+        let (tag, code) = match self {
+            ChildExit::Exited(code) => (Self::EXITED, *code),
+            ChildExit::Signaled(signal) => (Self::SIGNALED, *signal),
+            ChildExit::TimedOut => (Self::TIMED_OUT, 0),
+        };
 
-            let mut child_memory = parent_memory.reuse_mmap().unwrap();
+        let mut bytes = Vec::with_capacity(1 + 4 + message.len());
+        bytes.push(tag);
+        bytes.extend_from_slice(&code.to_le_bytes());
+        bytes.extend_from_slice(message);
 
-            let sequence = ExpectSequence::new(child_memory.ptr);
+        bytes
+    }
 
-            child_memory.set_shared_buffer(lib);
+    fn decode(bytes: &[u8]) -> Option<(Self, &[u8])> {
+        let (&tag, rest) = bytes.split_first()?;
+        let code = i32::from_le_bytes(rest.get(..4)?.try_into().ok()?);
+
+        let exit = match tag {
+            Self::EXITED => ChildExit::Exited(code),
+            Self::SIGNALED => ChildExit::Signaled(code),
+            Self::TIMED_OUT => ChildExit::TimedOut,
+            _ => return None,
+        };
+
+        Some((exit, &rest[4..]))
+    }
+}
 
-            let result: Result<(), (String, _)> =
-                try_run_jit_function!(lib, expect.name, (), |v: ()| v);
+/// Read everything the child sends, then reap it. Kills the child if it runs past the timeout.
+///
+/// Anything the child spawned can inherit its end of the pipe and keep it open after the
+/// child is gone, so instead of only waiting for the pipe to close, we also check every so
+/// often whether the child has exited.
+fn wait_for_child(
+    pid: libc::pid_t,
+    mut pipe: std::fs::File,
+    timeout: Option<Duration>,
+) -> std::io::Result<(Vec<u8>, ChildExit)> {
+    use std::io::Read;
+    use std::os::fd::AsRawFd;
+
+    const EXIT_CHECK_INTERVAL_MS: i32 = 50;
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut bytes = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let fd = pipe.as_raw_fd();
+    let poll = |timeout_ms: i32| {
+        let mut poll_fd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
 
-            if let Err((msg, _)) = result {
-                internal_error!("roc panic {msg}");
+        match unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) } {
+            -1 => {
+                let error = std::io::Error::last_os_error();
+                match error.kind() {
+                    std::io::ErrorKind::Interrupted => Ok(false),
+                    _ => Err(error),
+                }
             }
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    };
+
+    loop {
+        let poll_timeout_ms = match deadline {
+            None => EXIT_CHECK_INTERVAL_MS,
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    unsafe { libc::kill(pid, libc::SIGKILL) };
+                    reap(pid)?;
 
-            if sequence.count_failures() > 0 {
-                libc::kill(parent_id() as _, SIGUSR1);
+                    return Ok((bytes, ChildExit::TimedOut));
+                }
+
+                remaining
+                    .as_millis()
+                    .clamp(1, EXIT_CHECK_INTERVAL_MS as u128) as i32
             }
+        };
 
-            std::process::exit(0)
-        },
+        if poll(poll_timeout_ms)? {
+            match pipe.read(&mut chunk)? {
+                0 => break,
+                n => bytes.extend_from_slice(&chunk[..n]),
+            }
+        } else if let Some(exit) = try_reap(pid)? {
+            // it may have written its last bytes just before exiting
+            while poll(0)? {
+                match pipe.read(&mut chunk)? {
+                    0 => break,
+                    n => bytes.extend_from_slice(&chunk[..n]),
+                }
+            }
+
+            return Ok((bytes, exit));
+        }
+    }
+
+    let exit = reap(pid)?;
+
+    Ok((bytes, exit))
+}
+
+fn reap(pid: libc::pid_t) -> std::io::Result<ChildExit> {
+    let mut status = 0;
+
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } != -1 {
+            break;
+        }
+
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    Ok(child_exit(status))
+}
+
+/// Like `reap`, but returns `None` right away if the child is still running.
+fn try_reap(pid: libc::pid_t) -> std::io::Result<Option<ChildExit>> {
+    let mut status = 0;
+
+    match unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } {
+        0 => Ok(None),
         -1 => {
-            // something failed
+            let error = std::io::Error::last_os_error();
+            match error.kind() {
+                std::io::ErrorKind::Interrupted => Ok(None),
+                _ => Err(error),
+            }
+        }
+        _ => Ok(Some(child_exit(status))),
+    }
+}
+
+fn child_exit(status: libc::c_int) -> ChildExit {
+    if libc::WIFSIGNALED(status) {
+        ChildExit::Signaled(libc::WTERMSIG(status))
+    } else {
+        ChildExit::Exited(libc::WEXITSTATUS(status))
+    }
+}
 
-            // Display a human-friendly error message
-            println!("Error {:?}", std::io::Error::last_os_error());
+fn describe_signal(signal: i32) -> String {
+    let name = match signal {
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        _ => return format!("The process running it was killed by signal {signal}."),
+    };
 
-            std::process::exit(1)
+    let hint = match signal {
+        libc::SIGSEGV | libc::SIGBUS | libc::SIGABRT => {
+            " This is often caused by a stack overflow, for example from infinite recursion."
         }
-        1.. => {
-            let mut has_succeeded = true;
+        _ => "",
+    };
 
-            for sig in &mut signals {
-                match sig {
-                    SIGCHLD => {
-                        // done!
-                        return Ok(has_succeeded);
-                    }
-                    SIGUSR1 => {
-                        // this is the signal we use for an expect failure. Let's see what the child told us
-                        has_succeeded = false;
-
-                        let frame =
-                            ExpectFrame::at_offset(parent_memory.ptr, ExpectSequence::START_OFFSET);
-                        let module_id = frame.module_id;
-
-                        let data = expectations.get_mut(&module_id).unwrap();
-                        let filename = data.path.to_owned();
-                        let source = std::fs::read_to_string(&data.path).unwrap();
-
-                        let renderer = Renderer::new(
-                            arena,
-                            interns,
-                            render_target,
-                            module_id,
-                            filename,
-                            &source,
-                        );
-
-                        render_expect_failure(
-                            writer,
-                            &renderer,
-                            arena,
-                            None,
-                            expectations,
-                            interns,
-                            layout_interner,
-                            parent_memory.ptr,
-                            ExpectSequence::START_OFFSET,
-                        )?;
+    format!("The process running it was killed by signal {signal} ({name}).{hint}")
+}
+
+/// The server loop. It exits when a request can't be read, e.g. because the runner is gone.
+fn serve(
+    mut requests: std::fs::File,
+    mut responses: std::fs::File,
+    memory: &mut ExpectMemory,
+    arguments: *mut u8,
+) -> ! {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut lib = None;
+
+    loop {
+        let Ok(request) = read_frame(&mut requests) else {
+            unsafe { libc::_exit(0) }
+        };
+
+        let response = match request.split_first() {
+            Some((&ExpectRunner::LOAD, path)) => {
+                let path = std::ffi::OsStr::from_bytes(path);
+
+                match unsafe { libloading::Library::new(path) } {
+                    Ok(loaded) => {
+                        // the children inherit this, so it only needs to happen once
+                        memory.set_shared_buffer(&loaded);
+                        lib = Some(loaded);
+
+                        Vec::new()
                     }
-                    _ => println!("received signal {sig}"),
+                    Err(error) => error.to_string().into_bytes(),
+                }
+            }
+            Some((&ExpectRunner::RUN, [with_arguments, rest @ ..])) if rest.len() >= 8 => {
+                let (timeout_ms, name) = rest.split_at(8);
+                let timeout = match u64::from_le_bytes(timeout_ms.try_into().unwrap()) {
+                    u64::MAX => None,
+                    timeout_ms => Some(Duration::from_millis(timeout_ms)),
+                };
+                let arguments = (*with_arguments != 0).then_some(arguments);
+
+                let (Some(lib), Ok(name)) = (&lib, std::str::from_utf8(name)) else {
+                    unsafe { libc::_exit(1) }
+                };
+
+                match run_in_child(lib, name, arguments, timeout) {
+                    Ok((message, exit)) => exit.encode(&message),
+                    Err(_) => unsafe { libc::_exit(1) },
                 }
             }
+            _ => unsafe { libc::_exit(1) },
+        };
+
+        if write_frame(&mut responses, &response).is_err() {
+            unsafe { libc::_exit(0) }
+        }
+    }
+}
+
+/// Forks, and calls the expect in the child, which sends back whether it returned or panicked.
+/// If it does neither, it crashed or ran out of time.
+fn run_in_child(
+    lib: &libloading::Library,
+    name: &str,
+    arguments: Option<*mut u8>,
+    timeout: Option<Duration>,
+) -> std::io::Result<(Vec<u8>, ChildExit)> {
+    use roc_gen_llvm::try_run_jit_function;
+
+    let (pipe, mut child_pipe) = cloexec_pipe()?;
+
+    match unsafe { libc::fork() } {
+        0 => {
+            // we are the child
+            drop(pipe);
+
+            let result: Result<(), (String, _)> = match arguments {
+                None => try_run_jit_function!(lib, name, (), |v: ()| v),
+                Some(arguments) => {
+                    let property_fn = run_roc_dylib!(lib, name, *mut u8, ());
+                    let mut result = RocCallResult::default();
+                    unsafe { property_fn(arguments, &mut result) };
+
+                    result.into()
+                }
+            };
+
+            let message = match result {
+                Ok(()) => vec![ExpectRun::RETURNED],
+                Err((roc_panic_message, _roc_panic_tag)) => {
+                    let mut message = vec![ExpectRun::PANICKED];
+                    message.extend_from_slice(roc_panic_message.as_bytes());
+                    message
+                }
+            };
+
+            let _ = child_pipe.write_all(&message);
+
+            // Skip atexit handlers and destructors; those belong to the server.
+            unsafe { libc::_exit(0) }
+        }
+        -1 => Err(std::io::Error::last_os_error()),
+        pid => {
+            drop(child_pipe);
+
+            wait_for_child(pid, pipe, timeout)
+        }
+    }
+}
+
+/// A pipe that processes we spawn (like the linker) don't inherit. Returns the end to read
+/// from, then the end to write to.
+fn cloexec_pipe() -> std::io::Result<(std::fs::File, std::fs::File)> {
+    use std::os::fd::FromRawFd;
+
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
 
-            Ok(true)
+    let [read_fd, write_fd] = fds;
+    let (reader, writer) = unsafe {
+        (
+            std::fs::File::from_raw_fd(read_fd),
+            std::fs::File::from_raw_fd(write_fd),
+        )
+    };
+
+    for fd in fds {
+        if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+            return Err(std::io::Error::last_os_error());
         }
-        _ => unreachable!(),
     }
+
+    Ok((reader, writer))
+}
+
+/// Requests and responses between an [ExpectRunner] and its server are prefixed with their length.
+fn write_frame(file: &mut std::fs::File, payload: &[u8]) -> std::io::Result<()> {
+    file.write_all(&(payload.len() as u64).to_le_bytes())?;
+    file.write_all(payload)
+}
+
+fn read_frame(file: &mut std::fs::File) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut length = [0u8; 8];
+    file.read_exact(&mut length)?;
+
+    let mut payload = vec![0u8; u64::from_le_bytes(length) as usize];
+    file.read_exact(&mut payload)?;
+
+    Ok(payload)
 }
 
 pub fn render_expects_in_memory<'a>(
@@ -602,6 +1284,83 @@ pub struct ToplevelExpect<'a> {
     pub name: &'a str,
     pub symbol: Symbol,
     pub region: Region,
+    /// The closest top-level def above this expect in the source
+    pub def_name: Option<Symbol>,
+    /// Property expects take arguments, which `roc test` generates. Each has its name, unless
+    /// it is destructured, and its type.
+    pub arguments: &'a [(Option<Symbol>, Variable)],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExpectRunOptions {
+    /// Stop after the first expect that fails
+    pub fail_fast: bool,
+    /// Kill an expect that runs for longer than this. `None` lets it run forever.
+    pub timeout: Option<Duration>,
+    /// Where the random arguments of property expects come from
+    pub seed: u64,
+    /// Also render each failure as plain text, for machine-readable reports
+    pub collect_reports: bool,
+}
+
+impl ExpectRunOptions {
+    /// An expect that runs this long is almost certainly stuck in an infinite loop
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+}
+
+impl Default for ExpectRunOptions {
+    fn default() -> Self {
+        Self {
+            fail_fast: false,
+            timeout: Some(Self::DEFAULT_TIMEOUT),
+            seed: 0,
+            collect_reports: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectFailureKind {
+    /// The expectation was false
+    Failed,
+    /// The expect panicked, hit a `crash`, or its process died
+    Crashed,
+    /// The expect was still running when the timeout ran out
+    TimedOut,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpectFailure {
+    pub kind: ExpectFailureKind,
+    /// The failure report rendered with [RenderTarget::Generic], if
+    /// [ExpectRunOptions::collect_reports] was set. Otherwise it's empty.
+    pub report: String,
+}
+
+/// The result of running one top-level expect, for machine-readable test reports
+#[derive(Debug, Clone)]
+pub struct ExpectOutcome {
+    pub symbol: Symbol,
+    pub region: Region,
+    pub def_name: Option<Symbol>,
+    pub duration: Duration,
+    pub failure: Option<ExpectFailure>,
+}
+
+impl ExpectOutcome {
+    fn new(expect: ToplevelExpect<'_>, duration: Duration, failure: Option<ExpectFailure>) -> Self {
+        Self {
+            symbol: expect.symbol,
+            region: expect.region,
+            def_name: expect.def_name,
+            duration,
+            failure,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
 }
 
 #[derive(Debug)]
@@ -616,14 +1375,11 @@ pub fn expect_mono_module_to_dylib<'a>(
     loaded: MonomorphizedModule<'a>,
     opt_level: OptLevel,
     mode: LlvmBackendMode,
-) -> Result<
-    (
-        libloading::Library,
-        MutMap<ModuleId, ExpectFunctions<'a>>,
-        STLayoutInterner<'a>,
-    ),
-    libloading::Error,
-> {
+) -> (
+    ExpectDylib,
+    MutMap<ModuleId, ExpectFunctions<'a>>,
+    STLayoutInterner<'a>,
+) {
     let MonomorphizedModule {
         toplevel_expects,
         procedures,
@@ -663,6 +1419,14 @@ pub fn expect_mono_module_to_dylib<'a>(
     // platform to provide them.
     add_default_roc_externs(&env);
 
+    // property expects take arguments, all other expects are thunks
+    let argument_layouts = |symbol: Symbol| {
+        procedures
+            .keys()
+            .find(|(proc_symbol, _)| *proc_symbol == symbol)
+            .map_or(&[][..], |(_, proc_layout)| proc_layout.arguments)
+    };
+
     let expects_symbols = toplevel_expects
         .iter()
         .map(|(module_id, expects)| {
@@ -672,8 +1436,8 @@ pub fn expect_mono_module_to_dylib<'a>(
                     expects
                         .pure
                         .keys()
-                        .copied()
-                        .chain(expects.fx.keys().copied()),
+                        .chain(expects.fx.keys())
+                        .map(|symbol| (*symbol, argument_layouts(*symbol))),
                     env.arena,
                 ),
             )
@@ -702,6 +1466,8 @@ pub fn expect_mono_module_to_dylib<'a>(
                     symbol,
                     region,
                     name,
+                    def_name: expects.def_names.get(&symbol).copied(),
+                    arguments: &[],
                 }),
             env.arena,
         );
@@ -713,6 +1479,11 @@ pub fn expect_mono_module_to_dylib<'a>(
                         symbol,
                         region,
                         name,
+                        def_name: expects.def_names.get(&symbol).copied(),
+                        arguments: expects
+                            .property_arguments
+                            .get(&symbol)
+                            .map_or(&[][..], |arguments| env.arena.alloc_slice_copy(arguments)),
                     },
                 ),
                 env.arena,
@@ -750,6 +1521,11 @@ pub fn expect_mono_module_to_dylib<'a>(
         env.module.print_to_file(path).unwrap();
     }
 
-    llvm_module_to_dylib(env.module, target, opt_level)
-        .map(|dy_lib| (dy_lib, modules_expects, layout_interner))
+    let (dir, path) = llvm_module_to_dylib_file(env.module, target, opt_level);
+
+    (
+        ExpectDylib { path, _dir: dir },
+        modules_expects,
+        layout_interner,
+    )
 }
@@ -145,6 +145,212 @@ fn standalone_annotation() {
     assert!(matches!(action, ReplAction::Nothing));
 }
 
+#[test]
+fn import_from_package() {
+    let package_dir = tempfile::tempdir().unwrap();
+    let root_module = package_dir.path().join("main.roc");
+
+    std::fs::write(&root_module, "package [Foo] {}\n").unwrap();
+    std::fs::write(package_dir.path().join("Foo.roc"), "module [x]\n\nx = 42\n").unwrap();
+
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    let action = state.step(&arena, "import pkg.Foo", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Problem(_)));
+
+    let declare = format!(":package pkg \"{}\"", root_module.display());
+    let action = state.step(&arena, &declare, target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::PackagesAdded { .. }));
+
+    let action = state.step(&arena, "import pkg.Bar", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::FileProblem { .. }));
+
+    let action = state.step(&arena, "import pkg.Foo", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete("Foo.x + 1", &mut state, "43 : Num *");
+}
+
+#[test]
+fn type_defs_and_reset() {
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    complete("x = 5", &mut state, "5 : Num *");
+
+    match state.step(&arena, ":type x + 1", target, DEFAULT_PALETTE) {
+        ReplAction::Type {
+            opt_output: Some(output),
+            problems,
+        } => {
+            assert!(problems.is_empty());
+            assert_eq!(output.expr, "x + 1");
+            assert_eq!(output.expr_type, "Num *");
+        }
+        action => panic!("Unexpected action: {:?}", action),
+    }
+
+    match state.step(&arena, ":defs", target, DEFAULT_PALETTE) {
+        ReplAction::Defs(defs) => assert_eq!(defs, ["x = 5"]),
+        action => panic!("Unexpected action: {:?}", action),
+    }
+
+    let action = state.step(&arena, ":reset", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    match state.step(&arena, ":defs", target, DEFAULT_PALETTE) {
+        ReplAction::Defs(defs) => assert!(defs.is_empty()),
+        action => panic!("Unexpected action: {:?}", action),
+    };
+}
+
+#[test]
+fn tab_completion() {
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+    let record = "record = { name: \"Roc\", nested: { count: 1, color: Red } }";
+    let action = state.step(&arena, record, target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Eval { .. }));
+
+    assert_eq!(state.completions("rec", target), ["record"]);
+    assert_eq!(
+        state.completions("Str.con", target),
+        ["Str.concat", "Str.contains"]
+    );
+    assert_eq!(
+        state.completions("record.nested.co", target),
+        ["record.nested.color", "record.nested.count"]
+    );
+    assert_eq!(state.completions(":ty", target), [":type"]);
+}
+
+#[test]
+fn load_and_reload() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("Double.roc");
+
+    std::fs::write(&file, "module [double]\n\ndouble = \\n -> n * 2\n").unwrap();
+
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    let action = state.step(&arena, ":reload", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Problem(_)));
+
+    let load = format!(":load {}", file.display());
+    match state.step(&arena, &load, target, DEFAULT_PALETTE) {
+        ReplAction::Loaded {
+            def_count,
+            problems,
+            ..
+        } => {
+            assert_eq!(def_count, 1);
+            assert!(problems.is_empty());
+        }
+        action => panic!("Unexpected action: {:?}", action),
+    };
+
+    complete("double 21", &mut state, "42 : Num *");
+
+    // Reloading replaces the defs from last time, rather than adding to them
+    std::fs::write(&file, "module [double]\n\ndouble = \\n -> n * 3\n").unwrap();
+
+    let action = state.step(&arena, ":reload", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Loaded { def_count: 1, .. }));
+
+    complete("double 21", &mut state, "63 : Num *");
+
+    std::fs::write(&file, "module [double]\n\ndouble = \\n -> n *\n").unwrap();
+
+    match state.step(&arena, ":reload", target, DEFAULT_PALETTE) {
+        ReplAction::Problem(report) => assert!(report.contains("Double.roc"), "{report}"),
+        action => panic!("Unexpected action: {:?}", action),
+    };
+}
+
+#[test]
+fn load_file_with_imports() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("Main.roc");
+
+    std::fs::write(dir.path().join("Num2.roc"), "module [two]\n\ntwo = 2\n").unwrap();
+    std::fs::write(dir.path().join("greeting.txt"), "hi").unwrap();
+    std::fs::write(
+        &file,
+        indoc!(
+            r#"
+            module [four]
+
+            import Num2
+            import "greeting.txt" as greeting : Str
+
+            four = Num2.two * 2
+            "#
+        ),
+    )
+    .unwrap();
+
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    // The imports are next to the file, not in the current dir
+    let load = format!(":load {}", file.display());
+    match state.step(&arena, &load, target, DEFAULT_PALETTE) {
+        ReplAction::Loaded { problems, .. } => assert!(problems.is_empty(), "{problems:?}"),
+        action => panic!("Unexpected action: {:?}", action),
+    };
+
+    complete("four", &mut state, "4 : Num *");
+    complete("greeting", &mut state, "\"hi\" : Str");
+}
+
+#[test]
+fn load_file_keeps_earlier_imports() {
+    let modules_dir = tempfile::tempdir().unwrap();
+    let other_dir = tempfile::tempdir().unwrap();
+    let setup = modules_dir.path().join("Setup.roc");
+    let other = other_dir.path().join("Other.roc");
+
+    std::fs::write(
+        modules_dir.path().join("Num2.roc"),
+        "module [two]\n\ntwo = 2\n",
+    )
+    .unwrap();
+    std::fs::write(&setup, "one = 1\n").unwrap();
+    std::fs::write(&other, "three = 3\n").unwrap();
+
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    for (input, expected_def_count) in [
+        (format!(":load {}", setup.display()), Some(1)),
+        ("import Num2".to_string(), None),
+        (format!(":load {}", other.display()), Some(1)),
+    ] {
+        match state.step(&arena, &input, target, DEFAULT_PALETTE) {
+            ReplAction::Loaded {
+                def_count,
+                problems,
+                ..
+            } if Some(def_count) == expected_def_count => {
+                assert!(problems.is_empty(), "{problems:?}")
+            }
+            ReplAction::Nothing if expected_def_count.is_none() => {}
+            action => panic!("Unexpected action: {:?}", action),
+        };
+    }
+
+    // Num2 is still the one next to Setup.roc, not next to the last file loaded
+    complete("Num2.two + three", &mut state, "5 : Num *");
+}
+
 /// validate and step the given input, then check the Result vs the output
 /// with ANSI escape codes stripped.
 fn complete(input: &str, state: &mut ReplState, expected_start: &str) {
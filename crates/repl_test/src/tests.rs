@@ -1590,6 +1590,12 @@ fn interpolation_with_nested_interpolation() {
 
                   - ctrl-v + ctrl-j makes a newline
                   - :q quits
+                  - tab completes names, module members and record fields
+                  - :type 1 + 1 shows an expression's type without evaluating it
+                  - :defs lists the definitions so far, and :reset forgets them
+                  - :package json "https://…" lets you import modules from a package, like import json.Json
+                  - :package path/to/main.roc does that for every package of an app
+                  - :load path/to/File.roc adds the definitions in a file, and :reload reads it again after you edit it
                   - :help shows this text again
             "#
         ),
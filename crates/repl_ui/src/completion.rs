@@ -0,0 +1,106 @@
+//! Tab completion, from the type-checked defs entered so far. This uses the same completion
+//! as the language server, so it offers the defs in scope, the members of modules, and the
+//! fields of records.
+use roc_completion::{completion_kind, field_completions, get_completions, CompletionKind};
+use roc_load::LoadedModule;
+use roc_region::all::Position;
+
+/// The def that the REPL's app module provides, which the user never wrote
+const REPL_OUTPUT: &str = "replOutput";
+
+/// Everything that could come next for the given word, like `Str.concat` for `Str.con`
+pub fn completions(loaded: &mut LoadedModule, word: &str) -> Vec<String> {
+    let mut completions = match completion_kind(word) {
+        CompletionKind::Module => module_completions(loaded, word),
+        CompletionKind::RecordField => record_field_completions(loaded, word),
+        CompletionKind::Variable => variable_completions(loaded, word),
+    };
+
+    completions.sort();
+    completions.dedup();
+
+    completions
+}
+
+/// The end of the REPL's module, which is in the def the REPL provides, after all the others
+fn end_position(loaded: &LoadedModule) -> Position {
+    let src_len = loaded
+        .sources
+        .get(&loaded.module_id)
+        .map_or(0, |(_, src)| src.len());
+
+    Position::new(src_len as u32)
+}
+
+fn variable_completions(loaded: &LoadedModule, word: &str) -> Vec<String> {
+    let Some(decls) = loaded.declarations_by_id.get(&loaded.module_id) else {
+        return Vec::new();
+    };
+
+    get_completions(
+        end_position(loaded),
+        decls,
+        word.to_string(),
+        &loaded.interns,
+    )
+    .into_iter()
+    .map(|(symbol, _)| symbol.as_str(&loaded.interns).to_string())
+    .filter(|name| name != REPL_OUTPUT)
+    .collect()
+}
+
+/// Like the language server, offer the modules whose names start with the word,
+/// or if the word is like `Str.con`, what that module exposes
+fn module_completions(loaded: &LoadedModule, word: &str) -> Vec<String> {
+    let interns = &loaded.interns;
+
+    loaded
+        .exposes
+        .iter()
+        .filter(|(module_id, _)| **module_id != loaded.module_id)
+        .flat_map(|(module_id, exposed)| {
+            let module_name = interns.module_name(*module_id).as_str();
+
+            if module_name.starts_with(word) {
+                return vec![module_name.to_string()];
+            }
+
+            match word
+                .strip_prefix(module_name)
+                .and_then(|rest| rest.strip_prefix('.'))
+            {
+                Some(member) => exposed
+                    .iter()
+                    .map(|(symbol, _)| symbol.as_str(interns))
+                    .filter(|name| name.starts_with(member))
+                    .map(|name| format!("{module_name}.{name}"))
+                    .collect(),
+                None => Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// For `rec.a.b`, the fields of `rec.a` that start with `b`
+fn record_field_completions(loaded: &mut LoadedModule, word: &str) -> Vec<String> {
+    let position = end_position(loaded);
+    let Some((path, _)) = word.rsplit_once('.') else {
+        return Vec::new();
+    };
+    let module_id = loaded.module_id;
+    let LoadedModule {
+        declarations_by_id,
+        interns,
+        solved,
+        ..
+    } = loaded;
+    let Some(decls) = declarations_by_id.get(&module_id) else {
+        return Vec::new();
+    };
+
+    field_completions(position, word, decls, interns, solved.inner_mut())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, _)| format!("{path}.{name}"))
+        .collect()
+}
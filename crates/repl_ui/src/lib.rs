@@ -1,6 +1,8 @@
 //! UI functionality, shared between CLI and web, for the Read-Evaluate-Print-Loop (REPL).
 // We don't do anything here related to the terminal (doesn't exist on the web) or LLVM (too big for the web).
 pub mod colors;
+mod completion;
+pub mod packages;
 pub mod repl_state;
 
 use bumpalo::Bump;
@@ -10,6 +12,7 @@ use repl_state::{parse_src, ParseOutcome};
 use roc_parse::ast::{Expr, ExtractSpaces, ValueDef};
 use roc_repl_eval::gen::{Problems, ReplOutput};
 use roc_reporting::report::StyleCodes;
+use std::path::Path;
 
 // TODO add link to repl tutorial (does not yet exist).
 pub const TIPS: &str = concatcp!(
@@ -45,6 +48,60 @@ pub const TIPS: &str = concatcp!(
             "  - ",
             END_COL,
             GREEN,
+            "tab",
+            END_COL,
+            " completes names, module members and record fields\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":type 1 + 1",
+            END_COL,
+            " shows an expression's type without evaluating it\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":defs",
+            END_COL,
+            " lists the definitions so far, and ",
+            GREEN,
+            ":reset",
+            END_COL,
+            " forgets them\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":package json \"https://…\"",
+            END_COL,
+            " lets you import modules from a package, like ",
+            CYAN,
+            "import json.Json",
+            END_COL,
+            "\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":package path/to/main.roc",
+            END_COL,
+            " does that for every package of an app\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
+            ":load path/to/File.roc",
+            END_COL,
+            " adds the definitions in a file, and ",
+            GREEN,
+            ":reload",
+            END_COL,
+            " reads it again after you edit it\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
             ":help",
             END_COL,
             " shows this text again\n",
@@ -84,10 +141,61 @@ pub fn is_incomplete(input: &str) -> bool {
                 false
             }
         }
-        ParseOutcome::Empty | ParseOutcome::Help | ParseOutcome::Exit | ParseOutcome::SyntaxErr => {
-            false
-        }
+        ParseOutcome::Empty
+        | ParseOutcome::Help
+        | ParseOutcome::Exit
+        | ParseOutcome::Type(_)
+        | ParseOutcome::Defs
+        | ParseOutcome::Reset
+        | ParseOutcome::Package(_)
+        | ParseOutcome::Load(_)
+        | ParseOutcome::Reload
+        | ParseOutcome::SyntaxErr => false,
+    }
+}
+
+/// What to say after `:package` declared some packages
+pub fn format_packages_added(shorthands: &[String], skipped_platform: Option<&str>) -> String {
+    let mut buf = match shorthands {
+        [] => "There were no packages to declare.".to_string(),
+        [shorthand] => format!(
+            "You can import modules from {shorthand} now, like {CYAN}import {shorthand}.ModuleName{END_COL}"
+        ),
+        [shorthands @ .., last] => format!(
+            "You can import modules from {} and {last} now, like {CYAN}import {last}.ModuleName{END_COL}",
+            shorthands.join(", ")
+        ),
+    };
+
+    if let Some(platform) = skipped_platform {
+        buf.push_str(&format!(
+            "\n\nI skipped the platform {platform}, because the REPL can't run a platform's effects."
+        ));
     }
+
+    buf
+}
+
+/// What to say after `:load` or `:reload`
+pub fn format_loaded(
+    style_codes: StyleCodes,
+    filename: &Path,
+    def_count: usize,
+    problems: Problems,
+) -> String {
+    let mut buf = format_output(style_codes, None, problems);
+    let plural = if def_count == 1 { "" } else { "s" };
+
+    if !buf.is_empty() {
+        buf.push('\n');
+    }
+
+    buf.push_str(&format!(
+        "Loaded {def_count} definition{plural} from {}",
+        filename.display()
+    ));
+
+    buf
 }
 
 pub fn format_output(
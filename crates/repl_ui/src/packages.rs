@@ -0,0 +1,196 @@
+//! `:package` declares the packages the REPL can import modules from, like `import json.Json`.
+//! They end up in the `packages` of the app header that each REPL input gets compiled under.
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use bumpalo::Bump;
+use roc_packaging::cache;
+use roc_parse::ast::{ExtractSpaces, Header, SpacesBefore};
+use roc_parse::header::parse_header;
+use roc_parse::state::State;
+
+/// Packages without a URL fragment start at this module (the same default `roc_load` uses)
+#[cfg(not(target_family = "wasm"))]
+const DEFAULT_MAIN_NAME: &str = "main.roc";
+
+const USAGE: &str = "To declare a package, give it a shorthand, like :package json \"https://…\"\nTo use the packages of an app, give me its path, like :package path/to/main.roc";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplPackage {
+    pub shorthand: String,
+    /// What goes in the app header: a URL, or the path to the package's root module
+    pub name: String,
+    /// Where the package's modules are, so we can check an import before keeping it
+    pub root_module_dir: PathBuf,
+}
+
+#[derive(Debug, Default)]
+pub struct DeclaredPackages {
+    pub packages: Vec<ReplPackage>,
+    /// The app header's platform, if any. The REPL has no host to run a platform with,
+    /// so its modules can't be imported.
+    pub skipped_platform: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum PackageProblem {
+    /// An app or a package's root module couldn't be read
+    File {
+        filename: PathBuf,
+        error: io::ErrorKind,
+    },
+    /// Ready to print
+    Report(String),
+}
+
+/// Handle the arguments of a `:package` command, downloading any URL packages into the cache.
+pub fn declare_packages(args: &str) -> Result<DeclaredPackages, PackageProblem> {
+    match args.split_once(char::is_whitespace) {
+        Some((shorthand, package_name)) if !args.starts_with('"') => {
+            if !is_valid_shorthand(shorthand) {
+                return Err(PackageProblem::Report(format!(
+                    "{shorthand} is not a valid package shorthand. Shorthands are lowercase, like json or pf."
+                )));
+            }
+
+            let package = resolve_package(shorthand, unquote(package_name.trim()), None)?;
+
+            Ok(DeclaredPackages {
+                packages: vec![package],
+                skipped_platform: None,
+            })
+        }
+        _ if args.is_empty() => Err(PackageProblem::Report(USAGE.to_string())),
+        _ => app_header_packages(Path::new(unquote(args))),
+    }
+}
+
+fn is_valid_shorthand(shorthand: &str) -> bool {
+    shorthand.starts_with(|ch: char| ch.is_ascii_lowercase())
+        && shorthand.chars().all(|ch| ch.is_ascii_alphanumeric())
+}
+
+fn unquote(arg: &str) -> &str {
+    arg.strip_prefix('"')
+        .and_then(|arg| arg.strip_suffix('"'))
+        .unwrap_or(arg)
+}
+
+/// Every package in an app's header except its platform. Paths in there are relative to the app.
+fn app_header_packages(app_path: &Path) -> Result<DeclaredPackages, PackageProblem> {
+    let src = fs::read(app_path).map_err(|err| PackageProblem::File {
+        filename: app_path.to_path_buf(),
+        error: err.kind(),
+    })?;
+    let arena = Bump::new();
+
+    let header = match parse_header(&arena, State::new(&src)) {
+        Ok((
+            SpacesBefore {
+                item: Header::App(header),
+                ..
+            },
+            _,
+        )) => header,
+        Ok(_) => {
+            return Err(PackageProblem::Report(format!(
+                "{} is not an app, so I can't use its packages.\n\n{USAGE}",
+                app_path.display()
+            )))
+        }
+        Err(fail) => {
+            return Err(PackageProblem::Report(format!(
+                "I could not parse the header of {}: {:?}",
+                app_path.display(),
+                fail.problem
+            )))
+        }
+    };
+
+    let app_dir = app_path.parent().unwrap_or(Path::new(""));
+    let mut declared = DeclaredPackages::default();
+
+    for entry in header.packages.value.items {
+        let entry = entry.value.extract_spaces().item;
+
+        if entry.platform_marker.is_some() {
+            declared.skipped_platform = Some(entry.shorthand.to_string());
+        } else {
+            declared.packages.push(resolve_package(
+                entry.shorthand,
+                entry.package_name.value.to_str(),
+                Some(app_dir),
+            )?);
+        }
+    }
+
+    Ok(declared)
+}
+
+/// Find the package's root module, downloading the package first if it's a URL.
+/// A path is relative to the given dir, or else to the current dir.
+fn resolve_package(
+    shorthand: &str,
+    package_name: &str,
+    opt_relative_to: Option<&Path>,
+) -> Result<ReplPackage, PackageProblem> {
+    let (name, root_module) = if cache::is_package_url(package_name) {
+        (package_name.to_string(), install_url_package(package_name)?)
+    } else {
+        let root_module = match opt_relative_to {
+            Some(dir) => dir.join(package_name),
+            None => PathBuf::from(package_name),
+        };
+        // Modules are found next to the last file `:load` read, so don't depend on where that is.
+        let root_module = match env::current_dir() {
+            Ok(current_dir) => current_dir.join(root_module),
+            Err(_) => root_module,
+        };
+
+        if let Err(err) = fs::metadata(&root_module) {
+            return Err(PackageProblem::File {
+                filename: root_module,
+                error: err.kind(),
+            });
+        }
+
+        // Backslashes would be escapes in the header's string literal, and Windows accepts slashes.
+        let name = root_module.to_string_lossy().replace('\\', "/");
+
+        (name, root_module)
+    };
+
+    Ok(ReplPackage {
+        shorthand: shorthand.to_string(),
+        name,
+        root_module_dir: root_module
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn install_url_package(url: &str) -> Result<PathBuf, PackageProblem> {
+    let cache_dir = cache::roc_cache_packages_dir();
+
+    match cache::install_package(cache::RocCacheDir::Persistent(&cache_dir), url) {
+        Ok((package_dir, opt_root_module)) => {
+            Ok(package_dir.join(opt_root_module.unwrap_or(DEFAULT_MAIN_NAME)))
+        }
+        Err(problem) => Err(PackageProblem::Report(
+            roc_reporting::report::to_https_problem_report_string(
+                url,
+                problem,
+                PathBuf::from("replfile.roc"),
+            ),
+        )),
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn install_url_package(_url: &str) -> Result<PathBuf, PackageProblem> {
+    Err(PackageProblem::Report(
+        "The web version of the REPL cannot download packages... for now!".to_string(),
+    ))
+}
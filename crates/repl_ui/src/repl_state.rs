@@ -1,28 +1,71 @@
-use std::path::PathBuf;
-use std::{fs, io};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
 
 use bumpalo::Bump;
 use roc_collections::MutSet;
 use roc_load::MonomorphizedModule;
 use roc_parse::ast::{Defs, Expr, Pattern, StrLiteral, TypeDef, TypeHeader, ValueDef};
 use roc_parse::expr::parse_repl_defs_and_optional_expr;
+use roc_parse::header::parse_header;
 use roc_parse::parser::EWhen;
-use roc_parse::parser::{EClosure, EExpr, EPattern};
+use roc_parse::parser::{EClosure, EExpr, EPattern, SyntaxError};
 use roc_parse::state::State;
 use roc_region::all::Loc;
-use roc_repl_eval::gen::{compile_to_mono, Problems};
-use roc_reporting::report::Palette;
+use roc_repl_eval::gen::{
+    check_defs, check_expr_type, check_new_defs, compile_to_mono, Problems, ReplOutput,
+};
+use roc_reporting::report::{to_parse_problem_report_string, Palette};
 use roc_target::Target;
 
+use crate::completion;
+use crate::packages::{declare_packages, DeclaredPackages, PackageProblem, ReplPackage};
+
+/// The commands that tab completion offers
+const COMMANDS: [&str; 10] = [
+    ":defs", ":exit", ":help", ":load", ":package", ":q", ":quit", ":reload", ":reset", ":type",
+];
+
+const LOAD_USAGE: &str =
+    "To load the definitions in a file, give me its path, like :load path/to/File.roc";
+
 #[derive(Debug, Clone, PartialEq)]
 enum PastDef {
-    Def { ident: String, src: String },
-    Import(String),
+    Def {
+        ident: String,
+        src: String,
+    },
+    /// `module_dir` is the absolute dir a module import outside of packages was found in
+    Import {
+        src: String,
+        module_dir: Option<PathBuf>,
+    },
+}
+
+impl PastDef {
+    fn module_dir(&self) -> Option<&Path> {
+        match self {
+            PastDef::Import {
+                module_dir: Some(dir),
+                ..
+            } => Some(dir.as_path()),
+            _ => None,
+        }
+    }
+}
+
+/// The file `:reload` reads again, and the past defs it added last time
+struct LoadedFile {
+    filename: PathBuf,
+    defs: Vec<PastDef>,
 }
 
 pub struct ReplState {
     past_defs: Vec<PastDef>,
     past_def_idents: MutSet<String>,
+    packages: Vec<ReplPackage>,
+    last_loaded_file: Option<LoadedFile>,
+    /// Where imported modules are found until one is: next to the last file `:load` read
+    src_dir: PathBuf,
 }
 
 impl Default for ReplState {
@@ -40,10 +83,30 @@ pub enum ReplAction<'a> {
     },
     Exit,
     Help,
+    /// `:type` inferred the expr's type without evaluating it
+    Type {
+        opt_output: Option<ReplOutput>,
+        problems: Problems,
+    },
+    /// `:defs` lists the packages, imports and defs entered so far
+    Defs(Vec<String>),
     FileProblem {
         filename: PathBuf,
         error: io::ErrorKind,
     },
+    /// `:package` declared these packages, so their modules can be imported now
+    PackagesAdded {
+        shorthands: Vec<String>,
+        skipped_platform: Option<String>,
+    },
+    /// `:load` or `:reload` added the file's defs, and found these problems in them
+    Loaded {
+        filename: PathBuf,
+        def_count: usize,
+        problems: Problems,
+    },
+    /// A command couldn't do what was asked; ready to print
+    Problem(String),
     Nothing,
 }
 
@@ -52,6 +115,9 @@ impl ReplState {
         Self {
             past_defs: Default::default(),
             past_def_idents: Default::default(),
+            packages: Default::default(),
+            last_loaded_file: None,
+            src_dir: PathBuf::from("."),
         }
     }
 
@@ -66,6 +132,87 @@ impl ReplState {
         let src: &str = match parse_src(arena, line) {
             ParseOutcome::Empty | ParseOutcome::Help => return ReplAction::Help,
             ParseOutcome::Exit => return ReplAction::Exit,
+            ParseOutcome::Type("") => return ReplAction::Help,
+            ParseOutcome::Type(expr) => {
+                let (opt_expr_type, problems) = check_expr_type(
+                    arena,
+                    self.package_names(),
+                    self.past_def_srcs(),
+                    self.modules_dir(),
+                    expr,
+                    target,
+                    palette,
+                );
+
+                return ReplAction::Type {
+                    opt_output: opt_expr_type.map(|expr_type| ReplOutput {
+                        expr: expr.to_string(),
+                        expr_type,
+                    }),
+                    problems,
+                };
+            }
+            ParseOutcome::Defs => {
+                let packages = self
+                    .packages
+                    .iter()
+                    .map(|package| format!(":package {} \"{}\"", package.shorthand, package.name));
+
+                return ReplAction::Defs(
+                    packages
+                        .chain(self.past_def_srcs().map(str::to_string))
+                        .collect(),
+                );
+            }
+            ParseOutcome::Reset => {
+                *self = Self::new();
+
+                return ReplAction::Nothing;
+            }
+            ParseOutcome::Package(args) => {
+                return match declare_packages(args) {
+                    Ok(DeclaredPackages {
+                        packages,
+                        skipped_platform,
+                    }) => {
+                        let shorthands = packages
+                            .iter()
+                            .map(|package| package.shorthand.clone())
+                            .collect();
+
+                        for package in packages {
+                            self.add_package(package);
+                        }
+
+                        ReplAction::PackagesAdded {
+                            shorthands,
+                            skipped_platform,
+                        }
+                    }
+                    Err(PackageProblem::File { filename, error }) => {
+                        ReplAction::FileProblem { filename, error }
+                    }
+                    Err(PackageProblem::Report(report)) => ReplAction::Problem(report),
+                };
+            }
+            ParseOutcome::Load("") => return ReplAction::Problem(LOAD_USAGE.to_string()),
+            ParseOutcome::Load(path) => {
+                let filename = PathBuf::from(path.trim_matches('"'));
+
+                return self.load_file(filename, target, palette);
+            }
+            ParseOutcome::Reload => {
+                return match &self.last_loaded_file {
+                    Some(loaded_file) => {
+                        let filename = loaded_file.filename.clone();
+
+                        self.load_file(filename, target, palette)
+                    }
+                    None => ReplAction::Problem(format!(
+                        "There's no file to reload yet.\n\n{LOAD_USAGE}"
+                    )),
+                };
+            }
             ParseOutcome::Incomplete | ParseOutcome::SyntaxErr => {
                 pending_past_def = None;
 
@@ -199,49 +346,20 @@ impl ReplState {
                                 ValueDef::ExpectFx { .. } => {
                                     todo!("handle receiving an `expect-fx` - what should the repl do for that?")
                                 }
-                                ValueDef::ModuleImport(import) => match import.name.value.package {
-                                    Some(_) => {
-                                        todo!("handle importing a module from a package")
-                                    }
-                                    None => {
-                                        let mut filename = PathBuf::new();
-
-                                        for part in import.name.value.name.parts() {
-                                            filename.push(part);
-                                        }
-
-                                        filename.set_extension("roc");
-
-                                        // Check we can read the file before we add it to past defs.
-                                        // If we didn't do this, the bad import would remain in past_defs
-                                        // and we'd report it on every subsequent evaluation.
-                                        if let Err(err) = fs::metadata(&filename) {
-                                            return ReplAction::FileProblem {
-                                                filename,
-                                                error: err.kind(),
-                                            };
-                                        }
-
-                                        self.past_defs.push(PastDef::Import(
-                                            line[vd.byte_range()].to_string(),
-                                        ));
-
-                                        return ReplAction::Nothing;
-                                    }
-                                },
-                                ValueDef::IngestedFileImport(file) => {
-                                    if let StrLiteral::PlainLine(path) = file.path.value {
-                                        let filename = PathBuf::from(path);
-                                        if let Err(err) = fs::metadata(&filename) {
-                                            return ReplAction::FileProblem {
-                                                filename,
-                                                error: err.kind(),
-                                            };
-                                        }
+                                ValueDef::ModuleImport(_) | ValueDef::IngestedFileImport(_) => {
+                                    // Check we can read the file before we add it to past defs.
+                                    // If we didn't do this, the bad import would remain in past_defs
+                                    // and we'd report it on every subsequent evaluation.
+                                    let dir = self.modules_dir().to_path_buf();
+
+                                    if let Some(problem) = self.import_problem(&vd.value, &dir) {
+                                        return problem;
                                     }
 
-                                    self.past_defs
-                                        .push(PastDef::Import(line[vd.byte_range()].to_string()));
+                                    self.past_defs.push(PastDef::Import {
+                                        src: import_src(&line[vd.byte_range()], &vd.value, &dir),
+                                        module_dir: module_dir(&vd.value, &dir),
+                                    });
 
                                     return ReplAction::Nothing;
                                 }
@@ -262,10 +380,9 @@ impl ReplState {
 
         let (opt_mono, problems) = compile_to_mono(
             arena,
-            self.past_defs.iter().map(|past_def| match past_def {
-                PastDef::Def { ident: _, src } => src.as_str(),
-                PastDef::Import(src) => src.as_str(),
-            }),
+            self.package_names(),
+            self.past_def_srcs(),
+            self.modules_dir(),
             src,
             target,
             palette,
@@ -278,6 +395,288 @@ impl ReplState {
         ReplAction::Eval { opt_mono, problems }
     }
 
+    /// Add the top-level defs and imports of a module (or of a file of defs) to the past defs.
+    /// Loading the same file again replaces what it added before. The file's imports are
+    /// relative to its directory, so that's where modules are found from then on, unless
+    /// earlier imports already found modules somewhere else.
+    fn load_file<'a>(
+        &mut self,
+        filename: PathBuf,
+        target: Target,
+        palette: Palette,
+    ) -> ReplAction<'a> {
+        let src = match fs::read_to_string(&filename) {
+            Ok(src) => src,
+            Err(err) => {
+                return ReplAction::FileProblem {
+                    filename,
+                    error: err.kind(),
+                }
+            }
+        };
+        let arena = Bump::new();
+
+        // Skip the module header, if there is one. The regions of the defs after it
+        // are still offsets into the whole file.
+        let state = match parse_header(&arena, State::new(src.as_bytes())) {
+            Ok((_, state)) => state,
+            Err(_) => State::new(src.as_bytes()),
+        };
+
+        let defs = match parse_repl_defs_and_optional_expr(&arena, state.clone()) {
+            Ok((_, (defs, None), _)) => defs,
+            // There can't be an expr at the top level of a module, so that's a syntax error too
+            Ok((_, (_, Some(expr)), _)) => {
+                let fail = EExpr::UnexpectedTopLevelExpr(expr.region.start());
+
+                return syntax_problem(filename, fail, &state, palette);
+            }
+            Err((_, fail)) => return syntax_problem(filename, fail, &state, palette),
+        };
+        let file_dir = filename
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        let mut new_defs = Vec::new();
+
+        for def in defs.loc_defs() {
+            let new_def = match def {
+                Ok(td) => match td.value {
+                    TypeDef::Alias {
+                        header: TypeHeader { name, .. },
+                        ..
+                    }
+                    | TypeDef::Opaque {
+                        header: TypeHeader { name, .. },
+                        ..
+                    }
+                    | TypeDef::Ability {
+                        header: TypeHeader { name, .. },
+                        ..
+                    } => PastDef::Def {
+                        ident: name.value.trim_end().to_string(),
+                        src: src[td.byte_range()].to_string(),
+                    },
+                },
+                Err(vd) => match vd.value {
+                    ValueDef::Annotation(
+                        Loc {
+                            value: Pattern::Identifier { ident },
+                            ..
+                        },
+                        _,
+                    ) => PastDef::Def {
+                        ident: ident.trim_end().to_string(),
+                        src: src[vd.byte_range()].to_string(),
+                    },
+                    ValueDef::Body(
+                        Loc {
+                            value: Pattern::Identifier { ident },
+                            ..
+                        },
+                        _,
+                    )
+                    | ValueDef::AnnotatedBody {
+                        body_pattern:
+                            Loc {
+                                value: Pattern::Identifier { ident },
+                                ..
+                            },
+                        ..
+                    } => PastDef::Def {
+                        ident: ident.trim_end().to_string(),
+                        src: src[vd.byte_range()].to_string(),
+                    },
+                    ValueDef::ModuleImport(_) | ValueDef::IngestedFileImport(_) => {
+                        if let Some(problem) = self.import_problem(&vd.value, file_dir) {
+                            return problem;
+                        }
+
+                        PastDef::Import {
+                            src: import_src(&src[vd.byte_range()], &vd.value, file_dir),
+                            module_dir: module_dir(&vd.value, file_dir),
+                        }
+                    }
+                    // Like elsewhere in the REPL, there's no way to use these on their own yet.
+                    ValueDef::Annotation(..)
+                    | ValueDef::Body(..)
+                    | ValueDef::AnnotatedBody { .. }
+                    | ValueDef::Dbg { .. }
+                    | ValueDef::Expect { .. }
+                    | ValueDef::ExpectFx { .. }
+                    | ValueDef::Stmt(_)
+                    | ValueDef::StmtAfterExpr => continue,
+                },
+            };
+
+            new_defs.push(new_def);
+        }
+
+        // Earlier module imports have to keep referring to the modules they found, and there's
+        // only one dir to find modules in.
+        let replaced_defs = match &self.last_loaded_file {
+            Some(loaded_file) if loaded_file.filename == filename => loaded_file.defs.as_slice(),
+            _ => &[],
+        };
+        let earlier_dir = self
+            .past_defs
+            .iter()
+            .filter(|past_def| !replaced_defs.contains(past_def))
+            .find_map(PastDef::module_dir);
+        let new_dir = new_defs.iter().find_map(PastDef::module_dir);
+
+        if let (Some(earlier_dir), Some(new_dir)) = (earlier_dir, new_dir) {
+            if earlier_dir != new_dir {
+                return ReplAction::Problem(format!(
+                    "{} imports modules from {}, but the modules imported so far are in {}. \
+                    Modules can only be imported from one directory; use :reset to start over.",
+                    filename.display(),
+                    new_dir.display(),
+                    earlier_dir.display(),
+                ));
+            }
+        }
+
+        if let Some(loaded_file) = self.last_loaded_file.take() {
+            if loaded_file.filename == filename {
+                self.forget_past_defs(&loaded_file.defs);
+            } else {
+                self.last_loaded_file = Some(loaded_file);
+            }
+        }
+
+        self.src_dir = file_dir.to_path_buf();
+
+        let old_defs_count = self.past_defs.len();
+
+        for new_def in new_defs.iter().cloned() {
+            match new_def {
+                PastDef::Def { ident, src } => self.add_past_def(ident, src),
+                PastDef::Import { .. } => self.past_defs.push(new_def),
+            }
+        }
+
+        let problems = check_new_defs(
+            &arena,
+            self.package_names(),
+            self.past_def_srcs(),
+            self.modules_dir(),
+            old_defs_count,
+            target,
+            palette,
+        );
+
+        let def_count = new_defs.len();
+
+        self.last_loaded_file = Some(LoadedFile {
+            filename: filename.clone(),
+            defs: new_defs,
+        });
+
+        ReplAction::Loaded {
+            filename,
+            def_count,
+            problems,
+        }
+    }
+
+    /// Whether the file an import refers to is missing, or in a package that wasn't declared.
+    /// Modules and ingested files outside of packages are relative to `dir`.
+    fn import_problem<'a>(&self, def: &ValueDef<'_>, dir: &Path) -> Option<ReplAction<'a>> {
+        let filename = match def {
+            ValueDef::ModuleImport(import) => {
+                // A package's modules are relative to its root module.
+                let mut filename = match import.name.value.package {
+                    Some(shorthand) => {
+                        match self
+                            .packages
+                            .iter()
+                            .find(|package| package.shorthand == shorthand)
+                        {
+                            Some(package) => package.root_module_dir.clone(),
+                            None => {
+                                return Some(ReplAction::Problem(format!(
+                                    "There is no package called {shorthand} yet. \
+                                    Declare it with :package {shorthand} \"https://…\" first."
+                                )));
+                            }
+                        }
+                    }
+                    None => dir.to_path_buf(),
+                };
+
+                for part in import.name.value.name.parts() {
+                    filename.push(part);
+                }
+
+                filename.set_extension("roc");
+
+                filename
+            }
+            ValueDef::IngestedFileImport(file) => match file.path.value {
+                StrLiteral::PlainLine(path) => dir.join(path),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        match fs::metadata(&filename) {
+            Ok(_) => None,
+            Err(err) => Some(ReplAction::FileProblem {
+                filename,
+                error: err.kind(),
+            }),
+        }
+    }
+
+    /// Tab completions for the word before the cursor, like `Str.con`, `myRecord.fie` or `:ty`
+    pub fn completions(&self, word: &str, target: Target) -> Vec<String> {
+        if word.starts_with(':') {
+            return COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(word))
+                .map(|command| command.to_string())
+                .collect();
+        }
+
+        let arena = Bump::new();
+
+        match check_defs(
+            &arena,
+            self.package_names(),
+            self.past_def_srcs(),
+            self.modules_dir(),
+            target,
+        ) {
+            Some(mut loaded) => completion::completions(&mut loaded, word),
+            None => Vec::new(),
+        }
+    }
+
+    fn past_def_srcs(&self) -> impl Iterator<Item = &str> {
+        self.past_defs.iter().map(|past_def| match past_def {
+            PastDef::Def { ident: _, src } => src.as_str(),
+            PastDef::Import { src, .. } => src.as_str(),
+        })
+    }
+
+    /// Where imported modules are found. Once a module import found its module somewhere, the
+    /// others are found there too, so that it keeps referring to the same module.
+    fn modules_dir(&self) -> &Path {
+        self.past_defs
+            .iter()
+            .find_map(PastDef::module_dir)
+            .unwrap_or(&self.src_dir)
+    }
+
+    /// The (shorthand, URL or path) of each declared package
+    fn package_names(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.packages
+            .iter()
+            .map(|package| (package.shorthand.as_str(), package.name.as_str()))
+    }
+
     fn add_past_def(&mut self, ident: String, src: String) {
         let existing_idents = &mut self.past_def_idents;
 
@@ -285,6 +684,24 @@ impl ReplState {
 
         self.past_defs.push(PastDef::Def { ident, src });
     }
+
+    fn forget_past_defs(&mut self, defs: &[PastDef]) {
+        self.past_defs.retain(|past_def| !defs.contains(past_def));
+
+        for def in defs {
+            if let PastDef::Def { ident, .. } = def {
+                self.past_def_idents.remove(ident);
+            }
+        }
+    }
+
+    /// Declaring a shorthand again replaces the package it referred to.
+    fn add_package(&mut self, package: ReplPackage) {
+        self.packages
+            .retain(|existing| existing.shorthand != package.shorthand);
+
+        self.packages.push(package);
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -295,6 +712,15 @@ pub enum ParseOutcome<'a> {
     Empty,
     Help,
     Exit,
+    /// `:type` and the expr after it
+    Type(&'a str),
+    Defs,
+    Reset,
+    /// `:package` and its arguments
+    Package(&'a str),
+    /// `:load` and the path after it
+    Load(&'a str),
+    Reload,
 }
 
 /// Special case some syntax errors to allow for multi-line inputs
@@ -310,9 +736,24 @@ fn parse_outcome_for_error(e: EExpr<'_>) -> ParseOutcome<'_> {
 }
 
 pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
+    if let Some(args) = command_args(line, ":package") {
+        return ParseOutcome::Package(args);
+    }
+
+    if let Some(expr) = command_args(line, ":type") {
+        return ParseOutcome::Type(expr);
+    }
+
+    if let Some(path) = command_args(line, ":load") {
+        return ParseOutcome::Load(path);
+    }
+
     match line.trim().to_lowercase().as_str() {
         "" => ParseOutcome::Empty,
         ":help" => ParseOutcome::Help,
+        ":defs" => ParseOutcome::Defs,
+        ":reset" => ParseOutcome::Reset,
+        ":reload" => ParseOutcome::Reload,
         // These are all common things beginners try.
         // Let people exit the repl easily!
         // If you really need to evaluate `exit` for some reason,
@@ -334,3 +775,55 @@ pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
         }
     }
 }
+
+/// The arguments after a command like `:type 1 + 1`, if the line is that command
+fn command_args<'a>(line: &'a str, command: &str) -> Option<&'a str> {
+    let args = line.trim().strip_prefix(command)?;
+
+    if args.is_empty() || args.starts_with(char::is_whitespace) {
+        Some(args.trim())
+    } else {
+        None
+    }
+}
+
+/// A syntax error in a file `:load` read, rendered like the ones in a module
+fn syntax_problem<'a>(
+    filename: PathBuf,
+    fail: EExpr<'_>,
+    state: &State<'_>,
+    palette: Palette,
+) -> ReplAction<'a> {
+    let problem = SyntaxError::Expr(fail, state.pos()).into_file_error(filename, state);
+
+    ReplAction::Problem(to_parse_problem_report_string(problem, &palette))
+}
+
+/// The absolute dir a module import outside of packages finds its module in, relative to `dir`
+fn module_dir(def: &ValueDef<'_>, dir: &Path) -> Option<PathBuf> {
+    match def {
+        ValueDef::ModuleImport(import) if import.name.value.package.is_none() => Some(
+            env::current_dir()
+                .map(|cwd| cwd.join(dir))
+                .unwrap_or_else(|_| dir.to_path_buf()),
+        ),
+        _ => None,
+    }
+}
+
+/// The source of an import to keep in the past defs. An ingested file's path is relative
+/// to `dir`, but the REPL's module reads it relative to the current dir.
+fn import_src(src: &str, def: &ValueDef<'_>, dir: &Path) -> String {
+    match def {
+        ValueDef::IngestedFileImport(file) => match file.path.value {
+            StrLiteral::PlainLine(path) if dir != Path::new(".") => {
+                // Backslashes would be escapes in the string literal, and Windows accepts slashes.
+                let resolved = dir.join(path).to_string_lossy().replace('\\', "/");
+
+                src.replacen(&format!("\"{path}\""), &format!("\"{resolved}\""), 1)
+            }
+            _ => src.to_string(),
+        },
+        _ => src.to_string(),
+    }
+}
@@ -12,7 +12,7 @@ use roc_repl_eval::{
     ReplApp, ReplAppMemory,
 };
 use roc_repl_ui::{
-    format_output,
+    format_loaded, format_output, format_packages_added,
     repl_state::{ReplAction, ReplState},
     TIPS,
 };
@@ -206,6 +206,21 @@ pub async fn entrypoint_from_js(src: String) -> String {
         ReplAction::FileProblem { .. } => {
             "The web version of the REPL cannot import files... for now!".to_string()
         }
+        ReplAction::Type {
+            opt_output,
+            problems,
+        } => format_output(HTML_STYLE_CODES, opt_output, problems),
+        ReplAction::Defs(defs) => defs.join("\n\n"),
+        ReplAction::PackagesAdded {
+            shorthands,
+            skipped_platform,
+        } => format_packages_added(&shorthands, skipped_platform.as_deref()),
+        ReplAction::Loaded {
+            filename,
+            def_count,
+            problems,
+        } => format_loaded(HTML_STYLE_CODES, &filename, def_count, problems),
+        ReplAction::Problem(message) => message,
         ReplAction::Nothing => String::new(),
         ReplAction::Eval { opt_mono, problems } => {
             let opt_output = match opt_mono {
@@ -254,6 +269,7 @@ async fn eval_wasm<'a>(
             arena,
             module_id,
             stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+            runs_expects: false,
             exposed_to_host: exposed_to_host
                 .top_level_values
                 .keys()
@@ -67,6 +67,7 @@ const INCOMPLETE_ABILITY_IMPLEMENTATION: &str = "INCOMPLETE ABILITY IMPLEMENTATI
 const STATEMENT_AFTER_EXPRESSION: &str = "STATEMENT AFTER EXPRESSION";
 const MISSING_EXCLAMATION: &str = "MISSING EXCLAMATION";
 const UNNECESSARY_EXCLAMATION: &str = "UNNECESSARY EXCLAMATION";
+const SKIPPED_DOC_TEST: &str = "SKIPPED DOC TEST";
 
 pub fn can_problem<'b>(
     alloc: &'b RocDocAllocator<'b>,
@@ -269,6 +270,25 @@ pub fn can_problem<'b>(
             title = "BACKPASSING DEPRECATED".to_string();
         }
 
+        Problem::SkippedDocTest(region) => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This example has an "),
+                    alloc.keyword("expect"),
+                    alloc.reflow(", but it doesn't end in one, so I can't run it as a test:"),
+                ]),
+                alloc.region(lines.convert_region(region), severity),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow("End the example with the "),
+                    alloc.keyword("expect"),
+                    alloc.reflow(" that checks its result."),
+                ]),
+            ]);
+
+            title = SKIPPED_DOC_TEST.to_string();
+        }
+
         Problem::DefsOnlyUsedInRecursion(1, region) => {
             doc = alloc.stack([
                 alloc.reflow("This definition is only used in recursion with itself:"),
@@ -216,6 +216,74 @@ impl<'a> Renderer<'a> {
         message: &str,
         expect_region: Region,
     ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        self.render_runtime_problem(
+            writer,
+            "EXPECT PANICKED",
+            "This expectation crashed while running:",
+            expect_region,
+            &["The crash reported this message:", message],
+        )
+    }
+
+    /// The process running the expectation died, e.g. because of a stack overflow
+    pub fn render_crash<W>(
+        &self,
+        writer: &mut W,
+        description: &str,
+        expect_region: Region,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        self.render_runtime_problem(
+            writer,
+            "EXPECT CRASHED",
+            "This expectation crashed while running:",
+            expect_region,
+            &[description],
+        )
+    }
+
+    pub fn render_timeout<W>(
+        &self,
+        writer: &mut W,
+        timeout: std::time::Duration,
+        expect_region: Region,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let seconds = timeout.as_secs_f64();
+        let intro = if seconds == 1.0 {
+            "This expectation did not finish within 1 second:".to_string()
+        } else {
+            format!("This expectation did not finish within {seconds} seconds:")
+        };
+
+        self.render_runtime_problem(
+            writer,
+            "EXPECT TIMED OUT",
+            &intro,
+            expect_region,
+            &["I stopped it so that the remaining expectations could run."],
+        )
+    }
+
+    /// A property expect crashed. The arguments it crashed for are rendered like lookups.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_property_panic<W>(
+        &self,
+        writer: &mut W,
+        subs: &mut Subs,
+        message: &str,
+        symbols: &[Symbol],
+        variables: &[Variable],
+        expressions: &[Expr<'_>],
+        expect_region: Region,
+    ) -> std::io::Result<()>
     where
         W: std::io::Write,
     {
@@ -225,11 +293,24 @@ impl<'a> Renderer<'a> {
         let line_col_region = self.line_info.convert_region(expect_region);
         let severity = Severity::RuntimeError;
 
+        let lookups =
+            symbols
+                .iter()
+                .zip(variables)
+                .zip(expressions)
+                .map(|((symbol, variable), expr)| {
+                    let error_type = subs.var_to_error_type(*variable, Polarity::OF_VALUE);
+                    self.render_lookup(*symbol, expr, error_type)
+                });
+
         let doc = self.alloc.stack([
             self.alloc.text("This expectation crashed while running:"),
             self.alloc.region(line_col_region, severity),
             self.alloc.text("The crash reported this message:"),
-            self.alloc.text(message),
+            self.alloc.text(message.to_string()),
+            self.alloc.text("It crashed for these arguments:"),
+            self.alloc.stack(lookups),
+            self.alloc.text(""), // Blank line at the end
         ]);
 
         let report = Report {
@@ -250,4 +331,70 @@ impl<'a> Renderer<'a> {
 
         write!(writer, "{buf}")
     }
+
+    /// `roc test` can't generate arguments for a property expect
+    pub fn render_unsupported_property<W>(
+        &self,
+        writer: &mut W,
+        description: &str,
+        expect_region: Region,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let detail = format!("I don't know how to generate {description} yet.");
+
+        self.render_runtime_problem(
+            writer,
+            "UNSUPPORTED PROPERTY",
+            "I can't generate arguments for this expectation:",
+            expect_region,
+            &[&detail],
+        )
+    }
+
+    fn render_runtime_problem<W>(
+        &self,
+        writer: &mut W,
+        title: &str,
+        intro: &str,
+        expect_region: Region,
+        details: &[&str],
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        use crate::report::Report;
+        use ven_pretty::DocAllocator;
+
+        let line_col_region = self.line_info.convert_region(expect_region);
+        let severity = Severity::RuntimeError;
+
+        let doc = self.alloc.stack(
+            [
+                self.alloc.text(intro.to_string()),
+                self.alloc.region(line_col_region, severity),
+            ]
+            .into_iter()
+            .chain(details.iter().map(|text| self.alloc.text(text.to_string()))),
+        );
+
+        let report = Report {
+            title: title.into(),
+            doc,
+            filename: self.filename.clone(),
+            severity,
+        };
+
+        let mut buf = String::new();
+
+        report.render(
+            self.render_target,
+            &mut buf,
+            &self.alloc,
+            &crate::report::DEFAULT_PALETTE,
+        );
+
+        write!(writer, "{buf}")
+    }
 }
@@ -1,8 +1,9 @@
 use roc_module::ident::Ident;
 use roc_module::ident::{Lowercase, ModuleName, TagName, Uppercase};
 use roc_module::symbol::{Interns, ModuleId, ModuleIds, PQModuleName, PackageQualified, Symbol};
+use roc_parse::parser::{FileError, SyntaxError};
 use roc_problem::Severity;
-use roc_region::all::LineColumnRegion;
+use roc_region::all::{LineColumnRegion, LineInfo};
 use std::path::{Path, PathBuf};
 use std::{fmt, io};
 use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnnotated};
@@ -1356,6 +1357,77 @@ pub fn to_https_problem_report<'b>(
                 severity: Severity::Fatal,
             }
         }
+        Problem::NotAvailableOffline {
+            vendor_dir,
+            cache_dir,
+        } => {
+            let doc = alloc.stack([
+                alloc.reflow(r"I need the package at this URL:"),
+                alloc
+                    .string((&url).to_string())
+                    .annotate(Annotation::Url)
+                    .indent(4),
+                alloc.reflow(r"But I'm not allowed to download anything, and it isn't vendored in"),
+                alloc
+                    .string(vendor_dir.display().to_string())
+                    .annotate(Annotation::PlainText)
+                    .indent(4),
+                alloc.reflow(r"or in the package cache at"),
+                alloc
+                    .string(cache_dir.display().to_string())
+                    .annotate(Annotation::PlainText)
+                    .indent(4),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"Run "),
+                    alloc.keyword(r"roc vendor"),
+                    alloc.reflow(r" on a machine with network access to copy every package this project needs into its vendor directory. Otherwise, leave out "),
+                    alloc.keyword(r"--offline"),
+                    alloc.reflow(r" and unset "),
+                    alloc.keyword(r"ROC_OFFLINE"),
+                    alloc.reflow(r" so I can download it."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "PACKAGE NOT AVAILABLE OFFLINE".to_string(),
+                severity: Severity::Fatal,
+            }
+        }
+        Problem::CorruptedPackage { dir, problem } => {
+            let doc = alloc.stack([
+                alloc.reflow(r"I need the package at this URL:"),
+                alloc
+                    .string((&url).to_string())
+                    .annotate(Annotation::Url)
+                    .indent(4),
+                alloc.reflow(r"But its files in the package cache at"),
+                alloc
+                    .string(dir.display().to_string())
+                    .annotate(Annotation::PlainText)
+                    .indent(4),
+                alloc.reflow(r"don't match the ones it was downloaded with:"),
+                alloc
+                    .string(problem)
+                    .annotate(Annotation::PlainText)
+                    .indent(4),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"Run "),
+                    alloc.keyword(r"roc cache verify --remove"),
+                    alloc.reflow(r" and then build again with network access, so I can download a fresh copy."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "CORRUPTED PACKAGE".to_string(),
+                severity: Severity::Fatal,
+            }
+        }
         // TODO: The reporting text for IoErr and FsExtraErr could probably be unified
         Problem::IoErr(io_error) => {
             let doc = alloc.stack([
@@ -1604,7 +1676,13 @@ pub fn to_https_problem_report<'b>(
                 ]),
                 alloc.concat([
                     alloc.tip(),
-                    alloc.reflow(r"Check that you have the correct URL for this package/platform."),
+                    alloc
+                        .reflow(r"Check that you have the correct URL for this package/platform. "),
+                    alloc.reflow(r"To use a tarball on this machine, like one made by "),
+                    alloc.keyword(r"roc build --bundle"),
+                    alloc.reflow(r", use a "),
+                    alloc.keyword(r"file://"),
+                    alloc.reflow(r" URL instead."),
                 ]),
             ]);
 
@@ -1716,6 +1794,28 @@ pub fn to_file_problem_report_string(
     buf
 }
 
+/// Render a syntax error in a whole file, like one the REPL's `:load` found
+pub fn to_parse_problem_report_string(
+    problem: FileError<'_, SyntaxError<'_>>,
+    palette: &Palette,
+) -> String {
+    let src = String::from_utf8_lossy(problem.problem.bytes).into_owned();
+    let src_lines: Vec<&str> = src.lines().collect();
+    let mut module_ids = ModuleIds::default();
+    let module_id = module_ids.get_or_insert(&"find module name somehow?".into());
+    let interns = Interns::default();
+
+    let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
+    let lines = LineInfo::new(&src);
+    let filename = problem.filename.clone();
+    let report = parse_problem(&alloc, &lines, filename, 0, problem);
+
+    let mut buf = String::new();
+    report.render_color_terminal(&mut buf, &alloc, palette);
+
+    buf
+}
+
 pub fn to_file_problem_report<'b>(
     alloc: &'b RocDocAllocator<'b>,
     filename: PathBuf,
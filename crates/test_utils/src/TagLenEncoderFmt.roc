@@ -54,6 +54,8 @@ TagLenFmt := {}
             list: decodeList,
             record: decodeRecord,
             tuple: decodeTuple,
+            tag: decodeTag,
+            dict: decodeDict,
         },
     ]
 
@@ -253,6 +255,48 @@ expect
     actual = Decode.fromBytes (Str.toUtf8 "l3 n1 s3 abc l1 n0 ") tagLenFmt
     actual == Ok (1, "abc", [Bool.false])
 
+decodeTag = \stepTag -> Decode.custom \bytes, @TagLenFmt {} ->
+        decodeLenPre bytes 'l'
+        |> decodeTry \len, lenRest ->
+            Decode.decodeWith lenRest decodeString (@TagLenFmt {})
+            |> decodeTry \name, nameRest ->
+                when stepTag name is
+                    Keep payloadDecoder if len > 0 ->
+                        # the payload follows the name, laid out like a tuple without its header
+                        payloadBytes = appendPreLen [] 'l' (len - 1) |> List.concat nameRest
+                        Decode.decodeWith payloadBytes payloadDecoder (@TagLenFmt {})
+
+                    _ -> { result: Err TooShort, rest: bytes }
+
+expect
+    actual : Result [A U8 Str, B] _
+    actual = Decode.fromBytes (Str.toUtf8 "l3 s1 A n1 s3 foo ") tagLenFmt
+    actual == Ok (A 1 "foo")
+
+expect
+    input : List [A U8 Str, B, C (List U8)]
+    input = [A 1 "foo", B, C [2, 3]]
+    encoded = Encode.toBytes input tagLenFmt
+    decoded = Decode.fromBytes encoded tagLenFmt
+    decoded == Ok input
+
+decodeDict = \initState, insert, keyDecoder, valueDecoder -> Decode.custom \bytes, @TagLenFmt {} ->
+        step = \state -> Decode.custom \sbytes, @TagLenFmt {} ->
+                Decode.decodeWith sbytes keyDecoder (@TagLenFmt {})
+                |> decodeTry \key, bs ->
+                    Decode.decodeWith bs valueDecoder (@TagLenFmt {})
+                    |> Decode.mapResult \value -> insert state key value
+
+        repeatDecode 'm' bytes initState step
+
+expect
+    actual = Decode.fromBytes (Str.toUtf8 "m2 s1 a n1 s1 b n2 ") tagLenFmt
+    actual == Ok (Dict.fromList [("a", 1u8), ("b", 2u8)])
+
+expect
+    actual = Decode.fromBytes (Str.toUtf8 "l3 n1 n2 n1 ") tagLenFmt
+    actual == Ok (Set.fromList [1u8, 2u8])
+
 expect
     input = { foo: (1, "abc", [Bool.false, Bool.true]), bar: { baz: 0.32 } }
     encoded = Encode.toBytes input tagLenFmt
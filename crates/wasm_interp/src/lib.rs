@@ -3,11 +3,13 @@ mod instance;
 #[cfg(test)]
 mod tests;
 
+pub mod roc_host;
 mod value_store;
 pub mod wasi;
 
 // Main external interface
 pub use instance::Instance;
+pub use roc_host::RocHostDispatcher;
 pub use wasi::{WasiDispatcher, WasiFile};
 
 pub use roc_wasm_module::Value;
@@ -28,18 +30,21 @@ impl Default for DefaultImportDispatcher<'_> {
     fn default() -> Self {
         DefaultImportDispatcher {
             wasi: WasiDispatcher::new(&[]),
+            roc_host: RocHostDispatcher::default(),
         }
     }
 }
 
 pub struct DefaultImportDispatcher<'a> {
     pub wasi: WasiDispatcher<'a>,
+    pub roc_host: RocHostDispatcher,
 }
 
 impl<'a> DefaultImportDispatcher<'a> {
     pub fn new(args: &'a [&'a [u8]]) -> Self {
         DefaultImportDispatcher {
             wasi: WasiDispatcher::new(args),
+            roc_host: RocHostDispatcher::default(),
         }
     }
 }
@@ -54,6 +59,9 @@ impl<'a> ImportDispatcher for DefaultImportDispatcher<'a> {
     ) -> Option<Value> {
         if module_name == wasi::MODULE_NAME {
             self.wasi.dispatch(function_name, arguments, memory)
+        } else if module_name == roc_host::MODULE_NAME && RocHostDispatcher::handles(function_name)
+        {
+            self.roc_host.dispatch(function_name, arguments, memory)
         } else {
             panic!("DefaultImportDispatcher does not implement {module_name}.{function_name}");
         }
@@ -12,6 +12,8 @@ use roc_wasm_module::WasmModule;
 pub const FLAG_FUNCTION: &str = "function";
 pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_HEX: &str = "hex";
+pub const FLAG_DIR: &str = "dir";
+pub const FLAG_ENV: &str = "env";
 pub const WASM_FILE: &str = "WASM_FILE";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
 
@@ -38,6 +40,18 @@ fn main() -> io::Result<()> {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_dir = Arg::new(FLAG_DIR)
+        .long(FLAG_DIR)
+        .help("Give the WebAssembly app access to a directory, by the same name.\nCan be repeated. e.g. `roc_wasm_interp --dir . --dir /tmp app.wasm`")
+        .action(ArgAction::Append)
+        .required(false);
+
+    let flag_env = Arg::new(FLAG_ENV)
+        .long(FLAG_ENV)
+        .help("Pass an environment variable through to the WebAssembly app, if it's set.\nCan be repeated. e.g. `roc_wasm_interp --env HOME --env LANG app.wasm`")
+        .action(ArgAction::Append)
+        .required(false);
+
     let wasm_file_to_run = Arg::new(WASM_FILE)
         .help("The .wasm file to run")
         .required(true);
@@ -51,6 +65,8 @@ fn main() -> io::Result<()> {
         .arg(flag_function)
         .arg(flag_debug)
         .arg(flag_hex)
+        .arg(flag_dir)
+        .arg(flag_env)
         .arg(wasm_file_to_run)
         .arg(args_for_app);
 
@@ -61,6 +77,8 @@ fn main() -> io::Result<()> {
     let is_debug_mode = matches.get_flag(FLAG_DEBUG);
     let is_hex_format = matches.get_flag(FLAG_HEX);
     let start_arg_strings = matches.get_many::<String>(ARGS_FOR_APP).unwrap_or_default();
+    let preopen_dirs = matches.get_many::<String>(FLAG_DIR).unwrap_or_default();
+    let env_var_names = matches.get_many::<String>(FLAG_ENV).unwrap_or_default();
     let wasm_path = matches.get_one::<String>(WASM_FILE).unwrap();
     // WASI expects the .wasm file to be argv[0]
     let wasi_argv_iter = once(wasm_path)
@@ -87,7 +105,13 @@ fn main() -> io::Result<()> {
 
     // Create an execution instance
 
-    let dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    let mut dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    for dir in preopen_dirs {
+        dispatcher.wasi.preopen_dir(dir, dir);
+    }
+    for name in env_var_names {
+        dispatcher.wasi.pass_env_var(name);
+    }
     let mut inst =
         Instance::for_module(&arena, &module, dispatcher, is_debug_mode).unwrap_or_else(|e| {
             eprintln!("{e}");
@@ -115,5 +139,11 @@ fn main() -> io::Result<()> {
         }
     }
 
+    let expect_failures = inst.import_dispatcher.roc_host.expect_failures;
+    if expect_failures > 0 {
+        eprintln!("{expect_failures} inline expect(s) failed");
+        process::exit(4);
+    }
+
     Ok(())
 }
@@ -0,0 +1,77 @@
+//! Imports that Roc apps built by the Wasm backend expect the platform to provide
+//! for `dbg` and `expect`. Handling them here means we can run those apps without
+//! a browser or a JS host, and still see the output.
+
+use roc_wasm_module::Value;
+
+pub const MODULE_NAME: &str = "env";
+
+/// Size of a RocStr in 32-bit memory
+const ROC_STR_SIZE: usize = 12;
+
+#[derive(Debug, Default)]
+pub struct RocHostDispatcher {
+    /// Number of inline `expect`s that have failed so far
+    pub expect_failures: u32,
+}
+
+impl RocHostDispatcher {
+    pub fn handles(function_name: &str) -> bool {
+        matches!(function_name, "roc_dbg" | "roc_expect_failed")
+    }
+
+    pub fn dispatch(
+        &mut self,
+        function_name: &str,
+        arguments: &[Value],
+        memory: &[u8],
+    ) -> Option<Value> {
+        match function_name {
+            "roc_dbg" => {
+                // fn roc_dbg(loc: *const RocStr, msg: *const RocStr, src: *const RocStr)
+                let loc = decode_roc_str(memory, arguments[0].expect_i32().unwrap() as u32);
+                let msg = decode_roc_str(memory, arguments[1].expect_i32().unwrap() as u32);
+                let src = decode_roc_str(memory, arguments[2].expect_i32().unwrap() as u32);
+                eprintln!("{}", format_dbg(&loc, &msg, &src));
+                None
+            }
+            "roc_expect_failed" => {
+                // fn roc_expect_failed(module_name: *const RocStr, region_start: u32, region_end: u32)
+                let module_name = decode_roc_str(memory, arguments[0].expect_i32().unwrap() as u32);
+                let start = arguments[1].expect_i32().unwrap() as u32;
+                let end = arguments[2].expect_i32().unwrap() as u32;
+                self.expect_failures += 1;
+                eprintln!(
+                    "── EXPECT FAILED in {module_name} ──\n\nThe expectation at bytes {start}..{end} failed.\n"
+                );
+                None
+            }
+            _ => panic!("RocHostDispatcher does not implement {MODULE_NAME}.{function_name}"),
+        }
+    }
+}
+
+/// Same format as the `roc_dbg` in our test platforms
+pub fn format_dbg(loc: &str, msg: &str, src: &str) -> String {
+    format!("[{loc}] {src} = {msg}")
+}
+
+/// Read a RocStr from 32-bit Wasm memory
+pub fn decode_roc_str(memory: &[u8], addr: u32) -> String {
+    let bytes = &memory[addr as usize..][..ROC_STR_SIZE];
+    let word = |i: usize| u32::from_le_bytes(bytes[4 * i..][..4].try_into().unwrap()) as usize;
+
+    let last_byte = bytes[ROC_STR_SIZE - 1];
+    let content = if last_byte >= 0x80 {
+        // Small string, stored inline. The length is in the last byte.
+        let len = (last_byte & 0x7f) as usize;
+        &bytes[..len]
+    } else {
+        // The highest bit of the length is set for seamless slices
+        let ptr = word(0);
+        let len = word(1) & (i32::MAX as usize);
+        &memory[ptr..][..len]
+    };
+
+    String::from_utf8_lossy(content).into_owned()
+}
@@ -7,6 +7,8 @@ mod test_f64;
 mod test_i32;
 mod test_i64;
 mod test_mem;
+mod test_roc_host;
+mod test_wasi;
 
 use crate::{DefaultImportDispatcher, Instance};
 use bumpalo::{collections::Vec, Bump};
@@ -0,0 +1,91 @@
+#![cfg(test)]
+
+use crate::roc_host::{decode_roc_str, format_dbg, RocHostDispatcher};
+use crate::{DefaultImportDispatcher, ImportDispatcher};
+use roc_wasm_module::Value;
+
+const STR_PTR: usize = 0x100;
+const STR2_PTR: usize = 0x200;
+const BYTES_PTR: usize = 0x1000;
+
+fn write_small_str(memory: &mut [u8], addr: usize, s: &str) {
+    assert!(s.len() < 12);
+    memory[addr..][..12].fill(0);
+    memory[addr..][..s.len()].copy_from_slice(s.as_bytes());
+    memory[addr + 11] = 0x80 | s.len() as u8;
+}
+
+fn write_big_str(memory: &mut [u8], addr: usize, bytes_addr: usize, s: &str) {
+    memory[bytes_addr..][..s.len()].copy_from_slice(s.as_bytes());
+    memory[addr..][..4].copy_from_slice(&(bytes_addr as u32).to_le_bytes());
+    memory[addr + 4..][..4].copy_from_slice(&(s.len() as u32).to_le_bytes());
+    memory[addr + 8..][..4].copy_from_slice(&(s.len() as u32).to_le_bytes());
+}
+
+#[test]
+fn test_decode_roc_str() {
+    let mut memory = vec![0; 0x2000];
+
+    write_small_str(&mut memory, STR_PTR, "hello");
+    assert_eq!(decode_roc_str(&memory, STR_PTR as u32), "hello");
+
+    let big = "a string that is too long to fit inline";
+    write_big_str(&mut memory, STR2_PTR, BYTES_PTR, big);
+    assert_eq!(decode_roc_str(&memory, STR2_PTR as u32), big);
+
+    // Seamless slices set the highest bit of the length
+    let len_with_slice_bit = 6 | (1u32 << 31);
+    memory[STR2_PTR + 4..][..4].copy_from_slice(&len_with_slice_bit.to_le_bytes());
+    assert_eq!(decode_roc_str(&memory, STR2_PTR as u32), "a stri");
+}
+
+#[test]
+fn test_roc_dbg() {
+    let mut memory = vec![0; 0x2000];
+    write_small_str(&mut memory, STR_PTR, "Main.roc:3");
+    write_small_str(&mut memory, STR2_PTR, "42");
+    write_small_str(&mut memory, BYTES_PTR, "x + 1");
+
+    let mut dispatcher = DefaultImportDispatcher::default();
+    let args = [
+        Value::I32(STR_PTR as i32),
+        Value::I32(STR2_PTR as i32),
+        Value::I32(BYTES_PTR as i32),
+    ];
+    let result = dispatcher.dispatch("env", "roc_dbg", &args, &mut memory);
+
+    assert_eq!(result, None);
+    assert_eq!(dispatcher.roc_host.expect_failures, 0);
+    assert_eq!(
+        format_dbg("Main.roc:3", "42", "x + 1"),
+        "[Main.roc:3] x + 1 = 42"
+    );
+}
+
+#[test]
+fn test_roc_expect_failed() {
+    let mut memory = vec![0; 0x2000];
+    write_small_str(&mut memory, STR_PTR, "Main");
+
+    let mut dispatcher = DefaultImportDispatcher::default();
+    let args = [Value::I32(STR_PTR as i32), Value::I32(10), Value::I32(25)];
+    dispatcher.dispatch("env", "roc_expect_failed", &args, &mut memory);
+    dispatcher.dispatch("env", "roc_expect_failed", &args, &mut memory);
+
+    assert_eq!(dispatcher.roc_host.expect_failures, 2);
+}
+
+#[test]
+#[should_panic(expected = "does not implement env.roc_something_else")]
+fn test_unknown_env_import() {
+    let mut memory = vec![0; 0x100];
+    let mut dispatcher = DefaultImportDispatcher::default();
+    dispatcher.dispatch("env", "roc_something_else", &[], &mut memory);
+}
+
+#[test]
+fn test_handles() {
+    assert!(RocHostDispatcher::handles("roc_dbg"));
+    assert!(RocHostDispatcher::handles("roc_expect_failed"));
+    assert!(!RocHostDispatcher::handles("roc_panic"));
+}
@@ -0,0 +1,581 @@
+#![cfg(test)]
+
+use crate::wasi::{Errno, WasiDispatcher, WasiFile};
+use roc_wasm_module::Value;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const PATH_PTR: usize = 0x100;
+const PATH2_PTR: usize = 0x200;
+const IOV_PTR: usize = 0x300;
+const OUT_PTR: usize = 0x400;
+const BUF_PTR: usize = 0x1000;
+
+const OFLAGS_CREAT: i32 = 1;
+const OFLAGS_DIRECTORY: i32 = 2;
+const RIGHTS_READ_WRITE: i64 = (1 << 1) | (1 << 6);
+
+/// A directory on the host for one test, deleted at the end of it
+struct TestDir(PathBuf);
+
+impl TestDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("roc_wasm_interp_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TestDir(path)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn call(wasi: &mut WasiDispatcher, name: &str, args: &[Value], memory: &mut [u8]) -> Errno {
+    let code = match wasi.dispatch(name, args, memory) {
+        Some(Value::I32(code)) => code,
+        other => panic!("{name} returned {other:?}"),
+    };
+    let errnos = [
+        Errno::Success,
+        Errno::Badf,
+        Errno::Exist,
+        Errno::Isdir,
+        Errno::Noent,
+        Errno::Notdir,
+        Errno::Spipe,
+        Errno::Notcapable,
+        Errno::Inval,
+        Errno::Notsup,
+        Errno::Loop,
+    ];
+    *errnos
+        .iter()
+        .find(|errno| **errno as i32 == code)
+        .unwrap_or_else(|| panic!("{name} returned unexpected errno {code}"))
+}
+
+/// Write a path into memory, returning the pointer and length arguments for it
+fn path_args(memory: &mut [u8], ptr: usize, path: &str) -> [Value; 2] {
+    memory[ptr..][..path.len()].copy_from_slice(path.as_bytes());
+    [Value::I32(ptr as i32), Value::I32(path.len() as i32)]
+}
+
+/// Open a path, following symlinks
+fn path_open(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    dir_fd: u32,
+    path: &str,
+    oflags: i32,
+    rights: i64,
+) -> Result<u32, Errno> {
+    path_open_lookup(wasi, memory, dir_fd, 1, path, oflags, rights)
+}
+
+fn path_open_lookup(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    dir_fd: u32,
+    lookup_flags: i32,
+    path: &str,
+    oflags: i32,
+    rights: i64,
+) -> Result<u32, Errno> {
+    let [ptr, len] = path_args(memory, PATH_PTR, path);
+    let args = [
+        Value::I32(dir_fd as i32),
+        Value::I32(lookup_flags),
+        ptr,
+        len,
+        Value::I32(oflags),
+        Value::I64(rights),
+        Value::I64(0),
+        Value::I32(0),
+        Value::I32(OUT_PTR as i32),
+    ];
+    match call(wasi, "path_open", &args, memory) {
+        Errno::Success => Ok(read_u32(memory, OUT_PTR)),
+        errno => Err(errno),
+    }
+}
+
+fn path_call(wasi: &mut WasiDispatcher, memory: &mut [u8], name: &str, path: &str) -> Errno {
+    let [ptr, len] = path_args(memory, PATH_PTR, path);
+    call(wasi, name, &[Value::I32(3), ptr, len], memory)
+}
+
+fn path_size(wasi: &mut WasiDispatcher, memory: &mut [u8], path: &str) -> Result<u64, Errno> {
+    let [ptr, len] = path_args(memory, PATH_PTR, path);
+    let args = [
+        Value::I32(3),
+        Value::I32(1),
+        ptr,
+        len,
+        Value::I32(OUT_PTR as i32),
+    ];
+    match call(wasi, "path_filestat_get", &args, memory) {
+        Errno::Success => Ok(read_u64(memory, OUT_PTR + 32)),
+        errno => Err(errno),
+    }
+}
+
+/// The names of the entries `fd_readdir` finds in a directory
+fn readdir(wasi: &mut WasiDispatcher, memory: &mut [u8], fd: u32) -> Vec<String> {
+    let args = [
+        Value::I32(fd as i32),
+        Value::I32(BUF_PTR as i32),
+        Value::I32(1024),
+        Value::I64(0),
+        Value::I32(OUT_PTR as i32),
+    ];
+    assert_eq!(call(wasi, "fd_readdir", &args, memory), Errno::Success);
+
+    let used = read_u32(memory, OUT_PTR) as usize;
+    let mut names = vec![];
+    let mut offset = 0;
+    while offset < used {
+        let name_len = read_u32(memory, BUF_PTR + offset + 16) as usize;
+        let name = &memory[BUF_PTR + offset + 24..][..name_len];
+        names.push(String::from_utf8(name.to_vec()).unwrap());
+        offset += 24 + name_len;
+    }
+    names
+}
+
+fn read_u32(memory: &[u8], addr: usize) -> u32 {
+    u32::from_le_bytes(memory[addr..][..4].try_into().unwrap())
+}
+
+fn read_u64(memory: &[u8], addr: usize) -> u64 {
+    u64::from_le_bytes(memory[addr..][..8].try_into().unwrap())
+}
+
+#[test]
+fn test_wasi_preopen() {
+    let dir = TestDir::new("preopen");
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+
+    let fd = wasi.preopen_dir(&dir.0, "data");
+    assert_eq!(fd, 3);
+
+    let args = [Value::I32(3), Value::I32(OUT_PTR as i32)];
+    assert_eq!(
+        call(&mut wasi, "fd_prestat_get", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u32(&memory, OUT_PTR), 0); // directory
+    assert_eq!(read_u32(&memory, OUT_PTR + 4), 4); // name length
+
+    let args = [Value::I32(3), Value::I32(PATH_PTR as i32), Value::I32(4)];
+    assert_eq!(
+        call(&mut wasi, "fd_prestat_dir_name", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(&memory[PATH_PTR..][..4], b"data");
+
+    // wasi-libc stops looking for preopens at the first bad file descriptor
+    let args = [Value::I32(4), Value::I32(OUT_PTR as i32)];
+    assert_eq!(
+        call(&mut wasi, "fd_prestat_get", &args, &mut memory),
+        Errno::Badf
+    );
+}
+
+#[test]
+fn test_wasi_file_read_write_seek() {
+    let dir = TestDir::new("file");
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(&dir.0, ".");
+
+    assert_eq!(
+        path_open(&mut wasi, &mut memory, 3, "hello.txt", 0, RIGHTS_READ_WRITE),
+        Err(Errno::Noent)
+    );
+    let fd = path_open(
+        &mut wasi,
+        &mut memory,
+        3,
+        "hello.txt",
+        OFLAGS_CREAT,
+        RIGHTS_READ_WRITE,
+    )
+    .unwrap();
+
+    let text = b"hello world";
+    memory[BUF_PTR..][..text.len()].copy_from_slice(text);
+    memory[IOV_PTR..][..4].copy_from_slice(&(BUF_PTR as u32).to_le_bytes());
+    memory[IOV_PTR + 4..][..4].copy_from_slice(&(text.len() as u32).to_le_bytes());
+    let iov_args = [
+        Value::I32(fd as i32),
+        Value::I32(IOV_PTR as i32),
+        Value::I32(1),
+        Value::I32(OUT_PTR as i32),
+    ];
+    assert_eq!(
+        call(&mut wasi, "fd_write", &iov_args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u32(&memory, OUT_PTR), text.len() as u32);
+
+    let args = [Value::I32(fd as i32), Value::I32(OUT_PTR as i32)];
+    assert_eq!(
+        call(&mut wasi, "fd_tell", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u64(&memory, OUT_PTR), 11);
+
+    let args = [
+        Value::I32(fd as i32),
+        Value::I64(6),
+        Value::I32(0), // from the start
+        Value::I32(OUT_PTR as i32),
+    ];
+    assert_eq!(
+        call(&mut wasi, "fd_seek", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u64(&memory, OUT_PTR), 6);
+
+    memory[BUF_PTR..][..text.len()].fill(0);
+    assert_eq!(
+        call(&mut wasi, "fd_read", &iov_args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u32(&memory, OUT_PTR), 5);
+    assert_eq!(&memory[BUF_PTR..][..5], b"world");
+
+    let args = [Value::I32(fd as i32), Value::I32(OUT_PTR as i32)];
+    assert_eq!(
+        call(&mut wasi, "fd_filestat_get", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(memory[OUT_PTR + 16], 4); // regular file
+    assert_eq!(read_u64(&memory, OUT_PTR + 32), 11);
+
+    // stdout can't seek
+    let args = [Value::I32(1), Value::I32(OUT_PTR as i32)];
+    assert_eq!(call(&mut wasi, "fd_tell", &args, &mut memory), Errno::Spipe);
+
+    let args = [Value::I32(fd as i32)];
+    assert_eq!(
+        call(&mut wasi, "fd_close", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(call(&mut wasi, "fd_close", &args, &mut memory), Errno::Badf);
+
+    assert_eq!(
+        std::fs::read_to_string(dir.0.join("hello.txt")).unwrap(),
+        "hello world"
+    );
+
+    // Closed file descriptors get reused
+    let fd_again = path_open(&mut wasi, &mut memory, 3, "hello.txt", 0, RIGHTS_READ_WRITE);
+    assert_eq!(fd_again, Ok(fd));
+}
+
+#[test]
+fn test_wasi_directories() {
+    let dir = TestDir::new("directories");
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(&dir.0, ".");
+
+    let mkdir = path_call(&mut wasi, &mut memory, "path_create_directory", "sub");
+    assert_eq!(mkdir, Errno::Success);
+    let mkdir = path_call(&mut wasi, &mut memory, "path_create_directory", "sub");
+    assert_eq!(mkdir, Errno::Exist);
+
+    std::fs::write(dir.0.join("sub/a.txt"), "abc").unwrap();
+    assert_eq!(path_size(&mut wasi, &mut memory, "sub/a.txt"), Ok(3));
+
+    let [old_ptr, old_len] = path_args(&mut memory, PATH_PTR, "sub/a.txt");
+    let [new_ptr, new_len] = path_args(&mut memory, PATH2_PTR, "b.txt");
+    let args = [
+        Value::I32(3),
+        old_ptr,
+        old_len,
+        Value::I32(3),
+        new_ptr,
+        new_len,
+    ];
+    assert_eq!(
+        call(&mut wasi, "path_rename", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(
+        path_size(&mut wasi, &mut memory, "sub/a.txt"),
+        Err(Errno::Noent)
+    );
+    assert_eq!(path_size(&mut wasi, &mut memory, "b.txt"), Ok(3));
+
+    assert_eq!(
+        readdir(&mut wasi, &mut memory, 3),
+        [".", "..", "b.txt", "sub"]
+    );
+
+    let sub = path_open(&mut wasi, &mut memory, 3, "sub", OFLAGS_DIRECTORY, 0).unwrap();
+    assert_eq!(readdir(&mut wasi, &mut memory, sub), [".", ".."]);
+    assert_eq!(
+        path_open(&mut wasi, &mut memory, 3, "b.txt", OFLAGS_DIRECTORY, 0),
+        Err(Errno::Notdir)
+    );
+
+    let unlink = path_call(&mut wasi, &mut memory, "path_unlink_file", "sub");
+    assert_eq!(unlink, Errno::Isdir);
+    let unlink = path_call(&mut wasi, &mut memory, "path_unlink_file", "b.txt");
+    assert_eq!(unlink, Errno::Success);
+    assert!(!dir.0.join("b.txt").exists());
+}
+
+#[test]
+fn test_wasi_sandbox() {
+    let dir = TestDir::new("sandbox");
+    std::fs::create_dir(dir.0.join("sub")).unwrap();
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(dir.0.join("sub"), ".");
+
+    for path in ["../outside.txt", "/etc/passwd", "a/../../outside.txt"] {
+        assert_eq!(
+            path_open(
+                &mut wasi,
+                &mut memory,
+                3,
+                path,
+                OFLAGS_CREAT,
+                RIGHTS_READ_WRITE
+            ),
+            Err(Errno::Notcapable),
+            "{path}"
+        );
+    }
+    assert!(!dir.0.join("outside.txt").exists());
+
+    // Going up is fine, as long as it stays inside the directory
+    let mkdir = path_call(&mut wasi, &mut memory, "path_create_directory", "a");
+    assert_eq!(mkdir, Errno::Success);
+    let path = "a/../inside.txt";
+    let fd = path_open(
+        &mut wasi,
+        &mut memory,
+        3,
+        path,
+        OFLAGS_CREAT,
+        RIGHTS_READ_WRITE,
+    );
+    assert!(fd.is_ok());
+    assert!(dir.0.join("sub/inside.txt").exists());
+
+    // Paths are only resolved relative to directories
+    assert_eq!(
+        path_open(&mut wasi, &mut memory, 1, "inside.txt", 0, 0),
+        Err(Errno::Notdir)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wasi_sandbox_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let dir = TestDir::new("sandbox_symlinks");
+    std::fs::create_dir(dir.0.join("sub")).unwrap();
+    std::fs::write(dir.0.join("outside.txt"), "secret").unwrap();
+    std::fs::write(dir.0.join("sub/inside.txt"), "hi").unwrap();
+    symlink(dir.0.join("outside.txt"), dir.0.join("sub/to_outside")).unwrap();
+    symlink(&dir.0, dir.0.join("sub/to_parent")).unwrap();
+    symlink("inside.txt", dir.0.join("sub/to_inside")).unwrap();
+    symlink(dir.0.join("new.txt"), dir.0.join("sub/dangling")).unwrap();
+
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(dir.0.join("sub"), ".");
+
+    for path in ["to_outside", "to_parent", "to_parent/outside.txt"] {
+        assert_eq!(
+            path_open(&mut wasi, &mut memory, 3, path, 0, RIGHTS_READ_WRITE),
+            Err(Errno::Notcapable),
+            "{path}"
+        );
+        assert_eq!(
+            path_size(&mut wasi, &mut memory, path),
+            Err(Errno::Notcapable),
+            "{path}"
+        );
+    }
+
+    // We can't tell where a dangling symlink leads, so it doesn't get created
+    assert_eq!(
+        path_open(
+            &mut wasi,
+            &mut memory,
+            3,
+            "dangling",
+            OFLAGS_CREAT,
+            RIGHTS_READ_WRITE
+        ),
+        Err(Errno::Noent)
+    );
+    assert!(!dir.0.join("new.txt").exists());
+
+    // Symlinks that stay inside are fine, unless the app asked not to follow them
+    assert_eq!(path_size(&mut wasi, &mut memory, "to_inside"), Ok(2));
+    assert!(path_open(&mut wasi, &mut memory, 3, "to_inside", 0, 0).is_ok());
+    assert_eq!(
+        path_open_lookup(&mut wasi, &mut memory, 3, 0, "to_inside", 0, 0),
+        Err(Errno::Loop)
+    );
+
+    // Unlinking a symlink removes the link, not what it points to
+    let unlink = path_call(&mut wasi, &mut memory, "path_unlink_file", "to_outside");
+    assert_eq!(unlink, Errno::Success);
+    assert!(dir.0.join("outside.txt").exists());
+}
+
+fn clock_time(wasi: &mut WasiDispatcher, memory: &mut [u8], clock_id: i32) -> Result<u64, Errno> {
+    let args = [
+        Value::I32(clock_id),
+        Value::I64(0),
+        Value::I32(OUT_PTR as i32),
+    ];
+    match call(wasi, "clock_time_get", &args, memory) {
+        Errno::Success => Ok(read_u64(memory, OUT_PTR)),
+        errno => Err(errno),
+    }
+}
+
+/// Write a `subscription` for `poll_oneoff` to memory
+fn subscribe(memory: &mut [u8], index: usize, userdata: u64, eventtype: u8, details: &[u8]) {
+    let sub = &mut memory[IOV_PTR + 48 * index..][..48];
+    sub.fill(0);
+    sub[0..8].copy_from_slice(&userdata.to_le_bytes());
+    sub[8] = eventtype;
+    sub[16..][..details.len()].copy_from_slice(details);
+}
+
+fn subscribe_clock(memory: &mut [u8], index: usize, userdata: u64, timeout: Duration) {
+    let mut clock = [0; 26];
+    clock[0..4].copy_from_slice(&1u32.to_le_bytes()); // monotonic
+    clock[8..16].copy_from_slice(&(timeout.as_nanos() as u64).to_le_bytes());
+    subscribe(memory, index, userdata, 0, &clock);
+}
+
+/// Call `poll_oneoff`, returning the userdata, error and type of each event
+fn poll_oneoff(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    nsubscriptions: i32,
+) -> Vec<(u64, u16, u8)> {
+    let args = [
+        Value::I32(IOV_PTR as i32),
+        Value::I32(BUF_PTR as i32),
+        Value::I32(nsubscriptions),
+        Value::I32(OUT_PTR as i32),
+    ];
+    assert_eq!(call(wasi, "poll_oneoff", &args, memory), Errno::Success);
+
+    let nevents = read_u32(memory, OUT_PTR) as usize;
+    (0..nevents)
+        .map(|i| {
+            let event = &memory[BUF_PTR + 32 * i..][..32];
+            let userdata = read_u64(event, 0);
+            let error = u16::from_le_bytes([event[8], event[9]]);
+            (userdata, error, event[10])
+        })
+        .collect()
+}
+
+#[test]
+fn test_wasi_clocks() {
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+
+    let before = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let realtime = clock_time(&mut wasi, &mut memory, 0).unwrap();
+    let after = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    assert!(before.as_nanos() as u64 <= realtime && realtime <= after.as_nanos() as u64);
+
+    let first = clock_time(&mut wasi, &mut memory, 1).unwrap();
+    std::thread::sleep(Duration::from_millis(1));
+    let second = clock_time(&mut wasi, &mut memory, 1).unwrap();
+    assert!(second >= first + 1_000_000);
+
+    assert_eq!(clock_time(&mut wasi, &mut memory, 2), Err(Errno::Notsup));
+    assert_eq!(clock_time(&mut wasi, &mut memory, 99), Err(Errno::Inval));
+
+    let args = [Value::I32(1), Value::I32(OUT_PTR as i32)];
+    assert_eq!(
+        call(&mut wasi, "clock_res_get", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u64(&memory, OUT_PTR), 1);
+}
+
+#[test]
+fn test_wasi_poll_oneoff_sleep() {
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+
+    subscribe_clock(&mut memory, 0, 111, Duration::from_millis(50));
+    subscribe_clock(&mut memory, 1, 222, Duration::from_millis(10));
+
+    let start = Instant::now();
+    let events = poll_oneoff(&mut wasi, &mut memory, 2);
+    let elapsed = start.elapsed();
+
+    assert_eq!(events, [(222, 0, 0)]);
+    assert!(elapsed >= Duration::from_millis(10));
+    assert!(elapsed < Duration::from_millis(50));
+}
+
+#[test]
+fn test_wasi_poll_oneoff_stdin() {
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.files[0] = WasiFile::ReadOnly(b"some input".to_vec());
+
+    // Input that's ready doesn't wait for the timeout
+    subscribe_clock(&mut memory, 0, 111, Duration::from_secs(60));
+    subscribe(&mut memory, 1, 222, 1, &0u32.to_le_bytes());
+    subscribe(&mut memory, 2, 333, 1, &9u32.to_le_bytes());
+
+    let events = poll_oneoff(&mut wasi, &mut memory, 3);
+    assert_eq!(events, [(222, 0, 1), (333, Errno::Badf as u16, 1)]);
+    assert_eq!(read_u64(&memory, BUF_PTR + 16), 10); // bytes available
+}
+
+#[test]
+fn test_wasi_environ() {
+    let mut memory = vec![0; 0x2000];
+    let mut wasi = WasiDispatcher::default();
+
+    std::env::set_var("ROC_WASM_INTERP_TEST_PASSED", "yes");
+    std::env::set_var("ROC_WASM_INTERP_TEST_HIDDEN", "no");
+    wasi.pass_env_var("ROC_WASM_INTERP_TEST_PASSED");
+    wasi.pass_env_var("ROC_WASM_INTERP_TEST_UNSET");
+
+    let expected = "ROC_WASM_INTERP_TEST_PASSED=yes";
+
+    let args = [Value::I32(OUT_PTR as i32), Value::I32(OUT_PTR as i32 + 4)];
+    assert_eq!(
+        call(&mut wasi, "environ_sizes_get", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u32(&memory, OUT_PTR), 1);
+    assert_eq!(read_u32(&memory, OUT_PTR + 4), expected.len() as u32 + 1);
+
+    let args = [Value::I32(OUT_PTR as i32), Value::I32(BUF_PTR as i32)];
+    assert_eq!(
+        call(&mut wasi, "environ_get", &args, &mut memory),
+        Errno::Success
+    );
+    assert_eq!(read_u32(&memory, OUT_PTR), BUF_PTR as u32);
+    assert_eq!(&memory[BUF_PTR..][..expected.len()], expected.as_bytes());
+    assert_eq!(memory[BUF_PTR + expected.len()], 0);
+}
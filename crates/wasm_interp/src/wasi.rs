@@ -1,7 +1,11 @@
 use rand::prelude::*;
 use roc_wasm_module::Value;
-use std::io::{self, Read, StderrLock, StdoutLock, Write};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, StderrLock, StdoutLock, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::exit;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 pub const MODULE_NAME: &str = "wasi_snapshot_preview1";
 
@@ -9,6 +13,10 @@ pub struct WasiDispatcher<'a> {
     pub args: &'a [&'a [u8]],
     pub rng: ThreadRng,
     pub files: Vec<WasiFile>,
+    /// Environment variables for the app, as `NAME=value`
+    pub env: Vec<String>,
+    /// The zero point of the monotonic clock
+    start_time: Instant,
 }
 
 impl Default for WasiDispatcher<'_> {
@@ -22,14 +30,74 @@ pub enum WasiFile {
     WriteOnly(Vec<u8>),
     ReadWrite(Vec<u8>),
     HostSystemFile,
+    /// A file on the host filesystem, opened with `path_open`
+    HostFile(File),
+    /// A directory on the host filesystem. If it was preopened, the app sees it by the given name.
+    /// The app can only reach paths inside it, and only through the directories it's given.
+    HostDirectory {
+        path: PathBuf,
+        preopened_as: Option<String>,
+        /// The canonical path of the preopened directory this one is in. Paths resolved
+        /// from this directory must stay inside it, even after following symlinks.
+        root: PathBuf,
+    },
+    /// A file descriptor that has been closed, and can be reused
+    Closed,
 }
 
 enum WriteLock<'a> {
     StdOut(StdoutLock<'a>),
     Stderr(StderrLock<'a>),
     RegularFile(&'a mut Vec<u8>),
+    HostFile(&'a mut File),
 }
 
+// Values from the WASI preview1 spec
+// https://github.com/WebAssembly/WASI/blob/main/legacy/preview1/docs.md
+const FILETYPE_UNKNOWN: u8 = 0;
+const FILETYPE_CHARACTER_DEVICE: u8 = 2;
+const FILETYPE_DIRECTORY: u8 = 3;
+const FILETYPE_REGULAR_FILE: u8 = 4;
+const FILETYPE_SYMBOLIC_LINK: u8 = 7;
+
+const OFLAGS_CREAT: i32 = 1 << 0;
+const OFLAGS_DIRECTORY: i32 = 1 << 1;
+const OFLAGS_EXCL: i32 = 1 << 2;
+const OFLAGS_TRUNC: i32 = 1 << 3;
+
+const FDFLAGS_APPEND: i32 = 1 << 0;
+
+const LOOKUPFLAGS_SYMLINK_FOLLOW: i32 = 1 << 0;
+
+const RIGHTS_FD_READ: i64 = 1 << 1;
+const RIGHTS_FD_WRITE: i64 = 1 << 6;
+/// We don't restrict what the app can do with a file descriptor, beyond what the host allows.
+const RIGHTS_ALL: u64 = (1 << 30) - 1;
+
+const CLOCKID_REALTIME: i32 = 0;
+const CLOCKID_MONOTONIC: i32 = 1;
+const CLOCKID_PROCESS_CPUTIME_ID: i32 = 2;
+const CLOCKID_THREAD_CPUTIME_ID: i32 = 3;
+
+const EVENTTYPE_CLOCK: u8 = 0;
+const EVENTTYPE_FD_READ: u8 = 1;
+const EVENTTYPE_FD_WRITE: u8 = 2;
+
+const SUBCLOCKFLAGS_ABSTIME: u16 = 1 << 0;
+const EVENTRWFLAGS_FD_READWRITE_HANGUP: u16 = 1 << 0;
+
+/// Size of a `subscription` struct, the input to `poll_oneoff`
+const SUBSCRIPTION_SIZE: usize = 48;
+/// Size of an `event` struct, the output of `poll_oneoff`
+const EVENT_SIZE: usize = 32;
+
+const WHENCE_SET: i32 = 0;
+const WHENCE_CUR: i32 = 1;
+const WHENCE_END: i32 = 2;
+
+/// Size of a `dirent` struct, which is followed by the entry's name in `fd_readdir`'s buffer
+const DIRENT_SIZE: usize = 24;
+
 /// Implementation of WASI syscalls
 /// References for other engines:
 /// https://github.com/wasmerio/wasmer/blob/ef8d2f651ed29b4b06fdc2070eb8189922c54d82/lib/wasi/src/syscalls/mod.rs
@@ -44,9 +112,384 @@ impl<'a> WasiDispatcher<'a> {
                 WasiFile::HostSystemFile,
                 WasiFile::HostSystemFile,
             ],
+            env: vec![],
+            start_time: Instant::now(),
+        }
+    }
+
+    /// Pass an environment variable from the host through to the app, if it's set.
+    pub fn pass_env_var(&mut self, name: &str) {
+        if let Ok(value) = std::env::var(name) {
+            self.env.push(format!("{name}={value}"));
+        }
+    }
+
+    /// Give the app access to a directory on the host, which it will see by the name `name`.
+    /// This must be done before running the app, which looks for preopened directories on startup.
+    /// Returns the directory's file descriptor.
+    pub fn preopen_dir(&mut self, host_path: impl Into<PathBuf>, name: &str) -> u32 {
+        let path = host_path.into();
+        // If it doesn't exist, every path in it will fail to resolve anyway
+        let path = fs::canonicalize(&path).unwrap_or(path);
+
+        self.add_file(WasiFile::HostDirectory {
+            root: path.clone(),
+            path,
+            preopened_as: Some(name.to_string()),
+        })
+    }
+
+    fn add_file(&mut self, file: WasiFile) -> u32 {
+        match self
+            .files
+            .iter()
+            .position(|f| matches!(f, WasiFile::Closed))
+        {
+            Some(fd) => {
+                self.files[fd] = file;
+                fd as u32
+            }
+            None => {
+                self.files.push(file);
+                (self.files.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Resolve a path the app gave us, relative to one of its directories.
+    /// The path must not lead outside of that directory's preopened directory, even through
+    /// symlinks. A symlink at the end of the path is only followed if `lookup_flags` says so,
+    /// and callers that don't follow it must not let the host follow it either.
+    fn resolve_path(&self, dir_fd: usize, path: &str, lookup_flags: i32) -> Result<PathBuf, Errno> {
+        let (dir, root) = match self.files.get(dir_fd) {
+            Some(WasiFile::HostDirectory { path, root, .. }) => (path, root),
+            Some(WasiFile::Closed) | None => return Err(Errno::Badf),
+            Some(_) => return Err(Errno::Notdir),
+        };
+
+        let mut resolved = dir.clone();
+        let mut depth = 0;
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    depth += 1;
+                }
+                Component::CurDir => {}
+                Component::ParentDir if depth > 0 => {
+                    resolved.pop();
+                    depth -= 1;
+                }
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(Errno::Notcapable);
+                }
+            }
+        }
+
+        // The checks above can't see symlinks, so check where the path really leads on the host
+        let resolved = match (resolved.parent(), resolved.file_name()) {
+            (Some(parent), Some(name)) if depth > 0 => fs::canonicalize(parent)?.join(name),
+            _ => fs::canonicalize(&resolved)?,
+        };
+        if !resolved.starts_with(root) {
+            return Err(Errno::Notcapable);
+        }
+
+        if lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0 && resolved.is_symlink() {
+            // We can't tell where a dangling symlink leads, so treat it as missing rather than
+            // let the host create its target wherever it points.
+            let target = fs::canonicalize(&resolved)?;
+            if !target.starts_with(root) {
+                return Err(Errno::Notcapable);
+            }
+            return Ok(target);
+        }
+
+        Ok(resolved)
+    }
+
+    fn path_open(
+        &mut self,
+        dir_fd: usize,
+        path: &str,
+        lookup_flags: i32,
+        oflags: i32,
+        rights: i64,
+        fdflags: i32,
+    ) -> Result<u32, Errno> {
+        let path = self.resolve_path(dir_fd, path, lookup_flags)?;
+        // resolve_path has already followed a symlink at the end of the path, if we're allowed to
+        if path.is_symlink() {
+            return Err(Errno::Loop);
+        }
+        let root = match &self.files[dir_fd] {
+            WasiFile::HostDirectory { root, .. } => root.clone(),
+            _ => unreachable!("resolve_path only accepts directories"),
+        };
+
+        let is_dir = path.is_dir();
+        if oflags & OFLAGS_DIRECTORY != 0 && !is_dir {
+            return Err(if path.exists() {
+                Errno::Notdir
+            } else {
+                Errno::Noent
+            });
+        }
+
+        let write = rights & RIGHTS_FD_WRITE != 0;
+        if is_dir {
+            if oflags & OFLAGS_CREAT != 0 && oflags & OFLAGS_EXCL != 0 {
+                return Err(Errno::Exist);
+            }
+            if write {
+                return Err(Errno::Isdir);
+            }
+            return Ok(self.add_file(WasiFile::HostDirectory {
+                path,
+                preopened_as: None,
+                root,
+            }));
+        }
+
+        // The host needs some kind of access, even if the app only wants to stat the file
+        let read = rights & RIGHTS_FD_READ != 0 || !write;
+        let append = fdflags & FDFLAGS_APPEND != 0;
+        let file = OpenOptions::new()
+            .read(read)
+            .write(write && !append)
+            .append(append)
+            .create(oflags & OFLAGS_CREAT != 0)
+            .create_new(oflags & OFLAGS_CREAT != 0 && oflags & OFLAGS_EXCL != 0)
+            .truncate(oflags & OFLAGS_TRUNC != 0)
+            .open(path)?;
+
+        Ok(self.add_file(WasiFile::HostFile(file)))
+    }
+
+    fn fd_seek(&mut self, fd: usize, seek: SeekFrom) -> Result<u64, Errno> {
+        match self.files.get_mut(fd) {
+            Some(WasiFile::HostFile(file)) => Ok(file.seek(seek)?),
+            Some(WasiFile::HostDirectory { .. }) => Err(Errno::Isdir),
+            Some(WasiFile::Closed) | None => Err(Errno::Badf),
+            // Our other files are streams
+            Some(_) => Err(Errno::Spipe),
+        }
+    }
+
+    fn fd_filestat(&self, fd: usize) -> Result<Filestat, Errno> {
+        match self.files.get(fd) {
+            Some(WasiFile::HostFile(file)) => Ok(Filestat::from(file.metadata()?)),
+            Some(WasiFile::HostDirectory { path, .. }) => Ok(Filestat::from(fs::metadata(path)?)),
+            Some(WasiFile::HostSystemFile) => Ok(Filestat {
+                filetype: FILETYPE_CHARACTER_DEVICE,
+                ..Filestat::default()
+            }),
+            Some(WasiFile::ReadOnly(content) | WasiFile::WriteOnly(content))
+            | Some(WasiFile::ReadWrite(content)) => Ok(Filestat {
+                filetype: FILETYPE_REGULAR_FILE,
+                nlink: 1,
+                size: content.len() as u64,
+                ..Filestat::default()
+            }),
+            Some(WasiFile::Closed) | None => Err(Errno::Badf),
         }
     }
 
+    /// Write as many directory entries as fit into `buf`, starting at the one numbered `cookie`.
+    /// Returns the number of bytes written. If it's the length of the buffer, the app will
+    /// call again with a bigger buffer or a later cookie.
+    fn fd_readdir(&self, fd: usize, buf: &mut [u8], cookie: u64) -> Result<usize, Errno> {
+        let dir = match self.files.get(fd) {
+            Some(WasiFile::HostDirectory { path, .. }) => path,
+            Some(WasiFile::Closed) | None => return Err(Errno::Badf),
+            Some(_) => return Err(Errno::Notdir),
+        };
+
+        let mut entries = vec![
+            (".".to_string(), fs::metadata(dir)?),
+            ("..".to_string(), fs::metadata(dir.join(".."))?),
+        ];
+        let mut children = fs::read_dir(dir)?
+            .map(|entry| {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                Ok((name, entry.metadata()?))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        // Keep the order stable, so that cookies from earlier calls still make sense
+        children.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.extend(children);
+
+        let mut used = 0;
+        for (index, (name, metadata)) in entries.iter().enumerate().skip(cookie as usize) {
+            let stat = Filestat::from(metadata.clone());
+
+            let mut dirent = [0; DIRENT_SIZE];
+            dirent[0..8].copy_from_slice(&(index as u64 + 1).to_le_bytes()); // d_next
+            dirent[8..16].copy_from_slice(&stat.ino.to_le_bytes()); // d_ino
+            dirent[16..20].copy_from_slice(&(name.len() as u32).to_le_bytes()); // d_namlen
+            dirent[20] = stat.filetype; // d_type
+
+            for bytes in [&dirent[..], name.as_bytes()] {
+                let len = bytes.len().min(buf.len() - used);
+                buf[used..][..len].copy_from_slice(&bytes[..len]);
+                used += len;
+            }
+
+            if used == buf.len() {
+                break;
+            }
+        }
+
+        Ok(used)
+    }
+
+    /// The current time in nanoseconds, according to one of the WASI clocks
+    fn clock_time(&self, clock_id: i32) -> Result<u64, Errno> {
+        match clock_id {
+            CLOCKID_REALTIME => {
+                let since_epoch = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|_| Errno::Overflow)?;
+                Ok(since_epoch.as_nanos() as u64)
+            }
+            CLOCKID_MONOTONIC => Ok(self.start_time.elapsed().as_nanos() as u64),
+            // We'd need platform-specific APIs to measure CPU time
+            CLOCKID_PROCESS_CPUTIME_ID | CLOCKID_THREAD_CPUTIME_ID => Err(Errno::Notsup),
+            _ => Err(Errno::Inval),
+        }
+    }
+
+    /// Wait for at least one of the subscriptions to `poll_oneoff` to have an event,
+    /// and write the events to memory. Returns the number of events.
+    fn poll_oneoff(
+        &self,
+        memory: &mut [u8],
+        ptr_subscriptions: usize,
+        ptr_events: usize,
+        n_subscriptions: usize,
+    ) -> Result<u32, Errno> {
+        if n_subscriptions == 0 {
+            return Err(Errno::Inval);
+        }
+
+        struct Event {
+            userdata: u64,
+            error: Errno,
+            eventtype: u8,
+            nbytes: u64,
+            flags: u16,
+        }
+
+        let mut events = vec![];
+        let mut clocks = vec![];
+
+        for i in 0..n_subscriptions {
+            let sub = &memory[ptr_subscriptions + i * SUBSCRIPTION_SIZE..][..SUBSCRIPTION_SIZE];
+            let userdata = read_u64(sub, 0);
+            let eventtype = sub[8];
+            let mut event = Event {
+                userdata,
+                error: Errno::Success,
+                eventtype,
+                nbytes: 0,
+                flags: 0,
+            };
+
+            match eventtype {
+                EVENTTYPE_CLOCK => {
+                    let clock_id = read_i32(sub, 16);
+                    let timeout = read_u64(sub, 24);
+                    let flags = u16::from_le_bytes([sub[40], sub[41]]);
+
+                    match self.clock_time(clock_id) {
+                        Ok(now) => {
+                            let remaining = if flags & SUBCLOCKFLAGS_ABSTIME != 0 {
+                                timeout.saturating_sub(now)
+                            } else {
+                                timeout
+                            };
+                            clocks.push((remaining, userdata));
+                        }
+                        Err(error) => {
+                            event.error = error;
+                            events.push(event);
+                        }
+                    }
+                }
+                EVENTTYPE_FD_READ | EVENTTYPE_FD_WRITE => {
+                    let fd = read_u32(sub, 16) as usize;
+                    let is_read = eventtype == EVENTTYPE_FD_READ;
+
+                    // Our files never block, except for the host's stdin. We can't tell if
+                    // that has any input without blocking, so we say it's ready, and then
+                    // reading from it will wait for input.
+                    match (self.files.get(fd), is_read) {
+                        (
+                            Some(WasiFile::ReadOnly(content) | WasiFile::ReadWrite(content)),
+                            true,
+                        ) => {
+                            event.nbytes = content.len() as u64;
+                            if content.is_empty() {
+                                event.flags = EVENTRWFLAGS_FD_READWRITE_HANGUP;
+                            }
+                        }
+                        (Some(WasiFile::HostFile(file)), true) => {
+                            let mut file: &File = file;
+                            let size = file.metadata()?.len();
+                            let position = file.stream_position()?;
+                            event.nbytes = size.saturating_sub(position);
+                        }
+                        (Some(WasiFile::HostSystemFile), true) if fd == 0 => {}
+                        (Some(WasiFile::HostSystemFile), false) if fd != 0 => {}
+                        (
+                            Some(
+                                WasiFile::WriteOnly(_)
+                                | WasiFile::ReadWrite(_)
+                                | WasiFile::HostFile(_),
+                            ),
+                            false,
+                        ) => {}
+                        (Some(WasiFile::Closed) | None, _) => event.error = Errno::Badf,
+                        (Some(_), _) => event.error = Errno::Perm,
+                    }
+                    events.push(event);
+                }
+                _ => return Err(Errno::Inval),
+            }
+        }
+
+        // If nothing else is ready, sleep until the first clock goes off
+        if events.is_empty() {
+            if let Some(&(shortest, _)) = clocks.iter().min() {
+                thread::sleep(Duration::from_nanos(shortest));
+                for &(remaining, userdata) in clocks.iter() {
+                    if remaining == shortest {
+                        events.push(Event {
+                            userdata,
+                            error: Errno::Success,
+                            eventtype: EVENTTYPE_CLOCK,
+                            nbytes: 0,
+                            flags: 0,
+                        });
+                    }
+                }
+            }
+        }
+
+        for (i, event) in events.iter().enumerate() {
+            let bytes = &mut memory[ptr_events + i * EVENT_SIZE..][..EVENT_SIZE];
+            bytes.fill(0);
+            bytes[0..8].copy_from_slice(&event.userdata.to_le_bytes());
+            bytes[8..10].copy_from_slice(&(event.error as u16).to_le_bytes());
+            bytes[10] = event.eventtype;
+            bytes[16..24].copy_from_slice(&event.nbytes.to_le_bytes());
+            bytes[24..26].copy_from_slice(&event.flags.to_le_bytes());
+        }
+
+        Ok(events.len() as u32)
+    }
+
     pub fn dispatch(
         &mut self,
         function_name: &str,
@@ -89,8 +532,19 @@ impl<'a> WasiDispatcher<'a> {
                 success_code
             }
             "environ_get" => {
-                // `environ_sizes_get` always reports 0 environment variables
-                // so we don't have to do anything here.
+                // uint8_t ** environ,
+                let mut ptr_ptr_environ = arguments[0].expect_i32().unwrap() as usize;
+                // uint8_t * environ_buf
+                let mut ptr_environ_buf = arguments[1].expect_i32().unwrap() as usize;
+
+                for var in self.env.iter() {
+                    write_u32(memory, ptr_ptr_environ, ptr_environ_buf as u32);
+                    let bytes_target = &mut memory[ptr_environ_buf..][..var.len()];
+                    bytes_target.copy_from_slice(var.as_bytes());
+                    memory[ptr_environ_buf + var.len()] = 0; // C string zero termination
+                    ptr_environ_buf += var.len() + 1;
+                    ptr_ptr_environ += 4;
+                }
 
                 success_code
             }
@@ -98,20 +552,52 @@ impl<'a> WasiDispatcher<'a> {
                 let num_env_ptr = arguments[0].expect_i32().unwrap() as usize;
                 let size_env_ptr = arguments[1].expect_i32().unwrap() as usize;
 
-                // Calculate the total size required for environment variables
-                let total_size = 0;
-                let count = 0;
+                let count = self.env.len() as u32;
+                let total_size: u32 = self.env.iter().map(|var| 1 + var.len() as u32).sum();
 
                 write_u32(memory, num_env_ptr, count);
-                write_u32(memory, size_env_ptr, total_size as u32);
+                write_u32(memory, size_env_ptr, total_size);
 
                 success_code
             }
-            "clock_res_get" => success_code, // this dummy implementation seems to be good enough for some functions
-            "clock_time_get" => success_code,
+            "clock_res_get" => {
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // Out param: the resolution of the clock, in nanoseconds
+                let ptr_resolution = arguments[1].expect_i32().unwrap() as usize;
+
+                // Rust's clocks report nanoseconds, even if the host's clock is less precise
+                let result = self.clock_time(clock_id).map(|_| {
+                    write_u64(memory, ptr_resolution, 1);
+                });
+                errno_value(result)
+            }
+            "clock_time_get" => {
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // The maximum lag of the time from its actual value. We're always as precise as we can be.
+                let _precision = arguments[1].expect_i64().unwrap();
+                // Out param: the time, in nanoseconds
+                let ptr_time = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self.clock_time(clock_id).map(|time| {
+                    write_u64(memory, ptr_time, time);
+                });
+                errno_value(result)
+            }
             "fd_advise" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_allocate" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_close" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_close" => {
+                // file descriptor
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                match self.files.get_mut(fd) {
+                    Some(WasiFile::Closed) | None => Some(Value::I32(Errno::Badf as i32)),
+                    Some(file) => {
+                        // Dropping a host file closes it
+                        *file = WasiFile::Closed;
+                        success_code
+                    }
+                }
+            }
             "fd_datasync" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_fdstat_get" => {
                 // (i32, i32) -> i32
@@ -121,8 +607,13 @@ impl<'a> WasiDispatcher<'a> {
                 // ptr to a wasi_fdstat_t
                 let stat_mut_ptr = arguments[1].expect_i32().unwrap() as usize;
 
-                match fd {
-                    1 => {
+                // struct fdstat {
+                //     u8 fs_filetype; u16 fs_flags;
+                //     u64 fs_rights_base; u64 fs_rights_inheriting;
+                // }
+                let (filetype, rights_base, rights_inheriting) = match self.files.get(fd) {
+                    Some(WasiFile::Closed) | None => return Some(Value::I32(Errno::Badf as i32)),
+                    Some(_) if fd == 1 => {
                         // Tell WASI that stdout is a tty (no seek or tell)
                         // https://github.com/WebAssembly/wasi-libc/blob/659ff414560721b1660a19685110e484a081c3d4/libc-bottom-half/sources/isatty.c
                         // *Not* a tty if:
@@ -131,22 +622,39 @@ impl<'a> WasiDispatcher<'a> {
                         // So it's sufficient to set:
                         //     .fs_filetype = __WASI_FILETYPE_CHARACTER_DEVICE
                         //     .fs_rights_base = 0
-
-                        const WASI_FILETYPE_CHARACTER_DEVICE: u8 = 2;
-                        memory[stat_mut_ptr] = WASI_FILETYPE_CHARACTER_DEVICE;
-
-                        for b in memory[stat_mut_ptr + 1..stat_mut_ptr + 24].iter_mut() {
-                            *b = 0;
-                        }
+                        (FILETYPE_CHARACTER_DEVICE, 0, 0)
                     }
-                    _ => todo!("WASI {}({:?})", function_name, arguments),
-                }
+                    Some(WasiFile::HostSystemFile) => (FILETYPE_CHARACTER_DEVICE, 0, 0),
+                    Some(WasiFile::HostDirectory { .. }) => {
+                        // wasi-libc limits the rights of files opened in a directory to these
+                        (FILETYPE_DIRECTORY, RIGHTS_ALL, RIGHTS_ALL)
+                    }
+                    Some(_) => (FILETYPE_REGULAR_FILE, RIGHTS_ALL, 0),
+                };
+
+                memory[stat_mut_ptr..][..24].fill(0);
+                memory[stat_mut_ptr] = filetype;
+                memory[stat_mut_ptr + 8..][..8].copy_from_slice(&rights_base.to_le_bytes());
+                memory[stat_mut_ptr + 16..][..8].copy_from_slice(&rights_inheriting.to_le_bytes());
 
                 success_code
             }
             "fd_fdstat_set_flags" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_fdstat_set_rights" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_filestat_get" => {
+                // file descriptor
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // ptr to a wasi_filestat_t
+                let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
+
+                match self.fd_filestat(fd) {
+                    Ok(stat) => {
+                        stat.write(memory, ptr_buf);
+                        success_code
+                    }
+                    Err(errno) => Some(Value::I32(errno as i32)),
+                }
+            }
             "fd_filestat_set_size" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_pread" => todo!("WASI {}({:?})", function_name, arguments),
@@ -157,17 +665,38 @@ impl<'a> WasiDispatcher<'a> {
                 //  preopen type: 4 bytes, where 0=dir is the only one supported, it seems
                 //  preopen name length: 4 bytes
                 let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
-                memory[ptr_buf..][..8].copy_from_slice(&0u64.to_le_bytes());
-                if fd < self.files.len() {
-                    success_code
-                } else {
-                    println!("WASI warning: file descriptor {fd} does not exist");
-                    Some(Value::I32(Errno::Badf as i32))
+                match self.files.get(fd) {
+                    Some(WasiFile::HostDirectory {
+                        preopened_as: Some(name),
+                        ..
+                    }) => {
+                        memory[ptr_buf..][..4].copy_from_slice(&0u32.to_le_bytes());
+                        write_u32(memory, ptr_buf + 4, name.len() as u32);
+                        success_code
+                    }
+                    // wasi-libc looks for preopens from fd 3 up, until it gets this error
+                    _ => Some(Value::I32(Errno::Badf as i32)),
                 }
             }
             "fd_prestat_dir_name" => {
-                // We're not giving names to any of our files so just return success
-                success_code
+                // The preopened file descriptor to query
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Where to write the name
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                // The length of the name, from fd_prestat_get
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                match self.files.get(fd) {
+                    Some(WasiFile::HostDirectory {
+                        preopened_as: Some(name),
+                        ..
+                    }) => {
+                        let len = path_len.min(name.len());
+                        memory[ptr_path..][..len].copy_from_slice(&name.as_bytes()[..len]);
+                        success_code
+                    }
+                    _ => Some(Value::I32(Errno::Badf as i32)),
+                }
             }
             "fd_pwrite" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_read" => {
@@ -218,17 +747,91 @@ impl<'a> WasiDispatcher<'a> {
                             }
                         }
                     }
+                    Some(HostFile(file)) => {
+                        // Reading only needs a shared reference to a file
+                        let mut file: &File = file;
+                        for i in 0..iovs_len as usize {
+                            let ptr_iov = ptr_iovs + 8 * i;
+                            let iov_base = read_u32(memory, ptr_iov) as usize;
+                            let iov_len = read_i32(memory, ptr_iov + 4) as usize;
+                            match file.read(&mut memory[iov_base..][..iov_len]) {
+                                Ok(n) => {
+                                    n_read += n;
+                                    if n < iov_len {
+                                        break;
+                                    }
+                                }
+                                Err(e) => return Some(Value::I32(Errno::from(e) as i32)),
+                            }
+                        }
+                    }
+                    Some(HostDirectory { .. }) => return Some(Value::I32(Errno::Isdir as i32)),
                     _ => return Some(Value::I32(Errno::Badf as i32)),
                 };
 
                 memory[ptr_nread..][..4].copy_from_slice(&(n_read as u32).to_le_bytes());
                 success_code
             }
-            "fd_readdir" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_readdir" => {
+                // file descriptor
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Buffer to write the directory entries into
+                let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
+                let buf_len = arguments[2].expect_i32().unwrap() as usize;
+                // The entry to start from. 0 is the first entry, and each entry has the next one's cookie.
+                let cookie = arguments[3].expect_i64().unwrap() as u64;
+                // Out param: number of bytes written to the buffer
+                let ptr_bufused = arguments[4].expect_i32().unwrap() as usize;
+
+                match self.fd_readdir(fd, &mut memory[ptr_buf..][..buf_len], cookie) {
+                    Ok(used) => {
+                        write_u32(memory, ptr_bufused, used as u32);
+                        success_code
+                    }
+                    Err(errno) => Some(Value::I32(errno as i32)),
+                }
+            }
             "fd_renumber" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_seek" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_seek" => {
+                // file descriptor
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Number of bytes to move
+                let offset = arguments[1].expect_i64().unwrap();
+                // What the offset is relative to
+                let whence = arguments[2].expect_i32().unwrap();
+                // Out param: the new offset, relative to the start of the file
+                let ptr_newoffset = arguments[3].expect_i32().unwrap() as usize;
+
+                let seek = match whence {
+                    WHENCE_SET => SeekFrom::Start(offset as u64),
+                    WHENCE_CUR => SeekFrom::Current(offset),
+                    WHENCE_END => SeekFrom::End(offset),
+                    _ => return Some(Value::I32(Errno::Inval as i32)),
+                };
+
+                match self.fd_seek(fd, seek) {
+                    Ok(new_offset) => {
+                        write_u64(memory, ptr_newoffset, new_offset);
+                        success_code
+                    }
+                    Err(errno) => Some(Value::I32(errno as i32)),
+                }
+            }
             "fd_sync" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_tell" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_tell" => {
+                // file descriptor
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: the current offset, relative to the start of the file
+                let ptr_offset = arguments[1].expect_i32().unwrap() as usize;
+
+                match self.fd_seek(fd, SeekFrom::Current(0)) {
+                    Ok(offset) => {
+                        write_u64(memory, ptr_offset, offset);
+                        success_code
+                    }
+                    Err(errno) => Some(Value::I32(errno as i32)),
+                }
+            }
             "fd_write" => {
                 use WasiFile::*;
 
@@ -252,6 +855,8 @@ impl<'a> WasiDispatcher<'a> {
                     Some(WriteOnly(content) | ReadWrite(content)) => {
                         WriteLock::RegularFile(content)
                     }
+                    Some(HostFile(file)) => WriteLock::HostFile(file),
+                    Some(HostDirectory { .. }) => return Some(Value::I32(Errno::Isdir as i32)),
                     _ => return Some(Value::I32(Errno::Badf as i32)),
                 };
 
@@ -280,6 +885,7 @@ impl<'a> WasiDispatcher<'a> {
                         WriteLock::StdOut(stdout) => stdout.write_all(bytes),
                         WriteLock::Stderr(stderr) => stderr.write_all(bytes),
                         WriteLock::RegularFile(content) => content.write_all(bytes),
+                        WriteLock::HostFile(file) => file.write_all(bytes),
                     };
                     if write_result.is_err() {
                         break;
@@ -300,23 +906,142 @@ impl<'a> WasiDispatcher<'a> {
                     Err(_) => Some(Value::I32(Errno::Io as i32)),
                 }
             }
-            "path_create_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
+            "path_create_directory" => {
+                // The directory the path is relative to
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // The path of the directory to create
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = read_str(memory, ptr_path, path_len)
+                    .and_then(|path| self.resolve_path(fd, path, 0))
+                    .and_then(|path| Ok(fs::create_dir(path)?));
+
+                errno_value(result)
+            }
+            "path_filestat_get" => {
+                // The directory the path is relative to
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Lookup flags. Bit 0 means to follow symlinks.
+                let flags = arguments[1].expect_i32().unwrap();
+                // The path of the file to query
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                // ptr to a wasi_filestat_t
+                let ptr_buf = arguments[4].expect_i32().unwrap() as usize;
+
+                let result = read_str(memory, ptr_path, path_len)
+                    .and_then(|path| self.resolve_path(fd, path, flags))
+                    .and_then(|path| Ok(Filestat::from(fs::symlink_metadata(path)?)));
+
+                match result {
+                    Ok(stat) => {
+                        stat.write(memory, ptr_buf);
+                        success_code
+                    }
+                    Err(errno) => Some(Value::I32(errno as i32)),
+                }
+            }
             "path_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
             "path_link" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_open" => todo!("WASI {}({:?})", function_name, arguments),
+            "path_open" => {
+                // The directory the path is relative to
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Lookup flags. Bit 0 means to follow symlinks.
+                let dirflags = arguments[1].expect_i32().unwrap();
+                // The path of the file to open
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                // Whether to create, truncate, or require a directory
+                let oflags = arguments[4].expect_i32().unwrap();
+                // What the app wants to do with the new file descriptor
+                let rights_base = arguments[5].expect_i64().unwrap();
+                let _rights_inheriting = arguments[6].expect_i64().unwrap();
+                // Whether to append
+                let fdflags = arguments[7].expect_i32().unwrap();
+                // Out param: the new file descriptor
+                let ptr_fd = arguments[8].expect_i32().unwrap() as usize;
+
+                let result = read_str(memory, ptr_path, path_len).and_then(|path| {
+                    let path = path.to_string();
+                    self.path_open(fd, &path, dirflags, oflags, rights_base, fdflags)
+                });
+
+                match result {
+                    Ok(new_fd) => {
+                        write_u32(memory, ptr_fd, new_fd);
+                        success_code
+                    }
+                    Err(errno) => Some(Value::I32(errno as i32)),
+                }
+            }
             "path_readlink" => todo!("WASI {}({:?})", function_name, arguments),
             "path_remove_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_rename" => todo!("WASI {}({:?})", function_name, arguments),
+            "path_rename" => {
+                // The directory the old path is relative to
+                let old_fd = arguments[0].expect_i32().unwrap() as usize;
+                // The path to rename
+                let ptr_old_path = arguments[1].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[2].expect_i32().unwrap() as usize;
+                // The directory the new path is relative to
+                let new_fd = arguments[3].expect_i32().unwrap() as usize;
+                // The new path
+                let ptr_new_path = arguments[4].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[5].expect_i32().unwrap() as usize;
+
+                let result = read_str(memory, ptr_old_path, old_path_len)
+                    .and_then(|path| self.resolve_path(old_fd, path, 0))
+                    .and_then(|old_path| {
+                        let new_path = read_str(memory, ptr_new_path, new_path_len)?;
+                        let new_path = self.resolve_path(new_fd, new_path, 0)?;
+                        Ok(fs::rename(old_path, new_path)?)
+                    });
+
+                errno_value(result)
+            }
             "path_symlink" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_unlink_file" => todo!("WASI {}({:?})", function_name, arguments),
-            "poll_oneoff" => todo!("WASI {}({:?})", function_name, arguments),
+            "path_unlink_file" => {
+                // The directory the path is relative to
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // The path of the file to remove
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = read_str(memory, ptr_path, path_len)
+                    .and_then(|path| self.resolve_path(fd, path, 0))
+                    .and_then(|path| {
+                        if fs::symlink_metadata(&path)?.is_dir() {
+                            return Err(Errno::Isdir);
+                        }
+                        Ok(fs::remove_file(path)?)
+                    });
+
+                errno_value(result)
+            }
+            "poll_oneoff" => {
+                // The subscriptions to wait for
+                let ptr_in = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: the events that happened
+                let ptr_out = arguments[1].expect_i32().unwrap() as usize;
+                // The number of subscriptions
+                let nsubscriptions = arguments[2].expect_i32().unwrap() as usize;
+                // Out param: the number of events
+                let ptr_nevents = arguments[3].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .poll_oneoff(memory, ptr_in, ptr_out, nsubscriptions)
+                    .map(|nevents| write_u32(memory, ptr_nevents, nevents));
+                errno_value(result)
+            }
             "proc_exit" => {
                 let exit_code = arguments[0].expect_i32().unwrap();
                 exit(exit_code);
             }
             "proc_raise" => todo!("WASI {}({:?})", function_name, arguments),
-            "sched_yield" => todo!("WASI {}({:?})", function_name, arguments),
+            "sched_yield" => {
+                thread::yield_now();
+                success_code
+            }
             "random_get" => {
                 // A pointer to a buffer where the random bytes will be written
                 let ptr_buf = arguments[0].expect_i32().unwrap() as usize;
@@ -335,6 +1060,87 @@ impl<'a> WasiDispatcher<'a> {
     }
 }
 
+fn errno_value(result: Result<(), Errno>) -> Option<Value> {
+    let errno = match result {
+        Ok(()) => Errno::Success,
+        Err(errno) => errno,
+    };
+    Some(Value::I32(errno as i32))
+}
+
+fn read_str(memory: &[u8], addr: usize, len: usize) -> Result<&str, Errno> {
+    std::str::from_utf8(&memory[addr..][..len]).map_err(|_| Errno::Ilseq)
+}
+
+/// File metadata, as WASI represents it
+#[derive(Default)]
+struct Filestat {
+    dev: u64,
+    ino: u64,
+    filetype: u8,
+    nlink: u64,
+    size: u64,
+    atim: u64,
+    mtim: u64,
+    ctim: u64,
+}
+
+impl Filestat {
+    fn write(&self, memory: &mut [u8], addr: usize) {
+        let bytes = &mut memory[addr..][..64];
+        bytes.fill(0);
+        bytes[0..8].copy_from_slice(&self.dev.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.ino.to_le_bytes());
+        bytes[16] = self.filetype;
+        bytes[24..32].copy_from_slice(&self.nlink.to_le_bytes());
+        bytes[32..40].copy_from_slice(&self.size.to_le_bytes());
+        bytes[40..48].copy_from_slice(&self.atim.to_le_bytes());
+        bytes[48..56].copy_from_slice(&self.mtim.to_le_bytes());
+        bytes[56..64].copy_from_slice(&self.ctim.to_le_bytes());
+    }
+}
+
+impl From<Metadata> for Filestat {
+    fn from(metadata: Metadata) -> Self {
+        let file_type = metadata.file_type();
+        let filetype = if file_type.is_dir() {
+            FILETYPE_DIRECTORY
+        } else if file_type.is_file() {
+            FILETYPE_REGULAR_FILE
+        } else if file_type.is_symlink() {
+            FILETYPE_SYMBOLIC_LINK
+        } else {
+            FILETYPE_UNKNOWN
+        };
+
+        let nanos = |time: io::Result<std::time::SystemTime>| {
+            time.ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos() as u64)
+        };
+
+        #[cfg(unix)]
+        let (dev, ino, nlink, ctim) = {
+            use std::os::unix::fs::MetadataExt;
+            let ctim = metadata.ctime() as u64 * 1_000_000_000 + metadata.ctime_nsec() as u64;
+            (metadata.dev(), metadata.ino(), metadata.nlink(), ctim)
+        };
+        #[cfg(not(unix))]
+        let (dev, ino, nlink, ctim) = (0, 0, 1, nanos(metadata.modified()));
+
+        Filestat {
+            dev,
+            ino,
+            filetype,
+            nlink,
+            size: metadata.len(),
+            atim: nanos(metadata.accessed()),
+            mtim: nanos(metadata.modified()),
+            ctim,
+        }
+    }
+}
+
 fn read_u32(memory: &[u8], addr: usize) -> u32 {
     let mut bytes = [0; 4];
     bytes.copy_from_slice(&memory[addr..][..4]);
@@ -347,6 +1153,12 @@ fn read_i32(memory: &[u8], addr: usize) -> i32 {
     i32::from_le_bytes(bytes)
 }
 
+fn read_u64(memory: &[u8], addr: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&memory[addr..][..8]);
+    u64::from_le_bytes(bytes)
+}
+
 fn write_u32(memory: &mut [u8], addr: usize, value: u32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
@@ -355,12 +1167,16 @@ fn write_i32(memory: &mut [u8], addr: usize, value: i32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
 
+fn write_u64(memory: &mut [u8], addr: usize, value: u64) {
+    memory[addr..][..8].copy_from_slice(&value.to_le_bytes());
+}
+
 /// Error codes returned by functions.
 /// Not all of these error codes are returned by the functions provided by this
 /// API; some are used in higher-level library layers, and others are provided
 /// merely for alignment with POSIX.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Errno {
     /// No error occurred. System call completed successfully.
     Success,
@@ -517,3 +1333,16 @@ pub enum Errno {
     /// Extension: Capabilities insufficient.
     Notcapable,
 }
+
+impl From<io::Error> for Errno {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Errno::Noent,
+            io::ErrorKind::PermissionDenied => Errno::Access,
+            io::ErrorKind::AlreadyExists => Errno::Exist,
+            io::ErrorKind::InvalidInput => Errno::Inval,
+            io::ErrorKind::Unsupported => Errno::Notsup,
+            _ => Errno::Io,
+        }
+    }
+}
@@ -50,6 +50,8 @@ Json := {}
             list: decodeList,
             record: decodeRecord,
             tuple: decodeTuple,
+            tag: decodeTag,
+            dict: decodeDict,
         },
     ]
 
@@ -1279,6 +1281,119 @@ decodeRecord = \initialState, stepField, finalizer -> Decode.custom \bytes, @Jso
             # Begin decoding field:value pairs
             decodeFields initialState bytesBeforeFirstField
 
+# JSON TAGS --------------------------------------------------------------------
+
+decodeTag = \stepTag -> Decode.custom \bytes, @Json {} ->
+        # Tags are encoded as `A v1 v2` -> `{"A": [v1, v2]}`, see `encodeTag`
+        countBytesBeforeName =
+            when List.walkUntil bytes (BeforeOpeningBrace 0) objectHelp is
+                ObjectFieldNameStart n -> n
+                _ -> 0
+
+        if countBytesBeforeName == 0 then
+            # Invalid tag, expected opening brace '{' followed by the tag name
+            { result: Err TooShort, rest: bytes }
+        else
+            bytesBeforeName = List.dropFirst bytes countBytesBeforeName
+
+            { result: nameResult, rest: bytesAfterName } =
+                Decode.decodeWith bytesBeforeName decodeString json
+
+            countBytesBeforePayload =
+                when List.walkUntil bytesAfterName (BeforeColon 0) objectHelp is
+                    AfterColon n -> n
+                    _ -> 0
+
+            payloadBytes = List.dropFirst bytesAfterName countBytesBeforePayload
+
+            when nameResult is
+                Err TooShort -> { result: Err TooShort, rest: bytes }
+                Ok name ->
+                    when stepTag name is
+                        Skip ->
+                            # Not a tag of the union we are decoding
+                            { result: Err TooShort, rest: bytes }
+
+                        Keep payloadDecoder ->
+                            # The payload is a json array, decoded like a tuple
+                            Decode.decodeWith payloadBytes payloadDecoder json
+                            |> tryDecode \{ val, rest: bytesAfterPayload } ->
+                                when List.walkUntil bytesAfterPayload (AfterObjectValue 0) objectHelp is
+                                    AfterClosingBrace n ->
+                                        { result: Ok val, rest: List.dropFirst bytesAfterPayload n }
+
+                                    _ ->
+                                        # Invalid tag, expected a single field object
+                                        { result: Err TooShort, rest: bytesAfterPayload }
+
+# Test decode of tag union
+expect
+    input = Str.toUtf8 "{\"Rect\":[2,3]}"
+
+    actual : DecodeResult [Circle U64, Rect U64 U64]
+    actual = Decode.fromBytesPartial input json
+
+    actual.result == Ok (Rect 2 3)
+
+# JSON DICTS -------------------------------------------------------------------
+
+decodeDict = \initialState, insert, keyDecoder, valueDecoder -> Decode.custom \bytes, @Json {} ->
+
+        # Recursively insert the key:value pairs of the object
+        decodeEntries = \state, bytesBeforeKey ->
+            # Keys are json strings, so a key decoder has to accept a json string
+            Decode.decodeWith bytesBeforeKey keyDecoder json
+            |> tryDecode \{ val: key, rest: bytesAfterKey } ->
+                countBytesBeforeValue =
+                    when List.walkUntil bytesAfterKey (BeforeColon 0) objectHelp is
+                        AfterColon n -> n
+                        _ -> 0
+
+                if countBytesBeforeValue == 0 then
+                    # Invalid object, expected a colon ':' after the key
+                    { result: Err TooShort, rest: bytesAfterKey }
+                else
+                    valueBytes = List.dropFirst bytesAfterKey countBytesBeforeValue
+
+                    Decode.decodeWith valueBytes valueDecoder json
+                    |> tryDecode \{ val: value, rest: bytesAfterValue } ->
+                        newState = insert state key value
+
+                        # Check if another key or '}' for end of object
+                        when List.walkUntil bytesAfterValue (AfterObjectValue 0) objectHelp is
+                            ObjectFieldNameStart n ->
+                                decodeEntries newState (List.dropFirst bytesAfterValue n)
+
+                            AfterClosingBrace n ->
+                                { result: Ok newState, rest: List.dropFirst bytesAfterValue n }
+
+                            _ ->
+                                # Invalid object
+                                { result: Err TooShort, rest: bytesAfterValue }
+
+        when List.walkUntil bytes (BeforeOpeningBrace 0) objectHelp is
+            ObjectFieldNameStart n -> decodeEntries initialState (List.dropFirst bytes n)
+            AfterClosingBrace n -> { result: Ok initialState, rest: List.dropFirst bytes n }
+            _ -> { result: Err TooShort, rest: bytes }
+
+# Test decode of dict
+expect
+    input = Str.toUtf8 "{\"a\": 1, \"b\": 2}"
+
+    actual : DecodeResult (Dict Str U64)
+    actual = Decode.fromBytesPartial input json
+
+    actual.result == Ok (Dict.fromList [("a", 1), ("b", 2)])
+
+# Test decode of empty dict
+expect
+    input = Str.toUtf8 "{ }"
+
+    actual : DecodeResult (Dict Str U64)
+    actual = Decode.fromBytesPartial input json
+
+    actual.result == Ok (Dict.empty {})
+
 objectHelp : ObjectState, U8 -> [Break ObjectState, Continue ObjectState]
 objectHelp = \state, byte ->
     when (state, byte) is
@@ -1286,6 +1401,7 @@ objectHelp = \state, byte ->
         (BeforeOpeningBrace n, b) if b == '{' -> Continue (AfterOpeningBrace (n + 1))
         (AfterOpeningBrace n, b) if isWhitespace b -> Continue (AfterOpeningBrace (n + 1))
         (AfterOpeningBrace n, b) if b == '"' -> Break (ObjectFieldNameStart n)
+        (AfterOpeningBrace n, b) if b == '}' -> Continue (AfterClosingBrace (n + 1))
         (BeforeColon n, b) if isWhitespace b -> Continue (BeforeColon (n + 1))
         (BeforeColon n, b) if b == ':' -> Continue (AfterColon (n + 1))
         (AfterColon n, b) if isWhitespace b -> Continue (AfterColon (n + 1))